@@ -0,0 +1,30 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::event::WindowEvent;
+use kiss3d::light::Light;
+use kiss3d::window::Window;
+use na::Vector4;
+
+fn main() {
+    let mut window = Window::new("Kiss3d: clip planes");
+    let mut s = window.add_sphere(0.5);
+
+    s.set_color(0.0, 0.6, 1.0);
+    s.enable_backface_culling(false);
+
+    window.set_light(Light::StickToCamera);
+
+    // Keep everything below the plane y = 0.25.
+    window.set_clip_plane(0, Some(Vector4::new(0.0, -1.0, 0.0, 0.25)));
+
+    while window.render() {
+        for event in window.events().iter() {
+            if let WindowEvent::CursorPos(x, _, _) = event.value {
+                // Move the second clip plane along the x axis when the cursor moves.
+                let offset = (x as f32 / window.width() as f32 - 0.5) * 1.2;
+                window.set_clip_plane(1, Some(Vector4::new(1.0, 0.0, 0.0, -offset)));
+            }
+        }
+    }
+}
@@ -9,27 +9,177 @@ varying vec3 local_light_position;
 varying vec2 tex_coord_v;
 varying vec3 normalInterp;
 varying vec3 vertPos;
+varying vec3 worldPos;
+varying vec3 inst_color_v;
 
 uniform vec3 color;
 uniform sampler2D tex;
+uniform vec4 clip_plane0;
+uniform vec4 clip_plane1;
+// The scalar parameters are packed into vectors so that all the uniforms fit in the 16 vectors
+// guaranteed by GLSL ES 1.00.
+// (alpha, reflectivity, ssr_weight, double_sided) where ssr_weight is the fraction of the color
+// replaced by the screen-space reflections, stored in the alpha channel.
+uniform vec4 material_params;
+// (pbr, metallic, roughness, has_irradiance_map) where pbr tells whether the Cook-Torrance BRDF
+// replaces the Blinn-Phong shading.
+uniform vec4 pbr_params;
+// (ambient, shadow_enabled, shadow_texel) where ambient is the fraction of the object color lit
+// regardless of the light direction, and shadow_texel the size of a texel of the shadow map.
+uniform vec3 lighting_params;
+uniform vec3 fog_color;
+// (near, far, density, mode) with mode 0: no fog, 1: linear, 2: exponential, 3: exponential
+// squared.
+uniform vec4 fog_params;
+uniform sampler2D shadow_map;
+uniform mat4 light_view_proj;
+uniform samplerCube env_map;
+// Rotation from view-space to the space of the (possibly rotated) cube-maps.
+uniform mat3 env_rotation;
+// The diffuse irradiance lighting the physically-based materials instead of a uniform ambient light.
+uniform samplerCube irradiance_map;
 const vec3 specColor = vec3(0.4, 0.4, 0.4);
 
+float fog_factor(float dist) {
+  if (fog_params.w == 1.0) {
+    return clamp((dist - fog_params.x) / (fog_params.y - fog_params.x), 0.0, 1.0);
+  } else if (fog_params.w == 2.0) {
+    return 1.0 - exp(-fog_params.z * dist);
+  } else if (fog_params.w == 3.0) {
+    float d = fog_params.z * dist;
+    return 1.0 - exp(-d * d);
+  }
+
+  return 0.0;
+}
+
+float unpack_depth(vec4 rgba) {
+  return dot(rgba, vec4(1.0, 1.0 / 255.0, 1.0 / 65025.0, 1.0 / 16581375.0));
+}
+
+// Fraction of the 3x3 neighbour texels of the shadow map hiding this fragment from the light.
+float shadow_factor(float bias) {
+  vec4 light_pos4 = light_view_proj * vec4(worldPos, 1.0);
+  vec3 light_pos = light_pos4.xyz / light_pos4.w * 0.5 + 0.5;
+
+  if (light_pos.x < 0.0 || light_pos.x > 1.0 ||
+      light_pos.y < 0.0 || light_pos.y > 1.0 ||
+      light_pos.z > 1.0) {
+    return 0.0;
+  }
+
+  float shadow = 0.0;
+  for (int i = -1; i <= 1; i++) {
+    for (int j = -1; j <= 1; j++) {
+      vec2 offset = vec2(float(i), float(j)) * lighting_params.z;
+      float depth = unpack_depth(texture2D(shadow_map, light_pos.xy + offset));
+      if (light_pos.z - bias > depth) {
+        shadow += 1.0;
+      }
+    }
+  }
+
+  return shadow / 9.0;
+}
+
+const float PI = 3.14159265;
+
+// The Cook-Torrance BRDF, with the GGX normal distribution, the Smith-Schlick geometry term
+// and the Schlick Fresnel approximation, multiplied by the cosine of the light incidence.
+vec3 cook_torrance(vec3 normal, vec3 light_dir, vec3 view_dir, vec3 albedo) {
+  float metallic = pbr_params.y;
+  float roughness = pbr_params.z;
+  vec3 half_dir = normalize(light_dir + view_dir);
+  float n_dot_l = max(dot(normal, light_dir), 0.0);
+  float n_dot_v = max(dot(normal, view_dir), 0.0001);
+  float n_dot_h = max(dot(normal, half_dir), 0.0);
+  float h_dot_v = max(dot(half_dir, view_dir), 0.0);
+
+  float a = roughness * roughness;
+  float a2 = a * a;
+  float d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+  float ndf = a2 / (PI * d * d);
+
+  float k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+  float geometry = n_dot_v / (n_dot_v * (1.0 - k) + k) * n_dot_l / (n_dot_l * (1.0 - k) + k);
+
+  vec3 f0 = mix(vec3(0.04), albedo, metallic);
+  vec3 fresnel = f0 + (1.0 - f0) * pow(1.0 - h_dot_v, 5.0);
+
+  vec3 specular = ndf * geometry * fresnel / max(4.0 * n_dot_v * n_dot_l, 0.0001);
+  vec3 diffuse = (1.0 - fresnel) * (1.0 - metallic) * albedo / PI;
+
+  return (diffuse + specular) * n_dot_l;
+}
+
 void main() {
+  // GLSL 100 has no gl_ClipDistance, so user clip planes are emulated here.
+  if (dot(clip_plane0, vec4(worldPos, 1.0)) < 0.0 ||
+      dot(clip_plane1, vec4(worldPos, 1.0)) < 0.0) {
+    discard;
+  }
+
+  float alpha = material_params.x;
+  float reflectivity = material_params.y;
+  float ssr_weight = material_params.z;
+  float ambient = lighting_params.x;
   vec3 normal = normalize(normalInterp);
+
+  // Light the back faces of double-sided objects like their front faces.
+  if (material_params.w != 0.0 && dot(normal, normalize(-vertPos)) < 0.0) {
+    normal = -normal;
+  }
   vec3 lightDir = normalize(local_light_position - vertPos);
 
+  vec3 viewDir = normalize(-vertPos);
   float lambertian = max(dot(lightDir, normal), 0.0);
-  float specular = 0.0;
+  float shadow = 0.0;
 
-  if(lambertian > 0.0) {
-    vec3 viewDir = normalize(-vertPos);
-    vec3 halfDir = normalize(lightDir + viewDir);
-    float specAngle = max(dot(halfDir, normal), 0.0);
-    specular = pow(specAngle, 30.0);
+  if (lighting_params.y != 0.0) {
+    // Slope-scaled bias against shadow acne.
+    shadow = shadow_factor(max(0.005 * (1.0 - lambertian), 0.0005));
   }
 
   vec4 tex_color = texture2D(tex, tex_coord_v);
-  gl_FragColor = tex_color * vec4(color / 3.0 +
-                                  lambertian * color / 3.0 +
-                                  specular * specColor / 3.0, 1.0);
+  vec3 base_color = color * inst_color_v;
+  vec4 lit_color;
+
+  if (pbr_params.x != 0.0) {
+    vec3 albedo = tex_color.rgb * base_color;
+    // The direct light gets what the ambient term leaves, so that a white diffuse surface lit
+    // head-on is at most white.
+    vec3 radiance = vec3(PI * (1.0 - ambient));
+    vec3 direct = cook_torrance(normal, lightDir, viewDir, albedo) * radiance * (1.0 - shadow);
+    vec3 ambient_light = vec3(ambient);
+    if (pbr_params.w != 0.0) {
+      ambient_light *= textureCube(irradiance_map, env_rotation * normal).rgb;
+    }
+    lit_color = vec4(ambient_light * albedo + direct, tex_color.a * alpha);
+  } else {
+    float specular = 0.0;
+
+    if(lambertian > 0.0) {
+      vec3 halfDir = normalize(lightDir + viewDir);
+      float specAngle = max(dot(halfDir, normal), 0.0);
+      specular = pow(specAngle, 30.0);
+    }
+
+    lambertian *= 1.0 - shadow;
+    specular *= 1.0 - shadow;
+
+    // The directional terms share what the ambient term leaves, so that an ambient of 1/3 gives
+    // the same weight to the ambient, diffuse and specular terms.
+    float directional = (1.0 - ambient) / 2.0;
+    lit_color = tex_color * vec4(ambient * base_color +
+                                 directional * lambertian * base_color +
+                                 directional * specular * specColor, alpha);
+  }
+
+  if (reflectivity > 0.0) {
+    vec3 reflected = env_rotation * reflect(normalize(vertPos), normal);
+    lit_color.rgb = mix(lit_color.rgb, textureCube(env_map, reflected).rgb, reflectivity);
+  }
+
+  gl_FragColor = vec4(mix(lit_color.rgb, fog_color, fog_factor(length(vertPos))),
+                      lit_color.a * (1.0 - ssr_weight));
 }
@@ -2,21 +2,45 @@
 attribute vec3 position;
 attribute vec2 tex_coord;
 attribute vec3 normal;
+attribute vec4 inst_rot;
+attribute vec3 inst_tra;
+attribute vec3 inst_color;
 
 uniform mat3 ntransform, scale;
 uniform mat4 proj, view, transform;
 uniform vec3 light_position;
+uniform vec2 uv_offset, uv_scale;
+uniform int instanced;
 
 varying vec3 local_light_position;
 varying vec2 tex_coord_v;
 varying vec3 normalInterp;
 varying vec3 vertPos;
+varying vec3 worldPos;
+varying vec3 inst_color_v;
+
+// Rotates `v` by the unit quaternion `q`.
+vec3 rotate(vec4 q, vec3 v) {
+    return v + 2.0 * cross(q.xyz, cross(q.xyz, v) + q.w * v);
+}
 
 void main(){
-    gl_Position = proj * view * transform * vec4(scale * position, 1.0);
-    vec4 vertPos4 = view * transform * vec4(scale * position, 1.0);
+    vec3 local_pos = scale * position;
+    vec3 local_normal = normal;
+    inst_color_v = vec3(1.0);
+
+    if (instanced != 0) {
+        local_pos = rotate(inst_rot, local_pos) + inst_tra;
+        local_normal = rotate(inst_rot, normal);
+        inst_color_v = inst_color;
+    }
+
+    vec4 worldPos4 = transform * vec4(local_pos, 1.0);
+    worldPos = vec3(worldPos4) / worldPos4.w;
+    gl_Position = proj * view * worldPos4;
+    vec4 vertPos4 = view * worldPos4;
     vertPos = vec3(vertPos4) / vertPos4.w;
-    normalInterp = mat3(view) * ntransform * normal;
-    tex_coord_v = tex_coord;
+    normalInterp = mat3(view) * ntransform * local_normal;
+    tex_coord_v = tex_coord * uv_scale + uv_offset;
     local_light_position = (view * vec4(light_position, 1.0)).xyz;
 }
@@ -1,7 +1,10 @@
 //! Built-in geometries, shaders and effects.
 
 pub use self::normals_material::{NormalsMaterial, NORMAL_FRAGMENT_SRC, NORMAL_VERTEX_SRC};
-pub use self::object_material::{ObjectMaterial, OBJECT_FRAGMENT_SRC, OBJECT_VERTEX_SRC};
+pub(crate) use self::object_material::ShadowMapParams;
+pub use self::object_material::{
+    ObjectMaterial, DEFAULT_AMBIENT_LIGHT, MAX_CLIP_PLANES, OBJECT_FRAGMENT_SRC, OBJECT_VERTEX_SRC,
+};
 pub use self::uvs_material::{UvsMaterial, UVS_FRAGMENT_SRC, UVS_VERTEX_SRC};
 
 pub use self::planar_object_material::PlanarObjectMaterial;
@@ -1,51 +1,224 @@
 use crate::camera::Camera;
 use crate::context::Context;
+use crate::fog::FogMode;
 use crate::light::Light;
 use crate::resource::Material;
-use crate::resource::{Effect, Mesh, ShaderAttribute, ShaderUniform};
-use crate::scene::ObjectData;
-use na::{Isometry3, Matrix3, Matrix4, Point2, Point3, Vector3};
+use crate::resource::{
+    CubeMap, Effect, GLPrimitive, Mesh, ShaderAttribute, ShaderUniform, Texture,
+};
+use crate::scene::instances::InstancesBuffer;
+use crate::scene::{BlendMode, CullingMode, ObjectData, RENDER_LAYER_ALWAYS_ON_TOP};
+use na::{Isometry3, Matrix3, Matrix4, Point2, Point3, UnitQuaternion, Vector3, Vector4};
+use std::cell::RefCell;
+use std::rc::Rc;
 
 #[path = "../error.rs"]
 mod error;
 
+/// The maximum number of user-defined clip planes supported by the `ObjectMaterial`.
+pub const MAX_CLIP_PLANES: usize = 2;
+/// The default intensity of the ambient light of the objects rendered with `ObjectMaterial`.
+pub const DEFAULT_AMBIENT_LIGHT: f32 = 1.0 / 3.0;
+
+thread_local!(static KEY_CLIP_PLANES: RefCell<[Option<Vector4<f32>>; MAX_CLIP_PLANES]> = const { RefCell::new([None; MAX_CLIP_PLANES]) });
+thread_local!(static KEY_FOG: RefCell<FogMode> = const { RefCell::new(FogMode::None) });
+thread_local!(static KEY_AMBIENT: RefCell<f32> = const { RefCell::new(DEFAULT_AMBIENT_LIGHT) });
+thread_local!(static KEY_WIREFRAME: RefCell<bool> = const { RefCell::new(false) });
+thread_local!(static KEY_SSR_OUTPUT: RefCell<bool> = const { RefCell::new(false) });
+thread_local!(static KEY_SHADOW_MAP: RefCell<Option<ShadowMapParams>> = const { RefCell::new(None) });
+thread_local!(static KEY_IRRADIANCE_MAP: RefCell<Option<Rc<CubeMap>>> = const { RefCell::new(None) });
+thread_local!(static KEY_ENV_ROTATION: RefCell<UnitQuaternion<f32>> = RefCell::new(UnitQuaternion::identity()));
+
+/// A shadow map sampled by the `ObjectMaterial` to darken the shadowed fragments.
+pub(crate) struct ShadowMapParams {
+    /// The texture containing the packed depths seen from the light.
+    pub texture: Rc<Texture>,
+    /// The transformation from world-space to the light clip-space.
+    pub light_view_proj: Matrix4<f32>,
+    /// The number of pixels on each side of the texture.
+    pub resolution: u32,
+}
+
 /// The default material used to draw objects.
 pub struct ObjectMaterial {
     effect: Effect,
     pos: ShaderAttribute<Point3<f32>>,
     normal: ShaderAttribute<Vector3<f32>>,
     tex_coord: ShaderAttribute<Point2<f32>>,
+    inst_rot: ShaderAttribute<Vector4<f32>>,
+    inst_tra: ShaderAttribute<Point3<f32>>,
+    inst_color: ShaderAttribute<Point3<f32>>,
+    instanced: ShaderUniform<i32>,
     light: ShaderUniform<Point3<f32>>,
     color: ShaderUniform<Point3<f32>>,
     transform: ShaderUniform<Matrix4<f32>>,
     scale: ShaderUniform<Matrix3<f32>>,
+    uv_offset: ShaderUniform<Point2<f32>>,
+    uv_scale: ShaderUniform<Point2<f32>>,
     ntransform: ShaderUniform<Matrix3<f32>>,
     proj: ShaderUniform<Matrix4<f32>>,
     view: ShaderUniform<Matrix4<f32>>,
+    clip_planes: [ShaderUniform<Vector4<f32>>; MAX_CLIP_PLANES],
+    // (alpha, reflectivity, ssr_weight, double_sided)
+    material_params: ShaderUniform<Vector4<f32>>,
+    // (pbr, metallic, roughness, has_irradiance_map)
+    pbr_params: ShaderUniform<Vector4<f32>>,
+    // (ambient, shadow_enabled, shadow_texel)
+    lighting_params: ShaderUniform<Vector3<f32>>,
+    fog_color: ShaderUniform<Point3<f32>>,
+    // (near, far, density, mode)
+    fog_params: ShaderUniform<Vector4<f32>>,
+    shadow_map: ShaderUniform<i32>,
+    light_view_proj: ShaderUniform<Matrix4<f32>>,
+    env_map: ShaderUniform<i32>,
+    env_rotation: ShaderUniform<Matrix3<f32>>,
+    irradiance_map: ShaderUniform<i32>,
 }
 
 impl ObjectMaterial {
     /// Creates a new `ObjectMaterial`.
     pub fn new() -> ObjectMaterial {
+        ObjectMaterial::try_new_from_str(OBJECT_VERTEX_SRC, OBJECT_FRAGMENT_SRC)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a new `ObjectMaterial` from custom vertex and fragment shaders.
+    ///
+    /// The shaders must declare all the attributes and uniforms of the built-in shaders
+    /// `OBJECT_VERTEX_SRC` and `OBJECT_FRAGMENT_SRC`, and actually use them so they are not
+    /// optimized-out. Returns an error message if they fail to compile, or if any of these
+    /// variables is missing.
+    pub fn try_new_from_str(vshader: &str, fshader: &str) -> Result<ObjectMaterial, String> {
         // load the effect
-        let mut effect = Effect::new_from_str(OBJECT_VERTEX_SRC, OBJECT_FRAGMENT_SRC);
+        let mut effect = Effect::try_new_from_str(vshader, fshader)?;
 
         effect.use_program();
 
         // get the variables locations
-        ObjectMaterial {
-            pos: effect.get_attrib("position").unwrap(),
-            normal: effect.get_attrib("normal").unwrap(),
-            tex_coord: effect.get_attrib("tex_coord").unwrap(),
-            light: effect.get_uniform("light_position").unwrap(),
-            color: effect.get_uniform("color").unwrap(),
-            transform: effect.get_uniform("transform").unwrap(),
-            scale: effect.get_uniform("scale").unwrap(),
-            ntransform: effect.get_uniform("ntransform").unwrap(),
-            view: effect.get_uniform("view").unwrap(),
-            proj: effect.get_uniform("proj").unwrap(),
+        Ok(ObjectMaterial {
+            pos: attrib(&effect, "position")?,
+            normal: attrib(&effect, "normal")?,
+            tex_coord: attrib(&effect, "tex_coord")?,
+            inst_rot: attrib(&effect, "inst_rot")?,
+            inst_tra: attrib(&effect, "inst_tra")?,
+            inst_color: attrib(&effect, "inst_color")?,
+            instanced: uniform(&effect, "instanced")?,
+            light: uniform(&effect, "light_position")?,
+            color: uniform(&effect, "color")?,
+            transform: uniform(&effect, "transform")?,
+            scale: uniform(&effect, "scale")?,
+            uv_offset: uniform(&effect, "uv_offset")?,
+            uv_scale: uniform(&effect, "uv_scale")?,
+            ntransform: uniform(&effect, "ntransform")?,
+            view: uniform(&effect, "view")?,
+            proj: uniform(&effect, "proj")?,
+            clip_planes: [
+                uniform(&effect, "clip_plane0")?,
+                uniform(&effect, "clip_plane1")?,
+            ],
+            material_params: uniform(&effect, "material_params")?,
+            pbr_params: uniform(&effect, "pbr_params")?,
+            lighting_params: uniform(&effect, "lighting_params")?,
+            fog_color: uniform(&effect, "fog_color")?,
+            fog_params: uniform(&effect, "fog_params")?,
+            shadow_map: uniform(&effect, "shadow_map")?,
+            light_view_proj: uniform(&effect, "light_view_proj")?,
+            env_map: uniform(&effect, "env_map")?,
+            env_rotation: uniform(&effect, "env_rotation")?,
+            irradiance_map: uniform(&effect, "irradiance_map")?,
             effect: effect,
-        }
+        })
+    }
+
+    /// Sets the `i`-th clip plane used by every object rendered with this material.
+    ///
+    /// The plane is given by its equation `(a, b, c, d)` in world-space, i.e., only the points
+    /// `p` satisfying `a * p.x + b * p.y + c * p.z + d >= 0` are kept. Set to `None` to disable
+    /// the clip plane.
+    ///
+    /// Panics if `i >= MAX_CLIP_PLANES`.
+    pub fn set_clip_plane(i: usize, plane: Option<Vector4<f32>>) {
+        assert!(
+            i < MAX_CLIP_PLANES,
+            "Only {} clip planes are supported.",
+            MAX_CLIP_PLANES
+        );
+        KEY_CLIP_PLANES.with(|planes| planes.borrow_mut()[i] = plane)
+    }
+
+    /// The `i`-th clip plane used by every object rendered with this material.
+    pub fn clip_plane(i: usize) -> Option<Vector4<f32>> {
+        KEY_CLIP_PLANES.with(|planes| planes.borrow()[i])
+    }
+
+    /// Sets the fog applied to every object rendered with this material.
+    pub fn set_fog(fog: FogMode) {
+        KEY_FOG.with(|f| *f.borrow_mut() = fog)
+    }
+
+    /// The fog applied to every object rendered with this material.
+    pub fn fog() -> FogMode {
+        KEY_FOG.with(|f| *f.borrow())
+    }
+
+    /// Sets the intensity of the ambient light, clamped to `[0.0, 1.0]`.
+    ///
+    /// This is the fraction of the color of the objects lit regardless of the light direction:
+    /// `1.0` disables all directional shading. Defaults to `1.0 / 3.0`.
+    pub fn set_ambient_light(intensity: f32) {
+        KEY_AMBIENT.with(|a| *a.borrow_mut() = intensity.clamp(0.0, 1.0))
+    }
+
+    /// The intensity of the ambient light.
+    pub fn ambient_light() -> f32 {
+        KEY_AMBIENT.with(|a| *a.borrow())
+    }
+
+    /// Sets whether every object rendered with this material is drawn as a wireframe.
+    ///
+    /// The edges of the objects surfaces are then drawn with their color instead of the
+    /// surfaces themselves.
+    pub fn set_wireframe(wireframe: bool) {
+        KEY_WIREFRAME.with(|w| *w.borrow_mut() = wireframe)
+    }
+
+    /// Whether every object rendered with this material is drawn as a wireframe.
+    pub fn wireframe() -> bool {
+        KEY_WIREFRAME.with(|w| *w.borrow())
+    }
+
+    /// Sets whether the reflectivity of the screen-space reflectors is written to the alpha
+    /// channel, for the `ScreenSpaceReflection` effect.
+    pub(crate) fn set_ssr_output(enabled: bool) {
+        KEY_SSR_OUTPUT.with(|s| *s.borrow_mut() = enabled)
+    }
+
+    /// Sets the diffuse irradiance cube-map replacing the uniform ambient light of the objects
+    /// with a physically-based material.
+    pub fn set_irradiance_map(irradiance_map: Option<Rc<CubeMap>>) {
+        KEY_IRRADIANCE_MAP.with(|m| *m.borrow_mut() = irradiance_map)
+    }
+
+    /// The diffuse irradiance cube-map replacing the uniform ambient light of the objects with a
+    /// physically-based material.
+    pub fn irradiance_map() -> Option<Rc<CubeMap>> {
+        KEY_IRRADIANCE_MAP.with(|m| m.borrow().clone())
+    }
+
+    /// Sets the rotation of the environment and irradiance cube-maps, relative to the world
+    /// axes, e.g., to follow the rotation of the skybox.
+    pub fn set_environment_rotation(rotation: UnitQuaternion<f32>) {
+        KEY_ENV_ROTATION.with(|r| *r.borrow_mut() = rotation)
+    }
+
+    /// The rotation of the environment and irradiance cube-maps, relative to the world axes.
+    pub fn environment_rotation() -> UnitQuaternion<f32> {
+        KEY_ENV_ROTATION.with(|r| *r.borrow())
+    }
+
+    /// Sets the shadow map used to darken the fragments hidden from the light.
+    pub(crate) fn set_shadow_map(shadow_map: Option<ShadowMapParams>) {
+        KEY_SHADOW_MAP.with(|s| *s.borrow_mut() = shadow_map)
     }
 
     fn activate(&mut self) {
@@ -60,6 +233,65 @@ impl ObjectMaterial {
         self.normal.disable();
         self.tex_coord.disable();
     }
+
+    // Binds the per-instance attributes, if the instances are not drawn one at a time.
+    fn bind_instances(&mut self, instances: &mut InstancesBuffer) {
+        if Context::get().supports_instancing() {
+            self.inst_rot.enable();
+            self.inst_tra.enable();
+            self.inst_color.enable();
+            self.inst_rot.bind(&mut instances.rotations);
+            self.inst_tra.bind(&mut instances.translations);
+            self.inst_color.bind(&mut instances.colors);
+            self.inst_rot.set_divisor(1);
+            self.inst_tra.set_divisor(1);
+            self.inst_color.set_divisor(1);
+        }
+    }
+
+    fn unbind_instances(&mut self) {
+        if Context::get().supports_instancing() {
+            self.inst_rot.set_divisor(0);
+            self.inst_tra.set_divisor(0);
+            self.inst_color.set_divisor(0);
+            self.inst_rot.disable();
+            self.inst_tra.disable();
+            self.inst_color.disable();
+        }
+    }
+
+    // Draws the bound mesh, once for each instance if there are some.
+    fn draw_elements(
+        &mut self,
+        mode: u32,
+        count: i32,
+        index_type: u32,
+        instances: Option<&InstancesBuffer>,
+    ) {
+        let ctxt = Context::get();
+
+        match instances {
+            None => verify!(ctxt.draw_elements(mode, count, index_type, 0)),
+            Some(instances) if ctxt.supports_instancing() => verify!(ctxt.draw_elements_instanced(
+                mode,
+                count,
+                index_type,
+                0,
+                instances.len() as i32
+            )),
+            Some(instances) => {
+                // Without instancing support, the disabled per-instance attributes are set to
+                // the values of each instance in turn.
+                for instance in &instances.data {
+                    self.inst_rot.set_value(&instance.transform.rotation.coords);
+                    self.inst_tra
+                        .set_value(&Point3::from(instance.transform.translation.vector));
+                    self.inst_color.set_value(&instance.color);
+                    verify!(ctxt.draw_elements(mode, count, index_type, 0));
+                }
+            }
+        }
+    }
 }
 
 impl Material for ObjectMaterial {
@@ -90,6 +322,69 @@ impl Material for ObjectMaterial {
 
         self.light.upload(&pos);
 
+        KEY_CLIP_PLANES.with(|planes| {
+            for (uniform, plane) in self.clip_planes.iter_mut().zip(planes.borrow().iter()) {
+                // A disabled clip plane is replaced by one that never clips anything.
+                uniform.upload(&plane.unwrap_or(Vector4::w()));
+            }
+        });
+
+        let (color, params) = match ObjectMaterial::fog() {
+            FogMode::None => (Point3::origin(), Vector4::zeros()),
+            FogMode::Linear { near, far, color } => (color, Vector4::new(near, far, 0.0, 1.0)),
+            FogMode::Exponential { density, color } => {
+                (color, Vector4::new(0.0, 0.0, density, 2.0))
+            }
+            FogMode::ExponentialSquared { density, color } => {
+                (color, Vector4::new(0.0, 0.0, density, 3.0))
+            }
+        };
+
+        self.fog_color.upload(&color);
+        self.fog_params.upload(&params);
+
+        let shadow_texel = KEY_SHADOW_MAP.with(|shadow_map| match *shadow_map.borrow() {
+            Some(ref shadow_map) => {
+                verify!(ctxt.active_texture(Context::TEXTURE1));
+                verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&*shadow_map.texture)));
+                verify!(ctxt.active_texture(Context::TEXTURE0));
+
+                self.shadow_map.upload(&1);
+                self.light_view_proj.upload(&shadow_map.light_view_proj);
+                Some(1.0 / shadow_map.resolution as f32)
+            }
+            None => None,
+        });
+
+        self.lighting_params.upload(&Vector3::new(
+            ObjectMaterial::ambient_light(),
+            shadow_texel.is_some() as i32 as f32,
+            shadow_texel.unwrap_or(0.0),
+        ));
+
+        let has_irradiance_map = KEY_IRRADIANCE_MAP.with(|m| match *m.borrow() {
+            Some(ref irradiance_map) => {
+                verify!(ctxt.active_texture(Context::TEXTURE3));
+                verify!(
+                    ctxt.bind_texture(Context::TEXTURE_CUBE_MAP, Some(irradiance_map.texture()))
+                );
+                verify!(ctxt.active_texture(Context::TEXTURE0));
+                true
+            }
+            None => false,
+        });
+
+        // Both cube samplers must have their own texture unit.
+        self.irradiance_map.upload(&3);
+
+        // The reflections and the irradiance are computed in view-space, but the cube-maps are
+        // in world-space, possibly rotated.
+        let env_rotation = (ObjectMaterial::environment_rotation().inverse()
+            * camera.view_transform().rotation.inverse())
+        .to_rotation_matrix()
+        .into_inner();
+        self.env_rotation.upload(&env_rotation);
+
         /*
          *
          * Setup object-related stuffs.
@@ -103,52 +398,159 @@ impl Material for ObjectMaterial {
             self.transform.upload(&formated_transform);
             self.ntransform.upload(&formated_ntransform);
             self.scale.upload(&formated_scale);
+            self.uv_offset.upload(data.texture_uv_offset());
+            self.uv_scale.upload(data.texture_uv_scale());
 
             mesh.bind(&mut self.pos, &mut self.normal, &mut self.tex_coord);
+            mesh.bind_vertex_attributes(&self.effect);
+
+            let mut instances = data.instances().map(|instances| instances.borrow_mut());
+            if let Some(ref mut instances) = instances {
+                self.bind_instances(instances);
+            }
+            let instances = instances.as_ref().map(|instances| &**instances);
+            self.instanced.upload(&(instances.is_some() as i32));
 
             verify!(ctxt.active_texture(Context::TEXTURE0));
             verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&*data.texture())));
 
-            if data.surface_rendering_active() {
+            let pbr_params = match data.metallic_roughness() {
+                Some((metallic, roughness)) => {
+                    Vector4::new(1.0, metallic, roughness, has_irradiance_map as i32 as f32)
+                }
+                None => Vector4::zeros(),
+            };
+            self.pbr_params.upload(&pbr_params);
+
+            // The cube sampler must never share its texture unit with the 2D samplers.
+            self.env_map.upload(&2);
+
+            if let Some(environment_map) = data.environment_map() {
+                verify!(ctxt.active_texture(Context::TEXTURE2));
+                verify!(
+                    ctxt.bind_texture(Context::TEXTURE_CUBE_MAP, Some(environment_map.texture()))
+                );
+                verify!(ctxt.active_texture(Context::TEXTURE0));
+            }
+
+            let reflectivity = if data.environment_map().is_some() {
+                data.reflectivity()
+            } else {
+                0.0
+            };
+
+            let ssr_output = KEY_SSR_OUTPUT.with(|s| *s.borrow());
+            let ssr_weight = if ssr_output && data.ssr_reflector() && !data.is_transparent() {
+                data.reflectivity()
+            } else {
+                0.0
+            };
+
+            self.material_params.upload(&Vector4::new(
+                data.alpha(),
+                reflectivity,
+                ssr_weight,
+                data.double_sided() as i32 as f32,
+            ));
+
+            if data.is_transparent() {
+                verify!(ctxt.enable(Context::BLEND));
+
+                let (src, dst) = data.blend_mode().factors();
+
+                verify!(ctxt.blend_func_separate(
+                    src,
+                    dst,
+                    Context::ONE,
+                    Context::ONE_MINUS_SRC_ALPHA,
+                ));
+
+                if disables_depth_writes(data) {
+                    verify!(ctxt.depth_mask(false));
+                }
+            }
+
+            let wireframe = ObjectMaterial::wireframe();
+            let draw_lines =
+                data.lines_width() != 0.0 || (wireframe && data.surface_rendering_active());
+
+            if data.surface_rendering_active() && !wireframe {
                 self.color.upload(data.color());
 
-                if data.backface_culling_enabled() {
-                    verify!(ctxt.enable(Context::CULL_FACE));
+                let culling_mode = if data.double_sided() {
+                    CullingMode::None
                 } else {
-                    verify!(ctxt.disable(Context::CULL_FACE));
+                    data.culling_mode()
+                };
+
+                match culling_mode {
+                    CullingMode::None => verify!(ctxt.disable(Context::CULL_FACE)),
+                    CullingMode::Back => verify!(ctxt.enable(Context::CULL_FACE)),
+                    CullingMode::Front => {
+                        verify!(ctxt.enable(Context::CULL_FACE));
+                        verify!(ctxt.cull_face(Context::FRONT));
+                    }
+                }
+
+                // By default, push the surface slightly back so the lines drawn on top of it
+                // don't z-fight.
+                let depth_bias =
+                    data.depth_bias()
+                        .or(if draw_lines { Some((1.0, 1.0)) } else { None });
+
+                if let Some((factor, units)) = depth_bias {
+                    verify!(ctxt.enable(Context::POLYGON_OFFSET_FILL));
+                    verify!(ctxt.polygon_offset(factor, units));
                 }
 
                 let _ = verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::FILL));
-                verify!(ctxt.draw_elements(
+                self.draw_elements(
                     Context::TRIANGLES,
                     mesh.num_pts() as i32,
-                    Context::UNSIGNED_SHORT,
-                    0
-                ));
+                    mesh.index_type().to_gl(),
+                    instances,
+                );
+
+                if depth_bias.is_some() {
+                    verify!(ctxt.disable(Context::POLYGON_OFFSET_FILL));
+                }
+
+                // Restore the default culling state.
+                match culling_mode {
+                    CullingMode::None => verify!(ctxt.enable(Context::CULL_FACE)),
+                    CullingMode::Back => {}
+                    CullingMode::Front => verify!(ctxt.cull_face(Context::BACK)),
+                }
             }
 
-            if data.lines_width() != 0.0 {
+            if draw_lines {
+                let lines_width = if data.lines_width() != 0.0 {
+                    data.lines_width()
+                } else {
+                    1.0
+                };
+
                 self.color
                     .upload(data.lines_color().unwrap_or(data.color()));
 
                 verify!(ctxt.disable(Context::CULL_FACE));
-                ignore!(ctxt.line_width(data.lines_width()));
+                ignore!(ctxt.line_width(lines_width));
 
                 if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::LINE)) {
-                    verify!(ctxt.draw_elements(
+                    self.draw_elements(
                         Context::TRIANGLES,
                         mesh.num_pts() as i32,
-                        Context::UNSIGNED_SHORT,
-                        0
-                    ));
+                        mesh.index_type().to_gl(),
+                        instances,
+                    );
                 } else {
                     mesh.bind_edges();
-                    verify!(ctxt.draw_elements(
+                    self.draw_elements(
                         Context::LINES,
                         mesh.num_pts() as i32 * 2,
-                        Context::UNSIGNED_SHORT,
-                        0
-                    ));
+                        mesh.index_type().to_gl(),
+                        instances,
+                    );
                 }
                 ctxt.line_width(1.0);
             }
@@ -159,29 +561,73 @@ impl Material for ObjectMaterial {
                 verify!(ctxt.disable(Context::CULL_FACE));
                 ctxt.point_size(data.points_size());
                 if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::POINT)) {
-                    verify!(ctxt.draw_elements(
+                    self.draw_elements(
                         Context::TRIANGLES,
                         mesh.num_pts() as i32,
-                        Context::UNSIGNED_SHORT,
-                        0
-                    ));
+                        mesh.index_type().to_gl(),
+                        instances,
+                    );
                 } else {
-                    verify!(ctxt.draw_elements(
+                    self.draw_elements(
                         Context::POINTS,
                         mesh.num_pts() as i32,
-                        Context::UNSIGNED_SHORT,
-                        0
-                    ));
+                        mesh.index_type().to_gl(),
+                        instances,
+                    );
                 }
                 ctxt.point_size(1.0);
             }
         }
 
+        if data.is_transparent() {
+            verify!(ctxt.disable(Context::BLEND));
+
+            if disables_depth_writes(data) {
+                verify!(ctxt.depth_mask(true));
+            }
+        }
+
+        if shadow_texel.is_some() {
+            verify!(ctxt.active_texture(Context::TEXTURE1));
+            verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
+            verify!(ctxt.active_texture(Context::TEXTURE0));
+        }
+
+        if data.environment_map().is_some() {
+            verify!(ctxt.active_texture(Context::TEXTURE2));
+            verify!(ctxt.bind_texture(Context::TEXTURE_CUBE_MAP, None));
+            verify!(ctxt.active_texture(Context::TEXTURE0));
+        }
+
+        if data.instances().is_some() {
+            self.unbind_instances();
+        }
+
+        mesh.unbind_vertex_attributes(&self.effect);
         mesh.unbind();
         self.deactivate();
     }
 }
 
+fn attrib<T: GLPrimitive>(effect: &Effect, name: &str) -> Result<ShaderAttribute<T>, String> {
+    effect
+        .get_attrib(name)
+        .ok_or_else(|| format!("Missing or unused shader attribute: {}", name))
+}
+
+fn uniform<T: GLPrimitive>(effect: &Effect, name: &str) -> Result<ShaderUniform<T>, String> {
+    effect
+        .get_uniform(name)
+        .ok_or_else(|| format!("Missing or unused shader uniform: {}", name))
+}
+
+// Whether the depth writes must be disabled while drawing a blended object. The depth writes of
+// the always-on-top layers are already disabled by the scene graph.
+fn disables_depth_writes(data: &ObjectData) -> bool {
+    data.blend_mode() != BlendMode::Alpha
+        && data.effective_render_layer() < RENDER_LAYER_ALWAYS_ON_TOP
+}
+
 /// Vertex shader of the default object material.
 pub static OBJECT_VERTEX_SRC: &'static str = A_VERY_LONG_STRING;
 /// Fragment shader of the default object material.
@@ -3,7 +3,7 @@ use crate::context::Context;
 use crate::light::Light;
 use crate::resource::Material;
 use crate::resource::{Effect, Mesh, ShaderAttribute, ShaderUniform};
-use crate::scene::ObjectData;
+use crate::scene::{CullingMode, ObjectData};
 use na::{Isometry3, Matrix3, Matrix4, Point2, Point3, Vector3};
 
 #[path = "../error.rs"]
@@ -56,10 +56,13 @@ impl Material for UvsMaterial {
 
         let ctxt = Context::get();
         // enable/disable culling.
-        if data.backface_culling_enabled() {
-            verify!(ctxt.enable(Context::CULL_FACE));
-        } else {
-            verify!(ctxt.disable(Context::CULL_FACE));
+        match data.culling_mode() {
+            CullingMode::None => verify!(ctxt.disable(Context::CULL_FACE)),
+            CullingMode::Back => verify!(ctxt.enable(Context::CULL_FACE)),
+            CullingMode::Front => {
+                verify!(ctxt.enable(Context::CULL_FACE));
+                verify!(ctxt.cull_face(Context::FRONT));
+            }
         }
 
         self.shader.use_program();
@@ -92,11 +95,16 @@ impl Material for UvsMaterial {
             ctxt.draw_elements(
                 Context::TRIANGLES,
                 mesh.num_pts() as i32,
-                Context::UNSIGNED_INT,
+                mesh.index_type().to_gl(),
                 0,
             );
         }
 
+        // Restore the default culled faces.
+        if data.culling_mode() == CullingMode::Front {
+            verify!(ctxt.cull_face(Context::BACK));
+        }
+
         mesh.unbind();
 
         self.position.disable();
@@ -1,8 +1,10 @@
-use crate::camera::Camera;
+use crate::camera::{camera, Camera};
 use crate::event::{Action, Key, Modifiers, MouseButton, WindowEvent};
 use crate::resource::ShaderUniform;
 use crate::window::Canvas;
+use instant::Instant;
 use na::{self, Isometry3, Matrix4, Perspective3, Point3, Unit, UnitQuaternion, Vector2, Vector3};
+use ncollide3d::bounding_volume::AABB;
 use std::f32;
 
 /// Arc-ball camera mode.
@@ -14,7 +16,18 @@ use std::f32;
 /// * Right button press + drag - translates the focus point on the plane orthogonal to the view
 /// direction
 /// * Scroll in/out - zoom in/out
-/// * Enter key - set the focus point to the origin
+/// * Enter key - set the focus point to the origin, or restore the configuration given to
+/// `reset_to`
+///
+/// The camera starts rotating or translating only once the cursor moved farther than the drag
+/// threshold (see `set_drag_threshold`) since the button was pressed. Use `clicked` to detect
+/// the button releases that did not move the camera, e.g., to select an object under the cursor.
+///
+/// By default, the camera follows the inputs instantly. Use `enable_zoom_inertia`,
+/// `enable_pan_inertia`, and `enable_orbit_inertia` to let the camera keep moving with a
+/// decaying velocity once the inputs stop. The velocities are integrated with the frame
+/// duration given to `Camera::update_with_delta_time`, i.e., `Window::delta_time` when the
+/// window updates the camera.
 #[derive(Clone, Debug)]
 pub struct ArcBall {
     /// The focus point.
@@ -45,6 +58,24 @@ pub struct ArcBall {
     drag_button: Option<MouseButton>,
     drag_modifiers: Option<Modifiers>,
     reset_key: Option<Key>,
+    reset_state: Option<(Point3<f32>, Point3<f32>)>,
+    /// Distance, in pixels, the cursor must move before a press starts a drag.
+    drag_threshold: f32,
+    /// Cursor position when the rotate or drag button was pressed, while the press is not a drag.
+    drag_origin: Option<Vector2<f32>>,
+    clicked: bool,
+
+    /// Decay of the velocities, per 1/60 second, when inertia is enabled.
+    zoom_inertia: Option<f32>,
+    pan_inertia: Option<f32>,
+    orbit_inertia: Option<f32>,
+    /// Velocity of the distance, in units per second.
+    zoom_velocity: f32,
+    /// Velocity of the cursor displacement applied to the focus point, in pixels per second.
+    pan_velocity: Vector2<f32>,
+    /// Velocity of the cursor displacement applied to the yaw and pitch, in pixels per second.
+    orbit_velocity: Vector2<f32>,
+    last_cursor_time: Instant,
 
     projection: Perspective3<f32>,
     view: Matrix4<f32>,
@@ -86,6 +117,17 @@ impl ArcBall {
             drag_button: Some(MouseButton::Button2),
             drag_modifiers: None,
             reset_key: Some(Key::Return),
+            reset_state: None,
+            drag_threshold: 5.0,
+            drag_origin: None,
+            clicked: false,
+            zoom_inertia: None,
+            pan_inertia: None,
+            orbit_inertia: None,
+            zoom_velocity: 0.0,
+            pan_velocity: na::zero(),
+            orbit_velocity: na::zero(),
+            last_cursor_time: Instant::now(),
             projection: Perspective3::new(800.0 / 600.0, fov, znear, zfar),
             view: na::zero(),
             proj: na::zero(),
@@ -196,23 +238,69 @@ impl ArcBall {
     }
 
     /// Move and orient the camera such that it looks at a specific point.
+    ///
+    /// If `eye` is directly above or below `at`, the pitch is clamped such that the camera stays
+    /// at least 0.1 degrees away from the vertical axis. If `eye` and `at` coincide, the current
+    /// orientation of the camera is preserved.
     pub fn look_at(&mut self, eye: Point3<f32>, at: Point3<f32>) {
-        let dist = (eye - at).norm();
+        // Maximum angle between the view direction and the horizontal plane, i.e., 89.9°.
+        const MAX_ELEVATION: f32 = f32::consts::FRAC_PI_2 * (89.9 / 90.0);
 
-        let view_eye = self.coord_system.rotation_to_y_up * eye;
-        let view_at = self.coord_system.rotation_to_y_up * at;
-        let pitch = ((view_eye.y - view_at.y) / dist).acos();
-        let yaw = (view_eye.z - view_at.z).atan2(view_eye.x - view_at.x);
+        let dist = (eye - at).norm();
 
         self.at = at;
         self.dist = dist;
-        self.yaw = yaw;
-        self.pitch = pitch;
+
+        if dist > 0.0 {
+            let view_eye = self.coord_system.rotation_to_y_up * eye;
+            let view_at = self.coord_system.rotation_to_y_up * at;
+            let cos_pitch = ((view_eye.y - view_at.y) / dist).clamp(-1.0, 1.0);
+            let pitch = cos_pitch.acos().max(f32::consts::FRAC_PI_2 - MAX_ELEVATION);
+
+            self.pitch = pitch.min(f32::consts::FRAC_PI_2 + MAX_ELEVATION);
+
+            if view_eye.x != view_at.x || view_eye.z != view_at.z {
+                self.yaw = (view_eye.z - view_at.z).atan2(view_eye.x - view_at.x);
+            }
+        }
 
         self.update_restrictions();
         self.update_projviews();
     }
 
+    /// Moves the camera, without changing its orientation, such that the whole `aabb` is in
+    /// view.
+    ///
+    /// The focus point becomes the center of `aabb`, and the distance is chosen such that the
+    /// bounding sphere of `aabb`, with its radius multiplied by `padding`, fits in the field of
+    /// view. The maximum distance and the far clipping plane are pushed back if needed to keep
+    /// the whole box visible. A `padding` of `1.1` leaves a small margin around the box.
+    pub fn fit_to(&mut self, aabb: &AABB<f32>, padding: f32) {
+        let radius = (aabb.half_extents().norm() * padding).max(f32::EPSILON);
+        let half_fovy = self.projection.fovy() / 2.0;
+        // The narrowest of the vertical and horizontal fields of view.
+        let half_fov = half_fovy.min((half_fovy.tan() * self.projection.aspect()).atan());
+        let dist = radius / half_fov.sin();
+
+        self.at = aabb.center();
+        self.dist = dist;
+        self.max_dist = self.max_dist.max(dist);
+
+        if self.projection.zfar() < dist + radius {
+            self.projection.set_zfar(dist + radius);
+        }
+
+        self.update_restrictions();
+        self.update_projviews();
+    }
+
+    /// Move and orient the camera such that it looks at a specific point, and use this
+    /// configuration as the one restored when the reset key is pressed.
+    pub fn reset_to(&mut self, eye: Point3<f32>, at: Point3<f32>) {
+        self.reset_state = Some((eye, at));
+        self.look_at(eye, at)
+    }
+
     /// Transformation applied by the camera without perspective.
     fn update_restrictions(&mut self) {
         if self.dist < self.min_dist {
@@ -232,6 +320,88 @@ impl ArcBall {
         }
     }
 
+    /// Enables inertia for the zoom.
+    ///
+    /// Scrolling then sets a zoom velocity instead of changing the distance instantly. At each
+    /// frame, the velocity is applied to the distance and multiplied by `decay` (per 1/60
+    /// second, independently of the frame rate). The total zoom of a scroll is the same as
+    /// without inertia. `decay` must be in `[0.0, 1.0)`, `0.0` being the instant response.
+    pub fn enable_zoom_inertia(&mut self, decay: f32) {
+        self.zoom_inertia = Some(decay.clamp(0.0, 0.99));
+    }
+
+    /// Disables inertia for the zoom, restoring the instant response.
+    pub fn disable_zoom_inertia(&mut self) {
+        self.zoom_inertia = None;
+        self.zoom_velocity = 0.0;
+    }
+
+    /// Enables inertia for the translation of the focus point.
+    ///
+    /// Once the drag button is released, the camera keeps moving with the last drag velocity,
+    /// multiplied by `decay` at each frame (per 1/60 second, independently of the frame rate).
+    /// `decay` must be in `[0.0, 1.0)`.
+    pub fn enable_pan_inertia(&mut self, decay: f32) {
+        self.pan_inertia = Some(decay.clamp(0.0, 0.99));
+    }
+
+    /// Disables inertia for the translation of the focus point.
+    pub fn disable_pan_inertia(&mut self) {
+        self.pan_inertia = None;
+        self.pan_velocity = na::zero();
+    }
+
+    /// Enables inertia for the rotation around the focus point.
+    ///
+    /// Once the rotate button is released, the camera keeps rotating with the last rotation
+    /// velocity, multiplied by `decay` at each frame (per 1/60 second, independently of the
+    /// frame rate). `decay` must be in `[0.0, 1.0)`.
+    pub fn enable_orbit_inertia(&mut self, decay: f32) {
+        self.orbit_inertia = Some(decay.clamp(0.0, 0.99));
+    }
+
+    /// Disables inertia for the rotation around the focus point.
+    pub fn disable_orbit_inertia(&mut self) {
+        self.orbit_inertia = None;
+        self.orbit_velocity = na::zero();
+    }
+
+    /// The current zoom velocity, in distance units per second.
+    pub fn zoom_velocity(&self) -> f32 {
+        self.zoom_velocity
+    }
+
+    /// Sets the current zoom velocity, in distance units per second.
+    ///
+    /// This has an effect only if the zoom inertia is enabled.
+    pub fn set_zoom_velocity(&mut self, velocity: f32) {
+        self.zoom_velocity = velocity;
+    }
+
+    /// The current translation velocity, as a cursor displacement in pixels per second.
+    pub fn pan_velocity(&self) -> Vector2<f32> {
+        self.pan_velocity
+    }
+
+    /// Sets the current translation velocity, as a cursor displacement in pixels per second.
+    ///
+    /// This has an effect only if the pan inertia is enabled.
+    pub fn set_pan_velocity(&mut self, velocity: Vector2<f32>) {
+        self.pan_velocity = velocity;
+    }
+
+    /// The current rotation velocity, as a cursor displacement in pixels per second.
+    pub fn orbit_velocity(&self) -> Vector2<f32> {
+        self.orbit_velocity
+    }
+
+    /// Sets the current rotation velocity, as a cursor displacement in pixels per second.
+    ///
+    /// This has an effect only if the orbit inertia is enabled.
+    pub fn set_orbit_velocity(&mut self, velocity: Vector2<f32>) {
+        self.orbit_velocity = velocity;
+    }
+
     /// The button used to rotate the ArcBall camera.
     pub fn rotate_button(&self) -> Option<MouseButton> {
         self.rotate_button
@@ -293,6 +463,28 @@ impl ArcBall {
         self.reset_key = new_key;
     }
 
+    /// The distance, in pixels, the cursor must move while a button is pressed before the camera
+    /// starts rotating or translating. The default value is 5.0.
+    pub fn drag_threshold(&self) -> f32 {
+        self.drag_threshold
+    }
+
+    /// Sets the distance, in pixels, the cursor must move while a button is pressed before the
+    /// camera starts rotating or translating.
+    ///
+    /// Set this to 0.0 to start moving the camera as soon as the cursor moves.
+    pub fn set_drag_threshold(&mut self, threshold: f32) {
+        self.drag_threshold = threshold;
+    }
+
+    /// Whether the rotate or drag button was released without moving the cursor farther than the
+    /// drag threshold since it was pressed.
+    ///
+    /// This remains `true` until one of those buttons is pressed again.
+    pub fn clicked(&self) -> bool {
+        self.clicked
+    }
+
     fn handle_left_button_displacement(&mut self, dpos: &Vector2<f32>) {
         self.yaw = self.yaw + dpos.x * self.yaw_step;
         self.pitch = self.pitch - dpos.y * self.pitch_step;
@@ -313,9 +505,24 @@ impl ArcBall {
     }
 
     fn handle_scroll(&mut self, off: f32) {
-        self.dist = self.dist + self.dist_step * (off) / 120.0;
-        self.update_restrictions();
-        self.update_projviews();
+        let displacement = self.dist_step * (off) / 120.0;
+
+        match self.zoom_inertia {
+            // With a velocity decaying as `decay^(60t)`, the total displacement is
+            // `velocity / (-60 ln(decay))`.
+            Some(decay) if decay > 0.0 => {
+                self.zoom_velocity += displacement * -60.0 * decay.ln();
+            }
+            _ => {
+                self.dist = self.dist + displacement;
+                self.update_restrictions();
+                self.update_projviews();
+            }
+        }
+    }
+
+    fn is_pressed(canvas: &Canvas, button: Option<MouseButton>) -> bool {
+        button.map_or(false, |b| canvas.get_mouse_button(b) == Action::Press)
     }
 
     fn update_projviews(&mut self) {
@@ -352,6 +559,23 @@ impl Camera for ArcBall {
         (self.projection.znear(), self.projection.zfar())
     }
 
+    fn set_near_far(&mut self, near: f32, far: f32) -> Result<(), &'static str> {
+        camera::check_near_far(near, far)?;
+        self.projection.set_znear_and_zfar(near, far);
+        self.update_projviews();
+        Ok(())
+    }
+
+    fn fovy(&self) -> Option<f32> {
+        Some(self.projection.fovy())
+    }
+
+    fn set_fovy(&mut self, fovy: f32) -> Result<(), &'static str> {
+        self.projection.set_fovy(camera::clamp_fovy(fovy)?);
+        self.update_projviews();
+        Ok(())
+    }
+
     fn view_transform(&self) -> Isometry3<f32> {
         Isometry3::look_at_rh(&self.eye(), &self.at, &self.coord_system.up_axis)
     }
@@ -368,33 +592,80 @@ impl Camera for ArcBall {
         match *event {
             WindowEvent::CursorPos(x, y, modifiers) => {
                 let curr_pos = Vector2::new(x as f32, y as f32);
+                let now = Instant::now();
+                let cursor_dt = now
+                    .duration_since(self.last_cursor_time)
+                    .as_secs_f32()
+                    .max(1.0e-3);
+                let dpos = match self.drag_origin {
+                    // Still a click: don't move the camera yet.
+                    Some(origin) if (curr_pos - origin).norm() <= self.drag_threshold => None,
+                    // The drag starts: apply the whole displacement since the press.
+                    Some(origin) => {
+                        self.drag_origin = None;
+                        Some(curr_pos - origin)
+                    }
+                    None => Some(curr_pos - self.last_cursor_pos),
+                };
 
-                if let Some(rotate_button) = self.rotate_button {
+                if let (Some(rotate_button), Some(dpos)) = (self.rotate_button, dpos) {
                     if canvas.get_mouse_button(rotate_button) == Action::Press
                         && self
                             .rotate_modifiers
                             .map(|m| m == modifiers)
                             .unwrap_or(true)
                     {
-                        let dpos = curr_pos - self.last_cursor_pos;
-                        self.handle_left_button_displacement(&dpos)
+                        self.handle_left_button_displacement(&dpos);
+
+                        if self.orbit_inertia.is_some() {
+                            self.orbit_velocity = dpos / cursor_dt;
+                        }
                     }
                 }
 
-                if let Some(drag_button) = self.drag_button {
+                if let (Some(drag_button), Some(dpos)) = (self.drag_button, dpos) {
                     if canvas.get_mouse_button(drag_button) == Action::Press
                         && self.drag_modifiers.map(|m| m == modifiers).unwrap_or(true)
                     {
-                        let dpos = curr_pos - self.last_cursor_pos;
-                        self.handle_right_button_displacement(&dpos)
+                        self.handle_right_button_displacement(&dpos);
+
+                        if self.pan_inertia.is_some() {
+                            self.pan_velocity = dpos / cursor_dt;
+                        }
                     }
                 }
 
                 self.last_cursor_pos = curr_pos;
+                self.last_cursor_time = now;
             }
             WindowEvent::Key(key, Action::Press, _) if Some(key) == self.reset_key => {
-                self.at = Point3::origin();
-                self.update_projviews();
+                match self.reset_state {
+                    Some((eye, at)) => self.look_at(eye, at),
+                    None => {
+                        self.at = Point3::origin();
+                        self.update_projviews();
+                    }
+                }
+            }
+            WindowEvent::MouseButton(button, Action::Press, _) => {
+                // A new drag stops the current motion.
+                if Some(button) == self.rotate_button {
+                    self.orbit_velocity = na::zero();
+                }
+
+                if Some(button) == self.drag_button {
+                    self.pan_velocity = na::zero();
+                }
+
+                if Some(button) == self.rotate_button || Some(button) == self.drag_button {
+                    self.drag_origin = Some(self.last_cursor_pos);
+                    self.clicked = false;
+                }
+            }
+            WindowEvent::MouseButton(button, Action::Release, _)
+                if Some(button) == self.rotate_button || Some(button) == self.drag_button =>
+            {
+                self.clicked = self.drag_origin.take().is_some();
             }
             WindowEvent::Scroll(_, off, _) => self.handle_scroll(off as f32),
             WindowEvent::FramebufferSize(w, h) => {
@@ -424,7 +695,60 @@ impl Camera for ArcBall {
         self.inverse_proj_view
     }
 
-    fn update(&mut self, _: &Canvas) {}
+    fn update(&mut self, canvas: &Canvas) {
+        self.update_with_delta_time(canvas, 1.0 / 60.0)
+    }
+
+    fn update_with_delta_time(&mut self, canvas: &Canvas, dt: f32) {
+        // Avoid huge jumps after, e.g., the window has been minimized.
+        let dt = dt.clamp(0.0, 0.1);
+
+        if let Some(decay) = self.zoom_inertia {
+            if self.zoom_velocity != 0.0 {
+                self.dist = self.dist + self.zoom_velocity * dt;
+                self.zoom_velocity *= decay.powf(dt * 60.0);
+
+                if self.zoom_velocity.abs() < 1.0e-4 {
+                    self.zoom_velocity = 0.0;
+                }
+
+                self.update_restrictions();
+                self.update_projviews();
+            }
+        }
+
+        if let Some(decay) = self.orbit_inertia {
+            // The velocity is applied only once the rotate button is released.
+            if !ArcBall::is_pressed(canvas, self.rotate_button) {
+                let dpos = self.orbit_velocity * dt;
+                if dpos != Vector2::zeros() {
+                    self.handle_left_button_displacement(&dpos);
+                }
+            }
+
+            self.orbit_velocity *= decay.powf(dt * 60.0);
+
+            if self.orbit_velocity.norm_squared() < 1.0e-4 {
+                self.orbit_velocity = na::zero();
+            }
+        }
+
+        if let Some(decay) = self.pan_inertia {
+            // The velocity is applied only once the drag button is released.
+            if !ArcBall::is_pressed(canvas, self.drag_button) {
+                let dpos = self.pan_velocity * dt;
+                if dpos != Vector2::zeros() {
+                    self.handle_right_button_displacement(&dpos);
+                }
+            }
+
+            self.pan_velocity *= decay.powf(dt * 60.0);
+
+            if self.pan_velocity.norm_squared() < 1.0e-4 {
+                self.pan_velocity = na::zero();
+            }
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
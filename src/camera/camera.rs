@@ -2,6 +2,7 @@ use crate::event::WindowEvent;
 use crate::resource::ShaderUniform;
 use crate::window::Canvas;
 use na::{Isometry3, Matrix4, Point2, Point3, Point4, Vector2, Vector3};
+use ncollide3d::query::Ray;
 
 /// Trait every camera must implement.
 pub trait Camera {
@@ -26,6 +27,27 @@ pub trait Camera {
     fn inverse_transformation(&self) -> Matrix4<f32>;
     /// The clipping planes, aka. (`znear`, `zfar`).
     fn clip_planes(&self) -> (f32, f32); // FIXME: should this be here?
+    /// Sets the distances from the camera to the near and far clipping planes.
+    ///
+    /// Returns an error and leaves the camera unchanged if `near <= 0.0` or `far <= near`, or if
+    /// this camera does not support changing its clipping planes, which is the default.
+    fn set_near_far(&mut self, _near: f32, _far: f32) -> Result<(), &'static str> {
+        Err("This camera does not support changing its clipping planes.")
+    }
+    /// The vertical field of view of the perspective projection, in radians.
+    ///
+    /// Returns `None` if this camera has no perspective projection, which is the default.
+    fn fovy(&self) -> Option<f32> {
+        None
+    }
+    /// Sets the vertical field of view of the perspective projection, in radians.
+    ///
+    /// The field of view is clamped to `[0.01, π - 0.01]` so that the projection never
+    /// degenerates. Returns an error and leaves the camera unchanged if `fovy` is not finite,
+    /// or if this camera does not support changing its field of view, which is the default.
+    fn set_fovy(&mut self, _fovy: f32) -> Result<(), &'static str> {
+        Err("This camera does not support changing its field of view.")
+    }
 
     /*
      * Update & upload
@@ -33,6 +55,16 @@ pub trait Camera {
     /// Update the camera. This is called once at the beginning of the render loop.
     fn update(&mut self, canvas: &Canvas);
 
+    /// Update the camera, `dt` being the duration in seconds of the last frame.
+    ///
+    /// This is what the window calls at the beginning of the render loop, with
+    /// `Window::delta_time`. It defaults to `Camera::update`: cameras moving on their own, like
+    /// the `ArcBall` with inertia, use `dt` to move independently of the frame rate.
+    fn update_with_delta_time(&mut self, canvas: &Canvas, dt: f32) {
+        let _ = dt;
+        self.update(canvas)
+    }
+
     /// Upload the camera view and projection to the gpu. This can be called multiple times on the
     /// render loop.
     fn upload(
@@ -98,4 +130,113 @@ pub trait Camera {
             (unprojected_end - unprojected_begin).normalize(),
         )
     }
+
+    /// The ray starting on the near plane and going through the screen point `(x, y)`.
+    ///
+    /// The screen coordinates start at the top-left corner of a viewport of size `viewport`,
+    /// e.g., the window size for the positions given by `WindowEvent::CursorPos`. The ray
+    /// direction is normalized. This is useful for picking objects under the cursor.
+    fn screen_to_world_ray(&self, x: f32, y: f32, viewport: &Vector2<u32>) -> Ray<f32> {
+        let size = Vector2::new(viewport.x as f32, viewport.y as f32);
+        let (origin, dir) = self.unproject(&Point2::new(x, y), &size);
+
+        Ray::new(origin, dir)
+    }
+}
+
+/// Checks and clamps the field of view given to `Camera::set_fovy`.
+pub(crate) fn clamp_fovy(fovy: f32) -> Result<f32, &'static str> {
+    const MARGIN: f32 = 0.01;
+
+    if fovy.is_finite() {
+        Ok(fovy.clamp(MARGIN, std::f32::consts::PI - MARGIN))
+    } else {
+        Err("The field of view must be finite.")
+    }
+}
+
+/// Checks the clipping planes given to `Camera::set_near_far`.
+pub(crate) fn check_near_far(near: f32, far: f32) -> Result<(), &'static str> {
+    if !(near > 0.0) {
+        Err("The near clipping plane must be at a positive distance.")
+    } else if !(far > near) {
+        Err("The far clipping plane must be farther than the near clipping plane.")
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_near_far, clamp_fovy, Camera};
+    use crate::camera::{ArcBall, FixedView};
+    use na::{Point3, Vector2, Vector3};
+
+    fn assert_relative_eq(a: Vector3<f32>, b: Vector3<f32>) {
+        assert!((a - b).norm() < 1.0e-4, "{} != {}", a, b);
+    }
+
+    // The rays through the center and the corners of the viewport, in view-space.
+    fn check_rays(camera: &dyn Camera) {
+        let viewport = Vector2::new(800, 600);
+        let (near, _) = camera.clip_planes();
+        let view = camera.view_transform();
+        let half_height = (camera.fovy().unwrap() / 2.0).tan();
+        let half_width = half_height * 800.0 / 600.0;
+        let points = [
+            (400.0, 300.0, 0.0, 0.0),
+            (0.0, 0.0, -half_width, half_height),
+            (800.0, 0.0, half_width, half_height),
+            (0.0, 600.0, -half_width, -half_height),
+            (800.0, 600.0, half_width, -half_height),
+        ];
+
+        for &(x, y, dx, dy) in points.iter() {
+            let ray = camera.screen_to_world_ray(x, y, &viewport);
+            let expected_dir = Vector3::new(dx, dy, -1.0);
+
+            assert_relative_eq((view * ray.dir).normalize(), expected_dir.normalize());
+            assert_relative_eq((view * ray.origin).coords, expected_dir * near);
+        }
+    }
+
+    #[test]
+    fn screen_to_world_ray_fixed_view() {
+        check_rays(&FixedView::new());
+    }
+
+    #[test]
+    fn screen_to_world_ray_arc_ball() {
+        let camera = ArcBall::new(Point3::new(1.0, 2.0, 5.0), Point3::new(0.0, 0.5, 0.0));
+        check_rays(&camera);
+
+        // The center ray goes along the camera axis, from the eye toward the target.
+        let ray = camera.screen_to_world_ray(400.0, 300.0, &Vector2::new(800, 600));
+        let axis = (Point3::new(0.0, 0.5, 0.0) - camera.eye()).normalize();
+        assert_relative_eq(ray.dir, axis);
+        assert_relative_eq(ray.origin.coords, camera.eye().coords + axis * 0.1);
+    }
+
+    #[test]
+    fn fovy_is_clamped_to_an_open_half_turn() {
+        use std::f32::consts::PI;
+
+        assert_eq!(clamp_fovy(1.0), Ok(1.0));
+        assert_eq!(clamp_fovy(0.0), Ok(0.01));
+        assert_eq!(clamp_fovy(-1.0), Ok(0.01));
+        assert_eq!(clamp_fovy(4.0), Ok(PI - 0.01));
+        assert!(clamp_fovy(std::f32::NAN).is_err());
+        assert!(clamp_fovy(std::f32::INFINITY).is_err());
+    }
+
+    #[test]
+    fn near_far_must_be_positive_and_ordered() {
+        assert!(check_near_far(0.1, 1024.0).is_ok());
+        assert!(check_near_far(0.0, 1024.0).is_err());
+        assert!(check_near_far(-0.1, 1024.0).is_err());
+        assert!(check_near_far(1.0, 1.0).is_err());
+        assert!(check_near_far(1.0, 0.5).is_err());
+        assert!(check_near_far(std::f32::NAN, 1.0).is_err());
+        assert!(check_near_far(0.1, std::f32::NAN).is_err());
+    }
 }
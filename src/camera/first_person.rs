@@ -1,4 +1,4 @@
-use crate::camera::Camera;
+use crate::camera::{camera, Camera};
 use crate::event::{Action, Key, MouseButton, WindowEvent};
 use crate::resource::ShaderUniform;
 use crate::window::Canvas;
@@ -365,6 +365,23 @@ impl Camera for FirstPerson {
         (self.projection.znear(), self.projection.zfar())
     }
 
+    fn set_near_far(&mut self, near: f32, far: f32) -> Result<(), &'static str> {
+        camera::check_near_far(near, far)?;
+        self.projection.set_znear_and_zfar(near, far);
+        self.update_projviews();
+        Ok(())
+    }
+
+    fn fovy(&self) -> Option<f32> {
+        Some(self.projection.fovy())
+    }
+
+    fn set_fovy(&mut self, fovy: f32) -> Result<(), &'static str> {
+        self.projection.set_fovy(camera::clamp_fovy(fovy)?);
+        self.update_projviews();
+        Ok(())
+    }
+
     /// The camera view transformation (i-e transformation without projection).
     fn view_transform(&self) -> Isometry3<f32> {
         Isometry3::look_at_rh(&self.eye, &self.at(), &self.coord_system.up_axis)
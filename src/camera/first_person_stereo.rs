@@ -2,7 +2,7 @@ use std::f32;
 
 use na::{self, Isometry3, Matrix4, Perspective3, Point2, Point3, Vector2, Vector3};
 
-use crate::camera::Camera;
+use crate::camera::{camera, Camera};
 use crate::context::Context;
 use crate::event::{Action, Key, MouseButton, WindowEvent};
 use crate::resource::ShaderUniform;
@@ -214,6 +214,23 @@ impl Camera for FirstPersonStereo {
         (self.projection.znear(), self.projection.zfar())
     }
 
+    fn set_near_far(&mut self, near: f32, far: f32) -> Result<(), &'static str> {
+        camera::check_near_far(near, far)?;
+        self.projection.set_znear_and_zfar(near, far);
+        self.update_projviews();
+        Ok(())
+    }
+
+    fn fovy(&self) -> Option<f32> {
+        Some(self.projection.fovy())
+    }
+
+    fn set_fovy(&mut self, fovy: f32) -> Result<(), &'static str> {
+        self.projection.set_fovy(camera::clamp_fovy(fovy)?);
+        self.update_projviews();
+        Ok(())
+    }
+
     /// The imaginary middle eye camera view transformation (i-e transformation without projection).
     fn view_transform(&self) -> Isometry3<f32> {
         Isometry3::look_at_rh(&self.eye, &self.at(), &Vector3::y())
@@ -1,4 +1,4 @@
-use crate::camera::Camera;
+use crate::camera::{camera, Camera};
 use crate::event::WindowEvent;
 use crate::resource::ShaderUniform;
 use crate::window::Canvas;
@@ -21,11 +21,15 @@ impl FixedView {
 
     /// Creates a new arc ball camera with default sensitivity values.
     pub fn new_with_frustrum(fov: f32, znear: f32, zfar: f32) -> FixedView {
-        FixedView {
+        let mut res = FixedView {
             projection: Perspective3::new(800.0 / 600.0, fov, znear, zfar),
             proj: na::one(),
             inv_proj: na::one(),
-        }
+        };
+
+        res.update_projviews();
+
+        res
     }
 
     fn update_projviews(&mut self) {
@@ -42,6 +46,23 @@ impl Camera for FixedView {
         (self.projection.znear(), self.projection.zfar())
     }
 
+    fn set_near_far(&mut self, near: f32, far: f32) -> Result<(), &'static str> {
+        camera::check_near_far(near, far)?;
+        self.projection.set_znear_and_zfar(near, far);
+        self.update_projviews();
+        Ok(())
+    }
+
+    fn fovy(&self) -> Option<f32> {
+        Some(self.projection.fovy())
+    }
+
+    fn set_fovy(&mut self, fovy: f32) -> Result<(), &'static str> {
+        self.projection.set_fovy(camera::clamp_fovy(fovy)?);
+        self.update_projviews();
+        Ok(())
+    }
+
     fn view_transform(&self) -> Isometry3<f32> {
         Isometry3::identity()
     }
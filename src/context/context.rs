@@ -16,6 +16,7 @@ pub struct UniformLocation(<ContextImpl as AbstractContext>::UniformLocation);
 pub struct Buffer(<ContextImpl as AbstractContext>::Buffer);
 pub struct Program(<ContextImpl as AbstractContext>::Program);
 pub struct Shader(<ContextImpl as AbstractContext>::Shader);
+#[derive(Copy, Clone, PartialEq)]
 pub struct Framebuffer(<ContextImpl as AbstractContext>::Framebuffer);
 pub struct Renderbuffer(<ContextImpl as AbstractContext>::Renderbuffer);
 pub struct Texture(<ContextImpl as AbstractContext>::Texture);
@@ -72,6 +73,15 @@ impl Context {
     pub const RGBA: u32 = ContextImpl::RGBA;
     pub const TEXTURE0: u32 = ContextImpl::TEXTURE0;
     pub const TEXTURE1: u32 = ContextImpl::TEXTURE1;
+    pub const TEXTURE2: u32 = ContextImpl::TEXTURE2;
+    pub const TEXTURE3: u32 = ContextImpl::TEXTURE3;
+    pub const TEXTURE_CUBE_MAP: u32 = ContextImpl::TEXTURE_CUBE_MAP;
+    pub const TEXTURE_CUBE_MAP_POSITIVE_X: u32 = ContextImpl::TEXTURE_CUBE_MAP_POSITIVE_X;
+    pub const TEXTURE_CUBE_MAP_NEGATIVE_X: u32 = ContextImpl::TEXTURE_CUBE_MAP_NEGATIVE_X;
+    pub const TEXTURE_CUBE_MAP_POSITIVE_Y: u32 = ContextImpl::TEXTURE_CUBE_MAP_POSITIVE_Y;
+    pub const TEXTURE_CUBE_MAP_NEGATIVE_Y: u32 = ContextImpl::TEXTURE_CUBE_MAP_NEGATIVE_Y;
+    pub const TEXTURE_CUBE_MAP_POSITIVE_Z: u32 = ContextImpl::TEXTURE_CUBE_MAP_POSITIVE_Z;
+    pub const TEXTURE_CUBE_MAP_NEGATIVE_Z: u32 = ContextImpl::TEXTURE_CUBE_MAP_NEGATIVE_Z;
     pub const REPEAT: u32 = ContextImpl::REPEAT;
     pub const MIRRORED_REPEAT: u32 = ContextImpl::MIRRORED_REPEAT;
     pub const LINEAR_MIPMAP_LINEAR: u32 = ContextImpl::LINEAR_MIPMAP_LINEAR;
@@ -92,10 +102,17 @@ impl Context {
     pub const PROGRAM_POINT_SIZE: u32 = ContextImpl::PROGRAM_POINT_SIZE;
     pub const LEQUAL: u32 = ContextImpl::LEQUAL;
     pub const BACK: u32 = ContextImpl::BACK;
+    pub const FRONT: u32 = ContextImpl::FRONT;
     pub const PACK_ALIGNMENT: u32 = ContextImpl::PACK_ALIGNMENT;
     pub const BLEND: u32 = ContextImpl::BLEND;
+    pub const MULTISAMPLE: u32 = ContextImpl::MULTISAMPLE;
+    pub const SAMPLES: u32 = ContextImpl::SAMPLES;
+    pub const POLYGON_OFFSET_FILL: u32 = ContextImpl::POLYGON_OFFSET_FILL;
     pub const SRC_ALPHA: u32 = ContextImpl::SRC_ALPHA;
     pub const ONE_MINUS_SRC_ALPHA: u32 = ContextImpl::ONE_MINUS_SRC_ALPHA;
+    pub const ONE_MINUS_SRC_COLOR: u32 = ContextImpl::ONE_MINUS_SRC_COLOR;
+    pub const DST_COLOR: u32 = ContextImpl::DST_COLOR;
+    pub const ZERO: u32 = ContextImpl::ZERO;
     pub const ONE: u32 = ContextImpl::ONE;
     pub const UNPACK_ALIGNMENT: u32 = ContextImpl::UNPACK_ALIGNMENT;
     pub const ALPHA: u32 = ContextImpl::ALPHA;
@@ -124,6 +141,10 @@ impl Context {
         self.ctxt.get_error()
     }
 
+    pub fn get_parameter_i32(&self, pname: GLenum) -> i32 {
+        self.ctxt.get_parameter_i32(pname)
+    }
+
     pub fn uniform_matrix2fv(
         &self,
         location: Option<&UniformLocation>,
@@ -273,6 +294,14 @@ impl Context {
         self.ctxt.get_shader_info_log(&shader.0)
     }
 
+    pub fn get_program_link_status(&self, program: &Program) -> bool {
+        self.ctxt.get_program_link_status(&program.0)
+    }
+
+    pub fn get_program_info_log(&self, program: &Program) -> Option<String> {
+        self.ctxt.get_program_info_log(&program.0)
+    }
+
     pub fn vertex_attrib_pointer(
         &self,
         index: u32,
@@ -290,6 +319,18 @@ impl Context {
         self.ctxt.enable_vertex_attrib_array(index)
     }
 
+    pub fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        self.ctxt.vertex_attrib_divisor(index, divisor)
+    }
+
+    pub fn vertex_attrib3f(&self, index: u32, x: f32, y: f32, z: f32) {
+        self.ctxt.vertex_attrib3f(index, x, y, z)
+    }
+
+    pub fn vertex_attrib4f(&self, index: u32, x: f32, y: f32, z: f32, w: f32) {
+        self.ctxt.vertex_attrib4f(index, x, y, z, w)
+    }
+
     pub fn disable_vertex_attrib_array(&self, index: u32) {
         self.ctxt.disable_vertex_attrib_array(index)
     }
@@ -469,6 +510,25 @@ impl Context {
         self.ctxt.draw_elements(mode, count, type_, offset)
     }
 
+    pub fn draw_elements_instanced(
+        &self,
+        mode: GLenum,
+        count: i32,
+        type_: GLenum,
+        offset: GLintptr,
+        instance_count: i32,
+    ) {
+        self.ctxt
+            .draw_elements_instanced(mode, count, type_, offset, instance_count)
+    }
+
+    /// Whether `draw_elements_instanced` and `vertex_attrib_divisor` are supported.
+    ///
+    /// They require OpenGL 3.3, or the `ANGLE_instanced_arrays` extension on WebGL 1.
+    pub fn supports_instancing(&self) -> bool {
+        self.ctxt.supports_instancing()
+    }
+
     pub fn draw_arrays(&self, mode: GLenum, first: i32, count: i32) {
         self.ctxt.draw_arrays(mode, first, count)
     }
@@ -493,6 +553,14 @@ impl Context {
         self.ctxt.polygon_mode(face, mode)
     }
 
+    pub fn polygon_offset(&self, factor: f32, units: f32) {
+        self.ctxt.polygon_offset(factor, units)
+    }
+
+    pub fn depth_mask(&self, flag: bool) {
+        self.ctxt.depth_mask(flag)
+    }
+
     pub fn front_face(&self, mode: GLenum) {
         self.ctxt.front_face(mode)
     }
@@ -517,6 +585,10 @@ impl Context {
         self.ctxt.read_pixels(x, y, width, height, format, pixels)
     }
 
+    pub fn read_depth_pixels(&self, x: i32, y: i32, width: i32, height: i32, pixels: &mut [f32]) {
+        self.ctxt.read_depth_pixels(x, y, width, height, pixels)
+    }
+
     pub fn pixel_storei(&self, pname: GLenum, param: i32) {
         self.ctxt.pixel_storei(pname, param)
     }
@@ -565,6 +637,15 @@ pub(crate) trait AbstractContextConst {
     const RGBA: u32;
     const TEXTURE0: u32;
     const TEXTURE1: u32;
+    const TEXTURE2: u32;
+    const TEXTURE3: u32;
+    const TEXTURE_CUBE_MAP: u32;
+    const TEXTURE_CUBE_MAP_POSITIVE_X: u32;
+    const TEXTURE_CUBE_MAP_NEGATIVE_X: u32;
+    const TEXTURE_CUBE_MAP_POSITIVE_Y: u32;
+    const TEXTURE_CUBE_MAP_NEGATIVE_Y: u32;
+    const TEXTURE_CUBE_MAP_POSITIVE_Z: u32;
+    const TEXTURE_CUBE_MAP_NEGATIVE_Z: u32;
     const REPEAT: u32;
     const MIRRORED_REPEAT: u32;
     const LINEAR_MIPMAP_LINEAR: u32;
@@ -585,10 +666,17 @@ pub(crate) trait AbstractContextConst {
     const PROGRAM_POINT_SIZE: u32;
     const LEQUAL: u32;
     const BACK: u32;
+    const FRONT: u32;
     const PACK_ALIGNMENT: u32;
     const BLEND: u32;
+    const MULTISAMPLE: u32;
+    const SAMPLES: u32;
+    const POLYGON_OFFSET_FILL: u32;
     const SRC_ALPHA: u32;
     const ONE_MINUS_SRC_ALPHA: u32;
+    const ONE_MINUS_SRC_COLOR: u32;
+    const DST_COLOR: u32;
+    const ZERO: u32;
     const ONE: u32;
     const UNPACK_ALIGNMENT: u32;
     const ALPHA: u32;
@@ -606,6 +694,7 @@ pub(crate) trait AbstractContext {
     type VertexArray;
 
     fn get_error(&self) -> GLenum;
+    fn get_parameter_i32(&self, pname: GLenum) -> i32;
     fn uniform_matrix2fv(
         &self,
         location: Option<&Self::UniformLocation>,
@@ -656,6 +745,8 @@ pub(crate) trait AbstractContext {
     fn attach_shader(&self, program: &Self::Program, shader: &Self::Shader);
     fn get_shader_parameter_int(&self, shader: &Self::Shader, pname: GLenum) -> Option<i32>;
     fn get_shader_info_log(&self, shader: &Self::Shader) -> Option<String>;
+    fn get_program_link_status(&self, program: &Self::Program) -> bool;
+    fn get_program_info_log(&self, program: &Self::Program) -> Option<String>;
     fn vertex_attrib_pointer(
         &self,
         index: u32,
@@ -666,6 +757,9 @@ pub(crate) trait AbstractContext {
         offset: GLintptr,
     );
     fn enable_vertex_attrib_array(&self, index: u32);
+    fn vertex_attrib_divisor(&self, index: u32, divisor: u32);
+    fn vertex_attrib3f(&self, index: u32, x: f32, y: f32, z: f32);
+    fn vertex_attrib4f(&self, index: u32, x: f32, y: f32, z: f32, w: f32);
     fn disable_vertex_attrib_array(&self, index: u32);
 
     fn get_attrib_location(&self, program: &Self::Program, name: &str) -> i32;
@@ -745,6 +839,15 @@ pub(crate) trait AbstractContext {
     fn disable(&self, cap: GLenum);
 
     fn draw_elements(&self, mode: GLenum, count: i32, type_: GLenum, offset: GLintptr);
+    fn draw_elements_instanced(
+        &self,
+        mode: GLenum,
+        count: i32,
+        type_: GLenum,
+        offset: GLintptr,
+        instance_count: i32,
+    );
+    fn supports_instancing(&self) -> bool;
     fn draw_arrays(&self, mode: GLenum, first: i32, count: i32);
 
     fn point_size(&self, size: f32);
@@ -754,6 +857,8 @@ pub(crate) trait AbstractContext {
     fn clear_color(&self, r: f32, g: f32, b: f32, a: f32);
 
     fn polygon_mode(&self, face: GLenum, mode: GLenum) -> bool;
+    fn polygon_offset(&self, factor: f32, units: f32);
+    fn depth_mask(&self, flag: bool);
 
     fn front_face(&self, mode: GLenum);
     fn depth_func(&self, mode: GLenum);
@@ -768,6 +873,7 @@ pub(crate) trait AbstractContext {
         format: GLenum,
         pixels: Option<&mut [u8]>,
     );
+    fn read_depth_pixels(&self, x: i32, y: i32, width: i32, height: i32, pixels: &mut [f32]);
     fn pixel_storei(&self, pname: GLenum, param: i32);
 
     fn blend_func_separate(
@@ -1,4 +1,4 @@
-use std::mem;
+use std::mem::{self, size_of};
 use std::sync::Arc;
 
 use crate::context::{AbstractContext, AbstractContextConst, GLenum, GLintptr};
@@ -15,17 +15,84 @@ mod error;
 pub struct GLContext {
     /// The underlying glow context.
     pub context: Arc<Context>,
+    instancing: bool,
 }
 
 impl GLContext {
     /// Creates a new OpenGL context.
     pub fn new(ctxt: Context) -> Self {
+        let instancing = unsafe { supports_instancing(&ctxt) };
+
         Self {
             context: Arc::new(ctxt),
+            instancing,
         }
     }
 }
 
+// Instanced drawing and attribute divisors are part of OpenGL 3.3, OpenGL ES 3.0 and WebGL 2.
+// Older versions may still provide them through an extension.
+unsafe fn supports_instancing(ctxt: &Context) -> bool {
+    let version = ctxt.get_parameter_string(glow::VERSION);
+
+    match parse_version(&version) {
+        // The extensions of WebGL 1 cannot be queried through glow, so instancing is not used
+        // there.
+        Some(GLVersion::WebGL(major, _)) => major >= 2,
+        Some(GLVersion::Embedded(major, _)) if major >= 3 => true,
+        Some(GLVersion::Desktop(major, minor)) if (major, minor) >= (3, 3) => true,
+        Some(GLVersion::Desktop(major, _)) if major == 3 => {
+            let count = ctxt.get_parameter_i32(glow::NUM_EXTENSIONS).max(0) as u32;
+            (0..count).any(|i| {
+                is_instancing_extension(&ctxt.get_parameter_indexed_string(glow::EXTENSIONS, i))
+            })
+        }
+        _ => ctxt
+            .get_parameter_string(glow::EXTENSIONS)
+            .split_whitespace()
+            .any(is_instancing_extension),
+    }
+}
+
+fn is_instancing_extension(name: &str) -> bool {
+    match name {
+        "GL_ARB_instanced_arrays" | "GL_ANGLE_instanced_arrays" | "GL_EXT_instanced_arrays" => true,
+        _ => false,
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum GLVersion {
+    Desktop(u32, u32),
+    Embedded(u32, u32),
+    WebGL(u32, u32),
+}
+
+// Parses the `GL_VERSION` string, e.g., "4.6.0 NVIDIA 470.57", "OpenGL ES 3.2 Mesa 21.0", or
+// "WebGL 2.0 (OpenGL ES 3.0 Chromium)".
+fn parse_version(version: &str) -> Option<GLVersion> {
+    let (ctor, numbers): (fn(u32, u32) -> GLVersion, &str) = if version.starts_with("WebGL ") {
+        (GLVersion::WebGL, &version["WebGL ".len()..])
+    } else if version.starts_with("OpenGL ES ") {
+        (GLVersion::Embedded, &version["OpenGL ES ".len()..])
+    } else {
+        (GLVersion::Desktop, version)
+    };
+
+    let numbers = numbers.split_whitespace().next()?;
+    let mut parts = numbers.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts
+        .next()
+        .and_then(|minor| {
+            let digits: String = minor.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse().ok()
+        })
+        .unwrap_or(0);
+
+    Some(ctor(major, minor))
+}
+
 impl AbstractContextConst for GLContext {
     const FLOAT: u32 = glow::FLOAT;
     const INT: u32 = glow::INT;
@@ -58,6 +125,15 @@ impl AbstractContextConst for GLContext {
     const RGBA: u32 = glow::RGBA;
     const TEXTURE0: u32 = glow::TEXTURE0;
     const TEXTURE1: u32 = glow::TEXTURE1;
+    const TEXTURE2: u32 = glow::TEXTURE2;
+    const TEXTURE3: u32 = glow::TEXTURE3;
+    const TEXTURE_CUBE_MAP: u32 = glow::TEXTURE_CUBE_MAP;
+    const TEXTURE_CUBE_MAP_POSITIVE_X: u32 = glow::TEXTURE_CUBE_MAP_POSITIVE_X;
+    const TEXTURE_CUBE_MAP_NEGATIVE_X: u32 = glow::TEXTURE_CUBE_MAP_NEGATIVE_X;
+    const TEXTURE_CUBE_MAP_POSITIVE_Y: u32 = glow::TEXTURE_CUBE_MAP_POSITIVE_Y;
+    const TEXTURE_CUBE_MAP_NEGATIVE_Y: u32 = glow::TEXTURE_CUBE_MAP_NEGATIVE_Y;
+    const TEXTURE_CUBE_MAP_POSITIVE_Z: u32 = glow::TEXTURE_CUBE_MAP_POSITIVE_Z;
+    const TEXTURE_CUBE_MAP_NEGATIVE_Z: u32 = glow::TEXTURE_CUBE_MAP_NEGATIVE_Z;
     const REPEAT: u32 = glow::REPEAT;
     const MIRRORED_REPEAT: u32 = glow::MIRRORED_REPEAT;
     const LINEAR_MIPMAP_LINEAR: u32 = glow::LINEAR_MIPMAP_LINEAR;
@@ -74,14 +150,21 @@ impl AbstractContextConst for GLContext {
     const SCISSOR_TEST: u32 = glow::SCISSOR_TEST;
     const LEQUAL: u32 = glow::LEQUAL;
     const BACK: u32 = glow::BACK;
+    const FRONT: u32 = glow::FRONT;
     const PACK_ALIGNMENT: u32 = glow::PACK_ALIGNMENT;
     const PROGRAM_POINT_SIZE: u32 = glow::PROGRAM_POINT_SIZE;
     const LINE: u32 = glow::LINE;
     const POINT: u32 = glow::POINT;
     const FILL: u32 = glow::FILL;
     const BLEND: u32 = glow::BLEND;
+    const MULTISAMPLE: u32 = glow::MULTISAMPLE;
+    const SAMPLES: u32 = glow::SAMPLES;
+    const POLYGON_OFFSET_FILL: u32 = glow::POLYGON_OFFSET_FILL;
     const SRC_ALPHA: u32 = glow::SRC_ALPHA;
     const ONE_MINUS_SRC_ALPHA: u32 = glow::ONE_MINUS_SRC_ALPHA;
+    const ONE_MINUS_SRC_COLOR: u32 = glow::ONE_MINUS_SRC_COLOR;
+    const DST_COLOR: u32 = glow::DST_COLOR;
+    const ZERO: u32 = glow::ZERO;
     const ONE: u32 = glow::ONE;
     const UNPACK_ALIGNMENT: u32 = glow::UNPACK_ALIGNMENT;
     const ALPHA: u32 = glow::ALPHA;
@@ -105,6 +188,10 @@ impl AbstractContext for GLContext {
         unsafe { self.context.get_error() }
     }
 
+    fn get_parameter_i32(&self, pname: GLenum) -> i32 {
+        unsafe { self.context.get_parameter_i32(pname) }
+    }
+
     fn uniform_matrix2fv(
         &self,
         location: Option<&Self::UniformLocation>,
@@ -307,6 +394,14 @@ impl AbstractContext for GLContext {
         unsafe { Some(self.context.get_shader_info_log(*shader)) }
     }
 
+    fn get_program_link_status(&self, program: &Self::Program) -> bool {
+        unsafe { self.context.get_program_link_status(*program) }
+    }
+
+    fn get_program_info_log(&self, program: &Self::Program) -> Option<String> {
+        unsafe { Some(self.context.get_program_info_log(*program)) }
+    }
+
     fn vertex_attrib_pointer(
         &self,
         index: u32,
@@ -332,6 +427,18 @@ impl AbstractContext for GLContext {
         unsafe { self.context.enable_vertex_attrib_array(index) }
     }
 
+    fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        unsafe { self.context.vertex_attrib_divisor(index, divisor) }
+    }
+
+    fn vertex_attrib3f(&self, index: u32, x: f32, y: f32, z: f32) {
+        unsafe { self.context.vertex_attrib_3_f32(index, x, y, z) }
+    }
+
+    fn vertex_attrib4f(&self, index: u32, x: f32, y: f32, z: f32, w: f32) {
+        unsafe { self.context.vertex_attrib_4_f32(index, x, y, z, w) }
+    }
+
     fn disable_vertex_attrib_array(&self, index: u32) {
         unsafe { self.context.disable_vertex_attrib_array(index) }
     }
@@ -569,6 +676,24 @@ impl AbstractContext for GLContext {
         }
     }
 
+    fn draw_elements_instanced(
+        &self,
+        mode: GLenum,
+        count: i32,
+        type_: GLenum,
+        offset: GLintptr,
+        instance_count: i32,
+    ) {
+        unsafe {
+            self.context
+                .draw_elements_instanced(mode, count, type_, offset as i32, instance_count)
+        }
+    }
+
+    fn supports_instancing(&self) -> bool {
+        self.instancing
+    }
+
     fn draw_arrays(&self, mode: GLenum, first: i32, count: i32) {
         unsafe { self.context.draw_arrays(mode, first, count) }
     }
@@ -590,6 +715,12 @@ impl AbstractContext for GLContext {
     }
 
     fn polygon_mode(&self, face: GLenum, mode: GLenum) -> bool {
+        // WebGL has no polygon mode: the callers fall back to drawing the edges with `LINES`, or
+        // the vertices with `POINTS`.
+        if cfg!(target_arch = "wasm32") && mode != glow::FILL {
+            return false;
+        }
+
         unsafe {
             self.context.polygon_mode(face, mode);
         }
@@ -597,6 +728,14 @@ impl AbstractContext for GLContext {
         true
     }
 
+    fn polygon_offset(&self, factor: f32, units: f32) {
+        unsafe { self.context.polygon_offset(factor, units) }
+    }
+
+    fn depth_mask(&self, flag: bool) {
+        unsafe { self.context.depth_mask(flag) }
+    }
+
     fn front_face(&self, mode: GLenum) {
         unsafe { self.context.front_face(mode) }
     }
@@ -634,6 +773,24 @@ impl AbstractContext for GLContext {
         }
     }
 
+    fn read_depth_pixels(&self, x: i32, y: i32, width: i32, height: i32, pixels: &mut [f32]) {
+        unsafe {
+            let bytes = std::slice::from_raw_parts_mut(
+                pixels.as_mut_ptr() as *mut u8,
+                pixels.len() * size_of::<f32>(),
+            );
+            self.context.read_pixels(
+                x,
+                y,
+                width,
+                height,
+                Self::DEPTH_COMPONENT,
+                Self::FLOAT,
+                glow::PixelPackData::Slice(bytes),
+            );
+        }
+    }
+
     fn pixel_storei(&self, pname: GLenum, param: i32) {
         unsafe { self.context.pixel_store_i32(pname, param) }
     }
@@ -651,3 +808,33 @@ impl AbstractContext for GLContext {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{parse_version, GLVersion};
+
+    #[test]
+    fn parse_gl_versions() {
+        assert_eq!(
+            parse_version("4.6.0 NVIDIA 470.57.02"),
+            Some(GLVersion::Desktop(4, 6))
+        );
+        assert_eq!(
+            parse_version("3.3 (Core Profile) Mesa 21.0.3"),
+            Some(GLVersion::Desktop(3, 3))
+        );
+        assert_eq!(
+            parse_version("OpenGL ES 3.2 Mesa 21.0.3"),
+            Some(GLVersion::Embedded(3, 2))
+        );
+        assert_eq!(
+            parse_version("WebGL 2.0 (OpenGL ES 3.0 Chromium)"),
+            Some(GLVersion::WebGL(2, 0))
+        );
+        assert_eq!(
+            parse_version("WebGL 1.0 (OpenGL ES 2.0 Chromium)"),
+            Some(GLVersion::WebGL(1, 0))
+        );
+        assert_eq!(parse_version(""), None);
+    }
+}
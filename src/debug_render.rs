@@ -0,0 +1,67 @@
+//! Immediate-mode debug rendering of physics engines.
+
+use crate::window::Window;
+use na::Point3;
+use rapier3d::math::{Point, Real};
+use rapier3d::pipeline::{DebugRenderBackend, DebugRenderObject};
+
+/// A debug renderer drawing the lines requested by a physics engine with `Window::draw_line`.
+///
+/// The lines are drawn for the next frame only, so the debug rendering must be done at each
+/// frame, e.g., with `rapier3d::pipeline::DebugRenderPipeline::render`.
+pub struct KissDebugRenderer<'a> {
+    window: &'a mut Window,
+}
+
+impl<'a> KissDebugRenderer<'a> {
+    /// Creates a debug renderer drawing to `window`.
+    pub fn new(window: &'a mut Window) -> KissDebugRenderer<'a> {
+        KissDebugRenderer { window }
+    }
+
+    /// Draws a line with the given RGB color.
+    pub fn draw_line(&mut self, a: &Point3<f32>, b: &Point3<f32>, color: &Point3<f32>) {
+        self.window.draw_line(a, b, color)
+    }
+
+    /// Draws a point with the given RGB color.
+    pub fn draw_point(&mut self, pt: &Point3<f32>, color: &Point3<f32>) {
+        self.window.draw_point(pt, color)
+    }
+}
+
+impl<'a> DebugRenderBackend for KissDebugRenderer<'a> {
+    fn draw_line(
+        &mut self,
+        _object: DebugRenderObject,
+        a: Point<Real>,
+        b: Point<Real>,
+        color: [f32; 4],
+    ) {
+        // rapier and kiss3d may not depend on the same version of nalgebra.
+        let a = Point3::new(a.x as f32, a.y as f32, a.z as f32);
+        let b = Point3::new(b.x as f32, b.y as f32, b.z as f32);
+
+        self.window.draw_line(&a, &b, &hsl_to_rgb(color))
+    }
+}
+
+// Converts a rapier HSLA color, with the hue in degrees, to RGB. The alpha is ignored since the
+// lines are opaque.
+fn hsl_to_rgb(hsla: [f32; 4]) -> Point3<f32> {
+    let [h, s, l, _] = hsla;
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h = (h / 60.0).rem_euclid(6.0);
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = l - c / 2.0;
+
+    Point3::new(r + m, g + m, b + m)
+}
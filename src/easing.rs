@@ -0,0 +1,81 @@
+//! Easing functions.
+//!
+//! An easing function maps the fraction `t` of the duration of an animation elapsed, between
+//! `0.0` and `1.0`, to the fraction of the animated change applied at that time. Every function
+//! of this module maps `0.0` to `0.0` and `1.0` to `1.0`.
+
+/// An easing function, e.g., for `SceneNode::animate_color`.
+pub type EasingFn = fn(f32) -> f32;
+
+/// A constant speed.
+pub fn linear(t: f32) -> f32 {
+    t
+}
+
+/// Starts slowly, accelerates, then slows down again.
+pub fn ease_in_out(t: f32) -> f32 {
+    // The smoothstep polynomial.
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Hits the end value early, then bounces on it like a dropped ball, with decreasing bounces.
+pub fn bounce(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{bounce, ease_in_out, linear, EasingFn};
+
+    const FUNCTIONS: [EasingFn; 3] = [linear, ease_in_out, bounce];
+
+    #[test]
+    fn easing_functions_map_the_ends_to_themselves() {
+        for f in FUNCTIONS.iter() {
+            assert!(f(0.0).abs() < 1.0e-6);
+            assert!((f(1.0) - 1.0).abs() < 1.0e-6);
+        }
+    }
+
+    #[test]
+    fn easing_functions_are_continuous() {
+        for f in FUNCTIONS.iter() {
+            for i in 0..1000 {
+                let t = i as f32 / 1000.0;
+                assert!((f(t + 0.001) - f(t)).abs() < 0.02, "jump at {}", t);
+            }
+        }
+    }
+
+    #[test]
+    fn ease_in_out_is_symmetric() {
+        assert!((ease_in_out(0.5) - 0.5).abs() < 1.0e-6);
+
+        for i in 0..=10 {
+            let t = i as f32 / 10.0;
+            assert!((ease_in_out(t) + ease_in_out(1.0 - t) - 1.0).abs() < 1.0e-6);
+        }
+    }
+
+    #[test]
+    fn bounce_stays_below_the_end_value() {
+        for i in 0..=100 {
+            let y = bounce(i as f32 / 100.0);
+            assert!(y >= 0.0 && y <= 1.0 + 1.0e-6, "{}", y);
+        }
+    }
+}
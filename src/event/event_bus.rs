@@ -0,0 +1,110 @@
+use std::cell::RefCell;
+use std::mem;
+use std::rc::{Rc, Weak};
+
+use crate::event::WindowEvent;
+
+type Callback = Box<dyn FnMut(&WindowEvent)>;
+
+struct Subscribers {
+    next_id: usize,
+    callbacks: Vec<(usize, Callback)>,
+    // Callbacks unsubscribed while the events were being dispatched.
+    removed: Vec<usize>,
+}
+
+/// A dispatcher of window events to several subscribed callbacks.
+///
+/// This allows independent subsystems (camera, user interface, physics, etc.) to react to the
+/// same events without having to demultiplex them manually. Use `Window::dispatch_events` to
+/// send the events of the current frame to every subscriber.
+pub struct EventBus {
+    subscribers: Rc<RefCell<Subscribers>>,
+}
+
+/// A handle to a callback subscribed to an `EventBus`.
+///
+/// The callback is unsubscribed when this handle is dropped.
+#[must_use = "the callback is unsubscribed as soon as its handle is dropped"]
+pub struct SubscriptionHandle {
+    id: usize,
+    subscribers: Weak<RefCell<Subscribers>>,
+}
+
+impl EventBus {
+    /// Creates a new event bus without any subscriber.
+    pub fn new() -> EventBus {
+        EventBus {
+            subscribers: Rc::new(RefCell::new(Subscribers {
+                next_id: 0,
+                callbacks: Vec::new(),
+                removed: Vec::new(),
+            })),
+        }
+    }
+
+    /// Subscribes a callback called on every event dispatched by this bus.
+    ///
+    /// The callbacks are called in registration order. The callback stays subscribed as long as
+    /// the returned handle is alive.
+    pub fn subscribe<F: FnMut(&WindowEvent) + 'static>(
+        &mut self,
+        callback: F,
+    ) -> SubscriptionHandle {
+        let mut subscribers = self.subscribers.borrow_mut();
+        let id = subscribers.next_id;
+        subscribers.next_id += 1;
+        subscribers.callbacks.push((id, Box::new(callback)));
+
+        SubscriptionHandle {
+            id,
+            subscribers: Rc::downgrade(&self.subscribers),
+        }
+    }
+
+    /// The number of callbacks currently subscribed to this bus.
+    pub fn len(&self) -> usize {
+        self.subscribers.borrow().callbacks.len()
+    }
+
+    /// Whether no callback is subscribed to this bus.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Calls every subscribed callback with `event`.
+    pub fn dispatch(&mut self, event: &WindowEvent) {
+        // The callbacks are moved out of the `RefCell` so they can subscribe or unsubscribe
+        // callbacks themselves.
+        let mut callbacks = mem::replace(&mut self.subscribers.borrow_mut().callbacks, Vec::new());
+
+        for (_, callback) in callbacks.iter_mut() {
+            callback(event)
+        }
+
+        let mut subscribers = self.subscribers.borrow_mut();
+        let added = mem::replace(&mut subscribers.callbacks, Vec::new());
+        callbacks.extend(added);
+
+        let removed = mem::replace(&mut subscribers.removed, Vec::new());
+        callbacks.retain(|(id, _)| !removed.contains(id));
+        subscribers.callbacks = callbacks;
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> EventBus {
+        EventBus::new()
+    }
+}
+
+impl Drop for SubscriptionHandle {
+    fn drop(&mut self) {
+        if let Some(subscribers) = self.subscribers.upgrade() {
+            let mut subscribers = subscribers.borrow_mut();
+            let id = self.id;
+            subscribers.callbacks.retain(|(cid, _)| *cid != id);
+            subscribers.removed.push(id);
+        }
+    }
+}
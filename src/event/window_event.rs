@@ -17,6 +17,26 @@ pub enum WindowEvent {
     Char(char),
     CharModifiers(char, Modifiers),
     Touch(u64, f64, f64, TouchAction, Modifiers),
+    /// A mouse button has been pressed twice in a row at the given cursor position. This is
+    /// emitted right after the second `MouseButton` press event.
+    DoubleClick(MouseButton, f64, f64, Modifiers),
+    /// Text has been pasted into the canvas by the user, e.g., with `Ctrl+V`. The pasted text
+    /// is given by `Window::clipboard_string`. On native platforms, this is only emitted with
+    /// the `clipboard` feature.
+    Paste,
+    /// A file has been dropped onto the canvas. Its name and content are taken with
+    /// `Window::take_dropped_file` and the given identifier. Only emitted on the web.
+    FileDropped(u32),
+    /// The gamepad with the given index has been connected. Only emitted on the web.
+    GamepadConnected(u32),
+    /// The gamepad with the given index has been disconnected. Only emitted on the web.
+    GamepadDisconnected(u32),
+    /// A button of the gamepad with the given index has been pressed or released. Only emitted
+    /// on the web.
+    GamepadButton(u32, GamepadButton, Action),
+    /// An axis of the gamepad with the given index has moved to the given position, between
+    /// `-1.0` and `1.0`. Only emitted on the web.
+    GamepadAxis(u32, GamepadAxis, f32),
 }
 
 impl WindowEvent {
@@ -32,6 +52,7 @@ impl WindowEvent {
     pub fn is_mouse_event(&self) -> bool {
         match self {
             WindowEvent::MouseButton(..)
+            | WindowEvent::DoubleClick(..)
             | WindowEvent::CursorPos(..)
             | WindowEvent::CursorEnter(..)
             | WindowEvent::Scroll(..) => true,
@@ -212,8 +233,177 @@ pub enum Key {
     Copy,
     Paste,
     Cut,
+    // Must stay the last variant, see `Key::ALL`.
     Unknown,
 }
+
+impl Key {
+    /// All the keys, in declaration order.
+    pub(crate) const ALL: [Key; Key::Unknown as usize + 1] = [
+        Key::Key1,
+        Key::Key2,
+        Key::Key3,
+        Key::Key4,
+        Key::Key5,
+        Key::Key6,
+        Key::Key7,
+        Key::Key8,
+        Key::Key9,
+        Key::Key0,
+        Key::A,
+        Key::B,
+        Key::C,
+        Key::D,
+        Key::E,
+        Key::F,
+        Key::G,
+        Key::H,
+        Key::I,
+        Key::J,
+        Key::K,
+        Key::L,
+        Key::M,
+        Key::N,
+        Key::O,
+        Key::P,
+        Key::Q,
+        Key::R,
+        Key::S,
+        Key::T,
+        Key::U,
+        Key::V,
+        Key::W,
+        Key::X,
+        Key::Y,
+        Key::Z,
+        Key::Escape,
+        Key::F1,
+        Key::F2,
+        Key::F3,
+        Key::F4,
+        Key::F5,
+        Key::F6,
+        Key::F7,
+        Key::F8,
+        Key::F9,
+        Key::F10,
+        Key::F11,
+        Key::F12,
+        Key::F13,
+        Key::F14,
+        Key::F15,
+        Key::F16,
+        Key::F17,
+        Key::F18,
+        Key::F19,
+        Key::F20,
+        Key::F21,
+        Key::F22,
+        Key::F23,
+        Key::F24,
+        Key::Snapshot,
+        Key::Scroll,
+        Key::Pause,
+        Key::Insert,
+        Key::Home,
+        Key::Delete,
+        Key::End,
+        Key::PageDown,
+        Key::PageUp,
+        Key::Left,
+        Key::Up,
+        Key::Right,
+        Key::Down,
+        Key::Back,
+        Key::Return,
+        Key::Space,
+        Key::Compose,
+        Key::Caret,
+        Key::Numlock,
+        Key::Numpad0,
+        Key::Numpad1,
+        Key::Numpad2,
+        Key::Numpad3,
+        Key::Numpad4,
+        Key::Numpad5,
+        Key::Numpad6,
+        Key::Numpad7,
+        Key::Numpad8,
+        Key::Numpad9,
+        Key::AbntC1,
+        Key::AbntC2,
+        Key::Add,
+        Key::Apostrophe,
+        Key::Apps,
+        Key::At,
+        Key::Ax,
+        Key::Backslash,
+        Key::Calculator,
+        Key::Capital,
+        Key::Colon,
+        Key::Comma,
+        Key::Convert,
+        Key::Decimal,
+        Key::Divide,
+        Key::Equals,
+        Key::Grave,
+        Key::Kana,
+        Key::Kanji,
+        Key::LAlt,
+        Key::LBracket,
+        Key::LControl,
+        Key::LShift,
+        Key::LWin,
+        Key::Mail,
+        Key::MediaSelect,
+        Key::MediaStop,
+        Key::Minus,
+        Key::Multiply,
+        Key::Mute,
+        Key::MyComputer,
+        Key::NavigateForward,
+        Key::NavigateBackward,
+        Key::NextTrack,
+        Key::NoConvert,
+        Key::NumpadComma,
+        Key::NumpadEnter,
+        Key::NumpadEquals,
+        Key::OEM102,
+        Key::Period,
+        Key::PlayPause,
+        Key::Power,
+        Key::PrevTrack,
+        Key::RAlt,
+        Key::RBracket,
+        Key::RControl,
+        Key::RShift,
+        Key::RWin,
+        Key::Semicolon,
+        Key::Slash,
+        Key::Sleep,
+        Key::Stop,
+        Key::Subtract,
+        Key::Sysrq,
+        Key::Tab,
+        Key::Underline,
+        Key::Unlabeled,
+        Key::VolumeDown,
+        Key::VolumeUp,
+        Key::Wake,
+        Key::WebBack,
+        Key::WebFavorites,
+        Key::WebForward,
+        Key::WebHome,
+        Key::WebRefresh,
+        Key::WebSearch,
+        Key::WebStop,
+        Key::Yen,
+        Key::Copy,
+        Key::Paste,
+        Key::Cut,
+        Key::Unknown,
+    ];
+}
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
 pub enum MouseButton {
     Button1,
@@ -223,15 +413,106 @@ pub enum MouseButton {
     Button5,
     Button6,
     Button7,
+    // Must stay the last variant, see `MouseButton::ALL`.
     Button8,
 }
 
+impl MouseButton {
+    /// All the mouse buttons, in declaration order.
+    pub(crate) const ALL: [MouseButton; MouseButton::Button8 as usize + 1] = [
+        MouseButton::Button1,
+        MouseButton::Button2,
+        MouseButton::Button3,
+        MouseButton::Button4,
+        MouseButton::Button5,
+        MouseButton::Button6,
+        MouseButton::Button7,
+        MouseButton::Button8,
+    ];
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
 pub enum Action {
     Release,
     Press,
 }
 
+/// The buttons of a gamepad, named after their position on the standard layout.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum GamepadButton {
+    South,
+    East,
+    West,
+    North,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Home,
+    /// A button outside of the standard layout, with its index.
+    Other(u32),
+}
+
+impl GamepadButton {
+    /// The button with the given index on the standard layout.
+    pub fn from_index(index: u32) -> GamepadButton {
+        match index {
+            0 => GamepadButton::South,
+            1 => GamepadButton::East,
+            2 => GamepadButton::West,
+            3 => GamepadButton::North,
+            4 => GamepadButton::LeftBumper,
+            5 => GamepadButton::RightBumper,
+            6 => GamepadButton::LeftTrigger,
+            7 => GamepadButton::RightTrigger,
+            8 => GamepadButton::Select,
+            9 => GamepadButton::Start,
+            10 => GamepadButton::LeftStick,
+            11 => GamepadButton::RightStick,
+            12 => GamepadButton::DPadUp,
+            13 => GamepadButton::DPadDown,
+            14 => GamepadButton::DPadLeft,
+            15 => GamepadButton::DPadRight,
+            16 => GamepadButton::Home,
+            _ => GamepadButton::Other(index),
+        }
+    }
+}
+
+/// The axes of a gamepad, named after their position on the standard layout.
+///
+/// The `x` axes point right, and the `y` axes point down.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    /// An axis outside of the standard layout, with its index.
+    Other(u32),
+}
+
+impl GamepadAxis {
+    /// The axis with the given index on the standard layout.
+    pub fn from_index(index: u32) -> GamepadAxis {
+        match index {
+            0 => GamepadAxis::LeftStickX,
+            1 => GamepadAxis::LeftStickY,
+            2 => GamepadAxis::RightStickX,
+            3 => GamepadAxis::RightStickY,
+            _ => GamepadAxis::Other(index),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
 pub enum TouchAction {
     Start,
@@ -254,3 +535,32 @@ bitflags! {
         const Super       = 0b1000;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Key, MouseButton};
+
+    // `ALL` has one element per discriminant up to the last variant, so it contains every
+    // variant exactly once if each is at the index of its discriminant.
+    #[test]
+    fn all_keys_in_declaration_order() {
+        for (i, key) in Key::ALL.iter().enumerate() {
+            assert_eq!(*key as usize, i, "{:?} is misplaced in Key::ALL", key);
+        }
+
+        assert_eq!(Key::ALL.last(), Some(&Key::Unknown));
+    }
+
+    #[test]
+    fn all_mouse_buttons_in_declaration_order() {
+        for (i, button) in MouseButton::ALL.iter().enumerate() {
+            assert_eq!(
+                *button as usize, i,
+                "{:?} is misplaced in MouseButton::ALL",
+                button
+            );
+        }
+
+        assert_eq!(MouseButton::ALL.last(), Some(&MouseButton::Button8));
+    }
+}
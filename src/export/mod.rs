@@ -0,0 +1,4 @@
+//! File export.
+
+pub mod obj;
+pub mod stl;
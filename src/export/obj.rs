@@ -0,0 +1,97 @@
+//! Simplistic obj writer.
+
+use na::{Point2, Point3, Vector3};
+use ncollide3d::procedural::{IndexBuffer, TriMesh};
+use std::fs::File;
+use std::io::{BufWriter, Result as IoResult, Write};
+use std::path::Path;
+
+/// Writes named meshes to the obj file at `path`.
+///
+/// Each mesh is written as a separate `o` object.
+pub fn write_file(path: &Path, meshes: &[(String, TriMesh<f32>)]) -> IoResult<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    write(&mut out, meshes)?;
+    out.flush()
+}
+
+/// Writes named meshes with the obj format.
+///
+/// Each mesh is written as a separate `o` object.
+pub fn write<W: Write>(out: &mut W, meshes: &[(String, TriMesh<f32>)]) -> IoResult<()> {
+    // The obj indices are global to the file, and start at 1.
+    let mut coords_offset = 1;
+    let mut normals_offset = 1;
+    let mut uvs_offset = 1;
+
+    for (name, mesh) in meshes {
+        let mut mesh = mesh.clone();
+        mesh.unify_index_buffer();
+
+        writeln!(out, "o {}", name)?;
+
+        for c in &mesh.coords {
+            write_coord(out, c)?;
+        }
+
+        if let Some(ref normals) = mesh.normals {
+            for n in normals {
+                write_normal(out, n)?;
+            }
+        }
+
+        if let Some(ref uvs) = mesh.uvs {
+            for uv in uvs {
+                write_uv(out, uv)?;
+            }
+        }
+
+        if let IndexBuffer::Unified(ref faces) = mesh.indices {
+            let has_normals = mesh.normals.is_some();
+            let has_uvs = mesh.uvs.is_some();
+
+            for face in faces {
+                write!(out, "f")?;
+
+                for i in face.iter() {
+                    let i = *i as usize;
+
+                    match (has_uvs, has_normals) {
+                        (false, false) => write!(out, " {}", coords_offset + i)?,
+                        (true, false) => write!(out, " {}/{}", coords_offset + i, uvs_offset + i)?,
+                        (false, true) => {
+                            write!(out, " {}//{}", coords_offset + i, normals_offset + i)?
+                        }
+                        (true, true) => write!(
+                            out,
+                            " {}/{}/{}",
+                            coords_offset + i,
+                            uvs_offset + i,
+                            normals_offset + i
+                        )?,
+                    }
+                }
+
+                writeln!(out)?;
+            }
+        }
+
+        coords_offset += mesh.coords.len();
+        normals_offset += mesh.normals.as_ref().map(|n| n.len()).unwrap_or(0);
+        uvs_offset += mesh.uvs.as_ref().map(|uv| uv.len()).unwrap_or(0);
+    }
+
+    Ok(())
+}
+
+fn write_coord<W: Write>(out: &mut W, c: &Point3<f32>) -> IoResult<()> {
+    writeln!(out, "v {} {} {}", c.x, c.y, c.z)
+}
+
+fn write_normal<W: Write>(out: &mut W, n: &Vector3<f32>) -> IoResult<()> {
+    writeln!(out, "vn {} {} {}", n.x, n.y, n.z)
+}
+
+fn write_uv<W: Write>(out: &mut W, uv: &Point2<f32>) -> IoResult<()> {
+    writeln!(out, "vt {} {}", uv.x, uv.y)
+}
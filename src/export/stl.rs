@@ -0,0 +1,81 @@
+//! Simplistic binary stl writer.
+
+use na::Point3;
+use ncollide3d::procedural::{IndexBuffer, TriMesh};
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Result as IoResult, Write};
+use std::path::Path;
+
+/// Writes meshes to the binary stl file at `path`.
+///
+/// All the triangles of the meshes are written as a single solid.
+pub fn write_file(path: &Path, meshes: &[(String, TriMesh<f32>)]) -> IoResult<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    write(&mut out, meshes)?;
+    out.flush()
+}
+
+/// Writes meshes with the binary stl format.
+///
+/// All the triangles of the meshes are written as a single solid. The normals of the triangles
+/// are recomputed from their vertices since stl has no per-vertex normals.
+pub fn write<W: Write>(out: &mut W, meshes: &[(String, TriMesh<f32>)]) -> IoResult<()> {
+    let meshes: Vec<TriMesh<f32>> = meshes
+        .iter()
+        .map(|(_, mesh)| {
+            let mut mesh = mesh.clone();
+            mesh.unify_index_buffer();
+            mesh
+        })
+        .collect();
+
+    let num_triangles: usize = meshes
+        .iter()
+        .map(|mesh| match mesh.indices {
+            IndexBuffer::Unified(ref faces) => faces.len(),
+            IndexBuffer::Split(_) => 0,
+        })
+        .sum();
+
+    if num_triangles > u32::max_value() as usize {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "too many triangles for the stl format",
+        ));
+    }
+
+    let mut header = [0u8; 80];
+    let title = b"kiss3d";
+    header[..title.len()].copy_from_slice(title);
+    out.write_all(&header)?;
+    out.write_all(&(num_triangles as u32).to_le_bytes())?;
+
+    for mesh in &meshes {
+        if let IndexBuffer::Unified(ref faces) = mesh.indices {
+            for face in faces {
+                let a = mesh.coords[face.x as usize];
+                let b = mesh.coords[face.y as usize];
+                let c = mesh.coords[face.z as usize];
+                let normal = (b - a)
+                    .cross(&(c - a))
+                    .try_normalize(1.0e-12)
+                    .unwrap_or_else(na::zero);
+
+                write_vector(out, &Point3::from(normal))?;
+                write_vector(out, &a)?;
+                write_vector(out, &b)?;
+                write_vector(out, &c)?;
+                // The attribute byte count, unused.
+                out.write_all(&0u16.to_le_bytes())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_vector<W: Write>(out: &mut W, v: &Point3<f32>) -> IoResult<()> {
+    out.write_all(&v.x.to_le_bytes())?;
+    out.write_all(&v.y.to_le_bytes())?;
+    out.write_all(&v.z.to_le_bytes())
+}
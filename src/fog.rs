@@ -0,0 +1,36 @@
+//! Fog.
+
+use na::Point3;
+
+/// The fog configuration.
+///
+/// The fog blends the color of each fragment toward the fog color, depending on its distance to
+/// the camera. The fog color is independent from the background color.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FogMode {
+    /// No fog.
+    None,
+    /// A fog increasing linearly from `near` (no fog) to `far` (only fog).
+    Linear {
+        /// The distance to the camera where the fog starts.
+        near: f32,
+        /// The distance to the camera where the fog completely hides the objects.
+        far: f32,
+        /// The color of the fog.
+        color: Point3<f32>,
+    },
+    /// A fog with a visibility decreasing as `exp(-density * distance)`.
+    Exponential {
+        /// The density of the fog.
+        density: f32,
+        /// The color of the fog.
+        color: Point3<f32>,
+    },
+    /// A fog with a visibility decreasing as `exp(-(density * distance)²)`.
+    ExponentialSquared {
+        /// The density of the fog.
+        density: f32,
+        /// The color of the fog.
+        color: Point3<f32>,
+    },
+}
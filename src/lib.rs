@@ -137,13 +137,18 @@ extern crate num_traits as num;
 extern crate rusttype;
 #[macro_use]
 extern crate serde_derive;
+extern crate ron;
 extern crate serde;
 
 #[cfg(feature = "conrod")]
 pub extern crate conrod_core as conrod;
 #[cfg(not(target_arch = "wasm32"))]
-extern crate glutin;
+pub extern crate glutin;
 extern crate instant;
+#[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+extern crate notify;
+#[cfg(feature = "rapier3d")]
+extern crate rapier3d;
 #[cfg(feature = "conrod")]
 pub use conrod::widget_ids;
 
@@ -158,13 +163,19 @@ pub use crate::renderer::point_renderer;
 pub mod builtin;
 pub mod camera;
 pub mod context;
+#[cfg(feature = "rapier3d")]
+pub mod debug_render;
+pub mod easing;
 mod error;
 pub mod event;
+pub mod export;
+pub mod fog;
 pub mod light;
 pub mod loader;
 pub mod planar_camera;
 pub mod planar_line_renderer;
 pub mod post_processing;
+pub mod procedural;
 pub mod renderer;
 pub mod resource;
 pub mod scene;
@@ -3,7 +3,7 @@
 use crate::loader::mtl;
 use crate::loader::mtl::MtlMaterial;
 use crate::resource::GPUVec;
-use crate::resource::{AllocationType, BufferType, Mesh};
+use crate::resource::{self, AllocationType, BufferType, Mesh};
 use na::{Point2, Point3, Vector3};
 use num::Bounded;
 use std::collections::hash_map::Entry;
@@ -78,7 +78,7 @@ pub fn parse(
     let mut normals: Vec<Normal> = Vec::new();
     let mut uvs: Vec<UV> = Vec::new();
     let mut groups: HashMap<String, usize> = HashMap::new();
-    let mut groups_ids: Vec<Vec<Point3<u16>>> = Vec::new();
+    let mut groups_ids: Vec<Vec<Point3<u32>>> = Vec::new();
     let mut curr_group: usize = 0;
     let mut ignore_normals = false;
     let mut ignore_uvs = false;
@@ -172,7 +172,7 @@ fn parse_usemtl<'a>(
     mtllib: &HashMap<String, MtlMaterial>,
     group2mtl: &mut HashMap<usize, MtlMaterial>,
     groups: &mut HashMap<String, usize>,
-    groups_ids: &mut Vec<Vec<Point3<u16>>>,
+    groups_ids: &mut Vec<Vec<Point3<u32>>>,
     curr_mtl: &mut Option<MtlMaterial>,
 ) -> usize {
     let mname: Vec<&'a str> = ws.collect();
@@ -276,7 +276,7 @@ fn parse_f<'a>(
     normals: &[Vector3<f32>],
     ignore_uvs: &mut bool,
     ignore_normals: &mut bool,
-    groups_ids: &mut Vec<Vec<Point3<u16>>>,
+    groups_ids: &mut Vec<Vec<Point3<u32>>>,
     curr_group: usize,
 ) {
     // Four formats possible: v   v/t   v//n   v/t/n
@@ -335,7 +335,7 @@ fn parse_f<'a>(
         }
 
         assert!(x >= 0 && y >= 0 && z >= 0);
-        groups_ids[curr_group].push(Point3::new(x as u16, y as u16, z as u16));
+        groups_ids[curr_group].push(Point3::new(x as u32, y as u32, z as u32));
 
         i = i + 1;
     }
@@ -377,7 +377,7 @@ fn parse_g<'a>(
     ws: Words<'a>,
     prefix: &str,
     groups: &mut HashMap<String, usize>,
-    groups_ids: &mut Vec<Vec<Point3<u16>>>,
+    groups_ids: &mut Vec<Vec<Point3<u32>>>,
 ) -> usize {
     let suffix: Vec<&'a str> = ws.collect();
     let suffix = suffix.join(" ");
@@ -402,17 +402,17 @@ fn reformat(
     coords: Vec<Coord>,
     normals: Option<Vec<Normal>>,
     uvs: Option<Vec<UV>>,
-    groups_ids: Vec<Vec<Point3<u16>>>,
+    groups_ids: Vec<Vec<Point3<u32>>>,
     groups: HashMap<String, usize>,
     group2mtl: HashMap<usize, MtlMaterial>,
 ) -> Vec<(String, Mesh, Option<MtlMaterial>)> {
-    let mut vt2id: HashMap<Point3<u16>, u16> = HashMap::new();
-    let mut vertex_ids: Vec<u16> = Vec::new();
+    let mut vt2id: HashMap<Point3<u32>, u32> = HashMap::new();
+    let mut vertex_ids: Vec<u32> = Vec::new();
     let mut resc: Vec<Coord> = Vec::new();
     let mut resn: Option<Vec<Normal>> = normals.as_ref().map(|_| Vec::new());
     let mut resu: Option<Vec<UV>> = uvs.as_ref().map(|_| Vec::new());
-    let mut resfs: Vec<Vec<Point3<u16>>> = Vec::new();
-    let mut allfs: Vec<Point3<u16>> = Vec::new();
+    let mut resfs: Vec<Vec<Point3<u32>>> = Vec::new();
+    let mut allfs: Vec<Point3<u32>> = Vec::new();
     let mut names: Vec<String> = Vec::new();
     let mut mtls: Vec<Option<MtlMaterial>> = Vec::new();
 
@@ -427,7 +427,7 @@ fn reformat(
                     None
                 }
                 None => {
-                    let idx = resc.len() as u16;
+                    let idx = resc.len() as u32;
 
                     resc.push(coords[point.x as usize]);
 
@@ -460,7 +460,13 @@ fn reformat(
         vertex_ids.clear();
     }
 
-    let resn = resn.unwrap_or_else(|| Mesh::compute_normals_array(&resc[..], &allfs[..]));
+    let resn = resn.unwrap_or_else(|| {
+        let mut normals = Vec::new();
+        resource::compute_normals_generic(&resc[..], &allfs[..], &mut normals);
+        normals
+    });
+    // The index type is chosen from the final number of vertices, shared by all the groups.
+    let use_u32 = resc.len() > resource::MAX_U16_VERTICES;
     let resn = Arc::new(RwLock::new(GPUVec::new(
         resn,
         BufferType::Array,
@@ -485,15 +491,69 @@ fn reformat(
         .zip(mtls.into_iter())
     {
         if fs.len() != 0 {
-            let fs = Arc::new(RwLock::new(GPUVec::new(
-                fs,
-                BufferType::ElementArray,
-                AllocationType::StaticDraw,
-            )));
-            let mesh = Mesh::new_with_gpu_vectors(resc.clone(), fs, resn.clone(), resu.clone());
+            let mesh = if use_u32 {
+                let fs = Arc::new(RwLock::new(GPUVec::new(
+                    fs,
+                    BufferType::ElementArray,
+                    AllocationType::StaticDraw,
+                )));
+                Mesh::new_with_gpu_vectors_u32(resc.clone(), fs, resn.clone(), resu.clone())
+            } else {
+                let fs = Arc::new(RwLock::new(GPUVec::new(
+                    fs.into_iter().map(|f| na::convert(f)).collect(),
+                    BufferType::ElementArray,
+                    AllocationType::StaticDraw,
+                )));
+                Mesh::new_with_gpu_vectors(resc.clone(), fs, resn.clone(), resu.clone())
+            };
             meshes.push((name, mesh, mtl))
         }
     }
 
     meshes
 }
+
+#[cfg(test)]
+mod test {
+    use super::parse;
+    use crate::resource::IndexType;
+    use std::fmt::Write;
+    use std::path::Path;
+
+    // An obj file with `n` disjoint triangles.
+    fn triangles(n: usize) -> String {
+        let mut obj = String::new();
+
+        for i in 0..n {
+            let x = i as f32;
+            writeln!(obj, "v {} 0 0\nv {} 1 0\nv {} 0 1", x, x, x).unwrap();
+        }
+
+        for i in 0..n {
+            writeln!(obj, "f {} {} {}", i * 3 + 1, i * 3 + 2, i * 3 + 3).unwrap();
+        }
+
+        obj
+    }
+
+    #[test]
+    fn small_obj_uses_u16_indices() {
+        let meshes = parse(&triangles(10), Path::new(""), "test");
+        assert_eq!(meshes.len(), 1);
+        assert_eq!(meshes[0].1.index_type(), IndexType::U16);
+    }
+
+    #[test]
+    fn large_obj_uses_u32_indices() {
+        // 70002 vertices cannot be indexed with `u16`.
+        let n = 23334;
+        let meshes = parse(&triangles(n), Path::new(""), "test");
+        let mesh = &meshes[0].1;
+        assert_eq!(mesh.index_type(), IndexType::U32);
+
+        let faces = mesh.faces_u32().unwrap().read().unwrap();
+        let faces = faces.data().as_ref().unwrap();
+        assert_eq!(faces.len(), n);
+        assert_eq!(faces[n - 1].x as usize, (n - 1) * 3);
+    }
+}
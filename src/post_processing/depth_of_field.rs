@@ -0,0 +1,196 @@
+//! A post-processing effect to simulate the depth of field of a camera lens.
+
+use na::Vector2;
+
+use crate::context::Context;
+use crate::post_processing::post_processing_effect::PostProcessingEffect;
+use crate::resource::{
+    AllocationType, BufferType, Effect, GPUVec, RenderTarget, ShaderAttribute, ShaderUniform,
+};
+
+#[path = "../error.rs"]
+mod error;
+
+/// Post processing effect which blurs everything that is not on the focal plane.
+///
+/// The blur radius of a fragment, in pixels, is `aperture * |depth - focal_distance| / depth`
+/// (limited to 16 pixels), `depth` being the distance of the fragment to the camera. When the
+/// camera is an `ArcBall`, setting the focal distance to `ArcBall::dist` keeps its target in
+/// focus.
+pub struct DepthOfField {
+    focal_distance: f32,
+    aperture: f32,
+    pixel_size: Vector2<f32>,
+    zn: f32,
+    zf: f32,
+    shader: Effect,
+    gl_pixel_size: ShaderUniform<Vector2<f32>>,
+    gl_fbo_depth: ShaderUniform<i32>,
+    gl_fbo_texture: ShaderUniform<i32>,
+    gl_znear: ShaderUniform<f32>,
+    gl_zfar: ShaderUniform<f32>,
+    gl_focal_distance: ShaderUniform<f32>,
+    gl_aperture: ShaderUniform<f32>,
+    gl_v_coord: ShaderAttribute<Vector2<f32>>,
+    gl_fbo_vertices: GPUVec<Vector2<f32>>,
+}
+
+impl DepthOfField {
+    /// Creates a new DepthOfField post processing effect.
+    ///
+    /// # Arguments
+    /// * `focal_distance` - the distance to the camera of the points which are perfectly sharp.
+    /// * `aperture` - the strength of the blur of the points outside of the focal plane.
+    pub fn new(focal_distance: f32, aperture: f32) -> DepthOfField {
+        let fbo_vertices: Vec<Vector2<f32>> = vec![
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(1.0, 1.0),
+        ];
+
+        let mut fbo_vertices =
+            GPUVec::new(fbo_vertices, BufferType::Array, AllocationType::StaticDraw);
+        fbo_vertices.load_to_gpu();
+        fbo_vertices.unload_from_ram();
+
+        let mut shader = Effect::new_from_str(VERTEX_SHADER, FRAGMENT_SHADER);
+
+        shader.use_program();
+
+        DepthOfField {
+            focal_distance,
+            aperture,
+            pixel_size: Vector2::zeros(),
+            zn: 0.0,
+            zf: 0.0,
+            gl_pixel_size: shader.get_uniform("pixel_size").unwrap(),
+            gl_fbo_depth: shader.get_uniform("fbo_depth").unwrap(),
+            gl_fbo_texture: shader.get_uniform("fbo_texture").unwrap(),
+            gl_znear: shader.get_uniform("znear").unwrap(),
+            gl_zfar: shader.get_uniform("zfar").unwrap(),
+            gl_focal_distance: shader.get_uniform("focal_distance").unwrap(),
+            gl_aperture: shader.get_uniform("aperture").unwrap(),
+            gl_v_coord: shader.get_attrib("v_coord").unwrap(),
+            gl_fbo_vertices: fbo_vertices,
+            shader: shader,
+        }
+    }
+
+    /// The distance to the camera of the points which are perfectly sharp.
+    pub fn focal_distance(&self) -> f32 {
+        self.focal_distance
+    }
+
+    /// Sets the distance to the camera of the points which are perfectly sharp.
+    pub fn set_focal_distance(&mut self, focal_distance: f32) {
+        self.focal_distance = focal_distance
+    }
+
+    /// The strength of the blur of the points outside of the focal plane.
+    pub fn aperture(&self) -> f32 {
+        self.aperture
+    }
+
+    /// Sets the strength of the blur of the points outside of the focal plane.
+    pub fn set_aperture(&mut self, aperture: f32) {
+        self.aperture = aperture
+    }
+}
+
+impl PostProcessingEffect for DepthOfField {
+    fn update(&mut self, _: f32, w: f32, h: f32, znear: f32, zfar: f32) {
+        self.pixel_size = Vector2::new(1.0 / w, 1.0 / h);
+        self.zn = znear;
+        self.zf = zfar;
+    }
+
+    fn draw(&mut self, target: &RenderTarget) {
+        let ctxt = Context::get();
+        self.gl_v_coord.enable();
+
+        /*
+         * Finalize draw
+         */
+        verify!(ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT));
+
+        self.shader.use_program();
+
+        self.gl_pixel_size.upload(&self.pixel_size);
+        self.gl_znear.upload(&self.zn);
+        self.gl_zfar.upload(&self.zf);
+        self.gl_focal_distance.upload(&self.focal_distance);
+        self.gl_aperture.upload(&self.aperture);
+
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, target.texture_id()));
+
+        self.gl_fbo_texture.upload(&0);
+
+        verify!(ctxt.active_texture(Context::TEXTURE1));
+        verify!(ctxt.bind_texture(
+            Context::TEXTURE_2D,
+            target.depth_id().and_then(|id| id.as_ref().left())
+        ));
+
+        self.gl_fbo_depth.upload(&1);
+
+        self.gl_v_coord.bind(&mut self.gl_fbo_vertices);
+
+        verify!(ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4));
+
+        self.gl_v_coord.disable();
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+    }
+}
+
+static VERTEX_SHADER: &'static str = "#version 100
+    attribute vec2    v_coord;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+        gl_Position = vec4(v_coord, 0.0, 1.0);
+        f_texcoord  = (v_coord + 1.0) / 2.0;
+    }";
+
+static FRAGMENT_SHADER: &'static str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D fbo_depth;
+    uniform sampler2D fbo_texture;
+    uniform vec2      pixel_size;
+    uniform float     znear;
+    uniform float     zfar;
+    uniform float     focal_distance;
+    uniform float     aperture;
+    varying vec2      f_texcoord;
+
+    const float MAX_BLUR_RADIUS = 16.0;
+
+    float lin_depth(vec2 uv) {
+        float z_ndc = texture2D(fbo_depth, uv).x * 2.0 - 1.0;
+
+        return 2.0 * znear * zfar / (zfar + znear - z_ndc * (zfar - znear));
+    }
+
+    void main(void) {
+        float depth  = lin_depth(f_texcoord);
+        float radius = min(aperture * abs(depth - focal_distance) / depth, MAX_BLUR_RADIUS);
+
+        // Average the samples of three rings of eight points around the fragment.
+        vec4 color = texture2D(fbo_texture, f_texcoord);
+
+        for (int r = 1; r <= 3; ++r) {
+            for (int a = 0; a < 8; ++a) {
+                float angle = float(a) * 0.785398 + float(r) * 0.392699;
+                vec2 offset = vec2(cos(angle), sin(angle)) * radius * float(r) / 3.0;
+                color += texture2D(fbo_texture, f_texcoord + offset * pixel_size);
+            }
+        }
+
+        gl_FragColor = vec4((color / 25.0).rgb, 1.0);
+    }";
@@ -0,0 +1,103 @@
+//! Post-processing effect applying several effects one after the other.
+
+use crate::camera::Camera;
+use crate::post_processing::PostProcessingEffect;
+use crate::resource::{FramebufferManager, RenderTarget};
+
+/// Post processing effect which applies a sequence of post-processing effects.
+///
+/// Each effect of the chain takes the output of the previous one as input. The intermediate
+/// results are rendered alternatively to two off-screen textures, and the last effect draws to
+/// the render target selected when the chain is drawn, e.g., the screen. Every effect is given
+/// the depth buffer of the rendered scene. Note that nothing is drawn if the chain is empty.
+pub struct EffectChain {
+    effects: Vec<Box<dyn PostProcessingEffect>>,
+    targets: [RenderTarget; 2],
+    framebuffer_manager: FramebufferManager,
+    width: f32,
+    height: f32,
+}
+
+impl EffectChain {
+    /// Creates a new `EffectChain` applying `effects` in order.
+    pub fn new(effects: Vec<Box<dyn PostProcessingEffect>>) -> EffectChain {
+        EffectChain {
+            effects,
+            targets: [
+                FramebufferManager::new_render_target(1, 1, false),
+                FramebufferManager::new_render_target(1, 1, false),
+            ],
+            framebuffer_manager: FramebufferManager::new(),
+            width: 1.0,
+            height: 1.0,
+        }
+    }
+
+    /// The effects of this chain.
+    pub fn effects(&self) -> &[Box<dyn PostProcessingEffect>] {
+        &self.effects[..]
+    }
+
+    /// Mutable reference to the effects of this chain.
+    pub fn effects_mut(&mut self) -> &mut Vec<Box<dyn PostProcessingEffect>> {
+        &mut self.effects
+    }
+}
+
+impl PostProcessingEffect for EffectChain {
+    fn wants_ssr_output(&self) -> bool {
+        // Only the first effect reads the rendered scene.
+        self.effects
+            .first()
+            .map_or(false, |effect| effect.wants_ssr_output())
+    }
+
+    fn set_camera(&mut self, camera: &dyn Camera) {
+        for effect in self.effects.iter_mut() {
+            effect.set_camera(camera);
+        }
+    }
+
+    fn update(&mut self, dt: f32, w: f32, h: f32, znear: f32, zfar: f32) {
+        if w != self.width || h != self.height {
+            self.width = w;
+            self.height = h;
+
+            for target in self.targets.iter_mut() {
+                target.resize(w, h);
+            }
+        }
+
+        for effect in self.effects.iter_mut() {
+            effect.update(dt, w, h, znear, zfar);
+        }
+    }
+
+    fn draw(&mut self, target: &RenderTarget) {
+        let num_effects = self.effects.len();
+        // The render target selected by the caller, e.g., the screen.
+        let output = FramebufferManager::bound_framebuffer();
+
+        for (i, effect) in self.effects.iter_mut().enumerate() {
+            // The last effect is drawn to the caller's target, the others to the ping-pong
+            // textures.
+            if i == num_effects - 1 {
+                FramebufferManager::bind_framebuffer(output);
+            } else {
+                self.framebuffer_manager.select(&self.targets[i % 2]);
+            }
+
+            if i == 0 {
+                effect.draw(target);
+            } else {
+                // Every effect reads the depth of the scene.
+                let input = &self.targets[(i - 1) % 2];
+
+                match input.with_depth_of(target) {
+                    Some(input) => effect.draw(&input),
+                    None => effect.draw(input),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Post-processing effect implementing the Fast Approximate Anti-Aliasing (FXAA).
+
+use na::Vector2;
+
+use crate::context::Context;
+use crate::post_processing::PostProcessingEffect;
+use crate::resource::{
+    AllocationType, BufferType, Effect, GPUVec, RenderTarget, ShaderAttribute, ShaderUniform,
+};
+
+#[path = "../error.rs"]
+mod error;
+
+/// Post processing effect which smoothes the aliased edges of the scene.
+///
+/// This is a cheap alternative to multisampling, with the drawback of slightly blurring
+/// the sharp details like texts.
+pub struct Fxaa {
+    shader: Effect,
+    fbo_texture: ShaderUniform<i32>,
+    inv_size: ShaderUniform<Vector2<f32>>,
+    v_coord: ShaderAttribute<Vector2<f32>>,
+    fbo_vertices: GPUVec<Vector2<f32>>,
+    size: Vector2<f32>,
+}
+
+impl Fxaa {
+    /// Creates a new `Fxaa` post processing effect.
+    pub fn new() -> Fxaa {
+        let fbo_vertices: Vec<Vector2<f32>> = vec![
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(1.0, 1.0),
+        ];
+
+        let mut fbo_vertices =
+            GPUVec::new(fbo_vertices, BufferType::Array, AllocationType::StaticDraw);
+        fbo_vertices.load_to_gpu();
+        fbo_vertices.unload_from_ram();
+
+        let mut shader = Effect::new_from_str(VERTEX_SHADER, FRAGMENT_SHADER);
+
+        shader.use_program();
+
+        Fxaa {
+            fbo_texture: shader.get_uniform("fbo_texture").unwrap(),
+            inv_size: shader.get_uniform("inv_size").unwrap(),
+            v_coord: shader.get_attrib("v_coord").unwrap(),
+            fbo_vertices: fbo_vertices,
+            shader: shader,
+            size: Vector2::new(1.0, 1.0),
+        }
+    }
+}
+
+impl PostProcessingEffect for Fxaa {
+    fn update(&mut self, _: f32, w: f32, h: f32, _: f32, _: f32) {
+        self.size = Vector2::new(w, h);
+    }
+
+    fn draw(&mut self, target: &RenderTarget) {
+        let ctxt = Context::get();
+        self.v_coord.enable();
+
+        /*
+         * Finalize draw
+         */
+        self.shader.use_program();
+        verify!(ctxt.clear_color(0.0, 0.0, 0.0, 1.0));
+        verify!(ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, target.texture_id()));
+
+        self.fbo_texture.upload(&0);
+        self.inv_size
+            .upload(&Vector2::new(1.0 / self.size.x, 1.0 / self.size.y));
+        self.v_coord.bind(&mut self.fbo_vertices);
+
+        verify!(ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4));
+
+        self.v_coord.disable();
+    }
+}
+
+static VERTEX_SHADER: &'static str = "#version 100
+    attribute vec2    v_coord;
+    uniform sampler2D fbo_texture;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+      gl_Position = vec4(v_coord, 0.0, 1.0);
+      f_texcoord  = (v_coord + 1.0) / 2.0;
+    }";
+
+// Adapted from the FXAA algorithm by Timothy Lottes. The luma is computed from the RGB
+// components since the scene texture has no luma stored in its alpha channel.
+static FRAGMENT_SHADER: &'static str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    #define FXAA_REDUCE_MIN (1.0 / 128.0)
+    #define FXAA_REDUCE_MUL (1.0 / 8.0)
+    #define FXAA_SPAN_MAX   8.0
+
+    uniform sampler2D fbo_texture;
+    uniform vec2      inv_size;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+      vec3 rgbNW = texture2D(fbo_texture, f_texcoord + vec2(-1.0, -1.0) * inv_size).rgb;
+      vec3 rgbNE = texture2D(fbo_texture, f_texcoord + vec2(1.0, -1.0) * inv_size).rgb;
+      vec3 rgbSW = texture2D(fbo_texture, f_texcoord + vec2(-1.0, 1.0) * inv_size).rgb;
+      vec3 rgbSE = texture2D(fbo_texture, f_texcoord + vec2(1.0, 1.0) * inv_size).rgb;
+      vec4 rgbaM = texture2D(fbo_texture, f_texcoord);
+
+      vec3 luma   = vec3(0.299, 0.587, 0.114);
+      float lumaNW = dot(rgbNW, luma);
+      float lumaNE = dot(rgbNE, luma);
+      float lumaSW = dot(rgbSW, luma);
+      float lumaSE = dot(rgbSE, luma);
+      float lumaM  = dot(rgbaM.rgb, luma);
+      float lumaMin = min(lumaM, min(min(lumaNW, lumaNE), min(lumaSW, lumaSE)));
+      float lumaMax = max(lumaM, max(max(lumaNW, lumaNE), max(lumaSW, lumaSE)));
+
+      vec2 dir = vec2(-((lumaNW + lumaNE) - (lumaSW + lumaSE)),
+                      (lumaNW + lumaSW) - (lumaNE + lumaSE));
+
+      float dirReduce = max((lumaNW + lumaNE + lumaSW + lumaSE) * (0.25 * FXAA_REDUCE_MUL),
+                            FXAA_REDUCE_MIN);
+      float rcpDirMin = 1.0 / (min(abs(dir.x), abs(dir.y)) + dirReduce);
+      dir = min(vec2(FXAA_SPAN_MAX), max(vec2(-FXAA_SPAN_MAX), dir * rcpDirMin)) * inv_size;
+
+      vec3 rgbA = 0.5 * (
+          texture2D(fbo_texture, f_texcoord + dir * (1.0 / 3.0 - 0.5)).rgb +
+          texture2D(fbo_texture, f_texcoord + dir * (2.0 / 3.0 - 0.5)).rgb);
+      vec3 rgbB = rgbA * 0.5 + 0.25 * (
+          texture2D(fbo_texture, f_texcoord + dir * -0.5).rgb +
+          texture2D(fbo_texture, f_texcoord + dir * 0.5).rgb);
+      float lumaB = dot(rgbB, luma);
+
+      if (lumaB < lumaMin || lumaB > lumaMax) {
+        gl_FragColor = vec4(rgbA, rgbaM.a);
+      } else {
+        gl_FragColor = vec4(rgbB, rgbaM.a);
+      }
+    }";
@@ -1,15 +1,37 @@
 //! Post-processing effects.
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::post_processing::depth_of_field::DepthOfField;
+pub use crate::post_processing::effect_chain::EffectChain;
+pub use crate::post_processing::fxaa::Fxaa;
 pub use crate::post_processing::grayscales::Grayscales;
+pub use crate::post_processing::invert::Invert;
 pub use crate::post_processing::oculus_stereo::OculusStereo;
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::post_processing::outline::{Outline, OutlineConfig};
 pub use crate::post_processing::post_processing_effect::PostProcessingEffect;
 #[cfg(not(target_arch = "wasm32"))]
+pub use crate::post_processing::screen_space_reflection::{
+    ScreenSpaceReflection, SsrConfig, MAX_SSR_STEPS,
+};
+pub use crate::post_processing::sepia::Sepia;
+#[cfg(not(target_arch = "wasm32"))]
 pub use crate::post_processing::sobel_edge_highlight::SobelEdgeHighlight;
 pub use crate::post_processing::waves::Waves;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod depth_of_field;
+mod effect_chain;
+mod fxaa;
 mod grayscales;
+mod invert;
 mod oculus_stereo;
+#[cfg(not(target_arch = "wasm32"))]
+mod outline;
 pub mod post_processing_effect;
 #[cfg(not(target_arch = "wasm32"))]
+mod screen_space_reflection;
+mod sepia;
+#[cfg(not(target_arch = "wasm32"))]
 mod sobel_edge_highlight;
 mod waves;
@@ -0,0 +1,218 @@
+//! A post-processing effect outlining the objects.
+
+use na::{Point3, Vector2};
+
+use crate::context::Context;
+use crate::post_processing::post_processing_effect::PostProcessingEffect;
+use crate::resource::{
+    AllocationType, BufferType, Effect, GPUVec, RenderTarget, ShaderAttribute, ShaderUniform,
+};
+
+#[path = "../error.rs"]
+mod error;
+
+/// The parameters of the `Outline` post-processing effect.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OutlineConfig {
+    /// The color of the outlines.
+    pub color: Point3<f32>,
+    /// The width of the outlines, in pixels.
+    pub thickness: f32,
+}
+
+impl Default for OutlineConfig {
+    fn default() -> Self {
+        OutlineConfig {
+            color: Point3::origin(),
+            thickness: 1.0,
+        }
+    }
+}
+
+/// Post processing effect which outlines the objects, e.g., for cel-shaded rendering.
+///
+/// The outlines are drawn at the depth discontinuities, i.e., the silhouettes of the objects,
+/// and at the sharp creases of their surfaces, both detected from the depth buffer. The normals
+/// are not rendered to a separate buffer: a crease is a discontinuity of the depth slope.
+/// Nothing is drawn along the borders of the viewport.
+pub struct Outline {
+    config: OutlineConfig,
+    shiftx: f32,
+    shifty: f32,
+    zn: f32,
+    zf: f32,
+    shader: Effect,
+    gl_nx: ShaderUniform<f32>,
+    gl_ny: ShaderUniform<f32>,
+    gl_fbo_depth: ShaderUniform<i32>,
+    gl_fbo_texture: ShaderUniform<i32>,
+    gl_znear: ShaderUniform<f32>,
+    gl_zfar: ShaderUniform<f32>,
+    gl_color: ShaderUniform<Point3<f32>>,
+    gl_v_coord: ShaderAttribute<Vector2<f32>>,
+    gl_fbo_vertices: GPUVec<Vector2<f32>>,
+}
+
+impl Outline {
+    /// Creates a new Outline post processing effect.
+    pub fn new(config: OutlineConfig) -> Outline {
+        let fbo_vertices: Vec<Vector2<f32>> = vec![
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(1.0, 1.0),
+        ];
+
+        let mut fbo_vertices =
+            GPUVec::new(fbo_vertices, BufferType::Array, AllocationType::StaticDraw);
+        fbo_vertices.load_to_gpu();
+        fbo_vertices.unload_from_ram();
+
+        let mut shader = Effect::new_from_str(VERTEX_SHADER, FRAGMENT_SHADER);
+
+        shader.use_program();
+
+        Outline {
+            config,
+            shiftx: 0.0,
+            shifty: 0.0,
+            zn: 0.0,
+            zf: 0.0,
+            gl_nx: shader.get_uniform("nx").unwrap(),
+            gl_ny: shader.get_uniform("ny").unwrap(),
+            gl_fbo_depth: shader.get_uniform("fbo_depth").unwrap(),
+            gl_fbo_texture: shader.get_uniform("fbo_texture").unwrap(),
+            gl_znear: shader.get_uniform("znear").unwrap(),
+            gl_zfar: shader.get_uniform("zfar").unwrap(),
+            gl_color: shader.get_uniform("color").unwrap(),
+            gl_v_coord: shader.get_attrib("v_coord").unwrap(),
+            gl_fbo_vertices: fbo_vertices,
+            shader,
+        }
+    }
+
+    /// The parameters of this effect.
+    pub fn config(&self) -> &OutlineConfig {
+        &self.config
+    }
+
+    /// Sets the parameters of this effect.
+    pub fn set_config(&mut self, config: OutlineConfig) {
+        self.config = config
+    }
+}
+
+impl PostProcessingEffect for Outline {
+    fn update(&mut self, _: f32, w: f32, h: f32, znear: f32, zfar: f32) {
+        // The distance between the samples, in texture coordinates.
+        let thickness = self.config.thickness.max(1.0);
+        self.shiftx = thickness / w;
+        self.shifty = thickness / h;
+        self.zn = znear;
+        self.zf = zfar;
+    }
+
+    fn draw(&mut self, target: &RenderTarget) {
+        let ctxt = Context::get();
+        self.gl_v_coord.enable();
+
+        /*
+         * Finalize draw
+         */
+        verify!(ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT));
+
+        self.shader.use_program();
+
+        self.gl_nx.upload(&self.shiftx);
+        self.gl_ny.upload(&self.shifty);
+        self.gl_znear.upload(&self.zn);
+        self.gl_zfar.upload(&self.zf);
+        self.gl_color.upload(&self.config.color);
+
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, target.texture_id()));
+
+        self.gl_fbo_texture.upload(&0);
+
+        verify!(ctxt.active_texture(Context::TEXTURE1));
+        verify!(ctxt.bind_texture(
+            Context::TEXTURE_2D,
+            target.depth_id().and_then(|id| id.as_ref().left())
+        ));
+
+        self.gl_fbo_depth.upload(&1);
+
+        self.gl_v_coord.bind(&mut self.gl_fbo_vertices);
+
+        verify!(ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4));
+
+        self.gl_v_coord.disable();
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+    }
+}
+
+static VERTEX_SHADER: &'static str = "#version 100
+    attribute vec2    v_coord;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+        gl_Position = vec4(v_coord, 0.0, 1.0);
+        f_texcoord  = (v_coord + 1.0) / 2.0;
+    }";
+
+static FRAGMENT_SHADER: &'static str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D fbo_depth;
+    uniform sampler2D fbo_texture;
+    uniform float     nx;
+    uniform float     ny;
+    uniform float     znear;
+    uniform float     zfar;
+    uniform vec3      color;
+    varying vec2      f_texcoord;
+
+    // Relative depth difference considered as a silhouette.
+    const float silhouette_threshold = 0.05;
+    // Relative change of the depth slope considered as a crease.
+    const float crease_threshold = 0.01;
+
+    float lin_depth(vec2 uv) {
+        float nlin_depth = texture2D(fbo_depth, uv).x;
+
+        return znear * zfar / (zfar - nlin_depth * (zfar - znear));
+    }
+
+    // The samples outside of the viewport are replaced by the center so that no outline is
+    // drawn along the viewport borders.
+    float sample_depth(vec2 offset, float center) {
+        vec2 uv = f_texcoord + offset;
+
+        if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+            return center;
+        }
+
+        return lin_depth(uv);
+    }
+
+    void main(void) {
+        float d  = lin_depth(f_texcoord);
+        float dl = sample_depth(vec2(-nx, 0.0), d);
+        float dr = sample_depth(vec2(nx, 0.0), d);
+        float db = sample_depth(vec2(0.0, -ny), d);
+        float dt = sample_depth(vec2(0.0, ny), d);
+
+        float silhouette = max(max(abs(dl - d), abs(dr - d)), max(abs(db - d), abs(dt - d))) / d;
+        float crease = max(abs(dl + dr - 2.0 * d), abs(db + dt - 2.0 * d)) / d;
+
+        float edge = max(smoothstep(silhouette_threshold, 2.0 * silhouette_threshold, silhouette),
+                         smoothstep(crease_threshold, 2.0 * crease_threshold, crease));
+
+        vec4 scene_color = texture2D(fbo_texture, f_texcoord);
+
+        gl_FragColor = vec4(mix(scene_color.rgb, color, edge), 1.0);
+    }";
@@ -1,5 +1,6 @@
 //! Trait implemented by every post-processing effect.
 
+use crate::camera::Camera;
 use crate::resource::RenderTarget;
 
 /// Trait of post processing effect.
@@ -7,6 +8,17 @@ use crate::resource::RenderTarget;
 /// One post-processing effect can be used at a time. It is executed once the scene has been
 /// rendered on a texture.
 pub trait PostProcessingEffect {
+    /// Informs the effect of the camera the scene was rendered with.
+    ///
+    /// This is called before `update` at each frame. Does nothing by default.
+    fn set_camera(&mut self, _camera: &dyn Camera) {}
+    /// Whether the effect reads the reflectivity of the objects from the alpha channel of the
+    /// rendered scene, see `ScreenSpaceReflection`.
+    ///
+    /// The alpha channel is left unchanged otherwise. Returns `false` by default.
+    fn wants_ssr_output(&self) -> bool {
+        false
+    }
     /// Updates the post processing effect.
     fn update(&mut self, dt: f32, w: f32, h: f32, znear: f32, zfar: f32);
     /// Render the effect.
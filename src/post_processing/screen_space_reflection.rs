@@ -0,0 +1,322 @@
+//! A post-processing effect adding screen-space reflections.
+
+use std::rc::Rc;
+
+use na::{Matrix3, Matrix4, Point3, Vector2};
+
+use crate::camera::Camera;
+use crate::context::Context;
+use crate::post_processing::post_processing_effect::PostProcessingEffect;
+use crate::resource::{
+    AllocationType, BufferType, CubeMap, Effect, GPUVec, RenderTarget, ShaderAttribute,
+    ShaderUniform,
+};
+
+#[path = "../error.rs"]
+mod error;
+
+/// The maximum number of steps of the rays marched by the `ScreenSpaceReflection` effect.
+pub const MAX_SSR_STEPS: u32 = 256;
+
+/// The parameters of the `ScreenSpaceReflection` post-processing effect.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SsrConfig {
+    /// The maximum number of steps of each reflected ray, at most `MAX_SSR_STEPS`.
+    pub max_steps: u32,
+    /// The length, in view-space units, of each step of the reflected rays.
+    pub step_size: f32,
+    /// The color reflected by the rays leaving the screen when there is no skybox.
+    pub fallback_color: Point3<f32>,
+}
+
+impl Default for SsrConfig {
+    fn default() -> Self {
+        SsrConfig {
+            max_steps: 64,
+            step_size: 0.1,
+            fallback_color: Point3::origin(),
+        }
+    }
+}
+
+/// Post processing effect which adds the reflections of the visible objects onto the
+/// reflectors, e.g., water surfaces or glass floors.
+///
+/// Only the opaque objects marked with `Object::set_ssr_reflector` reflect their surroundings,
+/// proportionally to their reflectivity. Their reflectivity is stored in the alpha channel of the
+/// rendered scene, so this effect must be the first of an `EffectChain`. For each fragment of a
+/// reflector, the reflected ray is marched through the depth buffer until it hits the first
+/// object. The rays leaving the screen or missing every object reflect the skybox, or the
+/// fallback color.
+pub struct ScreenSpaceReflection {
+    config: SsrConfig,
+    skybox: Option<Rc<CubeMap>>,
+    proj: Matrix4<f32>,
+    inv_view_rotation: Matrix3<f32>,
+    texel: Vector2<f32>,
+    zn: f32,
+    zf: f32,
+    shader: Effect,
+    gl_fbo_texture: ShaderUniform<i32>,
+    gl_fbo_depth: ShaderUniform<i32>,
+    gl_skybox: ShaderUniform<i32>,
+    gl_has_skybox: ShaderUniform<i32>,
+    gl_fallback_color: ShaderUniform<Point3<f32>>,
+    gl_proj: ShaderUniform<Matrix4<f32>>,
+    gl_inv_view_rotation: ShaderUniform<Matrix3<f32>>,
+    gl_texel: ShaderUniform<Vector2<f32>>,
+    gl_znear: ShaderUniform<f32>,
+    gl_zfar: ShaderUniform<f32>,
+    gl_max_steps: ShaderUniform<i32>,
+    gl_step_size: ShaderUniform<f32>,
+    gl_v_coord: ShaderAttribute<Vector2<f32>>,
+    gl_fbo_vertices: GPUVec<Vector2<f32>>,
+}
+
+impl ScreenSpaceReflection {
+    /// Creates a new ScreenSpaceReflection post processing effect.
+    pub fn new(config: SsrConfig) -> ScreenSpaceReflection {
+        let fbo_vertices: Vec<Vector2<f32>> = vec![
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(1.0, 1.0),
+        ];
+
+        let mut fbo_vertices =
+            GPUVec::new(fbo_vertices, BufferType::Array, AllocationType::StaticDraw);
+        fbo_vertices.load_to_gpu();
+        fbo_vertices.unload_from_ram();
+
+        let mut shader = Effect::new_from_str(VERTEX_SHADER, FRAGMENT_SHADER);
+
+        shader.use_program();
+
+        ScreenSpaceReflection {
+            config,
+            skybox: None,
+            proj: Matrix4::identity(),
+            inv_view_rotation: Matrix3::identity(),
+            texel: Vector2::zeros(),
+            zn: 0.0,
+            zf: 0.0,
+            gl_fbo_texture: shader.get_uniform("fbo_texture").unwrap(),
+            gl_fbo_depth: shader.get_uniform("fbo_depth").unwrap(),
+            gl_skybox: shader.get_uniform("skybox").unwrap(),
+            gl_has_skybox: shader.get_uniform("has_skybox").unwrap(),
+            gl_fallback_color: shader.get_uniform("fallback_color").unwrap(),
+            gl_proj: shader.get_uniform("proj").unwrap(),
+            gl_inv_view_rotation: shader.get_uniform("inv_view_rotation").unwrap(),
+            gl_texel: shader.get_uniform("texel").unwrap(),
+            gl_znear: shader.get_uniform("znear").unwrap(),
+            gl_zfar: shader.get_uniform("zfar").unwrap(),
+            gl_max_steps: shader.get_uniform("max_steps").unwrap(),
+            gl_step_size: shader.get_uniform("step_size").unwrap(),
+            gl_v_coord: shader.get_attrib("v_coord").unwrap(),
+            gl_fbo_vertices: fbo_vertices,
+            shader,
+        }
+    }
+
+    /// The parameters of this effect.
+    pub fn config(&self) -> &SsrConfig {
+        &self.config
+    }
+
+    /// Sets the parameters of this effect.
+    pub fn set_config(&mut self, config: SsrConfig) {
+        self.config = config
+    }
+
+    /// Sets the cube-map reflected by the rays leaving the screen.
+    ///
+    /// The fallback color of the configuration is reflected instead if this is `None`.
+    pub fn set_skybox(&mut self, skybox: Option<Rc<CubeMap>>) {
+        self.skybox = skybox
+    }
+}
+
+impl PostProcessingEffect for ScreenSpaceReflection {
+    fn wants_ssr_output(&self) -> bool {
+        true
+    }
+
+    fn set_camera(&mut self, camera: &dyn Camera) {
+        let view = camera.view_transform();
+
+        // `transformation = proj * view`.
+        self.proj = camera.transformation() * view.inverse().to_homogeneous();
+        self.inv_view_rotation = view.rotation.inverse().to_rotation_matrix().into_inner();
+    }
+
+    fn update(&mut self, _: f32, w: f32, h: f32, znear: f32, zfar: f32) {
+        self.texel = Vector2::new(1.0 / w, 1.0 / h);
+        self.zn = znear;
+        self.zf = zfar;
+    }
+
+    fn draw(&mut self, target: &RenderTarget) {
+        let ctxt = Context::get();
+        self.gl_v_coord.enable();
+
+        /*
+         * Finalize draw
+         */
+        verify!(ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT));
+
+        self.shader.use_program();
+
+        let max_steps = self.config.max_steps.min(MAX_SSR_STEPS) as i32;
+        self.gl_fallback_color.upload(&self.config.fallback_color);
+        self.gl_proj.upload(&self.proj);
+        self.gl_inv_view_rotation.upload(&self.inv_view_rotation);
+        self.gl_texel.upload(&self.texel);
+        self.gl_znear.upload(&self.zn);
+        self.gl_zfar.upload(&self.zf);
+        self.gl_max_steps.upload(&max_steps);
+        self.gl_step_size.upload(&self.config.step_size);
+
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, target.texture_id()));
+
+        self.gl_fbo_texture.upload(&0);
+
+        verify!(ctxt.active_texture(Context::TEXTURE1));
+        verify!(ctxt.bind_texture(
+            Context::TEXTURE_2D,
+            target.depth_id().and_then(|id| id.as_ref().left())
+        ));
+
+        self.gl_fbo_depth.upload(&1);
+
+        // The cube sampler must never share its texture unit with the 2D samplers.
+        verify!(ctxt.active_texture(Context::TEXTURE2));
+        verify!(ctxt.bind_texture(
+            Context::TEXTURE_CUBE_MAP,
+            self.skybox.as_ref().map(|skybox| skybox.texture())
+        ));
+
+        self.gl_skybox.upload(&2);
+        self.gl_has_skybox.upload(&(self.skybox.is_some() as i32));
+
+        self.gl_v_coord.bind(&mut self.gl_fbo_vertices);
+
+        verify!(ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4));
+
+        self.gl_v_coord.disable();
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+    }
+}
+
+static VERTEX_SHADER: &'static str = "#version 100
+    attribute vec2    v_coord;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+        gl_Position = vec4(v_coord, 0.0, 1.0);
+        f_texcoord  = (v_coord + 1.0) / 2.0;
+    }";
+
+static FRAGMENT_SHADER: &'static str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D   fbo_texture;
+    uniform sampler2D   fbo_depth;
+    uniform samplerCube skybox;
+    uniform int         has_skybox;
+    uniform vec3        fallback_color;
+    uniform mat4        proj;
+    // Rotation from view-space to world-space.
+    uniform mat3        inv_view_rotation;
+    // Size of a pixel, in texture coordinates.
+    uniform vec2        texel;
+    uniform float       znear;
+    uniform float       zfar;
+    uniform int         max_steps;
+    uniform float       step_size;
+    varying vec2        f_texcoord;
+
+    // GLSL 100 loops must have a constant bound.
+    const int MAX_STEPS = 256;
+
+    float lin_depth(vec2 uv) {
+        float nlin_depth = texture2D(fbo_depth, uv).x;
+
+        return znear * zfar / (zfar - nlin_depth * (zfar - znear));
+    }
+
+    vec3 view_pos(vec2 uv) {
+        float z = lin_depth(uv);
+        vec2 ndc = uv * 2.0 - 1.0;
+
+        return vec3((ndc.x + proj[2][0]) * z / proj[0][0],
+                    (ndc.y + proj[2][1]) * z / proj[1][1],
+                    -z);
+    }
+
+    // The tangent along `offset`, taken on the side of the fragment closest in depth so that
+    // the normals stay accurate along the silhouettes.
+    vec3 tangent(vec3 center, vec2 offset) {
+        vec3 forward = view_pos(f_texcoord + offset) - center;
+        vec3 backward = center - view_pos(f_texcoord - offset);
+
+        return abs(forward.z) < abs(backward.z) ? forward : backward;
+    }
+
+    void main(void) {
+        vec4 scene_color = texture2D(fbo_texture, f_texcoord);
+        // The reflectors store their reflectivity into the alpha channel.
+        float reflectivity = 1.0 - scene_color.a;
+
+        if (reflectivity <= 0.0) {
+            gl_FragColor = vec4(scene_color.rgb, 1.0);
+            return;
+        }
+
+        vec3 pos = view_pos(f_texcoord);
+        vec3 normal = normalize(cross(tangent(pos, vec2(texel.x, 0.0)),
+                                      tangent(pos, vec2(0.0, texel.y))));
+
+        if (dot(normal, pos) > 0.0) {
+            normal = -normal;
+        }
+
+        vec3 dir = reflect(normalize(pos), normal);
+        vec3 reflection = has_skybox != 0 ? textureCube(skybox, inv_view_rotation * dir).rgb
+                                          : fallback_color;
+        // Start slightly above the surface to avoid hitting it right away.
+        vec3 ray = pos + normal * step_size * 0.5;
+
+        for (int i = 0; i < MAX_STEPS; i++) {
+            if (i >= max_steps) {
+                break;
+            }
+
+            ray += dir * step_size;
+
+            if (-ray.z < znear) {
+                break;
+            }
+
+            vec4 clip = proj * vec4(ray, 1.0);
+            vec2 uv = clip.xy / clip.w * 0.5 + 0.5;
+
+            if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+                break;
+            }
+
+            // The ray hits an object if it is behind the depth buffer, but not too far behind.
+            float depth_diff = -ray.z - lin_depth(uv);
+
+            if (depth_diff > 0.0 && depth_diff < 2.0 * step_size) {
+                reflection = texture2D(fbo_texture, uv).rgb;
+                break;
+            }
+        }
+
+        gl_FragColor = vec4(mix(scene_color.rgb, reflection, reflectivity), 1.0);
+    }";
@@ -0,0 +1,92 @@
+//! Convex hulls of point sets.
+
+use na::{Point3, Vector3};
+use ncollide3d::procedural::{IndexBuffer, TriMesh};
+use ncollide3d::transformation;
+
+/// Computes the flat-shaded convex hull of a set of points.
+///
+/// Each face of the hull has its own vertices, with the outward normal of the face. Returns an
+/// error if the points do not contain at least 4 non-coplanar points.
+pub fn convex_hull(points: &[Point3<f32>]) -> Result<TriMesh<f32>, &'static str> {
+    if !has_volume(points) {
+        return Err("A convex hull needs at least 4 non-coplanar points.");
+    }
+
+    let hull = transformation::convex_hull(points);
+    let faces = hull.indices.unwrap_unified();
+    let mut coords = Vec::with_capacity(faces.len() * 3);
+    let mut normals = Vec::with_capacity(faces.len() * 3);
+    let mut indices = Vec::with_capacity(faces.len());
+
+    for (i, face) in faces.iter().enumerate() {
+        let (a, b, c) = (
+            hull.coords[face.x as usize],
+            hull.coords[face.y as usize],
+            hull.coords[face.z as usize],
+        );
+        let normal = (b - a)
+            .cross(&(c - a))
+            .try_normalize(0.0)
+            .unwrap_or_else(Vector3::zeros);
+
+        coords.extend_from_slice(&[a, b, c]);
+        normals.extend_from_slice(&[normal, normal, normal]);
+
+        let first = i as u32 * 3;
+        indices.push(Point3::new(first, first + 1, first + 2));
+    }
+
+    Ok(TriMesh::new(
+        coords,
+        Some(normals),
+        None,
+        Some(IndexBuffer::Unified(indices)),
+    ))
+}
+
+/// Whether `points` contains at least 4 non-coplanar points.
+pub fn has_volume(points: &[Point3<f32>]) -> bool {
+    let first = match points.first() {
+        Some(pt) => *pt,
+        None => return false,
+    };
+
+    // The tolerance is relative to the extent of the point set.
+    let farthest = farthest_point(points, |pt| (pt - first).norm());
+    let extent = (farthest - first).norm();
+    let eps = extent * 1.0e-5;
+
+    if extent == 0.0 {
+        return false;
+    }
+
+    let axis = (farthest - first) / extent;
+    let off_axis = farthest_point(points, |pt| (pt - first).cross(&axis).norm());
+    let normal = match (farthest - first)
+        .cross(&(off_axis - first))
+        .try_normalize(eps * eps)
+    {
+        Some(normal) => normal,
+        None => return false,
+    };
+
+    points
+        .iter()
+        .any(|pt| (pt - first).dot(&normal).abs() > eps)
+}
+
+fn farthest_point(points: &[Point3<f32>], dist: impl Fn(&Point3<f32>) -> f32) -> Point3<f32> {
+    let mut res = points[0];
+    let mut max = dist(&res);
+
+    for pt in &points[1..] {
+        let d = dist(pt);
+        if d > max {
+            res = *pt;
+            max = d;
+        }
+    }
+
+    res
+}
@@ -0,0 +1,161 @@
+//! Cylinders, with or without their caps, and disks.
+
+use std::f32;
+
+use na::{Point2, Point3, Vector3};
+use ncollide3d::procedural::{IndexBuffer, TriMesh};
+
+/// Generates a cylinder with a height and a diameter equal to 1.0, and its caps.
+///
+/// The cylinder is centered at the origin, and has its principal axis aligned with the `y` axis.
+/// The caps have their own vertices so that they are lit as flat surfaces.
+///
+/// # Arguments
+/// * `nsubdiv` - the number of subdivisions of the circles. Must be at least 3.
+pub fn unit_cylinder(nsubdiv: u32) -> TriMesh<f32> {
+    let mut geom = Geometry::default();
+    geom.push_tube(nsubdiv);
+    geom.push_annulus(0.5, 0.0, 0.5, true, nsubdiv);
+    geom.push_annulus(0.5, 0.0, -0.5, false, nsubdiv);
+    geom.into_trimesh()
+}
+
+/// Generates a cylinder with a height and a diameter equal to 1.0, without its caps.
+///
+/// The cylinder is centered at the origin, and has its principal axis aligned with the `y` axis.
+/// Its normals point radially outward.
+///
+/// # Arguments
+/// * `nsubdiv` - the number of subdivisions of the circles. Must be at least 3.
+pub fn unit_open_cylinder(nsubdiv: u32) -> TriMesh<f32> {
+    let mut geom = Geometry::default();
+    geom.push_tube(nsubdiv);
+    geom.into_trimesh()
+}
+
+/// Generates a disk in the `xz` plane, facing the `y` axis.
+///
+/// The disk is centered at the origin. It has a hole of radius `inner_radius` if this radius is
+/// not zero.
+///
+/// # Arguments
+/// * `radius` - the radius of the disk.
+/// * `inner_radius` - the radius of the hole at the center of the disk.
+/// * `nsubdiv` - the number of subdivisions of the circles. Must be at least 3.
+pub fn disk(radius: f32, inner_radius: f32, nsubdiv: u32) -> TriMesh<f32> {
+    assert!(
+        inner_radius >= 0.0 && inner_radius < radius,
+        "The inner radius must be positive and smaller than the radius."
+    );
+
+    let mut geom = Geometry::default();
+    geom.push_annulus(radius, inner_radius, 0.0, true, nsubdiv);
+    geom.into_trimesh()
+}
+
+#[derive(Default)]
+struct Geometry {
+    coords: Vec<Point3<f32>>,
+    normals: Vec<Vector3<f32>>,
+    uvs: Vec<Point2<f32>>,
+    faces: Vec<Point3<u32>>,
+}
+
+impl Geometry {
+    // The wall of the unit cylinder. The first and last columns of vertices are at the same
+    // place but have different texture coordinates.
+    fn push_tube(&mut self, nsubdiv: u32) {
+        assert!(nsubdiv >= 3, "A circle needs at least 3 subdivisions.");
+
+        let first = self.coords.len() as u32;
+
+        for i in 0..=nsubdiv {
+            let (sin, cos) = angle(i, nsubdiv).sin_cos();
+            let u = i as f32 / nsubdiv as f32;
+
+            for &y in &[-0.5, 0.5] {
+                self.coords.push(Point3::new(cos * 0.5, y, sin * 0.5));
+                self.normals.push(Vector3::new(cos, 0.0, sin));
+                self.uvs.push(Point2::new(u, 0.5 - y));
+            }
+        }
+
+        for i in 0..nsubdiv {
+            let (bottom, top) = (first + i * 2, first + i * 2 + 1);
+            let (next_bottom, next_top) = (bottom + 2, top + 2);
+
+            self.faces.push(Point3::new(bottom, top, next_bottom));
+            self.faces.push(Point3::new(next_bottom, top, next_top));
+        }
+    }
+
+    // A flat ring at the height `y`, facing `+y` if `up` is `true`, and `-y` otherwise.
+    fn push_annulus(&mut self, radius: f32, inner_radius: f32, y: f32, up: bool, nsubdiv: u32) {
+        assert!(nsubdiv >= 3, "A circle needs at least 3 subdivisions.");
+
+        let first = self.coords.len() as u32;
+        let normal = if up { Vector3::y() } else { -Vector3::y() };
+        let radii: &[f32] = if inner_radius > 0.0 {
+            &[radius, inner_radius]
+        } else {
+            &[radius]
+        };
+
+        for i in 0..nsubdiv {
+            let (sin, cos) = angle(i, nsubdiv).sin_cos();
+
+            for &r in radii {
+                let (x, z) = (cos * r, sin * r);
+                self.coords.push(Point3::new(x, y, z));
+                self.normals.push(normal);
+                self.uvs.push(Point2::new(
+                    0.5 + x / (2.0 * radius),
+                    0.5 + z / (2.0 * radius),
+                ));
+            }
+        }
+
+        let mut push_face = |a: u32, b: u32, c: u32| {
+            let face = if up {
+                Point3::new(a, b, c)
+            } else {
+                Point3::new(a, c, b)
+            };
+            self.faces.push(face)
+        };
+
+        if inner_radius > 0.0 {
+            for i in 0..nsubdiv {
+                let next = (i + 1) % nsubdiv;
+                let (outer, inner) = (first + i * 2, first + i * 2 + 1);
+                let (next_outer, next_inner) = (first + next * 2, first + next * 2 + 1);
+
+                push_face(inner, next_outer, outer);
+                push_face(inner, next_inner, next_outer);
+            }
+        } else {
+            let center = first + nsubdiv;
+
+            for i in 0..nsubdiv {
+                push_face(center, first + (i + 1) % nsubdiv, first + i);
+            }
+
+            self.coords.push(Point3::new(0.0, y, 0.0));
+            self.normals.push(normal);
+            self.uvs.push(Point2::new(0.5, 0.5));
+        }
+    }
+
+    fn into_trimesh(self) -> TriMesh<f32> {
+        TriMesh::new(
+            self.coords,
+            Some(self.normals),
+            Some(self.uvs),
+            Some(IndexBuffer::Unified(self.faces)),
+        )
+    }
+}
+
+fn angle(i: u32, nsubdiv: u32) -> f32 {
+    f32::consts::PI * 2.0 * i as f32 / nsubdiv as f32
+}
@@ -0,0 +1,7 @@
+//! Procedural generation of the meshes not provided by `ncollide3d::procedural`.
+
+pub use self::convex_hull::{convex_hull, has_volume};
+pub use self::cylinder::{disk, unit_cylinder, unit_open_cylinder};
+
+mod convex_hull;
+mod cylinder;
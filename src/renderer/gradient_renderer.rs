@@ -0,0 +1,113 @@
+//! A renderer of a vertical color gradient drawn behind the scene.
+
+use crate::camera::Camera;
+use crate::context::Context;
+use crate::renderer::Renderer;
+use crate::resource::{AllocationType, BufferType, Effect, GPUVec, ShaderAttribute, ShaderUniform};
+use na::{Point3, Vector2};
+
+#[path = "../error.rs"]
+mod error;
+
+/// Structure which draws a vertical color gradient as the background of the scene.
+pub struct GradientRenderer {
+    shader: Effect,
+    v_coord: ShaderAttribute<Vector2<f32>>,
+    top: ShaderUniform<Point3<f32>>,
+    bottom: ShaderUniform<Point3<f32>>,
+    vertices: GPUVec<Vector2<f32>>,
+    colors: Option<(Point3<f32>, Point3<f32>)>,
+}
+
+impl GradientRenderer {
+    /// Creates a new gradient renderer, drawing nothing until colors are set.
+    pub fn new() -> GradientRenderer {
+        let vertices: Vec<Vector2<f32>> = vec![
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(1.0, 1.0),
+        ];
+
+        let mut vertices = GPUVec::new(vertices, BufferType::Array, AllocationType::StaticDraw);
+        vertices.load_to_gpu();
+        vertices.unload_from_ram();
+
+        let mut shader = Effect::new_from_str(GRADIENT_VERTEX_SRC, GRADIENT_FRAGMENT_SRC);
+
+        shader.use_program();
+
+        GradientRenderer {
+            v_coord: shader.get_attrib("v_coord").unwrap(),
+            top: shader.get_uniform("top").unwrap(),
+            bottom: shader.get_uniform("bottom").unwrap(),
+            vertices,
+            shader,
+            colors: None,
+        }
+    }
+
+    /// Sets the colors `(top, bottom)` of the gradient, or `None` to draw nothing.
+    pub fn set_colors(&mut self, colors: Option<(Point3<f32>, Point3<f32>)>) {
+        self.colors = colors
+    }
+
+    /// The colors `(top, bottom)` of the gradient, if any.
+    pub fn colors(&self) -> Option<(Point3<f32>, Point3<f32>)> {
+        self.colors
+    }
+}
+
+impl Renderer for GradientRenderer {
+    /// Draws the gradient over the whole viewport, without writing to the depth buffer.
+    fn render(&mut self, _pass: usize, _camera: &mut dyn Camera) {
+        let (top, bottom) = match self.colors {
+            Some(colors) => colors,
+            None => return,
+        };
+
+        let ctxt = Context::get();
+        self.shader.use_program();
+        self.v_coord.enable();
+
+        verify!(ctxt.disable(Context::DEPTH_TEST));
+        verify!(ctxt.depth_mask(false));
+
+        self.top.upload(&top);
+        self.bottom.upload(&bottom);
+        self.v_coord.bind(&mut self.vertices);
+
+        verify!(ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4));
+
+        self.v_coord.disable();
+        verify!(ctxt.depth_mask(true));
+        verify!(ctxt.enable(Context::DEPTH_TEST));
+    }
+}
+
+/// Vertex shader used to draw the gradient.
+pub static GRADIENT_VERTEX_SRC: &'static str = A_VERY_LONG_STRING;
+/// Fragment shader used to draw the gradient.
+pub static GRADIENT_FRAGMENT_SRC: &'static str = ANOTHER_VERY_LONG_STRING;
+
+const A_VERY_LONG_STRING: &'static str = "#version 100
+    attribute vec2 v_coord;
+    varying   float height;
+    void main() {
+        height = (v_coord.y + 1.0) / 2.0;
+        gl_Position = vec4(v_coord, 0.0, 1.0);
+    }";
+
+const ANOTHER_VERY_LONG_STRING: &'static str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform vec3  top;
+    uniform vec3  bottom;
+    varying float height;
+    void main() {
+        gl_FragColor = vec4(mix(bottom, top, height), 1.0);
+    }";
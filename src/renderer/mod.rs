@@ -2,12 +2,18 @@
 
 #[cfg(feature = "conrod")]
 pub use self::conrod_renderer::ConrodRenderer;
+pub use self::gradient_renderer::GradientRenderer;
 pub use self::line_renderer::LineRenderer;
+pub use self::particle_renderer::ParticleRenderer;
 pub use self::point_renderer::PointRenderer;
 pub use self::renderer::Renderer;
+pub use self::skybox_renderer::SkyboxRenderer;
 
 #[cfg(feature = "conrod")]
 mod conrod_renderer;
+pub mod gradient_renderer;
 pub mod line_renderer;
+pub mod particle_renderer;
 pub mod point_renderer;
 mod renderer;
+pub mod skybox_renderer;
@@ -0,0 +1,152 @@
+//! A renderer for particle systems.
+
+use crate::camera::Camera;
+use crate::context::Context;
+use crate::renderer::Renderer;
+use crate::resource::{AllocationType, BufferType, Effect, GPUVec, ShaderAttribute, ShaderUniform};
+use crate::scene::ParticleSystem;
+use na::{Matrix4, Point4};
+
+#[path = "../error.rs"]
+mod error;
+
+/// Structure which updates and draws a set of particle systems.
+pub struct ParticleRenderer {
+    shader: Effect,
+    pos: ShaderAttribute<Point4<f32>>,
+    color: ShaderAttribute<Point4<f32>>,
+    proj: ShaderUniform<Matrix4<f32>>,
+    view: ShaderUniform<Matrix4<f32>>,
+    points: GPUVec<Point4<f32>>,
+    systems: Vec<ParticleSystem>,
+}
+
+impl ParticleRenderer {
+    /// Creates a new particle renderer.
+    pub fn new() -> ParticleRenderer {
+        let mut shader = Effect::new_from_str(PARTICLES_VERTEX_SRC, PARTICLES_FRAGMENT_SRC);
+
+        shader.use_program();
+
+        ParticleRenderer {
+            points: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::StreamDraw),
+            pos: shader.get_attrib::<Point4<f32>>("position").unwrap(),
+            color: shader.get_attrib::<Point4<f32>>("color").unwrap(),
+            proj: shader.get_uniform::<Matrix4<f32>>("proj").unwrap(),
+            view: shader.get_uniform::<Matrix4<f32>>("view").unwrap(),
+            shader,
+            systems: Vec::new(),
+        }
+    }
+
+    /// Adds a particle system to be updated and drawn by this renderer.
+    pub fn add(&mut self, system: ParticleSystem) {
+        if !self.systems.iter().any(|s| s.is_same(&system)) {
+            self.systems.push(system)
+        }
+    }
+
+    /// Removes a particle system from this renderer.
+    pub fn remove(&mut self, system: &ParticleSystem) {
+        self.systems.retain(|s| !s.is_same(system))
+    }
+
+    /// Advances the simulation of every particle system by `dt` seconds.
+    pub fn update(&mut self, dt: f32) {
+        for system in &mut self.systems {
+            system.update(dt)
+        }
+    }
+}
+
+impl Renderer for ParticleRenderer {
+    /// Actually draws the particles.
+    fn render(&mut self, pass: usize, camera: &mut dyn Camera) {
+        let ctxt = Context::get();
+        let mut shader_enabled = false;
+
+        for system in &self.systems {
+            let data = system.data();
+
+            if !data.is_visible() || data.particles().is_empty() {
+                continue;
+            }
+
+            if !shader_enabled {
+                self.shader.use_program();
+                self.pos.enable();
+                self.color.enable();
+                camera.upload(pass, &mut self.proj, &mut self.view);
+
+                verify!(ctxt.enable(Context::BLEND));
+                verify!(ctxt.depth_mask(false));
+                shader_enabled = true;
+            }
+
+            let config = data.config();
+
+            for points in self.points.data_mut().iter_mut() {
+                points.clear();
+
+                for particle in data.particles() {
+                    let pos = particle.position;
+                    points.push(Point4::new(pos.x, pos.y, pos.z, particle.size(config)));
+                    points.push(particle.color(config));
+                }
+            }
+
+            let (src, dst) = config.blend_mode.factors();
+            verify!(ctxt.blend_func_separate(src, dst, Context::ONE, Context::ONE_MINUS_SRC_ALPHA));
+
+            self.color.bind_sub_buffer(&mut self.points, 1, 1);
+            self.pos.bind_sub_buffer(&mut self.points, 1, 0);
+
+            verify!(ctxt.draw_arrays(Context::POINTS, 0, (self.points.len() / 2) as i32));
+        }
+
+        if shader_enabled {
+            verify!(ctxt.depth_mask(true));
+            verify!(ctxt.disable(Context::BLEND));
+
+            self.pos.disable();
+            self.color.disable();
+        }
+    }
+}
+
+/// Vertex shader used to display the particles.
+pub static PARTICLES_VERTEX_SRC: &'static str = A_VERY_LONG_STRING;
+/// Fragment shader used to display the particles.
+pub static PARTICLES_FRAGMENT_SRC: &'static str = ANOTHER_VERY_LONG_STRING;
+
+const A_VERY_LONG_STRING: &'static str = "#version 100
+    attribute vec4 position;
+    attribute vec4 color;
+    varying   vec4 Color;
+    uniform   mat4 proj;
+    uniform   mat4 view;
+    void main() {
+        gl_Position = proj * view * vec4(position.xyz, 1.0);
+        gl_PointSize = position.w;
+        Color = color;
+    }";
+
+const ANOTHER_VERY_LONG_STRING: &'static str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    varying vec4 Color;
+    void main() {
+        // Round particles with a soft edge.
+        vec2 d = gl_PointCoord - vec2(0.5);
+        float r2 = dot(d, d);
+
+        if (r2 > 0.25) {
+            discard;
+        }
+
+        gl_FragColor = vec4(Color.rgb, Color.a * (1.0 - 4.0 * r2));
+    }";
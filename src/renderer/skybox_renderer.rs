@@ -0,0 +1,144 @@
+//! A renderer of a cube-map drawn behind the scene.
+
+use crate::camera::Camera;
+use crate::context::Context;
+use crate::renderer::Renderer;
+use crate::resource::{
+    AllocationType, BufferType, CubeMap, Effect, GPUVec, ShaderAttribute, ShaderUniform,
+};
+use na::{Matrix4, UnitQuaternion, Vector2};
+use std::rc::Rc;
+
+#[path = "../error.rs"]
+mod error;
+
+/// Structure which draws a cube-map as the background of the scene.
+///
+/// The cube-map is seen from the camera eye, so only the camera rotation affects it.
+/// The cube-map itself can be rotated, e.g., for a day/night cycle.
+pub struct SkyboxRenderer {
+    shader: Effect,
+    v_coord: ShaderAttribute<Vector2<f32>>,
+    inv_proj_rotation: ShaderUniform<Matrix4<f32>>,
+    skybox: ShaderUniform<i32>,
+    vertices: GPUVec<Vector2<f32>>,
+    cube_map: Option<Rc<CubeMap>>,
+    rotation: UnitQuaternion<f32>,
+}
+
+impl SkyboxRenderer {
+    /// Creates a new skybox renderer, drawing nothing until a cube-map is set.
+    pub fn new() -> SkyboxRenderer {
+        let vertices: Vec<Vector2<f32>> = vec![
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(1.0, 1.0),
+        ];
+
+        let mut vertices = GPUVec::new(vertices, BufferType::Array, AllocationType::StaticDraw);
+        vertices.load_to_gpu();
+        vertices.unload_from_ram();
+
+        let mut shader = Effect::new_from_str(SKYBOX_VERTEX_SRC, SKYBOX_FRAGMENT_SRC);
+
+        shader.use_program();
+
+        SkyboxRenderer {
+            v_coord: shader.get_attrib("v_coord").unwrap(),
+            inv_proj_rotation: shader.get_uniform("inv_proj_rotation").unwrap(),
+            skybox: shader.get_uniform("skybox").unwrap(),
+            vertices,
+            shader,
+            cube_map: None,
+            rotation: UnitQuaternion::identity(),
+        }
+    }
+
+    /// Sets the cube-map drawn behind the scene, or `None` to draw nothing.
+    pub fn set_cube_map(&mut self, cube_map: Option<Rc<CubeMap>>) {
+        self.cube_map = cube_map
+    }
+
+    /// The cube-map drawn behind the scene.
+    pub fn cube_map(&self) -> Option<&Rc<CubeMap>> {
+        self.cube_map.as_ref()
+    }
+
+    /// Sets the rotation of the cube-map, relative to the world axes.
+    pub fn set_rotation(&mut self, rotation: UnitQuaternion<f32>) {
+        self.rotation = rotation
+    }
+
+    /// The rotation of the cube-map, relative to the world axes.
+    pub fn rotation(&self) -> &UnitQuaternion<f32> {
+        &self.rotation
+    }
+}
+
+impl Renderer for SkyboxRenderer {
+    /// Draws the cube-map over the whole viewport, without writing to the depth buffer.
+    fn render(&mut self, _pass: usize, camera: &mut dyn Camera) {
+        let cube_map = match self.cube_map {
+            Some(ref cube_map) => cube_map,
+            None => return,
+        };
+
+        // The view-space directions are rotated to world-space without the camera translation:
+        // `rotation⁻¹ * proj⁻¹ = rotation⁻¹ * view * (proj * view)⁻¹`, then to the space of
+        // the rotated cube-map.
+        let view = camera.view_transform();
+        let inv_proj_rotation = self.rotation.inverse().to_homogeneous()
+            * view.rotation.inverse().to_homogeneous()
+            * view.to_homogeneous()
+            * camera.inverse_transformation();
+
+        let ctxt = Context::get();
+        self.shader.use_program();
+        self.v_coord.enable();
+
+        verify!(ctxt.disable(Context::DEPTH_TEST));
+        verify!(ctxt.depth_mask(false));
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_CUBE_MAP, Some(cube_map.texture())));
+
+        self.inv_proj_rotation.upload(&inv_proj_rotation);
+        self.skybox.upload(&0);
+        self.v_coord.bind(&mut self.vertices);
+
+        verify!(ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4));
+
+        self.v_coord.disable();
+        verify!(ctxt.bind_texture(Context::TEXTURE_CUBE_MAP, None));
+        verify!(ctxt.depth_mask(true));
+        verify!(ctxt.enable(Context::DEPTH_TEST));
+    }
+}
+
+/// Vertex shader used to draw the skybox.
+pub static SKYBOX_VERTEX_SRC: &'static str = A_VERY_LONG_STRING;
+/// Fragment shader used to draw the skybox.
+pub static SKYBOX_FRAGMENT_SRC: &'static str = ANOTHER_VERY_LONG_STRING;
+
+const A_VERY_LONG_STRING: &'static str = "#version 100
+    attribute vec2 v_coord;
+    uniform   mat4 inv_proj_rotation;
+    varying   vec3 direction;
+    void main() {
+        vec4 dir = inv_proj_rotation * vec4(v_coord, -1.0, 1.0);
+        direction = dir.xyz / dir.w;
+        gl_Position = vec4(v_coord, 0.0, 1.0);
+    }";
+
+const ANOTHER_VERY_LONG_STRING: &'static str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform samplerCube skybox;
+    varying vec3 direction;
+    void main() {
+        gl_FragColor = vec4(textureCube(skybox, direction).rgb, 1.0);
+    }";
@@ -0,0 +1,270 @@
+//! Cube-map textures.
+
+use image::{self, DynamicImage};
+use na::Vector2;
+use std::path::Path;
+
+use crate::context::{Context, Texture};
+use crate::resource::{AllocationType, BufferType, Effect, FramebufferManager, GPUVec};
+
+#[path = "../error.rs"]
+mod error;
+
+/// The size in pixels of the faces of the cube-maps computed by `CubeMap::compute_irradiance`.
+const IRRADIANCE_SIZE: i32 = 32;
+/// The maximum number of samples per texel of `CubeMap::compute_irradiance`.
+pub const MAX_IRRADIANCE_SAMPLES: u32 = 1024;
+
+const FACE_TARGETS: [u32; 6] = [
+    Context::TEXTURE_CUBE_MAP_POSITIVE_X,
+    Context::TEXTURE_CUBE_MAP_NEGATIVE_X,
+    Context::TEXTURE_CUBE_MAP_POSITIVE_Y,
+    Context::TEXTURE_CUBE_MAP_NEGATIVE_Y,
+    Context::TEXTURE_CUBE_MAP_POSITIVE_Z,
+    Context::TEXTURE_CUBE_MAP_NEGATIVE_Z,
+];
+
+/// A texture made of six square images, one for each face of a cube.
+///
+/// It is sampled with a direction instead of texture coordinates, e.g., for environment maps.
+pub struct CubeMap {
+    texture: Texture,
+}
+
+impl CubeMap {
+    /// Uploads a cube-map from the images of its faces.
+    ///
+    /// The faces are given in the order `+X`, `-X`, `+Y`, `-Y`, `+Z`, `-Z`. They must all be
+    /// square images of the same size, in the RGB8 or RGBA8 format.
+    pub fn from_images(faces: [DynamicImage; 6]) -> Result<CubeMap, &'static str> {
+        let ctxt = Context::get();
+        let texture = verify!(ctxt
+            .create_texture()
+            .expect("Could not create cube-map texture."));
+
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_CUBE_MAP, Some(&texture)));
+
+        for (face, target) in faces.iter().zip(FACE_TARGETS.iter()) {
+            let (format, width, height, pixels) = match *face {
+                DynamicImage::ImageRgb8(ref image) => {
+                    (Context::RGB, image.width(), image.height(), &**image)
+                }
+                DynamicImage::ImageRgba8(ref image) => {
+                    (Context::RGBA, image.width(), image.height(), &**image)
+                }
+                _ => {
+                    verify!(ctxt.bind_texture(Context::TEXTURE_CUBE_MAP, None));
+                    return Err("Failed to load cube-map, unsuported pixel format.");
+                }
+            };
+
+            verify!(ctxt.tex_image2d(
+                *target,
+                0,
+                format as i32,
+                width as i32,
+                height as i32,
+                0,
+                format,
+                Some(pixels)
+            ));
+        }
+
+        set_parameters();
+        verify!(ctxt.bind_texture(Context::TEXTURE_CUBE_MAP, None));
+
+        Ok(CubeMap { texture })
+    }
+
+    /// Loads a cube-map from the image files of its faces.
+    ///
+    /// The faces are given in the order `+X`, `-X`, `+Y`, `-Y`, `+Z`, `-Z`. Panics if one of
+    /// the files cannot be loaded. See `CubeMap::from_images` for details.
+    pub fn from_files(faces: [&Path; 6]) -> CubeMap {
+        let load = |path: &Path| image::open(path).expect(path.to_str().unwrap());
+        let images = [
+            load(faces[0]),
+            load(faces[1]),
+            load(faces[2]),
+            load(faces[3]),
+            load(faces[4]),
+            load(faces[5]),
+        ];
+
+        CubeMap::from_images(images).unwrap()
+    }
+
+    /// Convolves this cube-map into a diffuse irradiance cube-map, on the GPU.
+    ///
+    /// Each texel of the result is the cosine-weighted average of this cube-map over the
+    /// hemisphere around its direction, i.e., the light diffused by a white surface facing this
+    /// direction. It is meant for the ambient lighting of the physically-based materials, see
+    /// `Window::set_irradiance_map`. The average is estimated from `samples` directions, clamped
+    /// to `[1, MAX_IRRADIANCE_SAMPLES]`: a few hundreds give smooth results. The faces of the
+    /// result are 32 pixels wide, as the irradiance varies slowly.
+    ///
+    /// This renders to an off-screen framebuffer and changes the viewport, so it should not be
+    /// called while a frame is being rendered.
+    pub fn compute_irradiance(&self, samples: u32) -> CubeMap {
+        let ctxt = Context::get();
+        let texture = verify!(ctxt
+            .create_texture()
+            .expect("Could not create cube-map texture."));
+
+        verify!(ctxt.active_texture(Context::TEXTURE0));
+        verify!(ctxt.bind_texture(Context::TEXTURE_CUBE_MAP, Some(&texture)));
+
+        for target in FACE_TARGETS.iter() {
+            verify!(ctxt.tex_image2d(
+                *target,
+                0,
+                Context::RGB as i32,
+                IRRADIANCE_SIZE,
+                IRRADIANCE_SIZE,
+                0,
+                Context::RGB,
+                None
+            ));
+        }
+
+        set_parameters();
+
+        let vertices: Vec<Vector2<f32>> = vec![
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(1.0, 1.0),
+        ];
+        let mut vertices = GPUVec::new(vertices, BufferType::Array, AllocationType::StaticDraw);
+        vertices.load_to_gpu();
+        let mut shader = Effect::new_from_str(IRRADIANCE_VERTEX_SRC, IRRADIANCE_FRAGMENT_SRC);
+        shader.use_program();
+
+        let mut v_coord = shader.get_attrib::<Vector2<f32>>("v_coord").unwrap();
+        let mut source = shader.get_uniform::<i32>("source").unwrap();
+        let mut face = shader.get_uniform::<i32>("face").unwrap();
+        let mut num_samples = shader.get_uniform::<i32>("samples").unwrap();
+
+        let fbo = verify!(ctxt
+            .create_framebuffer()
+            .expect("Could not create framebuffer."));
+        verify!(ctxt.bind_framebuffer(Context::FRAMEBUFFER, Some(&fbo)));
+        verify!(ctxt.viewport(0, 0, IRRADIANCE_SIZE, IRRADIANCE_SIZE));
+        verify!(ctxt.disable(Context::DEPTH_TEST));
+
+        // Only the source is bound, as the result must not be sampled while rendered to.
+        verify!(ctxt.bind_texture(Context::TEXTURE_CUBE_MAP, Some(&self.texture)));
+        source.upload(&0);
+        num_samples.upload(&(samples.clamp(1, MAX_IRRADIANCE_SAMPLES) as i32));
+        v_coord.enable();
+        v_coord.bind(&mut vertices);
+
+        for (i, target) in FACE_TARGETS.iter().enumerate() {
+            verify!(ctxt.framebuffer_texture2d(
+                Context::FRAMEBUFFER,
+                Context::COLOR_ATTACHMENT0,
+                *target,
+                Some(&texture),
+                0
+            ));
+            face.upload(&(i as i32));
+            verify!(ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4));
+        }
+
+        v_coord.disable();
+        verify!(ctxt.bind_texture(Context::TEXTURE_CUBE_MAP, None));
+        verify!(ctxt.enable(Context::DEPTH_TEST));
+        verify!(ctxt.bind_framebuffer(
+            Context::FRAMEBUFFER,
+            FramebufferManager::bound_framebuffer().as_ref()
+        ));
+        verify!(ctxt.delete_framebuffer(Some(&fbo)));
+
+        CubeMap { texture }
+    }
+
+    /// The OpenGL texture of this cube-map, to be bound to `Context::TEXTURE_CUBE_MAP`.
+    #[inline]
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+}
+
+// Sets the sampling parameters of the cube-map bound to `Context::TEXTURE_CUBE_MAP`.
+fn set_parameters() {
+    let ctxt = Context::get();
+
+    for (param, value) in &[
+        (Context::TEXTURE_WRAP_S, Context::CLAMP_TO_EDGE),
+        (Context::TEXTURE_WRAP_T, Context::CLAMP_TO_EDGE),
+        (Context::TEXTURE_MIN_FILTER, Context::LINEAR),
+        (Context::TEXTURE_MAG_FILTER, Context::LINEAR),
+    ] {
+        verify!(ctxt.tex_parameteri(Context::TEXTURE_CUBE_MAP, *param, *value as i32));
+    }
+}
+
+static IRRADIANCE_VERTEX_SRC: &'static str = "#version 100
+    attribute vec2 v_coord;
+    varying   vec2 uv;
+    void main() {
+        uv = v_coord;
+        gl_Position = vec4(v_coord, 0.0, 1.0);
+    }";
+
+static IRRADIANCE_FRAGMENT_SRC: &'static str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform samplerCube source;
+    uniform int face;
+    uniform int samples;
+    varying vec2 uv;
+
+    const int MAX_SAMPLES = 1024;
+
+    // The direction of the texel at `uv` on the current face, following the cube-map layout
+    // of OpenGL.
+    vec3 face_direction() {
+        if (face == 0) {
+            return vec3(1.0, -uv.y, -uv.x);
+        } else if (face == 1) {
+            return vec3(-1.0, -uv.y, uv.x);
+        } else if (face == 2) {
+            return vec3(uv.x, 1.0, uv.y);
+        } else if (face == 3) {
+            return vec3(uv.x, -1.0, -uv.y);
+        } else if (face == 4) {
+            return vec3(uv.x, -uv.y, 1.0);
+        }
+
+        return vec3(-uv.x, -uv.y, -1.0);
+    }
+
+    void main() {
+        vec3 normal = normalize(face_direction());
+        vec3 up = abs(normal.y) < 0.999 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0);
+        vec3 tangent = normalize(cross(up, normal));
+        vec3 bitangent = cross(normal, tangent);
+
+        // Cosine-weighted directions on a spiral of golden angle steps.
+        vec3 sum = vec3(0.0);
+        float n = float(samples);
+        for (int i = 0; i < MAX_SAMPLES; i++) {
+            if (i >= samples) {
+                break;
+            }
+
+            float u = (float(i) + 0.5) / n;
+            float r = sqrt(u);
+            float phi = float(i) * 2.39996323;
+            vec3 dir = r * cos(phi) * tangent + r * sin(phi) * bitangent + sqrt(1.0 - u) * normal;
+            sum += textureCube(source, dir).rgb;
+        }
+
+        gl_FragColor = vec4(sum / n, 1.0);
+    }";
@@ -7,6 +7,7 @@ use std::str;
 
 use crate::context::{Context, GLintptr, Program, Shader, UniformLocation};
 use crate::resource::{GLPrimitive, GPUVec};
+use na::{Point3, Vector4};
 
 #[path = "../error.rs"]
 mod error;
@@ -42,14 +43,23 @@ impl Effect {
     }
 
     /// Creates a new shader program from strings of the vertex and fragment shader.
+    ///
+    /// Panics if the shaders fail to compile or to link.
     pub fn new_from_str(vshader: &str, fshader: &str) -> Effect {
-        let (program, vshader, fshader) = load_shader_program(vshader, fshader);
+        Effect::try_new_from_str(vshader, fshader).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a new shader program from strings of the vertex and fragment shader.
+    ///
+    /// Returns the compilation or link log if the shaders are invalid.
+    pub fn try_new_from_str(vshader: &str, fshader: &str) -> Result<Effect, String> {
+        let (program, vshader, fshader) = load_shader_program(vshader, fshader)?;
 
-        Effect {
+        Ok(Effect {
             program,
             vshader,
             fshader,
-        }
+        })
     }
 
     /// Gets a uniform variable from the shader program.
@@ -146,6 +156,13 @@ impl<T: GLPrimitive> ShaderAttribute<T> {
         ));
     }
 
+    /// Sets the number of instances drawn with each value of this attribute.
+    ///
+    /// A divisor of 0, the default, reads a new value for each vertex instead.
+    pub fn set_divisor(&mut self, divisor: u32) {
+        verify!(Context::get().vertex_attrib_divisor(self.id, divisor));
+    }
+
     /// Binds this attribute to non contiguous parts of a gpu vector.
     pub fn bind_sub_buffer(&mut self, vector: &mut GPUVec<T>, strides: usize, start_index: usize) {
         unsafe { self.bind_sub_buffer_generic(vector, strides, start_index) }
@@ -173,48 +190,88 @@ impl<T: GLPrimitive> ShaderAttribute<T> {
     }
 }
 
+impl ShaderAttribute<Point3<f32>> {
+    /// Sets the value of this attribute for all the vertices, when it is disabled.
+    pub fn set_value(&mut self, value: &Point3<f32>) {
+        verify!(Context::get().vertex_attrib3f(self.id, value.x, value.y, value.z));
+    }
+}
+
+impl ShaderAttribute<Vector4<f32>> {
+    /// Sets the value of this attribute for all the vertices, when it is disabled.
+    pub fn set_value(&mut self, value: &Vector4<f32>) {
+        verify!(Context::get().vertex_attrib4f(self.id, value.x, value.y, value.z, value.w));
+    }
+}
+
+impl ShaderAttribute<f32> {
+    /// Binds this attribute to a gpu vector of floats, with `components` consecutive floats per
+    /// vertex.
+    pub fn bind_components(&mut self, vector: &mut GPUVec<f32>, components: u32) {
+        vector.bind();
+
+        verify!(Context::get().vertex_attrib_pointer(
+            self.id,
+            components as i32,
+            f32::gl_type(),
+            false,
+            0,
+            0
+        ));
+    }
+}
+
 /// Loads a shader program using the given source codes for the vertex and fragment shader.
 ///
 /// Fails after displaying opengl compilation errors if the shaders are invalid.
-fn load_shader_program(vertex_shader: &str, fragment_shader: &str) -> (Program, Shader, Shader) {
+fn load_shader_program(
+    vertex_shader: &str,
+    fragment_shader: &str,
+) -> Result<(Program, Shader, Shader), String> {
     // Create and compile the vertex shader
     let ctxt = Context::get();
-    let vshader = verify!(ctxt
-        .create_shader(Context::VERTEX_SHADER)
-        .expect("Could not create vertex shader."));
-
-    verify!(ctxt.shader_source(&vshader, vertex_shader));
-    verify!(ctxt.compile_shader(&vshader));
-    check_shader_error(&vshader);
+    let vshader = compile_shader(Context::VERTEX_SHADER, vertex_shader)?;
 
     // Create and compile the fragment shader
-    let fshader = verify!(ctxt
-        .create_shader(Context::FRAGMENT_SHADER)
-        .expect("Could not create fragment shader."));
-    verify!(ctxt.shader_source(&fshader, fragment_shader));
-    verify!(ctxt.compile_shader(&fshader));
-    check_shader_error(&fshader);
+    let fshader = match compile_shader(Context::FRAGMENT_SHADER, fragment_shader) {
+        Ok(fshader) => fshader,
+        Err(e) => {
+            verify!(ctxt.delete_shader(Some(&vshader)));
+            return Err(e);
+        }
+    };
 
     // Link the vertex and fragment shader into a shader program
     let program = verify!(ctxt.create_program().expect("Could not create program."));
     verify!(ctxt.attach_shader(&program, &vshader));
     verify!(ctxt.attach_shader(&program, &fshader));
     verify!(ctxt.link_program(&program));
-    (program, vshader, fshader)
+
+    if !ctxt.get_program_link_status(&program) {
+        let log = ctxt.get_program_info_log(&program).unwrap_or_default();
+        verify!(ctxt.delete_program(Some(&program)));
+        verify!(ctxt.delete_shader(Some(&vshader)));
+        verify!(ctxt.delete_shader(Some(&fshader)));
+        return Err(format!("Shader program linking failed: {}", log));
+    }
+
+    Ok((program, vshader, fshader))
 }
 
-/// Checks if a shader handle is valid.
-///
-/// If it is not valid, it fails with a descriptive error message.
-fn check_shader_error(shader: &Shader) {
+fn compile_shader(shader_type: u32, source: &str) -> Result<Shader, String> {
     let ctxt = Context::get();
-    let compiles = ctxt.get_shader_parameter_int(shader, Context::COMPILE_STATUS);
+    let shader = verify!(ctxt
+        .create_shader(shader_type)
+        .expect("Could not create shader."));
 
-    if compiles == Some(0) {
-        if let Some(log) = ctxt.get_shader_info_log(shader) {
-            panic!("Shader compilation failed: {}", log);
-        } else {
-            println!("Shader compilation failed.");
-        }
+    verify!(ctxt.shader_source(&shader, source));
+    verify!(ctxt.compile_shader(&shader));
+
+    if ctxt.get_shader_parameter_int(&shader, Context::COMPILE_STATUS) == Some(0) {
+        let log = ctxt.get_shader_info_log(&shader).unwrap_or_default();
+        verify!(ctxt.delete_shader(Some(&shader)));
+        return Err(format!("Shader compilation failed: {}", log));
     }
+
+    Ok(shader)
 }
@@ -2,10 +2,15 @@
 
 use crate::context::{Context, Framebuffer, Renderbuffer, Texture};
 use either::Either;
+use std::cell::Cell;
+use std::rc::Rc;
 
 #[path = "../error.rs"]
 mod error;
 
+// The framebuffer bound by the framebuffer managers, `None` being the screen.
+thread_local!(static KEY_BOUND_FRAMEBUFFER: Cell<Option<Framebuffer>> = const { Cell::new(None) });
+
 /// The target to every rendering call.
 pub enum RenderTarget {
     /// The screen (main framebuffer).
@@ -16,8 +21,9 @@ pub enum RenderTarget {
 
 /// OpenGL identifiers to an off-screen buffer.
 pub struct OffscreenBuffers {
-    texture: Texture,
-    depth: Either<Texture, Renderbuffer>,
+    texture: Rc<Texture>,
+    depth: Rc<Either<Texture, Renderbuffer>>,
+    size: (u32, u32),
 }
 
 impl RenderTarget {
@@ -27,7 +33,28 @@ impl RenderTarget {
     pub fn texture_id(&self) -> Option<&Texture> {
         match *self {
             RenderTarget::Screen => None,
-            RenderTarget::Offscreen(ref o) => Some(&o.texture),
+            RenderTarget::Offscreen(ref o) => Some(&*o.texture),
+        }
+    }
+
+    /// Returns a shared handle to the off-screen color texture.
+    ///
+    /// This texture can be given to `Object::set_texture` to display the content of this render
+    /// target on an object. Returns `None` if this is the screen.
+    pub fn texture(&self) -> Option<Rc<Texture>> {
+        match *self {
+            RenderTarget::Screen => None,
+            RenderTarget::Offscreen(ref o) => Some(o.texture.clone()),
+        }
+    }
+
+    /// The size in pixels of the off-screen buffers.
+    ///
+    /// Returns `None` if this is the screen.
+    pub fn size(&self) -> Option<(u32, u32)> {
+        match *self {
+            RenderTarget::Screen => None,
+            RenderTarget::Offscreen(ref o) => Some(o.size),
         }
     }
 
@@ -38,7 +65,24 @@ impl RenderTarget {
     pub fn depth_id(&self) -> Option<&Either<Texture, Renderbuffer>> {
         match *self {
             RenderTarget::Screen => None,
-            RenderTarget::Offscreen(ref o) => Some(&o.depth),
+            RenderTarget::Offscreen(ref o) => Some(&*o.depth),
+        }
+    }
+
+    /// A render target with the color texture of `self` and the depth buffer of `depth`, both
+    /// shared with their render targets.
+    ///
+    /// Returns `None` if either is the screen.
+    pub(crate) fn with_depth_of(&self, depth: &RenderTarget) -> Option<RenderTarget> {
+        match (self, depth) {
+            (RenderTarget::Offscreen(ref o), RenderTarget::Offscreen(ref d)) => {
+                Some(RenderTarget::Offscreen(OffscreenBuffers {
+                    texture: o.texture.clone(),
+                    depth: d.depth.clone(),
+                    size: o.size,
+                }))
+            }
+            _ => None,
         }
     }
 
@@ -50,9 +94,11 @@ impl RenderTarget {
             RenderTarget::Screen => {
                 verify!(ctxt.viewport(0, 0, w as i32, h as i32));
             }
-            RenderTarget::Offscreen(ref o) => {
+            RenderTarget::Offscreen(ref mut o) => {
+                o.size = (w as u32, h as u32);
+
                 // Update the fbo
-                verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&o.texture)));
+                verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&*o.texture)));
                 verify!(ctxt.tex_image2d(
                     Context::TEXTURE_2D,
                     0,
@@ -65,7 +111,7 @@ impl RenderTarget {
                 ));
                 verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
 
-                match &o.depth {
+                match &*o.depth {
                     Either::Left(texture) => {
                         verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(texture)));
                         verify!(ctxt.tex_image2d(
@@ -98,7 +144,6 @@ impl RenderTarget {
 /// A framebuffer manager. It is a simple to to switch between an off-screen framebuffer and the
 /// default (window) framebuffer.
 pub struct FramebufferManager {
-    fbo_onscreen: bool,
     fbo: Framebuffer,
 }
 
@@ -113,12 +158,9 @@ impl FramebufferManager {
             .expect("Framebuffer creation failed.");
 
         // ensure that the current framebuffer is the screen
-        verify!(ctxt.bind_framebuffer(Context::FRAMEBUFFER, None));
+        FramebufferManager::bind_framebuffer(None);
 
-        FramebufferManager {
-            fbo_onscreen: true,
-            fbo: fbo,
-        }
+        FramebufferManager { fbo: fbo }
     }
 
     /// Creates a new render target. A render target is the combination of a color buffer and a
@@ -206,8 +248,9 @@ impl FramebufferManager {
             verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
 
             RenderTarget::Offscreen(OffscreenBuffers {
-                texture: fbo_texture,
-                depth: Either::Left(fbo_depth),
+                texture: Rc::new(fbo_texture),
+                depth: Rc::new(Either::Left(fbo_depth)),
+                size: (width as u32, height as u32),
             })
         } else {
             // Create a renderbuffer instead of the texture for the depth.
@@ -222,12 +265,29 @@ impl FramebufferManager {
             verify!(ctxt.bind_renderbuffer(None));
 
             RenderTarget::Offscreen(OffscreenBuffers {
-                texture: fbo_texture,
-                depth: Either::Right(renderbuffer),
+                texture: Rc::new(fbo_texture),
+                depth: Rc::new(Either::Right(renderbuffer)),
+                size: (width as u32, height as u32),
             })
         }
     }
 
+    /// The framebuffer currently bound, `None` being the screen.
+    ///
+    /// Rebinding it with `FramebufferManager::bind_framebuffer` restores the render target
+    /// selected by another framebuffer manager.
+    pub(crate) fn bound_framebuffer() -> Option<Framebuffer> {
+        KEY_BOUND_FRAMEBUFFER.with(|fbo| fbo.get())
+    }
+
+    /// Binds `framebuffer`, `None` being the screen, unless it is already bound.
+    pub(crate) fn bind_framebuffer(framebuffer: Option<Framebuffer>) {
+        if Self::bound_framebuffer() != framebuffer {
+            verify!(Context::get().bind_framebuffer(Context::FRAMEBUFFER, framebuffer.as_ref()));
+            KEY_BOUND_FRAMEBUFFER.with(|fbo| fbo.set(framebuffer));
+        }
+    }
+
     /// Returns the render target associated with the screen.
     pub fn screen() -> RenderTarget {
         RenderTarget::Screen
@@ -249,11 +309,11 @@ impl FramebufferManager {
                     Context::FRAMEBUFFER,
                     Context::COLOR_ATTACHMENT0,
                     Context::TEXTURE_2D,
-                    Some(&o.texture),
+                    Some(&*o.texture),
                     0
                 ));
 
-                match &o.depth {
+                match &*o.depth {
                     Either::Left(texture) => {
                         verify!(ctxt.framebuffer_texture2d(
                             Context::FRAMEBUFFER,
@@ -271,17 +331,11 @@ impl FramebufferManager {
     }
 
     fn select_onscreen(&mut self) {
-        if !self.fbo_onscreen {
-            verify!(Context::get().bind_framebuffer(Context::FRAMEBUFFER, None));
-            self.fbo_onscreen = true;
-        }
+        FramebufferManager::bind_framebuffer(None);
     }
 
     fn select_fbo(&mut self) {
-        if self.fbo_onscreen {
-            verify!(Context::get().bind_framebuffer(Context::FRAMEBUFFER, Some(&self.fbo)));
-            self.fbo_onscreen = false;
-        }
+        FramebufferManager::bind_framebuffer(Some(self.fbo));
     }
 }
 
@@ -289,7 +343,10 @@ impl Drop for FramebufferManager {
     fn drop(&mut self) {
         let ctxt = Context::get();
         if verify!(ctxt.is_framebuffer(Some(&self.fbo))) {
-            verify!(ctxt.bind_framebuffer(Context::FRAMEBUFFER, None));
+            // Deleting a bound framebuffer binds the screen.
+            if FramebufferManager::bound_framebuffer() == Some(self.fbo) {
+                FramebufferManager::bind_framebuffer(None);
+            }
             verify!(ctxt.delete_framebuffer(Some(&self.fbo)));
         }
     }
@@ -297,21 +354,13 @@ impl Drop for FramebufferManager {
 
 impl Drop for OffscreenBuffers {
     fn drop(&mut self) {
-        let ctxt = Context::get();
-        if verify!(ctxt.is_texture(Some(&self.texture))) {
-            verify!(ctxt.delete_texture(Some(&self.texture)));
-        }
+        // The textures may be shared with some objects or other render targets, so they delete
+        // themselves when their last reference is dropped.
+        if let Some(Either::Right(renderbuffer)) = Rc::get_mut(&mut self.depth) {
+            let ctxt = Context::get();
 
-        match &self.depth {
-            Either::Left(texture) => {
-                if verify!(ctxt.is_texture(Some(texture))) {
-                    verify!(ctxt.delete_texture(Some(texture)));
-                }
-            }
-            Either::Right(renderbuffer) => {
-                if verify!(ctxt.is_renderbuffer(Some(renderbuffer))) {
-                    verify!(ctxt.delete_renderbuffer(Some(renderbuffer)));
-                }
+            if verify!(ctxt.is_renderbuffer(Some(renderbuffer))) {
+                verify!(ctxt.delete_renderbuffer(Some(renderbuffer)));
             }
         }
     }
@@ -10,6 +10,7 @@ mod error;
 /// A vector of elements that can be loaded to the GPU, on the RAM, or both.
 pub struct GPUVec<T> {
     trash: bool,
+    version: usize,
     len: usize,
     buf_type: BufferType,
     alloc_type: AllocationType,
@@ -23,6 +24,7 @@ impl<T: GLPrimitive> GPUVec<T> {
     pub fn new(data: Vec<T>, buf_type: BufferType, alloc_type: AllocationType) -> GPUVec<T> {
         GPUVec {
             trash: true,
+            version: 0,
             len: data.len(),
             buf_type: buf_type,
             alloc_type: alloc_type,
@@ -46,10 +48,11 @@ impl<T: GLPrimitive> GPUVec<T> {
 
     /// Mutably accesses the vector if it is available on RAM.
     ///
-    /// This method will mark this vector as `trash`.
+    /// This method will mark this vector as `trash` and increment its version.
     #[inline]
     pub fn data_mut(&mut self) -> &mut Option<Vec<T>> {
         self.trash = true;
+        self.version = self.version.wrapping_add(1);
 
         &mut self.data
     }
@@ -60,6 +63,14 @@ impl<T: GLPrimitive> GPUVec<T> {
         &self.data
     }
 
+    /// The number of times this vector has been mutably accessed with `data_mut`.
+    ///
+    /// This is useful to know if the data computed from this vector are out of date.
+    #[inline]
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
     /// Returns `true` if this vector is already uploaded to the GPU.
     #[inline]
     pub fn is_on_gpu(&self) -> bool {
@@ -266,7 +277,7 @@ pub fn update_buffer<T: GLPrimitive>(
 
         verify!(ctxt.bind_buffer(gpu_buf_type.to_gl(), Some(gpu_buf)));
 
-        if arr.len() < gpu_buf_len {
+        if arr.len() <= gpu_buf_len {
             verify!(ctxt.buffer_sub_data(gpu_buf_type.to_gl(), 0, arr));
             gpu_buf_len
         } else {
@@ -63,6 +63,16 @@ impl MaterialManager {
         self.materials.get(&name.to_string()).map(|t| t.clone())
     }
 
+    /// The name of a registered material, if any.
+    ///
+    /// The default material is registered as `"object"`.
+    pub fn name_of(&self, material: &Rc<RefCell<Box<dyn Material + 'static>>>) -> Option<String> {
+        self.materials
+            .iter()
+            .find(|(_, m)| Rc::ptr_eq(m, material))
+            .map(|(name, _)| name.clone())
+    }
+
     /// Adds a material with the specified name to this cache.
     pub fn add(&mut self, material: Rc<RefCell<Box<dyn Material + 'static>>>, name: &str) {
         let _ = self.materials.insert(name.to_string(), material);
@@ -1,25 +1,117 @@
 //! Data structure of a scene node geometry.
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
 use std::iter;
 use std::sync::{Arc, RwLock};
 
+use crate::context::Context;
 use crate::resource::gpu_vector::{AllocationType, BufferType, GPUVec};
-use crate::resource::ShaderAttribute;
+use crate::resource::{Effect, GLPrimitive, ShaderAttribute};
 use na::{self, Point2, Point3, Vector3};
+use ncollide3d::bounding_volume::AABB;
 use ncollide3d::procedural::{IndexBuffer, TriMesh};
 use num::Zero;
 
 #[path = "../error.rs"]
 mod error;
 
+// The number of vertices that can be indexed with `u16`.
+pub(crate) const MAX_U16_VERTICES: usize = u16::max_value() as usize + 1;
+
+/// The integer type of the indices of a mesh.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IndexType {
+    /// 16-bits indices, for meshes with at most 65536 vertices.
+    U16,
+    /// 32-bits indices, for larger meshes.
+    U32,
+}
+
+impl IndexType {
+    /// The OpenGL type of the indices, as expected by `Context::draw_elements`.
+    #[inline]
+    pub fn to_gl(self) -> u32 {
+        match self {
+            IndexType::U16 => Context::UNSIGNED_SHORT,
+            IndexType::U32 => Context::UNSIGNED_INT,
+        }
+    }
+}
+
+/// Errors that may occur when creating a mesh.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MeshError {
+    /// A face refers to a vertex that does not exist.
+    InvalidIndex {
+        /// The invalid vertex index.
+        index: u32,
+        /// The number of vertices of the mesh.
+        num_vertices: usize,
+    },
+    /// There are not as many normals or texture coordinates as vertices.
+    InvalidAttributeLength {
+        /// The attribute with an invalid length, `"normals"` or `"uvs"`.
+        attribute: &'static str,
+        /// The number of values of the attribute.
+        len: usize,
+        /// The number of vertices of the mesh.
+        num_vertices: usize,
+    },
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MeshError::InvalidIndex {
+                index,
+                num_vertices,
+            } => write!(
+                f,
+                "Invalid mesh: a face refers to the vertex {} but there are only {} vertices.",
+                index, num_vertices
+            ),
+            MeshError::InvalidAttributeLength {
+                attribute,
+                len,
+                num_vertices,
+            } => write!(
+                f,
+                "Invalid mesh: found {} {} for {} vertices.",
+                len, attribute, num_vertices
+            ),
+        }
+    }
+}
+
+impl Error for MeshError {}
+
 /// Aggregation of vertices, indices, normals and texture coordinates.
 ///
-/// It also contains the GPU location of those buffers.
+/// It also contains the GPU location of those buffers. The indices are stored as `u16` unless
+/// the mesh has too many vertices, in which case they are stored as `u32`.
 pub struct Mesh {
     coords: Arc<RwLock<GPUVec<Point3<f32>>>>,
     faces: Arc<RwLock<GPUVec<Point3<u16>>>>,
+    faces_u32: Option<Arc<RwLock<GPUVec<Point3<u32>>>>>,
     normals: Arc<RwLock<GPUVec<Vector3<f32>>>>,
     uvs: Arc<RwLock<GPUVec<Point2<f32>>>>,
     edges: Option<Arc<RwLock<GPUVec<Point2<u16>>>>>,
+    edges_u32: Option<Arc<RwLock<GPUVec<Point2<u32>>>>>,
+    attributes: Vec<VertexAttribute>,
+    aabb: Cached<AABB<f32>>,
+    bounding_sphere: Cached<(Point3<f32>, f32)>,
+}
+
+// A value computed from the vertex coordinates of a mesh, with the version of the coordinates
+// it has been computed for.
+type Cached<T> = Cell<Option<(usize, Option<T>)>>;
+
+// A custom vertex attribute, bound by name to the shaders declaring it.
+struct VertexAttribute {
+    name: String,
+    components: u32,
+    data: Arc<RwLock<GPUVec<f32>>>,
 }
 
 impl Mesh {
@@ -68,10 +160,110 @@ impl Mesh {
         Mesh::new_with_gpu_vectors(cs, fs, ns, us)
     }
 
+    /// Creates a new mesh with 32-bits indices, checking its validity.
+    ///
+    /// The indices are converted to `u16` if the mesh has at most 65536 vertices, and are kept
+    /// as `u32` otherwise. If the normals and uvs are not given, they are automatically
+    /// computed.
+    pub fn try_new(
+        coords: Vec<Point3<f32>>,
+        faces: Vec<Point3<u32>>,
+        normals: Option<Vec<Vector3<f32>>>,
+        uvs: Option<Vec<Point2<f32>>>,
+        dynamic_draw: bool,
+    ) -> Result<Mesh, MeshError> {
+        let num_vertices = coords.len();
+
+        if let Some(index) = faces
+            .iter()
+            .flat_map(|f| f.coords.iter())
+            .find(|i| **i as usize >= num_vertices)
+        {
+            return Err(MeshError::InvalidIndex {
+                index: *index,
+                num_vertices,
+            });
+        }
+
+        for (attribute, len) in &[
+            ("normals", normals.as_ref().map(|n| n.len())),
+            ("uvs", uvs.as_ref().map(|u| u.len())),
+        ] {
+            if let Some(len) = *len {
+                if len != num_vertices {
+                    return Err(MeshError::InvalidAttributeLength {
+                        attribute: *attribute,
+                        len,
+                        num_vertices,
+                    });
+                }
+            }
+        }
+
+        if num_vertices <= MAX_U16_VERTICES {
+            let faces = faces.into_iter().map(|e| na::convert(e)).collect();
+            return Ok(Mesh::new(coords, faces, normals, uvs, dynamic_draw));
+        }
+
+        let normals = match normals {
+            Some(ns) => ns,
+            None => {
+                let mut ns = Vec::new();
+                compute_normals_generic(&coords[..], &faces[..], &mut ns);
+                ns
+            }
+        };
+
+        let uvs = match uvs {
+            Some(us) => us,
+            None => iter::repeat(Point2::origin()).take(num_vertices).collect(),
+        };
+
+        let location = if dynamic_draw {
+            AllocationType::DynamicDraw
+        } else {
+            AllocationType::StaticDraw
+        };
+        let cs = Arc::new(RwLock::new(GPUVec::new(
+            coords,
+            BufferType::Array,
+            location,
+        )));
+        let fs = Arc::new(RwLock::new(GPUVec::new(
+            Vec::new(),
+            BufferType::ElementArray,
+            location,
+        )));
+        let ns = Arc::new(RwLock::new(GPUVec::new(
+            normals,
+            BufferType::Array,
+            location,
+        )));
+        let us = Arc::new(RwLock::new(GPUVec::new(uvs, BufferType::Array, location)));
+
+        let mut mesh = Mesh::new_with_gpu_vectors(cs, fs, ns, us);
+        mesh.faces_u32 = Some(Arc::new(RwLock::new(GPUVec::new(
+            faces,
+            BufferType::ElementArray,
+            location,
+        ))));
+
+        Ok(mesh)
+    }
+
     /// Creates a new mesh from a mesh descr.
     ///
-    /// In the normals and uvs are not given, they are automatically computed.
+    /// In the normals and uvs are not given, they are automatically computed. Panics if the
+    /// mesh is invalid, see `Mesh::try_from_trimesh`.
     pub fn from_trimesh(mesh: TriMesh<f32>, dynamic_draw: bool) -> Mesh {
+        Mesh::try_from_trimesh(mesh, dynamic_draw).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a new mesh from a mesh descr, checking its validity.
+    ///
+    /// Meshes with more than 65536 vertices use 32-bits indices. See `Mesh::try_new` for
+    /// details.
+    pub fn try_from_trimesh(mesh: TriMesh<f32>, dynamic_draw: bool) -> Result<Mesh, MeshError> {
         let mut mesh = mesh;
 
         mesh.unify_index_buffer();
@@ -83,17 +275,7 @@ impl Mesh {
             indices,
         } = mesh;
 
-        Mesh::new(
-            coords,
-            indices
-                .unwrap_unified()
-                .into_iter()
-                .map(|e| na::convert(e))
-                .collect(),
-            normals,
-            uvs,
-            dynamic_draw,
-        )
+        Mesh::try_new(coords, indices.unwrap_unified(), normals, uvs, dynamic_draw)
     }
 
     // XXX: The `load_to_ram` require WebGL 2.
@@ -110,21 +292,25 @@ impl Mesh {
         }
 
         let coords = self.coords.read().unwrap().to_owned();
-        let faces = self.faces.read().unwrap().to_owned();
         let normals = self.normals.read().unwrap().to_owned();
         let uvs = self.uvs.read().unwrap().to_owned();
+        let faces = match self.faces_u32 {
+            Some(ref faces) => faces.read().unwrap().to_owned()?,
+            None => self
+                .faces
+                .read()
+                .unwrap()
+                .to_owned()?
+                .into_iter()
+                .map(|e| Point3::new(e.x as u32, e.y as u32, e.z as u32))
+                .collect(),
+        };
 
         Some(TriMesh::new(
             coords.unwrap(),
             normals,
             uvs,
-            Some(IndexBuffer::Unified(
-                faces
-                    .unwrap()
-                    .into_iter()
-                    .map(|e| Point3::new(e.x as u32, e.y as u32, e.z as u32))
-                    .collect(),
-            )),
+            Some(IndexBuffer::Unified(faces)),
         ))
 
         /*
@@ -174,6 +360,66 @@ impl Mesh {
         }*/
     }
 
+    /// Creates a flat-shaded copy of this mesh.
+    ///
+    /// Each triangle of the copy has its own three vertices, all with the normal of the face.
+    /// The copy uses 32-bits indices if it has more than 65536 vertices. Return `None` if the
+    /// mesh data is not available on the CPU.
+    pub fn to_flat_shaded(&self) -> Option<Mesh> {
+        let faces: Vec<Point3<u32>> = match self.faces_u32 {
+            Some(ref faces) => faces.read().unwrap().data().as_ref()?.clone(),
+            None => self
+                .faces
+                .read()
+                .unwrap()
+                .data()
+                .as_ref()?
+                .iter()
+                .map(|f| na::convert(*f))
+                .collect(),
+        };
+        let coords = self.coords.read().unwrap();
+        let uvs = self.uvs.read().unwrap();
+        let (coords, uvs) = (coords.data().as_ref()?, uvs.data());
+
+        let mut flat_coords = Vec::with_capacity(faces.len() * 3);
+        let mut flat_normals = Vec::with_capacity(faces.len() * 3);
+        let mut flat_uvs = Vec::with_capacity(faces.len() * 3);
+        let mut flat_faces = Vec::with_capacity(faces.len());
+
+        for (i, face) in faces.iter().enumerate() {
+            let (a, b, c) = (face.x as usize, face.y as usize, face.z as usize);
+            let cross = (coords[b] - coords[a]).cross(&(coords[c] - coords[a]));
+            let normal = if !cross.is_zero() {
+                cross.normalize()
+            } else {
+                cross
+            };
+
+            for &id in [a, b, c].iter() {
+                flat_coords.push(coords[id]);
+                flat_normals.push(normal);
+                flat_uvs.push(
+                    uvs.as_ref()
+                        .map(|uvs| uvs[id])
+                        .unwrap_or_else(Point2::origin),
+                );
+            }
+
+            let first = (i * 3) as u32;
+            flat_faces.push(Point3::new(first, first + 1, first + 2));
+        }
+
+        Mesh::try_new(
+            flat_coords,
+            flat_faces,
+            Some(flat_normals),
+            Some(flat_uvs),
+            false,
+        )
+        .ok()
+    }
+
     /// Creates a new mesh. Arguments set to `None` are automatically computed.
     pub fn new_with_gpu_vectors(
         coords: Arc<RwLock<GPUVec<Point3<f32>>>>,
@@ -186,10 +432,34 @@ impl Mesh {
             faces: faces,
             normals: normals,
             uvs: uvs,
+            faces_u32: None,
             edges: None,
+            edges_u32: None,
+            attributes: Vec::new(),
+            aabb: Cell::new(None),
+            bounding_sphere: Cell::new(None),
         }
     }
 
+    /// Creates a new mesh with 32-bits indices, e.g., for meshes with more than 65536
+    /// vertices.
+    pub fn new_with_gpu_vectors_u32(
+        coords: Arc<RwLock<GPUVec<Point3<f32>>>>,
+        faces: Arc<RwLock<GPUVec<Point3<u32>>>>,
+        normals: Arc<RwLock<GPUVec<Vector3<f32>>>>,
+        uvs: Arc<RwLock<GPUVec<Point2<f32>>>>,
+    ) -> Mesh {
+        let empty = GPUVec::new(
+            Vec::new(),
+            BufferType::ElementArray,
+            AllocationType::StaticDraw,
+        );
+        let mut mesh =
+            Mesh::new_with_gpu_vectors(coords, Arc::new(RwLock::new(empty)), normals, uvs);
+        mesh.faces_u32 = Some(faces);
+        mesh
+    }
+
     /// Binds this mesh vertex coordinates buffer to a vertex attribute.
     pub fn bind_coords(&mut self, coords: &mut ShaderAttribute<Point3<f32>>) {
         coords.bind(&mut *self.coords.write().unwrap());
@@ -205,9 +475,113 @@ impl Mesh {
         uvs.bind(&mut *self.uvs.write().unwrap());
     }
 
+    /// Adds a custom vertex attribute to this mesh, or replaces the one with the same name.
+    ///
+    /// The attribute is available to the shaders declaring an `attribute` named `name`, of type
+    /// `float`, `vec2`, `vec3` or `vec4` depending on `components`. The `data` contains
+    /// `components` consecutive values for each vertex of this mesh. The shaders of the
+    /// materials have to bind it with `Mesh::bind_vertex_attributes`, like `ObjectMaterial`
+    /// does.
+    pub fn add_vertex_attribute(&mut self, name: &str, components: u32, data: &[f32]) {
+        assert!(
+            components >= 1 && components <= 4,
+            "A vertex attribute must have between 1 and 4 components."
+        );
+        self.check_vertex_attribute_len(components, data);
+
+        let data = GPUVec::new(
+            data.to_vec(),
+            BufferType::Array,
+            AllocationType::DynamicDraw,
+        );
+        let attribute = VertexAttribute {
+            name: name.to_string(),
+            components,
+            data: Arc::new(RwLock::new(data)),
+        };
+
+        match self.attributes.iter_mut().find(|a| a.name == name) {
+            Some(old) => *old = attribute,
+            None => self.attributes.push(attribute),
+        }
+    }
+
+    /// Replaces the values of the custom vertex attribute named `name`.
+    ///
+    /// Panics if this mesh has no such attribute, or if `data` does not contain as many values
+    /// as when the attribute was added.
+    pub fn update_vertex_attribute(&mut self, name: &str, data: &[f32]) {
+        let components = self
+            .attributes
+            .iter()
+            .find(|a| a.name == name)
+            .map(|a| a.components)
+            .unwrap_or_else(|| panic!("Unknown vertex attribute: {}.", name));
+        self.check_vertex_attribute_len(components, data);
+
+        let attribute = self.attributes.iter().find(|a| a.name == name).unwrap();
+        let mut gpu_data = attribute.data.write().unwrap();
+
+        match gpu_data.data_mut() {
+            Some(values) => values.copy_from_slice(data),
+            values => *values = Some(data.to_vec()),
+        }
+    }
+
+    /// The values of the custom vertex attribute named `name`, if this mesh has one.
+    pub fn vertex_attribute(&self, name: &str) -> Option<&Arc<RwLock<GPUVec<f32>>>> {
+        self.attributes
+            .iter()
+            .find(|a| a.name == name)
+            .map(|a| &a.data)
+    }
+
+    /// Binds the custom vertex attributes of this mesh declared by the shaders of `effect`.
+    ///
+    /// The attributes not declared by the shaders are ignored.
+    pub fn bind_vertex_attributes(&mut self, effect: &Effect) {
+        for attribute in &self.attributes {
+            if let Some(mut shader_attribute) = effect.get_attrib::<f32>(&attribute.name) {
+                shader_attribute.enable();
+                shader_attribute
+                    .bind_components(&mut *attribute.data.write().unwrap(), attribute.components);
+            }
+        }
+    }
+
+    /// Unbinds the custom vertex attributes of this mesh bound by `bind_vertex_attributes`.
+    pub fn unbind_vertex_attributes(&self, effect: &Effect) {
+        for attribute in &self.attributes {
+            if let Some(mut shader_attribute) = effect.get_attrib::<f32>(&attribute.name) {
+                shader_attribute.disable();
+                attribute.data.write().unwrap().unbind();
+            }
+        }
+    }
+
+    fn check_vertex_attribute_len(&self, components: u32, data: &[f32]) {
+        assert!(
+            data.len() == components as usize * self.coords.read().unwrap().len(),
+            "A vertex attribute must have `components` values per vertex."
+        );
+    }
+
     /// Binds this mesh index buffer to a vertex attribute.
     pub fn bind_faces(&mut self) {
-        self.faces.write().unwrap().bind();
+        match self.faces_u32 {
+            Some(ref faces) => faces.write().unwrap().bind(),
+            None => self.faces.write().unwrap().bind(),
+        }
+    }
+
+    /// The integer type of the indices of this mesh, to be given to `Context::draw_elements`.
+    #[inline]
+    pub fn index_type(&self) -> IndexType {
+        if self.faces_u32.is_some() {
+            IndexType::U32
+        } else {
+            IndexType::U16
+        }
     }
 
     /// Binds this mesh buffers to vertex attributes.
@@ -225,19 +599,21 @@ impl Mesh {
 
     /// Binds this mesh buffers to vertex attributes.
     pub fn bind_edges(&mut self) {
-        if self.edges.is_none() {
-            let mut edges = Vec::new();
-            for face in self.faces.read().unwrap().data().as_ref().unwrap() {
-                edges.push(Point2::new(face.x, face.y));
-                edges.push(Point2::new(face.y, face.z));
-                edges.push(Point2::new(face.z, face.x));
+        if let Some(ref faces) = self.faces_u32 {
+            if self.edges_u32.is_none() {
+                let edges = edges(faces.read().unwrap().data().as_ref().unwrap());
+                self.edges_u32 = Some(Arc::new(RwLock::new(edges)));
+            }
+
+            self.edges_u32.as_mut().unwrap().write().unwrap().bind();
+        } else {
+            if self.edges.is_none() {
+                let edges = edges(self.faces.read().unwrap().data().as_ref().unwrap());
+                self.edges = Some(Arc::new(RwLock::new(edges)));
             }
-            let gpu_edges =
-                GPUVec::new(edges, BufferType::ElementArray, AllocationType::StaticDraw);
-            self.edges = Some(Arc::new(RwLock::new(gpu_edges)));
-        }
 
-        self.edges.as_mut().unwrap().write().unwrap().bind();
+            self.edges.as_mut().unwrap().write().unwrap().bind();
+        }
     }
 
     /// Unbind this mesh buffers to vertex attributes.
@@ -245,28 +621,53 @@ impl Mesh {
         self.coords.write().unwrap().unbind();
         self.normals.write().unwrap().unbind();
         self.uvs.write().unwrap().unbind();
-        self.faces.write().unwrap().unbind();
+        match self.faces_u32 {
+            Some(ref faces) => faces.write().unwrap().unbind(),
+            None => self.faces.write().unwrap().unbind(),
+        }
     }
 
     /// Number of points needed to draw this mesh.
     pub fn num_pts(&self) -> usize {
-        self.faces.read().unwrap().len() * 3
+        match self.faces_u32 {
+            Some(ref faces) => faces.read().unwrap().len() * 3,
+            None => self.faces.read().unwrap().len() * 3,
+        }
     }
 
     /// Recompute this mesh normals.
     pub fn recompute_normals(&mut self) {
-        Mesh::compute_normals(
-            &self.coords.read().unwrap().data().as_ref().unwrap()[..],
-            &self.faces.read().unwrap().data().as_ref().unwrap()[..],
-            self.normals.write().unwrap().data_mut().as_mut().unwrap(),
-        );
+        let coords = self.coords.read().unwrap();
+        let coords = &coords.data().as_ref().unwrap()[..];
+        let mut normals = self.normals.write().unwrap();
+        let normals = normals.data_mut().as_mut().unwrap();
+
+        match self.faces_u32 {
+            Some(ref faces) => compute_normals_generic(
+                coords,
+                &faces.read().unwrap().data().as_ref().unwrap()[..],
+                normals,
+            ),
+            None => compute_normals_generic(
+                coords,
+                &self.faces.read().unwrap().data().as_ref().unwrap()[..],
+                normals,
+            ),
+        }
     }
 
-    /// This mesh faces.
+    /// This mesh faces, if it uses 16-bits indices.
+    ///
+    /// This is empty if `self.index_type()` is `IndexType::U32`, see `Mesh::faces_u32`.
     pub fn faces(&self) -> &Arc<RwLock<GPUVec<Point3<u16>>>> {
         &self.faces
     }
 
+    /// This mesh faces, if it uses 32-bits indices.
+    pub fn faces_u32(&self) -> Option<&Arc<RwLock<GPUVec<Point3<u32>>>>> {
+        self.faces_u32.as_ref()
+    }
+
     /// This mesh normals.
     pub fn normals(&self) -> &Arc<RwLock<GPUVec<Vector3<f32>>>> {
         &self.normals
@@ -277,6 +678,62 @@ impl Mesh {
         &self.coords
     }
 
+    /// The axis-aligned bounding box of this mesh vertex coordinates.
+    ///
+    /// This is computed once and cached until the coordinates are modified through
+    /// `GPUVec::data_mut`, so it is shared by all the objects using this mesh. Returns `None` if
+    /// the mesh has no vertex, or if its coordinates have never been available on the CPU.
+    pub fn aabb(&self) -> Option<AABB<f32>> {
+        let coords = self.coords.read().unwrap();
+
+        if let Some((version, aabb)) = self.aabb.get() {
+            if version == coords.version() {
+                return aabb;
+            }
+        }
+
+        let data = coords.data().as_ref()?;
+        let aabb = data.first().map(|first| {
+            let (mins, maxs) = data.iter().fold((*first, *first), |(mins, maxs), coord| {
+                (mins.inf(coord), maxs.sup(coord))
+            });
+
+            AABB::new(mins, maxs)
+        });
+
+        self.aabb.set(Some((coords.version(), aabb)));
+        aabb
+    }
+
+    /// The bounding sphere `(center, radius)` of this mesh vertex coordinates.
+    ///
+    /// Its center is the center of `Mesh::aabb`. This is cached like `Mesh::aabb`.
+    pub fn bounding_sphere(&self) -> Option<(Point3<f32>, f32)> {
+        let aabb = self.aabb();
+        let coords = self.coords.read().unwrap();
+
+        if let Some((version, sphere)) = self.bounding_sphere.get() {
+            if version == coords.version() {
+                return sphere;
+            }
+        }
+
+        let data = coords.data().as_ref()?;
+        let sphere = aabb.map(|aabb| {
+            let center = na::center(&aabb.mins, &aabb.maxs);
+            let radius = data
+                .iter()
+                .map(|coord| na::distance_squared(&center, coord))
+                .fold(0.0f32, f32::max)
+                .sqrt();
+
+            (center, radius)
+        });
+
+        self.bounding_sphere.set(Some((coords.version(), sphere)));
+        sphere
+    }
+
     /// This mesh texture coordinates.
     pub fn uvs(&self) -> &Arc<RwLock<GPUVec<Point2<f32>>>> {
         &self.uvs
@@ -300,36 +757,158 @@ impl Mesh {
         faces: &[Point3<u16>],
         normals: &mut Vec<Vector3<f32>>,
     ) {
-        let mut divisor: Vec<f32> = iter::repeat(0f32).take(coordinates.len()).collect();
+        compute_normals_generic(coordinates, faces, normals)
+    }
+}
 
-        normals.clear();
-        normals.extend(iter::repeat(Vector3::<f32>::zero()).take(coordinates.len()));
+pub(crate) fn compute_normals_generic<I: na::Scalar + Copy + Into<u32>>(
+    coordinates: &[Point3<f32>],
+    faces: &[Point3<I>],
+    normals: &mut Vec<Vector3<f32>>,
+) {
+    let mut divisor: Vec<f32> = iter::repeat(0f32).take(coordinates.len()).collect();
+
+    normals.clear();
+    normals.extend(iter::repeat(Vector3::<f32>::zero()).take(coordinates.len()));
+
+    // Accumulate normals ...
+    for f in faces.iter() {
+        let (x, y, z) = (
+            f.x.into() as usize,
+            f.y.into() as usize,
+            f.z.into() as usize,
+        );
+        let edge1 = coordinates[y] - coordinates[x];
+        let edge2 = coordinates[z] - coordinates[x];
+        let cross = edge1.cross(&edge2);
+        let normal;
+
+        if !cross.is_zero() {
+            normal = cross.normalize()
+        } else {
+            normal = cross
+        }
 
-        // Accumulate normals ...
-        for f in faces.iter() {
-            let edge1 = coordinates[f.y as usize] - coordinates[f.x as usize];
-            let edge2 = coordinates[f.z as usize] - coordinates[f.x as usize];
-            let cross = edge1.cross(&edge2);
-            let normal;
+        normals[x] = normals[x] + normal;
+        normals[y] = normals[y] + normal;
+        normals[z] = normals[z] + normal;
 
-            if !cross.is_zero() {
-                normal = cross.normalize()
-            } else {
-                normal = cross
-            }
+        divisor[x] = divisor[x] + 1.0;
+        divisor[y] = divisor[y] + 1.0;
+        divisor[z] = divisor[z] + 1.0;
+    }
+
+    // ... and compute the mean
+    for (n, divisor) in normals.iter_mut().zip(divisor.iter()) {
+        *n = *n / *divisor
+    }
+}
 
-            normals[f.x as usize] = normals[f.x as usize] + normal;
-            normals[f.y as usize] = normals[f.y as usize] + normal;
-            normals[f.z as usize] = normals[f.z as usize] + normal;
+// The three edges of each face, to draw a wireframe.
+fn edges<I: na::Scalar + Copy>(faces: &[Point3<I>]) -> GPUVec<Point2<I>>
+where
+    Point2<I>: GLPrimitive,
+{
+    let mut edges = Vec::with_capacity(faces.len() * 3);
+    for face in faces {
+        edges.push(Point2::new(face.x, face.y));
+        edges.push(Point2::new(face.y, face.z));
+        edges.push(Point2::new(face.z, face.x));
+    }
 
-            divisor[f.x as usize] = divisor[f.x as usize] + 1.0;
-            divisor[f.y as usize] = divisor[f.y as usize] + 1.0;
-            divisor[f.z as usize] = divisor[f.z as usize] + 1.0;
-        }
+    GPUVec::new(edges, BufferType::ElementArray, AllocationType::StaticDraw)
+}
 
-        // ... and compute the mean
-        for (n, divisor) in normals.iter_mut().zip(divisor.iter()) {
-            *n = *n / *divisor
+#[cfg(test)]
+mod test {
+    use super::{IndexType, Mesh};
+    use na::Point3;
+
+    // A grid of `n * n` vertices, with two triangles per cell.
+    fn grid(n: u32) -> Mesh {
+        let coords = (0..n * n)
+            .map(|i| Point3::new((i % n) as f32, (i / n) as f32, 0.0))
+            .collect();
+        let mut faces = Vec::new();
+
+        for y in 0..n - 1 {
+            for x in 0..n - 1 {
+                let i = y * n + x;
+                faces.push(Point3::new(i, i + 1, i + n));
+                faces.push(Point3::new(i + 1, i + n + 1, i + n));
+            }
         }
+
+        Mesh::try_new(coords, faces, None, None, false).unwrap()
+    }
+
+    #[test]
+    fn flat_shaded_copy_of_small_mesh() {
+        let mesh = grid(4);
+        let flat = mesh.to_flat_shaded().unwrap();
+
+        assert_eq!(flat.index_type(), IndexType::U16);
+        assert_eq!(flat.coords().read().unwrap().len(), 9 * 2 * 3);
+    }
+
+    #[test]
+    fn flat_shaded_copy_exceeding_u16() {
+        // 2 * 99 * 99 triangles have 58806 vertices once flat shaded, which still fit `u16`,
+        // whereas the 2 * 199 * 199 triangles of this mesh do not.
+        let small = grid(100).to_flat_shaded().unwrap();
+        assert_eq!(small.index_type(), IndexType::U16);
+
+        let mesh = grid(200);
+        assert_eq!(mesh.index_type(), IndexType::U16);
+        let flat = mesh.to_flat_shaded().unwrap();
+        assert_eq!(flat.index_type(), IndexType::U32);
+        assert_eq!(flat.coords().read().unwrap().len(), 199 * 199 * 2 * 3);
+    }
+
+    #[test]
+    fn flat_shaded_copy_of_u32_mesh() {
+        let mesh = grid(300);
+        assert_eq!(mesh.index_type(), IndexType::U32);
+
+        let flat = mesh.to_flat_shaded().unwrap();
+        assert_eq!(flat.index_type(), IndexType::U32);
+        let normals = flat.normals().read().unwrap();
+        assert!(normals
+            .data()
+            .as_ref()
+            .unwrap()
+            .iter()
+            .all(|n| (n.z.abs() - 1.0).abs() < 1.0e-6));
+    }
+
+    #[test]
+    fn aabb_follows_coords_modifications() {
+        let mesh = grid(3);
+        let aabb = mesh.aabb().unwrap();
+        assert_eq!(aabb.mins, Point3::new(0.0, 0.0, 0.0));
+        assert_eq!(aabb.maxs, Point3::new(2.0, 2.0, 0.0));
+
+        // The coordinates may be modified by anyone sharing the mesh.
+        let coords = mesh.coords().clone();
+        coords.write().unwrap().data_mut().as_mut().unwrap()[4] = Point3::new(1.0, 5.0, -1.0);
+
+        let aabb = mesh.aabb().unwrap();
+        assert_eq!(aabb.mins, Point3::new(0.0, 0.0, -1.0));
+        assert_eq!(aabb.maxs, Point3::new(2.0, 5.0, 0.0));
+    }
+
+    #[test]
+    fn bounding_sphere_follows_coords_modifications() {
+        let mesh = grid(3);
+        let (center, radius) = mesh.bounding_sphere().unwrap();
+        assert_eq!(center, Point3::new(1.0, 1.0, 0.0));
+        assert!((radius - 2.0f32.sqrt()).abs() < 1.0e-6);
+
+        let coords = mesh.coords().clone();
+        coords.write().unwrap().data_mut().as_mut().unwrap()[4] = Point3::new(1.0, 4.0, 0.0);
+
+        let (center, radius) = mesh.bounding_sphere().unwrap();
+        assert_eq!(center, Point3::new(1.0, 2.0, 0.0));
+        assert!((radius - 5.0f32.sqrt()).abs() < 1.0e-6);
     }
 }
@@ -9,18 +9,30 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::io::Result as IoResult;
 use std::path::Path;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
 thread_local!(static KEY_MESH_MANAGER: RefCell<MeshManager> = RefCell::new(MeshManager::new()));
 
 /// The mesh manager.
 ///
-/// Upon construction, it contains:
+/// Upon construction, it contains the unit `"sphere"`, `"cube"`, `"cone"`, `"cylinder"`, and
+/// `"open_cylinder"` meshes, shared by all the objects created by the corresponding
+/// `SceneNode::add_*` methods and only scaled differently.
+///
+/// The shapes that cannot be obtained by scaling a unit mesh, or that use a custom number of
+/// subdivisions, are generated the first time they are needed, under a name derived from their
+/// parameters, e.g., `MeshManager::capsule_name`. They are cached only as long as an object
+/// uses them, and an object modifying the vertices of such a mesh works on its own copy. The
+/// cache can be pre-warmed by keeping the meshes returned by `MeshManager::get_or_generate`,
+/// or by registering them permanently with `MeshManager::get_or_add_trimesh`.
+///
+/// Note that modifying the vertices of a registered mesh affects all the objects using it.
 ///
 /// It keeps a cache of already-loaded meshes. Note that this is only a cache, nothing more.
 /// Thus, its usage is not required to load meshes.
 pub struct MeshManager {
     meshes: HashMap<String, Rc<RefCell<Mesh>>>,
+    generated: HashMap<String, Weak<RefCell<Mesh>>>,
 }
 
 impl MeshManager {
@@ -28,12 +40,18 @@ impl MeshManager {
     pub fn new() -> MeshManager {
         let mut res = MeshManager {
             meshes: HashMap::new(),
+            generated: HashMap::new(),
         };
 
         let _ = res.add_trimesh(procedural::unit_sphere(50, 50, true), false, "sphere");
         let _ = res.add_trimesh(procedural::unit_cuboid(), false, "cube");
         let _ = res.add_trimesh(procedural::unit_cone(50), false, "cone");
-        let _ = res.add_trimesh(procedural::unit_cylinder(50), false, "cylinder");
+        let _ = res.add_trimesh(crate::procedural::unit_cylinder(50), false, "cylinder");
+        let _ = res.add_trimesh(
+            crate::procedural::unit_open_cylinder(50),
+            false,
+            "open_cylinder",
+        );
 
         res
     }
@@ -68,6 +86,89 @@ impl MeshManager {
         mesh
     }
 
+    /// Get the mesh with the specified name, or creates, adds, and returns it if it is not
+    /// registered yet.
+    pub fn get_or_add_trimesh<F: FnOnce() -> TriMesh<f32>>(
+        &mut self,
+        name: &str,
+        descr: F,
+    ) -> Rc<RefCell<Mesh>> {
+        match self.get(name) {
+            Some(mesh) => mesh,
+            None => self.add_trimesh(descr(), false, name),
+        }
+    }
+
+    /// The name of the generated capsule with the given radius, height, and number of
+    /// subdivisions, used by `SceneNode::add_capsule`.
+    pub fn capsule_name(r: f32, h: f32, subdivs: u32) -> String {
+        format!("capsule_{}_{}_{}", r, h, subdivs)
+    }
+
+    /// The name of the generated disk with the given radii and number of subdivisions, used by
+    /// `SceneNode::add_disk`.
+    pub fn disk_name(r: f32, inner_r: f32, subdivs: u32) -> String {
+        format!("disk_{}_{}_{}", r, inner_r, subdivs)
+    }
+
+    /// The name of the generated unit sphere tessellated with `subdivs` subdivisions, used by
+    /// `SceneNode::add_sphere_with_subdivisions` and `SceneNode::add_sphere_lod`.
+    pub fn sphere_name(subdivs: u32) -> String {
+        format!("sphere_{}", subdivs)
+    }
+
+    /// The name of the generated unit cylinder tessellated with `subdivs` subdivisions, used by
+    /// `SceneNode::add_cylinder_lod`.
+    pub fn cylinder_name(subdivs: u32) -> String {
+        format!("cylinder_{}", subdivs)
+    }
+
+    /// Get the mesh with the specified name, or generates, adds, and returns it if its name is
+    /// one of the names of the generated meshes.
+    ///
+    /// Those are the names returned by `MeshManager::capsule_name`, `MeshManager::disk_name`,
+    /// `MeshManager::sphere_name`, and `MeshManager::cylinder_name`. The generated meshes are
+    /// only cached while they are used. Returns `None` if the mesh is not registered and cannot
+    /// be generated.
+    pub fn get_or_generate(&mut self, name: &str) -> Option<Rc<RefCell<Mesh>>> {
+        if let Some(mesh) = self.get(name) {
+            return Some(mesh);
+        }
+
+        if let Some(mesh) = self.generated.get(name).and_then(Weak::upgrade) {
+            return Some(mesh);
+        }
+
+        let mesh = Rc::new(RefCell::new(Mesh::from_trimesh(generate_trimesh(name)?, false)));
+
+        // Forget the generated meshes no object uses anymore.
+        self.generated.retain(|_, mesh| mesh.strong_count() > 0);
+        let _ = self
+            .generated
+            .insert(name.to_string(), Rc::downgrade(&mesh));
+
+        Some(mesh)
+    }
+
+    /// The name of a registered or generated mesh, if any.
+    pub fn name_of(&self, mesh: &Rc<RefCell<Mesh>>) -> Option<String> {
+        self.meshes
+            .iter()
+            .find(|(_, m)| Rc::ptr_eq(m, mesh))
+            .map(|(name, _)| name.clone())
+            .or_else(|| {
+                self.generated
+                    .iter()
+                    .find(|(_, m)| is_same_mesh(m, mesh))
+                    .map(|(name, _)| name.clone())
+            })
+    }
+
+    /// Whether `mesh` is a generated mesh cached by this manager.
+    pub fn is_generated(&self, mesh: &Rc<RefCell<Mesh>>) -> bool {
+        self.generated.values().any(|m| is_same_mesh(m, mesh))
+    }
+
     /// Removes a mesh from this cache.
     pub fn remove(&mut self, name: &str) {
         let _ = self.meshes.remove(&name.to_string());
@@ -93,3 +194,72 @@ impl MeshManager {
         })
     }
 }
+
+// The address of a dropped mesh may be reused by another one, so it must still be alive.
+fn is_same_mesh(cached: &Weak<RefCell<Mesh>>, mesh: &Rc<RefCell<Mesh>>) -> bool {
+    cached.strong_count() > 0 && cached.as_ptr() == Rc::as_ptr(mesh)
+}
+
+// Generates the meshes named after their parameters.
+fn generate_trimesh(name: &str) -> Option<TriMesh<f32>> {
+    let mut words = name.split('_');
+    let kind = words.next()?;
+    let params: Vec<&str> = words.collect();
+
+    match (kind, &params[..]) {
+        ("capsule", [r, h, subdivs]) => {
+            let (r, h): (f32, f32) = (r.parse().ok()?, h.parse().ok()?);
+            let subdivs = subdivs.parse().ok()?;
+            Some(procedural::capsule(&(r * 2.0), &h, subdivs, subdivs))
+        }
+        ("disk", [r, inner_r, subdivs]) => Some(crate::procedural::disk(
+            r.parse().ok()?,
+            inner_r.parse().ok()?,
+            subdivs.parse().ok()?,
+        )),
+        ("sphere", [subdivs]) => {
+            let subdivs = subdivs.parse().ok()?;
+            Some(procedural::unit_sphere(subdivs, subdivs, true))
+        }
+        ("cylinder", [subdivs]) => Some(crate::procedural::unit_cylinder(subdivs.parse().ok()?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MeshManager;
+    use std::rc::Rc;
+
+    #[test]
+    fn generated_meshes_are_shared_while_used() {
+        let mut mm = MeshManager::new();
+        let name = MeshManager::capsule_name(0.5, 1.0, 10);
+        let a = mm.get_or_generate(&name).unwrap();
+        let b = mm.get_or_generate(&name).unwrap();
+        let c = mm
+            .get_or_generate(&MeshManager::capsule_name(0.5, 1.0, 20))
+            .unwrap();
+
+        assert!(Rc::ptr_eq(&a, &b));
+        assert!(!Rc::ptr_eq(&a, &c));
+        assert!(mm.is_generated(&a));
+        assert_eq!(mm.name_of(&a), Some(name));
+        let sphere = mm.get("sphere").unwrap();
+        assert!(!mm.is_generated(&sphere));
+    }
+
+    #[test]
+    fn unused_generated_meshes_are_evicted() {
+        let mut mm = MeshManager::new();
+        let disk = mm
+            .get_or_generate(&MeshManager::disk_name(1.0, 0.0, 10))
+            .unwrap();
+        drop(disk);
+
+        let sphere = mm.get_or_generate(&MeshManager::sphere_name(8)).unwrap();
+
+        assert_eq!(mm.generated.len(), 1);
+        assert!(mm.is_generated(&sphere));
+    }
+}
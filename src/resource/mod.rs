@@ -1,6 +1,7 @@
 //! GPU resource managers
 
 pub use crate::context::Texture;
+pub use crate::resource::cube_map::{CubeMap, MAX_IRRADIANCE_SAMPLES};
 pub use crate::resource::effect::{Effect, ShaderAttribute, ShaderUniform};
 pub use crate::resource::framebuffer_manager::{
     FramebufferManager, OffscreenBuffers, RenderTarget,
@@ -9,13 +10,15 @@ pub use crate::resource::gl_primitive::{GLPrimitive, PrimitiveArray};
 pub use crate::resource::gpu_vector::{AllocationType, BufferType, GPUVec};
 pub use crate::resource::material::{Material, PlanarMaterial};
 pub use crate::resource::material_manager::MaterialManager;
-pub use crate::resource::mesh::Mesh;
+pub(crate) use crate::resource::mesh::{compute_normals_generic, MAX_U16_VERTICES};
+pub use crate::resource::mesh::{IndexType, Mesh, MeshError};
 pub use crate::resource::mesh_manager::MeshManager;
 pub use crate::resource::planar_material_manager::PlanarMaterialManager;
 pub use crate::resource::planar_mesh::PlanarMesh;
 pub use crate::resource::planar_mesh_manager::PlanarMeshManager;
 pub use crate::resource::texture_manager::{TextureManager, TextureWrapping};
 
+mod cube_map;
 mod effect;
 mod framebuffer_manager;
 mod gl_primitive;
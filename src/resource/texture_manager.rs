@@ -144,6 +144,16 @@ impl TextureManager {
         self.textures.get(&name.to_string()).map(|t| t.0.clone())
     }
 
+    /// The name of a registered texture, if any.
+    ///
+    /// The default texture has no name.
+    pub fn name_of(&self, texture: &Rc<Texture>) -> Option<String> {
+        self.textures
+            .iter()
+            .find(|(_, t)| Rc::ptr_eq(&t.0, texture))
+            .map(|(name, _)| name.clone())
+    }
+
     /// Get a texture (and its size) with the specified name. Returns `None` if the texture is not registered.
     pub fn get_with_size(&mut self, name: &str) -> Option<(Rc<Texture>, (u32, u32))> {
         self.textures
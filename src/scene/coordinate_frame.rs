@@ -0,0 +1,98 @@
+//! Coordinate frames drawn as three arrows.
+
+use na::{Isometry3, Translation3, UnitQuaternion, Vector3};
+
+use crate::scene::SceneNode;
+
+/// The radius of the shaft of the arrows of a frame of scale `1.0`.
+const SHAFT_RADIUS: f32 = 0.02;
+/// The length of the shaft of the arrows of a frame of scale `1.0`.
+const SHAFT_LENGTH: f32 = 0.8;
+/// The base radius of the head of the arrows of a frame of scale `1.0`.
+const HEAD_RADIUS: f32 = 0.06;
+
+/// A coordinate frame displayed as three arrows: red for `x`, green for `y`, and blue for `z`.
+///
+/// The arrows are the children of a single group node, so they are moved and scaled together.
+#[derive(Clone)]
+pub struct CoordinateFrameNode {
+    node: SceneNode,
+}
+
+impl CoordinateFrameNode {
+    /// Adds a coordinate frame to the children of `parent`, with the given local transformation
+    /// and arrows `scale` long.
+    pub(crate) fn new(
+        parent: &mut SceneNode,
+        transform: Isometry3<f32>,
+        scale: f32,
+    ) -> CoordinateFrameNode {
+        let mut node = parent.add_group();
+        let axes = [
+            (
+                UnitQuaternion::from_axis_angle(&Vector3::z_axis(), -std::f32::consts::FRAC_PI_2),
+                (1.0, 0.0, 0.0),
+            ),
+            (UnitQuaternion::identity(), (0.0, 1.0, 0.0)),
+            (
+                UnitQuaternion::from_axis_angle(&Vector3::x_axis(), std::f32::consts::FRAC_PI_2),
+                (0.0, 0.0, 1.0),
+            ),
+        ];
+
+        // Each arrow is built along the `y` axis, then rotated toward its own axis.
+        for (rotation, (r, g, b)) in axes.iter() {
+            let mut arrow = node.add_group();
+            let mut shaft = arrow.add_cylinder(SHAFT_RADIUS, SHAFT_LENGTH);
+            shaft.set_local_translation(Translation3::new(0.0, SHAFT_LENGTH / 2.0, 0.0));
+
+            let head_length = 1.0 - SHAFT_LENGTH;
+            let mut head = arrow.add_cone(HEAD_RADIUS, head_length);
+            head.set_local_translation(Translation3::new(
+                0.0,
+                SHAFT_LENGTH + head_length / 2.0,
+                0.0,
+            ));
+
+            arrow.set_local_rotation(*rotation);
+            arrow.set_color(*r, *g, *b);
+        }
+
+        let mut res = CoordinateFrameNode { node };
+        res.set_transform(transform);
+        res.set_scale(scale);
+        res
+    }
+
+    /// The group node containing the three arrows.
+    #[inline]
+    pub fn node(&self) -> &SceneNode {
+        &self.node
+    }
+
+    /// The group node containing the three arrows, e.g., to reparent or hide them.
+    #[inline]
+    pub fn node_mut(&mut self) -> &mut SceneNode {
+        &mut self.node
+    }
+
+    /// Sets the position and orientation of this frame, relative to its parent node.
+    #[inline]
+    pub fn set_transform(&mut self, transform: Isometry3<f32>) {
+        self.node.set_local_transformation(transform)
+    }
+
+    /// Sets the length of the arrows of this frame.
+    ///
+    /// Their thickness is scaled accordingly.
+    #[inline]
+    pub fn set_scale(&mut self, scale: f32) {
+        self.node.set_local_scale(scale, scale, scale)
+    }
+
+    /// Removes this frame from the scene.
+    #[inline]
+    pub fn unlink(&mut self) {
+        self.node.unlink()
+    }
+}
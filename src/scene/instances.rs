@@ -0,0 +1,75 @@
+//! Per-instance data of the objects drawn several times with a single draw call.
+
+use na::{Isometry3, Point3, Vector4};
+
+use crate::resource::{AllocationType, BufferType, GLPrimitive, GPUVec};
+
+/// The placement and color of one instance of an object.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InstanceData {
+    /// The transformation of this instance, relative to the object.
+    pub transform: Isometry3<f32>,
+    /// The color of this instance, multiplied by the color of the object.
+    pub color: Point3<f32>,
+}
+
+impl Default for InstanceData {
+    fn default() -> Self {
+        InstanceData {
+            transform: Isometry3::identity(),
+            color: Point3::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+/// The instances of an object, and their GPU buffers.
+pub(crate) struct InstancesBuffer {
+    pub data: Vec<InstanceData>,
+    /// The rotations of the instances as `(i, j, k, w)` quaternions coordinates.
+    pub rotations: GPUVec<Vector4<f32>>,
+    pub translations: GPUVec<Point3<f32>>,
+    pub colors: GPUVec<Point3<f32>>,
+}
+
+impl InstancesBuffer {
+    pub fn new(instances: &[InstanceData]) -> InstancesBuffer {
+        let mut res = InstancesBuffer {
+            data: Vec::new(),
+            rotations: new_buffer(),
+            translations: new_buffer(),
+            colors: new_buffer(),
+        };
+
+        res.set(instances);
+        res
+    }
+
+    pub fn set(&mut self, instances: &[InstanceData]) {
+        self.data.clear();
+        self.data.extend_from_slice(instances);
+
+        let rotations = self.rotations.data_mut().get_or_insert_with(Vec::new);
+        rotations.clear();
+        rotations.extend(instances.iter().map(|i| i.transform.rotation.coords));
+
+        let translations = self.translations.data_mut().get_or_insert_with(Vec::new);
+        translations.clear();
+        translations.extend(
+            instances
+                .iter()
+                .map(|i| Point3::from(i.transform.translation.vector)),
+        );
+
+        let colors = self.colors.data_mut().get_or_insert_with(Vec::new);
+        colors.clear();
+        colors.extend(instances.iter().map(|i| i.color));
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+}
+
+fn new_buffer<T: GLPrimitive>() -> GPUVec<T> {
+    GPUVec::new(Vec::new(), BufferType::Array, AllocationType::DynamicDraw)
+}
@@ -1,11 +1,31 @@
 //! Everything related to the scene graph.
 
-pub use self::object::{Object, ObjectData};
+pub use self::coordinate_frame::CoordinateFrameNode;
+pub use self::instances::InstanceData;
+pub use self::object::{
+    BillboardMode, BlendMode, CullingMode, Object, ObjectData, RENDER_LAYER_ALWAYS_ON_TOP,
+    RENDER_LAYER_OPAQUE, RENDER_LAYER_TRANSPARENT,
+};
+pub use self::particle_system::{
+    ParticleConfig, ParticleState, ParticleSystem, ParticleSystemData,
+};
 pub use self::planar_object::{PlanarObject, PlanarObjectData};
 pub use self::planar_scene_node::{PlanarSceneNode, PlanarSceneNodeData};
+pub use self::scene_file::SceneError;
+pub(crate) use self::scene_node::{
+    frustum_culling, num_culled_objects, set_frustum_culling, update_color_animations,
+};
 pub use self::scene_node::{SceneNode, SceneNodeData};
+pub use self::spline::{Spline, SplineKind};
+pub use self::sprite_animation::{SpriteAnimation, SpriteAnimationMode};
 
+mod coordinate_frame;
+pub(crate) mod instances;
 mod object;
+mod particle_system;
 mod planar_object;
 mod planar_scene_node;
+mod scene_file;
 mod scene_node;
+mod spline;
+mod sprite_animation;
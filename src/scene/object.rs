@@ -1,27 +1,124 @@
 //! Data structure of a scene node.
 
 use crate::camera::Camera;
+use crate::context::Context;
 use crate::light::Light;
-use crate::resource::{Material, Mesh, Texture, TextureManager};
-use na::{Isometry3, Point2, Point3, Vector3};
+use crate::resource::{CubeMap, Material, Mesh, MeshManager, Texture, TextureManager};
+use crate::scene::instances::{InstanceData, InstancesBuffer};
+use na::{Isometry3, Matrix3, Point2, Point3, Rotation3, UnitQuaternion, Vector3};
+use ncollide3d::bounding_volume::AABB;
 use std::any::Any;
 use std::cell::RefCell;
-use std::path::Path;
+use std::cmp::Ordering;
+use std::mem;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 #[path = "../error.rs"]
 mod error;
 
+/// The faces of an object discarded before rasterization.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CullingMode {
+    /// No face is culled.
+    None,
+    /// The faces pointing away from the camera are culled.
+    Back,
+    /// The faces pointing toward the camera are culled.
+    Front,
+}
+
+/// How the color of an object is combined with the colors already rendered behind it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlendMode {
+    /// The colors are interpolated with the object alpha. Objects with an alpha of `1.0` are
+    /// opaque.
+    Alpha,
+    /// The object color, multiplied by its alpha, is added to the colors behind it.
+    Additive,
+    /// The colors behind the object are multiplied by its color.
+    Multiply,
+    /// The colors behind the object are brightened by its color, i.e., both are inverted,
+    /// multiplied, and inverted again.
+    Screen,
+}
+
+/// How an object is rotated to face the camera.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BillboardMode {
+    /// The object keeps the rotation of its scene node.
+    None,
+    /// The local axes of the object are aligned with the axes of the camera view: its local `z`
+    /// axis points toward the viewer, and its local `y` axis is the vertical of the screen.
+    Full,
+    /// The local `y` axis of the object is aligned with the given world-space axis, and the
+    /// object rotates around it to face the camera, e.g., for tree sprites.
+    AxisLocked(Vector3<f32>),
+}
+
+impl BillboardMode {
+    // The object transform with its rotation replaced by the billboard rotation.
+    fn apply(self, transform: &Isometry3<f32>, camera: &dyn Camera) -> Isometry3<f32> {
+        let rotation = match self {
+            BillboardMode::None => return *transform,
+            BillboardMode::Full => camera.view_transform().rotation.inverse(),
+            BillboardMode::AxisLocked(axis) => {
+                let y = match axis.try_normalize(1.0e-6) {
+                    Some(y) => y,
+                    None => return *transform,
+                };
+                let to_eye = camera.eye() - Point3::from(transform.translation.vector);
+                // The direction toward the camera, projected on the plane orthogonal to the axis.
+                let z = match (to_eye - y * to_eye.dot(&y)).try_normalize(1.0e-6) {
+                    Some(z) => z,
+                    None => return *transform,
+                };
+                let x = y.cross(&z);
+                let matrix = Matrix3::from_columns(&[x, y, z]);
+
+                UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix_unchecked(matrix))
+            }
+        };
+
+        Isometry3::from_parts(transform.translation, rotation)
+    }
+}
+
+impl BlendMode {
+    /// The source and destination color factors passed to `glBlendFunc` for this mode.
+    pub(crate) fn factors(self) -> (u32, u32) {
+        match self {
+            BlendMode::Alpha => (Context::SRC_ALPHA, Context::ONE_MINUS_SRC_ALPHA),
+            BlendMode::Additive => (Context::SRC_ALPHA, Context::ONE),
+            BlendMode::Multiply => (Context::DST_COLOR, Context::ZERO),
+            BlendMode::Screen => (Context::ONE, Context::ONE_MINUS_SRC_COLOR),
+        }
+    }
+}
+
 /// Set of data identifying a scene node.
 pub struct ObjectData {
     material: Rc<RefCell<Box<dyn Material + 'static>>>,
     texture: Rc<Texture>,
     color: Point3<f32>,
+    alpha: f32,
     lines_color: Option<Point3<f32>>,
     wlines: f32,
     wpoints: f32,
     draw_surface: bool,
-    cull: bool,
+    cull: CullingMode,
+    double_sided: bool,
+    render_layer: u32,
+    blend_mode: BlendMode,
+    billboard: BillboardMode,
+    instances: Option<Rc<RefCell<InstancesBuffer>>>,
+    depth_bias: Option<(f32, f32)>,
+    uv_offset: Point2<f32>,
+    uv_scale: Point2<f32>,
+    environment_map: Option<Rc<CubeMap>>,
+    reflectivity: f32,
+    ssr_reflector: bool,
+    metallic_roughness: Option<(f32, f32)>,
     user_data: Box<dyn Any + 'static>,
 }
 
@@ -38,6 +135,31 @@ impl ObjectData {
         &self.color
     }
 
+    /// The opacity of this object, between `0.0` (fully transparent) and `1.0` (opaque).
+    #[inline]
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    /// Whether this object is transparent, i.e., has an alpha smaller than `1.0` or a blend
+    /// mode other than `BlendMode::Alpha`.
+    #[inline]
+    pub fn is_transparent(&self) -> bool {
+        self.alpha < 1.0 || self.blend_mode != BlendMode::Alpha
+    }
+
+    /// How the color of this object is combined with the colors behind it.
+    #[inline]
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// How this object is rotated to face the camera.
+    #[inline]
+    pub fn billboard(&self) -> BillboardMode {
+        self.billboard
+    }
+
     /// The width of the lines draw for this object.
     #[inline]
     pub fn lines_width(&self) -> f32 {
@@ -65,9 +187,94 @@ impl ObjectData {
     /// Whether this object uses backface culling or not.
     #[inline]
     pub fn backface_culling_enabled(&self) -> bool {
+        self.cull == CullingMode::Back
+    }
+
+    /// The faces of this object discarded before rasterization.
+    #[inline]
+    pub fn culling_mode(&self) -> CullingMode {
         self.cull
     }
 
+    /// Whether both faces of this object are rendered and lit.
+    #[inline]
+    pub fn double_sided(&self) -> bool {
+        self.double_sided
+    }
+
+    /// The render layer of this object.
+    #[inline]
+    pub fn render_layer(&self) -> u32 {
+        self.render_layer
+    }
+
+    /// The number of instances of this object, or `None` if it is not instanced.
+    #[inline]
+    pub fn num_instances(&self) -> Option<usize> {
+        self.instances.as_ref().map(|i| i.borrow().len())
+    }
+
+    /// The instances of this object, if any.
+    #[inline]
+    pub(crate) fn instances(&self) -> Option<&Rc<RefCell<InstancesBuffer>>> {
+        self.instances.as_ref()
+    }
+
+    /// The render layer this object is actually rendered on.
+    ///
+    /// Transparent objects on the opaque layer are moved to the transparent layer.
+    #[inline]
+    pub(crate) fn effective_render_layer(&self) -> u32 {
+        if self.render_layer == RENDER_LAYER_OPAQUE && self.is_transparent() {
+            RENDER_LAYER_TRANSPARENT
+        } else {
+            self.render_layer
+        }
+    }
+
+    /// The `(factor, units)` depth offset applied when rendering this object surface, if any.
+    #[inline]
+    pub fn depth_bias(&self) -> Option<(f32, f32)> {
+        self.depth_bias
+    }
+
+    /// The offset added to the texture coordinates of this object after scaling them.
+    #[inline]
+    pub fn texture_uv_offset(&self) -> &Point2<f32> {
+        &self.uv_offset
+    }
+
+    /// The scaling factors applied to the texture coordinates of this object.
+    #[inline]
+    pub fn texture_uv_scale(&self) -> &Point2<f32> {
+        &self.uv_scale
+    }
+
+    /// The cube-map reflected by this object, if any.
+    #[inline]
+    pub fn environment_map(&self) -> Option<&Rc<CubeMap>> {
+        self.environment_map.as_ref()
+    }
+
+    /// How much of the environment map is reflected by this object, between 0.0 and 1.0.
+    #[inline]
+    pub fn reflectivity(&self) -> f32 {
+        self.reflectivity
+    }
+
+    /// Whether this object reflects its surroundings with the `ScreenSpaceReflection` effect.
+    #[inline]
+    pub fn ssr_reflector(&self) -> bool {
+        self.ssr_reflector
+    }
+
+    /// The `(metallic, roughness)` parameters of the physically-based shading of this object, or
+    /// `None` if it is shaded with the Blinn-Phong model.
+    #[inline]
+    pub fn metallic_roughness(&self) -> Option<(f32, f32)> {
+        self.metallic_roughness
+    }
+
     /// An user-defined data.
     ///
     /// Use dynamic typing capabilities of the `Any` type to recover the actual data.
@@ -77,6 +284,13 @@ impl ObjectData {
     }
 }
 
+/// The render layer of the opaque world geometry, rendered first in the scene graph order.
+pub const RENDER_LAYER_OPAQUE: u32 = 0;
+/// The render layer of the transparent objects, rendered after the opaque world geometry.
+pub const RENDER_LAYER_TRANSPARENT: u32 = 1;
+/// The render layer of the objects drawn on top of everything else, without depth test.
+pub const RENDER_LAYER_ALWAYS_ON_TOP: u32 = 2;
+
 /// A 3d objects on the scene.
 ///
 /// This is the only interface to manipulate the object position, color, vertices and texture.
@@ -85,6 +299,12 @@ pub struct Object {
     // (thus removing the need of ObjectData at all.)
     data: ObjectData,
     mesh: Rc<RefCell<Mesh>>,
+    // The original mesh, set only while `mesh` is its flat-shaded copy.
+    smooth_mesh: Option<Rc<RefCell<Mesh>>>,
+    // The levels of detail replacing `mesh` far from the camera, sorted by increasing distance.
+    lods: Vec<(f32, Rc<RefCell<Mesh>>)>,
+    // The obj file and group `mesh` was loaded from, if any.
+    obj_source: Option<(PathBuf, String)>,
 }
 
 impl Object {
@@ -100,17 +320,74 @@ impl Object {
         let user_data = ();
         let data = ObjectData {
             color: Point3::new(r, g, b),
+            alpha: 1.0,
             lines_color: None,
             texture,
             wlines: 0.0,
             wpoints: 0.0,
             draw_surface: true,
-            cull: true,
+            cull: CullingMode::Back,
+            double_sided: false,
+            render_layer: RENDER_LAYER_OPAQUE,
+            blend_mode: BlendMode::Alpha,
+            billboard: BillboardMode::None,
+            instances: None,
+            depth_bias: None,
+            uv_offset: Point2::origin(),
+            uv_scale: Point2::new(1.0, 1.0),
+            environment_map: None,
+            reflectivity: 0.0,
+            ssr_reflector: false,
+            metallic_roughness: None,
             material,
             user_data: Box::new(user_data),
         };
 
-        Object { data, mesh }
+        Object {
+            data,
+            mesh,
+            smooth_mesh: None,
+            lods: Vec::new(),
+            obj_source: None,
+        }
+    }
+
+    /// Creates a new object sharing the same mesh as `self`.
+    ///
+    /// The color, texture, material, and rendering parameters are copied. The user-data are not.
+    pub(crate) fn clone_geometry(&self) -> Object {
+        let data = ObjectData {
+            material: self.data.material.clone(),
+            texture: self.data.texture.clone(),
+            color: self.data.color,
+            alpha: self.data.alpha,
+            lines_color: self.data.lines_color,
+            wlines: self.data.wlines,
+            wpoints: self.data.wpoints,
+            draw_surface: self.data.draw_surface,
+            cull: self.data.cull,
+            double_sided: self.data.double_sided,
+            render_layer: self.data.render_layer,
+            blend_mode: self.data.blend_mode,
+            billboard: self.data.billboard,
+            instances: self.data.instances.clone(),
+            depth_bias: self.data.depth_bias,
+            uv_offset: self.data.uv_offset,
+            uv_scale: self.data.uv_scale,
+            environment_map: self.data.environment_map.clone(),
+            reflectivity: self.data.reflectivity,
+            ssr_reflector: self.data.ssr_reflector,
+            metallic_roughness: self.data.metallic_roughness,
+            user_data: Box::new(()),
+        };
+
+        Object {
+            data,
+            mesh: self.mesh.clone(),
+            smooth_mesh: self.smooth_mesh.clone(),
+            lods: self.lods.clone(),
+            obj_source: self.obj_source.clone(),
+        }
     }
 
     #[doc(hidden)]
@@ -122,6 +399,9 @@ impl Object {
         camera: &mut dyn Camera,
         light: &Light,
     ) {
+        let transform = &self.data.billboard.apply(transform, camera);
+        let mesh = self.lod_mesh(transform, camera);
+
         self.data.material.borrow_mut().render(
             pass,
             transform,
@@ -129,10 +409,56 @@ impl Object {
             camera,
             light,
             &self.data,
-            &mut *self.mesh.borrow_mut(),
+            &mut *mesh.borrow_mut(),
         );
     }
 
+    // The mesh of the level of detail matching the distance from `transform` to the camera.
+    fn lod_mesh(&self, transform: &Isometry3<f32>, camera: &dyn Camera) -> &Rc<RefCell<Mesh>> {
+        let dist = na::distance(&camera.eye(), &Point3::from(transform.translation.vector));
+
+        match self.lods.iter().rposition(|lod| lod.0 <= dist) {
+            Some(i) => &self.lods[i].1,
+            None => &self.mesh,
+        }
+    }
+
+    /// Sets the levels of detail of this object.
+    ///
+    /// Each level of detail `(distance, mesh)` replaces the mesh of this object when the camera
+    /// is at least at `distance` from the object origin, and closer than the next level. The
+    /// mesh of this object, flat-shaded or not, is rendered closer than all the levels. They are
+    /// sorted by increasing distance. No levels of detail are used if `lods` is empty.
+    pub fn set_lods(&mut self, lods: Vec<(f32, Rc<RefCell<Mesh>>)>) {
+        self.lods = lods;
+        self.sort_lods();
+    }
+
+    /// The levels of detail of this object, sorted by increasing camera distance.
+    ///
+    /// They do not include the mesh of this object, rendered closer than all of them.
+    #[inline]
+    pub fn lods(&self) -> &[(f32, Rc<RefCell<Mesh>>)] {
+        &self.lods[..]
+    }
+
+    /// Overrides the distances at which each level of detail of this object is used.
+    ///
+    /// The `i`-th distance applies to the `i`-th level of detail of `Object::lods`. Extra
+    /// distances are ignored. The levels are sorted again by increasing distance afterwards.
+    pub fn set_lod_distances(&mut self, distances: &[f32]) {
+        for (lod, dist) in self.lods.iter_mut().zip(distances.iter()) {
+            lod.0 = *dist;
+        }
+
+        self.sort_lods();
+    }
+
+    fn sort_lods(&mut self) {
+        self.lods
+            .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+    }
+
     /// Gets the data of this object.
     #[inline]
     pub fn data(&self) -> &ObjectData {
@@ -148,7 +474,163 @@ impl Object {
     /// Enables or disables backface culling for this object.
     #[inline]
     pub fn enable_backface_culling(&mut self, active: bool) {
-        self.data.cull = active;
+        self.data.cull = if active {
+            CullingMode::Back
+        } else {
+            CullingMode::None
+        };
+    }
+
+    /// Sets the faces of this object discarded before rasterization.
+    ///
+    /// Defaults to `CullingMode::Back`. Use `CullingMode::None` for meshes with inconsistent
+    /// winding.
+    #[inline]
+    pub fn set_backface_culling(&mut self, mode: CullingMode) {
+        self.data.cull = mode;
+    }
+
+    /// Sets the render layer of this object.
+    ///
+    /// The layers are rendered in increasing order, so the objects of a layer are drawn on top
+    /// of those of the previous layers:
+    ///
+    /// * `RENDER_LAYER_OPAQUE` (0, the default) is for the opaque world geometry, rendered in
+    ///   the scene graph order. Transparent objects of this layer are moved to the next one.
+    /// * `RENDER_LAYER_TRANSPARENT` (1) is for transparent objects.
+    /// * `RENDER_LAYER_ALWAYS_ON_TOP` (2) is for objects like HUD geometry or gizmos, drawn
+    ///   without depth test nor depth writes.
+    ///
+    /// Layers above 2 are also drawn without depth test. The objects of every layer other than
+    /// the first one are sorted from the farthest to the closest to the camera.
+    #[inline]
+    pub fn set_render_layer(&mut self, layer: u32) {
+        self.data.render_layer = layer;
+    }
+
+    /// Sets how the color of this object is combined with the colors behind it.
+    ///
+    /// Objects with a blend mode other than `BlendMode::Alpha` are rendered with the transparent
+    /// objects, see `Object::set_render_layer`, and do not write to the depth buffer so that
+    /// their order does not matter. Defaults to `BlendMode::Alpha`.
+    #[inline]
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.data.blend_mode = mode;
+    }
+
+    /// Sets how this object is rotated to face the camera, e.g., for sprites and labels.
+    ///
+    /// Unless the mode is `BillboardMode::None`, the rotation of the object is recomputed from
+    /// the camera at each rendering, and overrides the rotation of its scene node. The children
+    /// of its node are not affected. Defaults to `BillboardMode::None`.
+    #[inline]
+    pub fn set_billboard(&mut self, mode: BillboardMode) {
+        self.data.billboard = mode;
+    }
+
+    /// Draws this object once for each of the given instances, with a single draw call.
+    ///
+    /// Each instance is placed by its transformation relative to the object, and has its color
+    /// multiplied by the object color. Instancing requires OpenGL 3.3, or WebGL with the
+    /// `ANGLE_instanced_arrays` extension: otherwise, the instances are drawn one at a time.
+    /// Only the default `ObjectMaterial` handles instances.
+    ///
+    /// This can be called at each frame to animate the instances. The instances are shared with
+    /// the objects created by `Object::clone_geometry` until either is given new instances.
+    pub fn set_instances(&mut self, instances: &[InstanceData]) {
+        match self.data.instances {
+            Some(ref buffer) if Rc::strong_count(buffer) == 1 => buffer.borrow_mut().set(instances),
+            _ => self.data.instances = Some(Rc::new(RefCell::new(InstancesBuffer::new(instances)))),
+        }
+    }
+
+    /// Draws this object only once, at its own position.
+    pub fn clear_instances(&mut self) {
+        self.data.instances = None
+    }
+
+    /// Sets whether both faces of this object are rendered and lit.
+    ///
+    /// When enabled, face culling is disabled while drawing this object, whatever its culling
+    /// mode, and the normals of the faces seen from behind are flipped so they are lit like the
+    /// front faces. This is useful for flat geometries like walls, flags, or billboards.
+    #[inline]
+    pub fn set_double_sided(&mut self, double_sided: bool) {
+        self.data.double_sided = double_sided;
+    }
+
+    /// Sets the depth offset applied when rendering this object surface.
+    ///
+    /// The depth of each fragment is offset by `factor * dz + units * r` where `dz` is the depth
+    /// slope of the triangle, and `r` the smallest resolvable depth difference (see
+    /// `glPolygonOffset`). Positive values push the surface away from the camera. This avoids
+    /// z-fighting between coplanar geometries like decals.
+    #[inline]
+    pub fn set_depth_bias(&mut self, factor: f32, units: f32) {
+        self.data.depth_bias = Some((factor, units));
+    }
+
+    /// Removes the depth offset applied when rendering this object surface.
+    #[inline]
+    pub fn clear_depth_bias(&mut self) {
+        self.data.depth_bias = None;
+    }
+
+    /// Sets the transformation applied to the texture coordinates of this object.
+    ///
+    /// Each texture coordinate `uv` is replaced by `uv * scale + offset` (component-wise), which
+    /// allows selecting a sub-image of a texture atlas or of a sprite sheet.
+    #[inline]
+    pub fn set_texture_uv_offset(&mut self, offset: Point2<f32>, scale: Point2<f32>) {
+        self.data.uv_offset = offset;
+        self.data.uv_scale = scale;
+    }
+
+    /// Sets the cube-map reflected by this object.
+    ///
+    /// The reflections are only visible if the reflectivity of this object is not zero. Set to
+    /// `None` to stop reflecting anything.
+    #[inline]
+    pub fn set_environment_map(&mut self, environment_map: Option<Rc<CubeMap>>) {
+        self.data.environment_map = environment_map;
+    }
+
+    /// Sets how much of the environment map is reflected by this object.
+    ///
+    /// The reflection replaces the lit color of the object proportionally to `reflectivity`,
+    /// which is clamped to `[0.0, 1.0]`: `0.0` disables the reflections, and `1.0` makes this
+    /// object a perfect mirror.
+    #[inline]
+    pub fn set_reflectivity(&mut self, reflectivity: f32) {
+        self.data.reflectivity = reflectivity.clamp(0.0, 1.0);
+    }
+
+    /// Sets whether this object reflects the other visible objects.
+    ///
+    /// The reflections are only computed by the `ScreenSpaceReflection` post-processing effect,
+    /// see `Window::enable_ssr`, and replace the lit color of the object proportionally to its
+    /// reflectivity. Transparent objects never reflect. Defaults to `false`.
+    #[inline]
+    pub fn set_ssr_reflector(&mut self, reflector: bool) {
+        self.data.ssr_reflector = reflector;
+    }
+
+    /// Shades this object with a physically-based metallic-roughness material.
+    ///
+    /// The Cook-Torrance BRDF then replaces the Blinn-Phong shading, with the color of this
+    /// object as its albedo. Both parameters are clamped to `[0.0, 1.0]`: `metallic` is `0.0`
+    /// for a dielectric and `1.0` for a metal, and `roughness` goes from a perfectly smooth to a
+    /// fully rough surface. `(0.0, 0.5)` gives a typical dielectric, e.g., plastic.
+    #[inline]
+    pub fn set_metallic_roughness(&mut self, metallic: f32, roughness: f32) {
+        // A null roughness would make the highlights infinitely small.
+        self.data.metallic_roughness = Some((metallic.clamp(0.0, 1.0), roughness.clamp(0.02, 1.0)));
+    }
+
+    /// Shades this object with the Blinn-Phong model again, which is the default.
+    #[inline]
+    pub fn unset_metallic_roughness(&mut self) {
+        self.data.metallic_roughness = None;
     }
 
     /// Attaches user-defined data to this object.
@@ -193,6 +675,28 @@ impl Object {
         self.data.lines_color
     }
 
+    /// Draws the edges of this object triangles on top of its surface, with the given color.
+    ///
+    /// This is a shortcut for setting the lines color, and a lines width of 1 if it was zero.
+    /// Set to `None` to stop drawing the lines. On WebGL, which has no polygon mode, the edges
+    /// are drawn as `LINES` instead.
+    #[inline]
+    pub fn set_wireframe_overlay(&mut self, color: Option<Point3<f32>>) {
+        match color {
+            Some(color) => {
+                self.data.lines_color = Some(color);
+
+                if self.data.wlines == 0.0 {
+                    self.data.wlines = 1.0;
+                }
+            }
+            None => {
+                self.data.lines_color = None;
+                self.data.wlines = 0.0;
+            }
+        }
+    }
+
     /// Sets the size of the points drawn for this object.
     #[inline]
     pub fn set_points_size(&mut self, size: f32) {
@@ -217,15 +721,150 @@ impl Object {
         self.data.draw_surface
     }
 
+    /// Activate or deactivate flat shading for this object.
+    ///
+    /// When enabled, the object is rendered with a copy of its mesh where each triangle has its
+    /// own vertices with the face normal. The original mesh is not modified and is used again
+    /// once flat shading is disabled. Nothing happens if the mesh data are not available on the
+    /// CPU.
+    ///
+    /// Note that, while flat shading is active, the `modify_*` and `read_*` methods operate on
+    /// the flat-shaded copy.
+    #[inline]
+    pub fn set_flat_shading(&mut self, active: bool) {
+        if active == self.flat_shading() {
+            return;
+        }
+
+        if active {
+            let flat = self.mesh.borrow().to_flat_shaded();
+
+            if let Some(flat) = flat {
+                let smooth = mem::replace(&mut self.mesh, Rc::new(RefCell::new(flat)));
+                self.smooth_mesh = Some(smooth);
+            }
+        } else if let Some(smooth) = self.smooth_mesh.take() {
+            self.mesh = smooth;
+        }
+    }
+
+    /// Whether this object is rendered with flat shading.
+    #[inline]
+    pub fn flat_shading(&self) -> bool {
+        self.smooth_mesh.is_some()
+    }
+
+    /// This object's mesh before flat shading, see `Object::set_flat_shading`.
+    #[inline]
+    pub(crate) fn smooth_mesh(&self) -> &Rc<RefCell<Mesh>> {
+        self.smooth_mesh.as_ref().unwrap_or(&self.mesh)
+    }
+
+    /// The path of the obj file and the name of the group this object's mesh was loaded from, see
+    /// `SceneNode::add_obj`.
+    #[inline]
+    pub(crate) fn obj_source(&self) -> Option<&(PathBuf, String)> {
+        self.obj_source.as_ref()
+    }
+
+    /// Sets the obj file and group this object's mesh was loaded from.
+    #[inline]
+    pub(crate) fn set_obj_source(&mut self, path: &Path, group: &str) {
+        self.obj_source = Some((path.to_path_buf(), group.to_string()))
+    }
+
     /// This object's mesh.
     #[inline]
     pub fn mesh(&self) -> &Rc<RefCell<Mesh>> {
         &self.mesh
     }
 
+    /// Replaces this object's mesh, discarding its levels of detail.
+    pub(crate) fn set_mesh(&mut self, mesh: Rc<RefCell<Mesh>>) {
+        self.mesh = mesh;
+        self.smooth_mesh = None;
+        self.lods.clear();
+        self.obj_source = None;
+    }
+
+    /// Adds a custom vertex attribute to this object's mesh.
+    ///
+    /// See `Mesh::add_vertex_attribute` for details.
+    #[inline]
+    pub fn add_vertex_attribute(&mut self, name: &str, components: u32, data: &[f32]) {
+        self.unshare_generated_mesh();
+        self.mesh
+            .borrow_mut()
+            .add_vertex_attribute(name, components, data)
+    }
+
+    /// Replaces the values of a custom vertex attribute of this object's mesh.
+    ///
+    /// See `Mesh::update_vertex_attribute` for details.
+    #[inline]
+    pub fn update_vertex_attribute(&mut self, name: &str, data: &[f32]) {
+        self.unshare_generated_mesh();
+        self.mesh.borrow_mut().update_vertex_attribute(name, data)
+    }
+
+    /// The axis-aligned bounding box of this object's mesh, in its local frame and without its
+    /// scale.
+    ///
+    /// This is cached by the mesh, see `Mesh::aabb`. Returns `None` if the mesh vertices are not
+    /// available on the CPU.
+    pub fn local_aabb(&self) -> Option<AABB<f32>> {
+        self.mesh.borrow().aabb()
+    }
+
+    /// The axis-aligned bounding box of this object's mesh, once scaled by `scale` then moved by
+    /// `transform`, e.g., the world transformation and scale of its scene node.
+    ///
+    /// This bounds the transformed corners of `Object::local_aabb`, so it may be larger than the
+    /// tightest box around the transformed vertices. This is however cheap to compute at each
+    /// frame.
+    pub fn world_aabb(
+        &self,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+    ) -> Option<AABB<f32>> {
+        Some(transformed_aabb(&self.local_aabb()?, transform, scale))
+    }
+
+    /// The bounding sphere `(center, radius)` of this object's mesh, in its local frame and
+    /// without its scale.
+    ///
+    /// This is cached by the mesh, see `Mesh::bounding_sphere`. Returns `None` if the mesh
+    /// vertices are not available on the CPU.
+    pub fn bounding_sphere(&self) -> Option<(Point3<f32>, f32)> {
+        self.mesh.borrow().bounding_sphere()
+    }
+
+    // Replaces the mesh by a copy if it is a generated mesh cached by the `MeshManager`, so that
+    // modifying it does not modify the other objects using it.
+    fn unshare_generated_mesh(&mut self) {
+        if !MeshManager::get_global_manager(|mm| mm.is_generated(&self.mesh)) {
+            return;
+        }
+
+        let copy = self.mesh.borrow().to_trimesh();
+
+        if let Some(copy) = copy {
+            self.mesh = Rc::new(RefCell::new(Mesh::from_trimesh(copy, false)));
+        }
+    }
+
     /// Mutably access the object's vertices.
+    ///
+    /// The modified vertices are uploaded in place to the existing GPU buffer before the next
+    /// rendering, so this can be called at each frame to animate the mesh. Call
+    /// `Object::recompute_normals` afterwards to update the lighting. Note that the mesh may be
+    /// shared with other objects, e.g., all the objects created by `SceneNode::add_cube`. The
+    /// meshes generated by the `MeshManager`, e.g., by `SceneNode::add_capsule`, are copied first
+    /// instead.
     #[inline(always)]
     pub fn modify_vertices<F: FnMut(&mut Vec<Point3<f32>>)>(&mut self, f: &mut F) {
+        self.unshare_generated_mesh();
+
         let bmesh = self.mesh.borrow_mut();
         let _ = bmesh
             .coords()
@@ -252,12 +891,15 @@ impl Object {
     /// Recomputes the normals of this object's mesh.
     #[inline]
     pub fn recompute_normals(&mut self) {
+        self.unshare_generated_mesh();
         self.mesh.borrow_mut().recompute_normals();
     }
 
     /// Mutably access the object's normals.
     #[inline(always)]
     pub fn modify_normals<F: FnMut(&mut Vec<Vector3<f32>>)>(&mut self, f: &mut F) {
+        self.unshare_generated_mesh();
+
         let bmesh = self.mesh.borrow_mut();
         let _ = bmesh
             .normals()
@@ -282,8 +924,12 @@ impl Object {
     }
 
     /// Mutably access the object's faces.
+    ///
+    /// The faces are empty if the mesh uses 32-bits indices, see `Mesh::faces_u32`.
     #[inline(always)]
     pub fn modify_faces<F: FnMut(&mut Vec<Point3<u16>>)>(&mut self, f: &mut F) {
+        self.unshare_generated_mesh();
+
         let bmesh = self.mesh.borrow_mut();
         let _ = bmesh
             .faces()
@@ -295,6 +941,8 @@ impl Object {
     }
 
     /// Access the object's faces.
+    ///
+    /// The faces are empty if the mesh uses 32-bits indices, see `Mesh::faces_u32`.
     #[inline(always)]
     pub fn read_faces<F: FnMut(&[Point3<u16>])>(&self, f: &mut F) {
         let bmesh = self.mesh.borrow();
@@ -310,6 +958,8 @@ impl Object {
     /// Mutably access the object's texture coordinates.
     #[inline(always)]
     pub fn modify_uvs<F: FnMut(&mut Vec<Point2<f32>>)>(&mut self, f: &mut F) {
+        self.unshare_generated_mesh();
+
         let bmesh = self.mesh.borrow_mut();
         let _ = bmesh
             .uvs()
@@ -343,6 +993,15 @@ impl Object {
         self.data.color.z = b;
     }
 
+    /// Sets the opacity of the object.
+    ///
+    /// The alpha must be on the range `[0.0, 1.0]`. Transparent objects are rendered after all
+    /// the opaque objects, from the farthest to the closest to the camera.
+    #[inline]
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.data.alpha = alpha;
+    }
+
     /// Sets the texture of the object.
     ///
     /// The texture is loaded from a file and registered by the global `TextureManager`.
@@ -376,3 +1035,84 @@ impl Object {
         self.data.texture = texture
     }
 }
+
+// The box bounding the corners of `local`, once scaled by `scale` then moved by `transform`.
+fn transformed_aabb(
+    local: &AABB<f32>,
+    transform: &Isometry3<f32>,
+    scale: &Vector3<f32>,
+) -> AABB<f32> {
+    let mut mins = Point3::new(f32::MAX, f32::MAX, f32::MAX);
+    let mut maxs = Point3::new(f32::MIN, f32::MIN, f32::MIN);
+
+    for i in 0..8 {
+        let corner = Point3::new(
+            if i & 1 == 0 {
+                local.mins.x
+            } else {
+                local.maxs.x
+            },
+            if i & 2 == 0 {
+                local.mins.y
+            } else {
+                local.maxs.y
+            },
+            if i & 4 == 0 {
+                local.mins.z
+            } else {
+                local.maxs.z
+            },
+        );
+        let pt = transform * Point3::from(corner.coords.component_mul(scale));
+        mins = mins.inf(&pt);
+        maxs = maxs.sup(&pt);
+    }
+
+    AABB::new(mins, maxs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::transformed_aabb;
+    use na::{Isometry3, Point3, Vector3};
+    use ncollide3d::bounding_volume::AABB;
+
+    fn assert_relative_eq(a: &Point3<f32>, b: &Point3<f32>) {
+        assert!((a - b).norm() < 1.0e-5, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn transformed_aabb_is_scaled_then_moved() {
+        let local = AABB::new(Point3::new(-1.0, -2.0, -3.0), Point3::new(1.0, 2.0, 3.0));
+        let transform = Isometry3::translation(10.0, 0.0, -10.0);
+        let aabb = transformed_aabb(&local, &transform, &Vector3::new(2.0, 1.0, 0.5));
+
+        assert_relative_eq(&aabb.mins, &Point3::new(8.0, -2.0, -11.5));
+        assert_relative_eq(&aabb.maxs, &Point3::new(12.0, 2.0, -8.5));
+    }
+
+    #[test]
+    fn transformed_aabb_bounds_the_rotated_corners() {
+        let local = AABB::new(Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 1.0, 1.0));
+        let transform =
+            Isometry3::new(Vector3::zeros(), Vector3::z() * std::f32::consts::FRAC_PI_2);
+        let aabb = transformed_aabb(&local, &transform, &Vector3::repeat(1.0));
+
+        // A quarter turn around `z` maps `x` to `y` and `y` to `-x`.
+        assert_relative_eq(&aabb.mins, &Point3::new(-1.0, 0.0, 0.0));
+        assert_relative_eq(&aabb.maxs, &Point3::new(0.0, 2.0, 1.0));
+    }
+
+    #[test]
+    fn transformed_aabb_with_a_negative_scale() {
+        let local = AABB::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0));
+        let aabb = transformed_aabb(
+            &local,
+            &Isometry3::identity(),
+            &Vector3::new(-1.0, 1.0, 1.0),
+        );
+
+        assert_relative_eq(&aabb.mins, &Point3::new(-1.0, 0.0, 0.0));
+        assert_relative_eq(&aabb.maxs, &Point3::new(0.0, 1.0, 1.0));
+    }
+}
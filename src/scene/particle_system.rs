@@ -0,0 +1,292 @@
+//! Particle systems simulated on the CPU.
+
+use std::cell::{Ref, RefCell};
+use std::rc::Rc;
+
+use na::{Point3, Point4, Vector3};
+
+use crate::scene::BlendMode;
+
+/// The parameters of the particles spawned by a `ParticleSystem`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParticleConfig {
+    /// The number of particles spawned per second.
+    pub emission_rate: f32,
+    /// The duration, in seconds, between the birth and the death of each particle.
+    pub lifetime: f32,
+    /// The lower bound of the initial velocity of the particles.
+    ///
+    /// Each component of the initial velocity is drawn uniformly between its `min_velocity` and
+    /// `max_velocity` counterparts.
+    pub min_velocity: Vector3<f32>,
+    /// The upper bound of the initial velocity of the particles.
+    pub max_velocity: Vector3<f32>,
+    /// The acceleration applied to every particle.
+    pub gravity: Vector3<f32>,
+    /// The RGBA color of the particles when they are born.
+    pub start_color: Point4<f32>,
+    /// The RGBA color of the particles when they die.
+    ///
+    /// The color of each particle is interpolated linearly between `start_color` and
+    /// `end_color` during its lifetime.
+    pub end_color: Point4<f32>,
+    /// The size, in pixels, of the particles when they are born.
+    pub start_size: f32,
+    /// The size, in pixels, of the particles when they die.
+    pub end_size: f32,
+    /// How the particles are combined with the colors already rendered behind them.
+    ///
+    /// The particles are not sorted by depth so `BlendMode::Additive` gives the best results
+    /// for dense effects like fire or sparks.
+    pub blend_mode: BlendMode,
+}
+
+impl Default for ParticleConfig {
+    fn default() -> Self {
+        ParticleConfig {
+            emission_rate: 100.0,
+            lifetime: 2.0,
+            min_velocity: Vector3::new(-0.5, 1.0, -0.5),
+            max_velocity: Vector3::new(0.5, 2.0, 0.5),
+            gravity: Vector3::new(0.0, -1.0, 0.0),
+            start_color: Point4::new(1.0, 1.0, 1.0, 1.0),
+            end_color: Point4::new(1.0, 1.0, 1.0, 0.0),
+            start_size: 8.0,
+            end_size: 2.0,
+            blend_mode: BlendMode::Alpha,
+        }
+    }
+}
+
+/// The state of a single living particle.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParticleState {
+    /// The position of the particle, in world-space.
+    pub position: Point3<f32>,
+    /// The velocity of the particle, in world-space.
+    pub velocity: Vector3<f32>,
+    /// The time, in seconds, elapsed since the particle was born.
+    pub age: f32,
+}
+
+impl ParticleState {
+    /// The fraction of its lifetime elapsed since the particle was born, between `0.0` and
+    /// `1.0`.
+    #[inline]
+    pub fn progress(&self, config: &ParticleConfig) -> f32 {
+        if config.lifetime > 0.0 {
+            (self.age / config.lifetime).min(1.0)
+        } else {
+            1.0
+        }
+    }
+
+    /// The RGBA color of the particle.
+    #[inline]
+    pub fn color(&self, config: &ParticleConfig) -> Point4<f32> {
+        let t = self.progress(config);
+        config.start_color + (config.end_color - config.start_color) * t
+    }
+
+    /// The size, in pixels, of the particle.
+    #[inline]
+    pub fn size(&self, config: &ParticleConfig) -> f32 {
+        let t = self.progress(config);
+        config.start_size + (config.end_size - config.start_size) * t
+    }
+}
+
+/// The simulation state of a `ParticleSystem`.
+pub struct ParticleSystemData {
+    config: ParticleConfig,
+    max_particles: usize,
+    particles: Vec<ParticleState>,
+    position: Point3<f32>,
+    emitting: bool,
+    visible: bool,
+    pending_emissions: f32,
+    seed: u32,
+}
+
+impl ParticleSystemData {
+    /// The parameters of the spawned particles.
+    #[inline]
+    pub fn config(&self) -> &ParticleConfig {
+        &self.config
+    }
+
+    /// The living particles.
+    #[inline]
+    pub fn particles(&self) -> &[ParticleState] {
+        &self.particles[..]
+    }
+
+    /// The maximum number of particles alive at the same time.
+    #[inline]
+    pub fn max_particles(&self) -> usize {
+        self.max_particles
+    }
+
+    /// The world-space position the particles are spawned at.
+    #[inline]
+    pub fn position(&self) -> Point3<f32> {
+        self.position
+    }
+
+    /// Whether new particles are spawned.
+    #[inline]
+    pub fn is_emitting(&self) -> bool {
+        self.emitting
+    }
+
+    /// Whether the particles are rendered.
+    #[inline]
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn update(&mut self, dt: f32) {
+        let lifetime = self.config.lifetime;
+        let gravity = self.config.gravity;
+
+        // Remove the dead particles first so their slots can be reused right away.
+        self.particles.retain(|p| p.age + dt < lifetime);
+
+        for particle in &mut self.particles {
+            particle.velocity += gravity * dt;
+            particle.position += particle.velocity * dt;
+            particle.age += dt;
+        }
+
+        if !self.emitting {
+            self.pending_emissions = 0.0;
+            return;
+        }
+
+        self.pending_emissions += self.config.emission_rate.max(0.0) * dt;
+        let num_emissions = self.pending_emissions.floor();
+        self.pending_emissions -= num_emissions;
+
+        for _ in 0..num_emissions as usize {
+            if self.particles.len() >= self.max_particles {
+                break;
+            }
+
+            let velocity = Vector3::new(
+                self.random_between(self.config.min_velocity.x, self.config.max_velocity.x),
+                self.random_between(self.config.min_velocity.y, self.config.max_velocity.y),
+                self.random_between(self.config.min_velocity.z, self.config.max_velocity.z),
+            );
+
+            self.particles.push(ParticleState {
+                position: self.position,
+                velocity,
+                age: 0.0,
+            });
+        }
+    }
+
+    // A xorshift generator: good enough for visual jitter without pulling a random crate.
+    fn random_between(&mut self, min: f32, max: f32) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+
+        let t = (self.seed >> 8) as f32 / (1u32 << 24) as f32;
+        min + (max - min) * t
+    }
+}
+
+/// A set of particles spawned at a point and moving under a constant acceleration.
+///
+/// The particles are simulated on the CPU and rendered as points whose color and size vary with
+/// their remaining lifetime. This is a reference-counted handle: clones share the same
+/// particles. Use `Window::add_particle_system` to create a particle system rendered and updated
+/// by the window.
+#[derive(Clone)]
+pub struct ParticleSystem {
+    data: Rc<RefCell<ParticleSystemData>>,
+}
+
+impl ParticleSystem {
+    /// Creates a particle system with at most `max_particles` particles alive at the same time.
+    ///
+    /// The particles are emitted from the origin until `set_position` is called.
+    pub fn new(max_particles: usize, config: ParticleConfig) -> ParticleSystem {
+        let data = ParticleSystemData {
+            config,
+            max_particles,
+            particles: Vec::with_capacity(max_particles),
+            position: Point3::origin(),
+            emitting: true,
+            visible: true,
+            pending_emissions: 0.0,
+            seed: 0x9e37_79b9,
+        };
+
+        ParticleSystem {
+            data: Rc::new(RefCell::new(data)),
+        }
+    }
+
+    /// A reference to the simulation state of this particle system.
+    #[inline]
+    pub fn data(&self) -> Ref<ParticleSystemData> {
+        self.data.borrow()
+    }
+
+    /// Whether `self` and `other` are handles to the same particle system.
+    #[inline]
+    pub fn is_same(&self, other: &ParticleSystem) -> bool {
+        Rc::ptr_eq(&self.data, &other.data)
+    }
+
+    /// Replaces the parameters of the particles.
+    ///
+    /// The particles already alive keep their position and velocity.
+    #[inline]
+    pub fn set_config(&mut self, config: ParticleConfig) {
+        self.data.borrow_mut().config = config
+    }
+
+    /// Sets the world-space position new particles are spawned at.
+    #[inline]
+    pub fn set_position(&mut self, position: Point3<f32>) {
+        self.data.borrow_mut().position = position
+    }
+
+    /// Starts or stops spawning new particles.
+    ///
+    /// The particles already alive continue to move until they die.
+    #[inline]
+    pub fn set_emitting(&mut self, emitting: bool) {
+        self.data.borrow_mut().emitting = emitting
+    }
+
+    /// Shows or hides the particles. Hidden particles are still simulated.
+    #[inline]
+    pub fn set_visible(&mut self, visible: bool) {
+        self.data.borrow_mut().visible = visible
+    }
+
+    /// The number of living particles.
+    #[inline]
+    pub fn num_particles(&self) -> usize {
+        self.data.borrow().particles.len()
+    }
+
+    /// Kills every living particle.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.data.borrow_mut().particles.clear()
+    }
+
+    /// Advances the simulation by `dt` seconds.
+    ///
+    /// This is called once per frame by the window for the particle systems created with
+    /// `Window::add_particle_system`.
+    #[inline]
+    pub fn update(&mut self, dt: f32) {
+        self.data.borrow_mut().update(dt)
+    }
+}
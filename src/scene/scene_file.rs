@@ -0,0 +1,540 @@
+//! Saving and loading of scene graphs to and from RON files.
+
+use crate::resource::{MaterialManager, Mesh, MeshManager, TextureManager};
+use crate::scene::{BlendMode, CullingMode, Object, SceneNode};
+use na::{Isometry3, Point2, Point3, Quaternion, Translation3, UnitQuaternion, Vector3};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// An error raised while saving or loading a scene.
+#[derive(Debug)]
+pub enum SceneError {
+    /// The scene file could not be read or written.
+    Io(io::Error),
+    /// The scene file is not a valid RON scene description.
+    Format(String),
+    /// The mesh of an object is not registered into the `MeshManager`, so it cannot be referenced
+    /// by the scene file.
+    UnregisteredMesh {
+        /// The name of the scene node of the object, if any.
+        node: Option<String>,
+    },
+    /// The material of an object is not registered into the `MaterialManager`, so it cannot be
+    /// referenced by the scene file.
+    UnregisteredMaterial {
+        /// The name of the scene node of the object, if any.
+        node: Option<String>,
+    },
+    /// A mesh referenced by the scene file is neither registered nor generated by the
+    /// `MeshManager`.
+    UnknownMesh(String),
+    /// A group referenced by the scene file is missing from its obj file.
+    UnknownObjGroup {
+        /// The path of the obj file.
+        path: PathBuf,
+        /// The name of the missing group.
+        group: String,
+    },
+    /// A texture referenced by the scene file is neither registered into the `TextureManager`
+    /// nor the path of an existing file.
+    UnknownTexture(String),
+    /// A material referenced by the scene file is not registered into the `MaterialManager`.
+    UnknownMaterial(String),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SceneError::Io(ref e) => write!(f, "scene file error: {}", e),
+            SceneError::Format(ref e) => write!(f, "invalid scene file: {}", e),
+            SceneError::UnregisteredMesh {
+                node: Some(ref node),
+            } => write!(
+                f,
+                "the mesh of the node `{}` is not registered into the mesh manager",
+                node
+            ),
+            SceneError::UnregisteredMesh { node: None } => write!(
+                f,
+                "the mesh of an unnamed node is not registered into the mesh manager"
+            ),
+            SceneError::UnregisteredMaterial {
+                node: Some(ref node),
+            } => write!(
+                f,
+                "the material of the node `{}` is not registered into the material manager",
+                node
+            ),
+            SceneError::UnregisteredMaterial { node: None } => write!(
+                f,
+                "the material of an unnamed node is not registered into the material manager"
+            ),
+            SceneError::UnknownMesh(ref name) => write!(f, "unknown mesh `{}`", name),
+            SceneError::UnknownObjGroup {
+                ref path,
+                ref group,
+            } => write!(
+                f,
+                "unknown group `{}` in the obj file `{}`",
+                group,
+                path.display()
+            ),
+            SceneError::UnknownTexture(ref name) => write!(f, "unknown texture `{}`", name),
+            SceneError::UnknownMaterial(ref name) => write!(f, "unknown material `{}`", name),
+        }
+    }
+}
+
+impl Error for SceneError {}
+
+impl From<io::Error> for SceneError {
+    fn from(e: io::Error) -> SceneError {
+        SceneError::Io(e)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct NodeDescr {
+    #[serde(default)]
+    name: Option<String>,
+    translation: [f32; 3],
+    /// The `(i, j, k, w)` coordinates of the unit quaternion of the rotation.
+    rotation: [f32; 4],
+    scale: [f32; 3],
+    visible: bool,
+    #[serde(default)]
+    object: Option<ObjectDescr>,
+    #[serde(default)]
+    children: Vec<NodeDescr>,
+}
+
+/// Where the mesh of an object comes from.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum MeshDescr {
+    /// A mesh registered into or generated by the `MeshManager`, referenced by its name.
+    Named(String),
+    /// The mesh of a group of an obj file, see `SceneNode::add_obj`.
+    Obj { path: PathBuf, group: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct ObjectDescr {
+    mesh: MeshDescr,
+    /// The levels of detail `(distance, mesh)` replacing `mesh` far from the camera.
+    #[serde(default)]
+    lods: Vec<(f32, String)>,
+    color: [f32; 3],
+    alpha: f32,
+    /// The default, white, texture if `None`.
+    #[serde(default)]
+    texture: Option<String>,
+    uv_offset: [f32; 2],
+    uv_scale: [f32; 2],
+    /// The default material if `None`.
+    #[serde(default)]
+    material: Option<String>,
+    blend_mode: BlendMode,
+    render_layer: u32,
+    culling: CullingMode,
+    #[serde(default)]
+    double_sided: bool,
+    #[serde(default)]
+    flat_shading: bool,
+    surface_rendering: bool,
+    #[serde(default)]
+    lines_color: Option<[f32; 3]>,
+    lines_width: f32,
+    points_size: f32,
+    #[serde(default)]
+    depth_bias: Option<(f32, f32)>,
+    #[serde(default)]
+    metallic_roughness: Option<(f32, f32)>,
+}
+
+impl SceneNode {
+    /// Saves the scene graph rooted by this node to the RON file at `path`.
+    ///
+    /// The hierarchy, names, local transformations, scales, and visibility of the nodes are
+    /// saved, as well as the meshes, levels of detail, colors, textures, materials, blend modes,
+    /// and rendering and shading parameters of their objects. The meshes loaded by
+    /// `SceneNode::add_obj` are referenced by their obj file and group. The other meshes, the
+    /// textures, and the materials are referenced by their names in the `MeshManager`,
+    /// `TextureManager`, and `MaterialManager`, so the meshes created with
+    /// `SceneNode::add_trimesh` or `SceneNode::add_quad` must be registered into the former to
+    /// be saved. The instances, environment maps, billboard modes, and user data are not saved.
+    pub fn save_scene(&self, path: &Path) -> Result<(), SceneError> {
+        let ron = to_ron(&node_descr(self)?)?;
+
+        fs::write(path, ron)?;
+        Ok(())
+    }
+
+    /// Replaces the scene graph rooted by this node by the one saved to the RON file at `path`.
+    ///
+    /// This node keeps its parent, but its children are unlinked, and its name, local
+    /// transformation, scale, visibility, and object are replaced by the saved ones. The meshes
+    /// missing from the `MeshManager` are generated if possible, see
+    /// `MeshManager::get_or_generate`, the obj files are loaded again, one mesh per object like
+    /// with `SceneNode::add_obj`, and the textures missing from the `TextureManager` are loaded
+    /// from their names treated as paths. Nothing is modified if an error occurs.
+    pub fn load_scene(&mut self, path: &Path) -> Result<(), SceneError> {
+        let descr = from_ron(&fs::read_to_string(path)?)?;
+        let mut obj_meshes = ObjMeshes::default();
+        let object = match descr.object {
+            Some(ref object) => Some(object_from_descr(object, &mut obj_meshes)?),
+            None => None,
+        };
+        let children = descr
+            .children
+            .iter()
+            .map(|child| node_from_descr(child, &mut obj_meshes))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.unlink_children();
+        self.set_descr_properties(&descr);
+        self.data_mut().set_object(object);
+
+        for child in children {
+            self.add_child(child);
+        }
+
+        Ok(())
+    }
+
+    fn set_descr_properties(&mut self, descr: &NodeDescr) {
+        let [tx, ty, tz] = descr.translation;
+        let [i, j, k, w] = descr.rotation;
+        let [sx, sy, sz] = descr.scale;
+        let rotation = UnitQuaternion::from_quaternion(Quaternion::new(w, i, j, k));
+
+        self.set_local_transformation(Isometry3::from_parts(
+            Translation3::new(tx, ty, tz),
+            rotation,
+        ));
+        self.set_local_scale(sx, sy, sz);
+        self.set_visible(descr.visible);
+
+        match descr.name {
+            Some(ref name) => self.set_name(name),
+            None => self.data_mut().unset_name(),
+        }
+    }
+}
+
+fn to_ron(descr: &NodeDescr) -> Result<String, SceneError> {
+    ron::ser::to_string_pretty(descr, ron::ser::PrettyConfig::default())
+        .map_err(|e| SceneError::Format(e.to_string()))
+}
+
+fn from_ron(ron: &str) -> Result<NodeDescr, SceneError> {
+    ron::de::from_str(ron).map_err(|e| SceneError::Format(e.to_string()))
+}
+
+fn node_descr(node: &SceneNode) -> Result<NodeDescr, SceneError> {
+    let data = node.data();
+    let transform = data.local_transformation();
+    let translation = transform.translation.vector;
+    let rotation = transform.rotation.coords;
+    let scale = data.local_scale();
+    let name = data.name().map(|name| name.to_string());
+
+    let object = match data.object() {
+        Some(o) => Some(object_descr(o, &name)?),
+        None => None,
+    };
+
+    Ok(NodeDescr {
+        name,
+        translation: [translation.x, translation.y, translation.z],
+        rotation: [rotation.x, rotation.y, rotation.z, rotation.w],
+        scale: [scale.x, scale.y, scale.z],
+        visible: data.is_visible(),
+        object,
+        children: data
+            .children()
+            .iter()
+            .map(node_descr)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+fn object_descr(o: &Object, node: &Option<String>) -> Result<ObjectDescr, SceneError> {
+    let unregistered_mesh = || SceneError::UnregisteredMesh { node: node.clone() };
+    let (mesh, lods) = MeshManager::get_global_manager(|mm| -> Result<_, SceneError> {
+        let mesh = match o.obj_source() {
+            Some((path, group)) => MeshDescr::Obj {
+                path: path.clone(),
+                group: group.clone(),
+            },
+            None => MeshDescr::Named(mm.name_of(o.smooth_mesh()).ok_or_else(unregistered_mesh)?),
+        };
+        let lods = o
+            .lods()
+            .iter()
+            .map(|(dist, mesh)| Ok((*dist, mm.name_of(mesh).ok_or_else(unregistered_mesh)?)))
+            .collect::<Result<Vec<_>, SceneError>>()?;
+
+        Ok((mesh, lods))
+    })?;
+
+    let data = o.data();
+    let material = o.material();
+    let material = MaterialManager::get_global_manager(|mm| {
+        if Rc::ptr_eq(&material, &mm.get_default()) {
+            Ok(None)
+        } else {
+            mm.name_of(&material)
+                .map(Some)
+                .ok_or_else(|| SceneError::UnregisteredMaterial { node: node.clone() })
+        }
+    })?;
+    let (color, uv_offset, uv_scale) = (
+        data.color(),
+        data.texture_uv_offset(),
+        data.texture_uv_scale(),
+    );
+
+    Ok(ObjectDescr {
+        mesh,
+        lods,
+        color: [color.x, color.y, color.z],
+        alpha: data.alpha(),
+        texture: TextureManager::get_global_manager(|tm| tm.name_of(data.texture())),
+        uv_offset: [uv_offset.x, uv_offset.y],
+        uv_scale: [uv_scale.x, uv_scale.y],
+        material,
+        blend_mode: data.blend_mode(),
+        render_layer: data.render_layer(),
+        culling: data.culling_mode(),
+        double_sided: data.double_sided(),
+        flat_shading: o.flat_shading(),
+        surface_rendering: data.surface_rendering_active(),
+        lines_color: data.lines_color().map(|c| [c.x, c.y, c.z]),
+        lines_width: data.lines_width(),
+        points_size: data.points_size(),
+        depth_bias: data.depth_bias(),
+        metallic_roughness: data.metallic_roughness(),
+    })
+}
+
+// The meshes of the obj files loaded while loading a scene.
+//
+// Each mesh is given to a single object, and the file is loaded again for the next objects
+// using the same group, so that the objects do not share their meshes, like with
+// `SceneNode::add_obj`.
+#[derive(Default)]
+struct ObjMeshes(HashMap<PathBuf, Vec<(String, Rc<RefCell<Mesh>>)>>);
+
+impl ObjMeshes {
+    fn take(&mut self, path: &Path, group: &str) -> Result<Rc<RefCell<Mesh>>, SceneError> {
+        if let Some(meshes) = self.0.get_mut(path) {
+            if let Some(i) = meshes.iter().position(|(g, _)| g == group) {
+                return Ok(meshes.swap_remove(i).1);
+            }
+        }
+
+        let mtl_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let mut meshes: Vec<_> = MeshManager::load_obj(path, mtl_dir, &path.to_string_lossy())?
+            .into_iter()
+            .map(|(group, mesh, _)| (group, mesh))
+            .collect();
+        let i = meshes.iter().position(|(g, _)| g == group).ok_or_else(|| {
+            SceneError::UnknownObjGroup {
+                path: path.to_path_buf(),
+                group: group.to_string(),
+            }
+        })?;
+        let mesh = meshes.swap_remove(i).1;
+
+        let _ = self.0.insert(path.to_path_buf(), meshes);
+        Ok(mesh)
+    }
+}
+
+fn node_from_descr(descr: &NodeDescr, obj_meshes: &mut ObjMeshes) -> Result<SceneNode, SceneError> {
+    let object = match descr.object {
+        Some(ref object) => Some(object_from_descr(object, obj_meshes)?),
+        None => None,
+    };
+    let mut res = SceneNode::new(Vector3::from_element(1.0), na::one(), object);
+
+    res.set_descr_properties(descr);
+
+    for child in &descr.children {
+        res.add_child(node_from_descr(child, obj_meshes)?);
+    }
+
+    Ok(res)
+}
+
+fn object_from_descr(
+    descr: &ObjectDescr,
+    obj_meshes: &mut ObjMeshes,
+) -> Result<Object, SceneError> {
+    let named_mesh = |name: &str| {
+        MeshManager::get_global_manager(|mm| mm.get_or_generate(name))
+            .ok_or_else(|| SceneError::UnknownMesh(name.to_string()))
+    };
+
+    let mesh = match descr.mesh {
+        MeshDescr::Named(ref name) => named_mesh(name)?,
+        MeshDescr::Obj {
+            ref path,
+            ref group,
+        } => obj_meshes.take(path, group)?,
+    };
+    let texture = match descr.texture {
+        Some(ref name) => TextureManager::get_global_manager(|tm| match tm.get(name) {
+            Some(texture) => Ok(texture),
+            None if Path::new(name).is_file() => Ok(tm.add(Path::new(name), name)),
+            None => Err(SceneError::UnknownTexture(name.clone())),
+        })?,
+        None => TextureManager::get_global_manager(|tm| tm.get_default()),
+    };
+    let material = MaterialManager::get_global_manager(|mm| match descr.material {
+        Some(ref name) => mm
+            .get(name)
+            .ok_or_else(|| SceneError::UnknownMaterial(name.clone())),
+        None => Ok(mm.get_default()),
+    })?;
+    let lods = descr
+        .lods
+        .iter()
+        .map(|(dist, name)| Ok((*dist, named_mesh(name)?)))
+        .collect::<Result<Vec<_>, SceneError>>()?;
+
+    let [r, g, b] = descr.color;
+    let mut res = Object::new(mesh, r, g, b, texture, material);
+
+    if let MeshDescr::Obj {
+        ref path,
+        ref group,
+    } = descr.mesh
+    {
+        res.set_obj_source(path, group);
+    }
+
+    if !lods.is_empty() {
+        res.set_lods(lods);
+    }
+
+    let ([u, v], [su, sv]) = (descr.uv_offset, descr.uv_scale);
+    res.set_texture_uv_offset(Point2::new(u, v), Point2::new(su, sv));
+    res.set_alpha(descr.alpha);
+    res.set_blend_mode(descr.blend_mode);
+    res.set_render_layer(descr.render_layer);
+    res.set_backface_culling(descr.culling);
+    res.set_double_sided(descr.double_sided);
+    res.set_flat_shading(descr.flat_shading);
+    res.set_surface_rendering_activation(descr.surface_rendering);
+    res.set_lines_color(descr.lines_color.map(|[r, g, b]| Point3::new(r, g, b)));
+    res.set_lines_width(descr.lines_width);
+    res.set_points_size(descr.points_size);
+
+    match descr.depth_bias {
+        Some((factor, units)) => res.set_depth_bias(factor, units),
+        None => res.clear_depth_bias(),
+    }
+
+    match descr.metallic_roughness {
+        Some((metallic, roughness)) => res.set_metallic_roughness(metallic, roughness),
+        None => res.unset_metallic_roughness(),
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{from_ron, node_descr, to_ron, MeshDescr, NodeDescr, ObjectDescr};
+    use crate::scene::{BlendMode, CullingMode, SceneNode};
+    use na::{Isometry3, Translation3, UnitQuaternion, Vector3};
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn object_descr(mesh: MeshDescr) -> ObjectDescr {
+        ObjectDescr {
+            mesh,
+            lods: vec![(10.0, "sphere_8".to_string())],
+            color: [1.0, 0.5, 0.25],
+            alpha: 0.5,
+            texture: Some("checkerboard".to_string()),
+            uv_offset: [0.5, 0.0],
+            uv_scale: [2.0, 2.0],
+            material: Some("normals".to_string()),
+            blend_mode: BlendMode::Additive,
+            render_layer: 2,
+            culling: CullingMode::Front,
+            double_sided: true,
+            flat_shading: true,
+            surface_rendering: false,
+            lines_color: Some([0.0, 1.0, 0.0]),
+            lines_width: 2.0,
+            points_size: 4.0,
+            depth_bias: Some((1.0, 2.0)),
+            metallic_roughness: Some((0.25, 0.75)),
+        }
+    }
+
+    #[test]
+    fn descr_round_trip() {
+        let descr = NodeDescr {
+            name: Some("root".to_string()),
+            translation: [1.0, 2.0, 3.0],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            scale: [1.0, 1.0, 1.0],
+            visible: true,
+            object: Some(object_descr(MeshDescr::Named(
+                "capsule_0.5_1_50".to_string(),
+            ))),
+            children: vec![NodeDescr {
+                name: None,
+                translation: [0.0; 3],
+                rotation: [0.0, 1.0, 0.0, 0.0],
+                scale: [2.0, 2.0, 2.0],
+                visible: false,
+                object: Some(object_descr(MeshDescr::Obj {
+                    path: PathBuf::from("media/teapot/teapot.obj"),
+                    group: "Teapot001".to_string(),
+                })),
+                children: Vec::new(),
+            }],
+        };
+
+        assert_eq!(from_ron(&to_ron(&descr).unwrap()).unwrap(), descr);
+    }
+
+    #[test]
+    fn scene_round_trip() {
+        let mut root = SceneNode::new_empty();
+        root.set_name("root");
+        root.set_local_scale(1.0, 2.0, 3.0);
+
+        let mut child = root.add_group();
+        child.set_local_transformation(Isometry3::from_parts(
+            Translation3::new(1.0, 2.0, 3.0),
+            UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.5),
+        ));
+        child.set_visible(false);
+        let mut grandchild = child.add_group();
+        grandchild.set_name("grandchild");
+
+        let path = env::temp_dir().join(format!("kiss3d_scene_{}.ron", std::process::id()));
+        root.save_scene(&path).unwrap();
+
+        let mut loaded = SceneNode::new_empty();
+        let result = loaded.load_scene(&path);
+        let _ = fs::remove_file(&path);
+        result.unwrap();
+
+        assert_eq!(node_descr(&loaded).unwrap(), node_descr(&root).unwrap());
+        assert_eq!(loaded.data().children().len(), 1);
+    }
+}
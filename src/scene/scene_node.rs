@@ -1,15 +1,125 @@
 use crate::camera::Camera;
+use crate::context::Context;
+use crate::easing::EasingFn;
+use crate::export::{obj, stl};
 use crate::light::Light;
-use crate::resource::{Material, MaterialManager, Mesh, MeshManager, Texture, TextureManager};
-use crate::scene::Object;
+use crate::resource::{
+    CubeMap, Material, MaterialManager, Mesh, MeshManager, Texture, TextureManager,
+};
+use crate::scene::spline::SplineData;
+use crate::scene::{
+    BillboardMode, BlendMode, CoordinateFrameNode, CullingMode, InstanceData, Object, Spline,
+    RENDER_LAYER_ALWAYS_ON_TOP, RENDER_LAYER_OPAQUE,
+};
 use na;
-use na::{Isometry3, Point2, Point3, Translation3, UnitQuaternion, Vector3};
+use na::{Isometry3, Matrix4, Point2, Point3, Translation3, UnitQuaternion, Vector3, Vector4};
+use ncollide3d::bounding_volume::{BoundingVolume, AABB};
 use ncollide3d::procedural;
-use ncollide3d::procedural::TriMesh;
-use std::cell::{Ref, RefCell, RefMut};
+use ncollide3d::procedural::{IndexBuffer, TriMesh};
+use std::cell::{Cell, Ref, RefCell, RefMut};
+use std::cmp::Ordering;
+use std::f32;
+use std::io;
 use std::mem;
 use std::path::{Path, PathBuf};
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
+
+#[path = "../error.rs"]
+mod error;
+
+// A polyline, as a mesh whose faces are degenerate triangles: the first edge of each face is a
+// segment of the polyline.
+fn polyline_mesh(vertices: &[Point3<f32>]) -> Rc<RefCell<Mesh>> {
+    let faces = (0..vertices.len().saturating_sub(1) as u32)
+        .map(|i| Point3::new(i, i + 1, i + 1))
+        .collect();
+    // The normals only matter for the lighting of the lines.
+    let normals = vec![Vector3::y(); vertices.len()];
+    let trimesh = TriMesh::new(
+        vertices.to_vec(),
+        Some(normals),
+        None,
+        Some(IndexBuffer::Unified(faces)),
+    );
+
+    Rc::new(RefCell::new(Mesh::from_trimesh(trimesh, true)))
+}
+
+struct ColorAnimation {
+    node: Weak<RefCell<SceneNodeData>>,
+    from: Point3<f32>,
+    to: Point3<f32>,
+    duration: f32,
+    elapsed: f32,
+    easing: EasingFn,
+}
+
+thread_local!(static KEY_COLOR_ANIMATIONS: RefCell<Vec<ColorAnimation>> = const { RefCell::new(Vec::new()) });
+thread_local!(static KEY_FRUSTUM_CULLING: Cell<bool> = const { Cell::new(false) });
+thread_local!(static KEY_NUM_CULLED_OBJECTS: Cell<usize> = const { Cell::new(0) });
+
+/// Advances the color animations started by `SceneNode::animate_color` by `dt` seconds.
+///
+/// The finished animations, and the animations of the nodes that have been dropped, are
+/// removed.
+pub(crate) fn update_color_animations(dt: f32) {
+    KEY_COLOR_ANIMATIONS.with(|animations| {
+        let mut animations = animations.borrow_mut();
+
+        for animation in animations.iter_mut() {
+            animation.elapsed += dt;
+        }
+
+        animations.retain(|animation| {
+            let node = match animation.node.upgrade() {
+                Some(node) => node,
+                None => return false,
+            };
+            let t = if animation.duration > 0.0 {
+                (animation.elapsed / animation.duration).min(1.0)
+            } else {
+                1.0
+            };
+            let color = animation.from + (animation.to - animation.from) * (animation.easing)(t);
+
+            node.borrow_mut().set_color(color.x, color.y, color.z);
+            t < 1.0
+        })
+    })
+}
+
+/// Sets whether the objects outside of the camera frustum are skipped by `SceneNode::render`.
+pub(crate) fn set_frustum_culling(enabled: bool) {
+    KEY_FRUSTUM_CULLING.with(|f| f.set(enabled))
+}
+
+/// Whether the objects outside of the camera frustum are skipped by `SceneNode::render`.
+pub(crate) fn frustum_culling() -> bool {
+    KEY_FRUSTUM_CULLING.with(|f| f.get())
+}
+
+/// The number of objects skipped by the last call to `SceneNode::render`.
+pub(crate) fn num_culled_objects() -> usize {
+    KEY_NUM_CULLED_OBJECTS.with(|n| n.get())
+}
+
+// The planes `(normal, offset)` of the frustum of the given projection-view matrix. The points
+// inside of the frustum are on the positive side of all the planes.
+fn frustum_planes(proj_view: &Matrix4<f32>) -> [Vector4<f32>; 6] {
+    let row = |i: usize| proj_view.row(i).transpose();
+    let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+    let mut planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r3 + r2, r3 - r2];
+
+    for plane in planes.iter_mut() {
+        let norm = plane.xyz().norm();
+
+        if norm != 0.0 {
+            *plane /= norm;
+        }
+    }
+
+    planes
+}
 
 // XXX: once something like `fn foo(self: Rc<RefCell<SceneNode>>)` is allowed, this extra struct
 // will not be needed any more.
@@ -23,6 +133,8 @@ pub struct SceneNodeData {
     up_to_date: bool,
     children: Vec<SceneNode>,
     object: Option<Object>,
+    name: Option<String>,
+    spline: Option<SplineData>,
     // FIXME: use Weak pointers instead of the raw pointer.
     parent: Option<*const RefCell<SceneNodeData>>,
 }
@@ -81,10 +193,104 @@ impl SceneNodeData {
         self.parent.is_none()
     }
 
+    /// The name of this node, if any.
+    #[inline]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|name| &name[..])
+    }
+
+    /// Sets the name of this node, used by `SceneNode::find_node`.
+    ///
+    /// Names do not need to be unique.
+    #[inline]
+    pub fn set_name(&mut self, name: &str) {
+        self.name = Some(name.to_string())
+    }
+
+    /// Removes the name of this node.
+    #[inline]
+    pub(crate) fn unset_name(&mut self) {
+        self.name = None
+    }
+
     /// Render the scene graph rooted by this node.
+    ///
+    /// Opaque objects are rendered in the scene graph order. The objects of the other render
+    /// layers (including the transparent objects) are rendered afterwards, layer by layer, sorted
+    /// from the farthest to the closest to the camera. See `Object::set_render_layer`.
     pub fn render(&mut self, pass: usize, camera: &mut dyn Camera, light: &Light) {
         if self.visible {
-            self.do_render(&na::one(), &Vector3::from_element(1.0), pass, camera, light)
+            let mut deferred = Vec::new();
+            let frustum = if frustum_culling() {
+                Some(frustum_planes(&camera.transformation()))
+            } else {
+                None
+            };
+
+            KEY_NUM_CULLED_OBJECTS.with(|n| n.set(0));
+
+            self.do_render(
+                &na::one(),
+                &Vector3::from_element(1.0),
+                pass,
+                camera,
+                light,
+                frustum.as_ref(),
+                &mut deferred,
+            );
+
+            if !deferred.is_empty() {
+                let view = camera.view_transform();
+
+                let mut sorted: Vec<_> = deferred
+                    .into_iter()
+                    .map(|node| {
+                        let bnode = node.data();
+                        let layer = bnode
+                            .object
+                            .as_ref()
+                            .map_or(RENDER_LAYER_OPAQUE, |o| o.data().effective_render_layer());
+                        let depth = (view * bnode.object_center()).coords.norm();
+                        drop(bnode);
+                        (layer, depth, node)
+                    })
+                    .collect();
+                sorted.sort_by(|a, b| {
+                    a.0.cmp(&b.0)
+                        .then(b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal))
+                });
+
+                let ctxt = Context::get();
+                let mut depth_test = true;
+
+                for (layer, _, node) in sorted {
+                    if depth_test && layer >= RENDER_LAYER_ALWAYS_ON_TOP {
+                        depth_test = false;
+                        verify!(ctxt.disable(Context::DEPTH_TEST));
+                        verify!(ctxt.depth_mask(false));
+                    }
+
+                    let bnode = node.data();
+                    if bnode.is_culled(frustum.as_ref()) {
+                        continue;
+                    }
+
+                    if let Some(ref o) = bnode.object {
+                        o.render(
+                            &bnode.world_transform,
+                            &bnode.world_scale,
+                            pass,
+                            camera,
+                            light,
+                        )
+                    }
+                }
+
+                if !depth_test {
+                    verify!(ctxt.enable(Context::DEPTH_TEST));
+                    verify!(ctxt.depth_mask(true));
+                }
+            }
         }
     }
 
@@ -95,6 +301,8 @@ impl SceneNodeData {
         pass: usize,
         camera: &mut dyn Camera,
         light: &Light,
+        frustum: Option<&[Vector4<f32>; 6]>,
+        deferred: &mut Vec<SceneNode>,
     ) {
         if !self.up_to_date {
             self.up_to_date = true;
@@ -103,20 +311,14 @@ impl SceneNodeData {
         }
 
         match self.object {
-            Some(ref o) => o.render(
-                &self.world_transform,
-                &self.world_scale,
-                pass,
-                camera,
-                light,
-            ),
-            None => {}
-        }
-
-        for c in self.children.iter_mut() {
-            let mut bc = c.data_mut();
-            if bc.visible {
-                bc.do_render(
+            // The objects of the children not on the opaque layer are deferred by the loop
+            // below. Only the root node cannot be deferred since we have no handle to it.
+            Some(ref o)
+                if (o.data().effective_render_layer() == RENDER_LAYER_OPAQUE
+                    || self.parent.is_none())
+                    && !self.is_culled(frustum) =>
+            {
+                o.render(
                     &self.world_transform,
                     &self.world_scale,
                     pass,
@@ -124,6 +326,77 @@ impl SceneNodeData {
                     light,
                 )
             }
+            _ => {}
+        }
+
+        for c in self.children.iter_mut() {
+            let is_deferred = {
+                let mut bc = c.data_mut();
+                if bc.visible {
+                    bc.do_render(
+                        &self.world_transform,
+                        &self.world_scale,
+                        pass,
+                        camera,
+                        light,
+                        frustum,
+                        deferred,
+                    );
+
+                    bc.object.as_ref().map_or(false, |o| {
+                        o.data().effective_render_layer() != RENDER_LAYER_OPAQUE
+                    })
+                } else {
+                    false
+                }
+            };
+
+            if is_deferred {
+                deferred.push(c.clone())
+            }
+        }
+    }
+
+    // Whether the bounding sphere of this node's object lies entirely outside of the frustum.
+    // Instanced objects are never culled since their bounding sphere ignores the instances.
+    fn is_culled(&self, frustum: Option<&[Vector4<f32>; 6]>) -> bool {
+        let frustum = match frustum {
+            Some(frustum) => frustum,
+            None => return false,
+        };
+
+        let sphere = match self.object {
+            Some(ref o) if o.data().instances().is_none() => o.bounding_sphere(),
+            _ => None,
+        };
+
+        let (center, radius) = match sphere {
+            Some(sphere) => sphere,
+            None => return false,
+        };
+
+        let center =
+            self.world_transform * Point3::from(center.coords.component_mul(&self.world_scale));
+        let radius = radius * self.world_scale.amax();
+        let culled = frustum
+            .iter()
+            .any(|plane| plane.xyz().dot(&center.coords) + plane.w < -radius);
+
+        if culled {
+            KEY_NUM_CULLED_OBJECTS.with(|n| n.set(n.get() + 1));
+        }
+
+        culled
+    }
+
+    // The world-space center of the bounding box of this node's object.
+    fn object_center(&self) -> Point3<f32> {
+        match self.object.as_ref().and_then(|o| o.local_aabb()) {
+            Some(aabb) => {
+                let center = na::center(&aabb.mins, &aabb.maxs);
+                self.world_transform * Point3::from(center.coords.component_mul(&self.world_scale))
+            }
+            None => self.world_transform * Point3::origin(),
         }
     }
 
@@ -139,6 +412,18 @@ impl SceneNodeData {
         self.object.as_mut()
     }
 
+    /// The children of this node.
+    #[inline]
+    pub(crate) fn children(&self) -> &[SceneNode] {
+        &self.children[..]
+    }
+
+    /// Replaces the object contained by this node.
+    #[inline]
+    pub(crate) fn set_object(&mut self, object: Option<Object>) {
+        self.object = object
+    }
+
     /// A reference to the object possibly contained by this node.
     ///
     /// # Failure
@@ -202,6 +487,126 @@ impl SceneNodeData {
         self.apply_to_objects_mut(&mut |o| o.set_lines_color(color))
     }
 
+    /// Sets whether both faces of the objects contained by this node and its children are
+    /// rendered and lit.
+    #[inline]
+    pub fn set_double_sided(&mut self, double_sided: bool) {
+        self.apply_to_objects_mut(&mut |o| o.set_double_sided(double_sided))
+    }
+
+    /// Sets the depth offset applied when rendering the objects contained by this node and its
+    /// children.
+    #[inline]
+    pub fn set_depth_bias(&mut self, factor: f32, units: f32) {
+        self.apply_to_objects_mut(&mut |o| o.set_depth_bias(factor, units))
+    }
+
+    /// Sets the transformation applied to the texture coordinates of the objects contained by
+    /// this node and its children.
+    #[inline]
+    pub fn set_texture_uv_offset(&mut self, offset: Point2<f32>, scale: Point2<f32>) {
+        self.apply_to_objects_mut(&mut |o| o.set_texture_uv_offset(offset, scale))
+    }
+
+    /// Sets the cube-map reflected by the objects contained by this node and its children.
+    #[inline]
+    pub fn set_environment_map(&mut self, environment_map: Option<Rc<CubeMap>>) {
+        self.apply_to_objects_mut(&mut |o| o.set_environment_map(environment_map.clone()))
+    }
+
+    /// Sets how much of their environment map is reflected by the objects contained by this
+    /// node and its children.
+    #[inline]
+    pub fn set_reflectivity(&mut self, reflectivity: f32) {
+        self.apply_to_objects_mut(&mut |o| o.set_reflectivity(reflectivity))
+    }
+
+    /// Sets whether the objects contained by this node and its children reflect the other
+    /// visible objects.
+    #[inline]
+    pub fn set_ssr_reflector(&mut self, reflector: bool) {
+        self.apply_to_objects_mut(&mut |o| o.set_ssr_reflector(reflector))
+    }
+
+    /// Shades the objects contained by this node and its children with a physically-based
+    /// metallic-roughness material.
+    #[inline]
+    pub fn set_metallic_roughness(&mut self, metallic: f32, roughness: f32) {
+        self.apply_to_objects_mut(&mut |o| o.set_metallic_roughness(metallic, roughness))
+    }
+
+    /// Shades the objects contained by this node and its children with the Blinn-Phong model.
+    #[inline]
+    pub fn unset_metallic_roughness(&mut self) {
+        self.apply_to_objects_mut(&mut |o| o.unset_metallic_roughness())
+    }
+
+    /// Sets the render layer of the objects contained by this node and its children.
+    #[inline]
+    pub fn set_render_layer(&mut self, layer: u32) {
+        self.apply_to_objects_mut(&mut |o| o.set_render_layer(layer))
+    }
+
+    /// Sets whether the objects contained by this node and its children are drawn on top of
+    /// every other object, regardless of depth.
+    #[inline]
+    pub fn set_always_on_top(&mut self, always_on_top: bool) {
+        let layer = if always_on_top {
+            RENDER_LAYER_ALWAYS_ON_TOP
+        } else {
+            RENDER_LAYER_OPAQUE
+        };
+        self.set_render_layer(layer)
+    }
+
+    /// Sets the blend mode of the objects contained by this node and its children.
+    #[inline]
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.apply_to_objects_mut(&mut |o| o.set_blend_mode(mode))
+    }
+
+    /// Sets how the objects contained by this node and its children are rotated to face the
+    /// camera.
+    #[inline]
+    pub fn set_billboard(&mut self, mode: BillboardMode) {
+        self.apply_to_objects_mut(&mut |o| o.set_billboard(mode))
+    }
+
+    /// Sets the instances drawn for each object contained by this node and its children.
+    #[inline]
+    pub fn set_instances(&mut self, instances: &[InstanceData]) {
+        self.apply_to_objects_mut(&mut |o| o.set_instances(instances))
+    }
+
+    /// Draws the objects contained by this node and its children only once each.
+    #[inline]
+    pub fn clear_instances(&mut self) {
+        self.apply_to_objects_mut(&mut |o| o.clear_instances())
+    }
+
+    /// Draws the triangle edges of the objects contained by this node and its children on top
+    /// of their surfaces.
+    #[inline]
+    pub fn set_wireframe_overlay(&mut self, color: Option<Point3<f32>>) {
+        self.apply_to_objects_mut(&mut |o| o.set_wireframe_overlay(color))
+    }
+
+    /// Activate or deactivate flat shading for the objects contained by this node and its
+    /// children.
+    ///
+    /// See `Object::set_flat_shading` for details.
+    #[inline]
+    pub fn set_flat_shading(&mut self, active: bool) {
+        self.apply_to_objects_mut(&mut |o| o.set_flat_shading(active))
+    }
+
+    /// Overrides the distances at which each level of detail of the objects contained by this
+    /// node and its children are used.
+    #[inline]
+    pub fn set_lod_distances(&mut self, distances: &[f32]) {
+        self.apply_to_objects_mut(&mut |o| o.set_lod_distances(distances))
+    }
+
     /// Sets the size of the points drawn for the objects contained by this node and its children.
     #[inline]
     pub fn set_points_size(&mut self, size: f32) {
@@ -222,6 +627,13 @@ impl SceneNodeData {
         self.apply_to_objects_mut(&mut |o| o.enable_backface_culling(active))
     }
 
+    /// Sets the faces discarded before rasterization for the objects contained by this node and
+    /// its children.
+    #[inline]
+    pub fn set_backface_culling(&mut self, mode: CullingMode) {
+        self.apply_to_objects_mut(&mut |o| o.set_backface_culling(mode))
+    }
+
     /// Mutably accesses the vertices of the objects contained by this node and its children.
     ///
     /// The provided closure is called once per object.
@@ -238,6 +650,24 @@ impl SceneNodeData {
         self.apply_to_objects(&mut |o| o.read_vertices(f))
     }
 
+    /// Adds a custom vertex attribute to the meshes of the objects contained by this node and
+    /// its children.
+    ///
+    /// The same values are given to each mesh.
+    #[inline]
+    pub fn add_vertex_attribute(&mut self, name: &str, components: u32, data: &[f32]) {
+        self.apply_to_objects_mut(&mut |o| o.add_vertex_attribute(name, components, data))
+    }
+
+    /// Replaces the values of a custom vertex attribute of the meshes of the objects contained
+    /// by this node and its children.
+    ///
+    /// The same values are given to each mesh.
+    #[inline]
+    pub fn update_vertex_attribute(&mut self, name: &str, data: &[f32]) {
+        self.apply_to_objects_mut(&mut |o| o.update_vertex_attribute(name, data))
+    }
+
     /// Recomputes the normals of the meshes of the objects contained by this node and its
     /// children.
     #[inline]
@@ -316,6 +746,14 @@ impl SceneNodeData {
         self.apply_to_objects_mut(&mut |o| o.set_color(r, g, b))
     }
 
+    /// Sets the opacity of the objects contained by this node and its children.
+    ///
+    /// The alpha must be on the range `[0.0, 1.0]`.
+    #[inline]
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.apply_to_objects_mut(&mut |o| o.set_alpha(alpha))
+    }
+
     /// Sets the texture of the objects contained by this node and its children.
     ///
     /// The texture is loaded from a file and registered by the global `TextureManager`.
@@ -457,6 +895,129 @@ impl SceneNodeData {
         self.local_transform.inverse()
     }
 
+    /// The world-space axis-aligned bounding box of the objects contained by this node and its
+    /// children.
+    ///
+    /// Returns `None` if neither this node nor its children contain any object. This merges the
+    /// cached boxes of every object, see `Object::world_aabb`, so it is cheap enough to be
+    /// called at each frame but may be larger than the tightest box around the vertices.
+    pub fn aabb(&self) -> Option<AABB<f32>> {
+        let transform = self.world_transformation();
+        let mut res = None;
+
+        self.do_aabb(&transform, &self.world_scale, &mut res);
+
+        res
+    }
+
+    fn do_aabb(
+        &self,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        res: &mut Option<AABB<f32>>,
+    ) {
+        if let Some(aabb) = self
+            .object
+            .as_ref()
+            .and_then(|o| o.world_aabb(transform, scale))
+        {
+            *res = Some(match *res {
+                Some(ref merged) => merged.merged(&aabb),
+                None => aabb,
+            });
+        }
+
+        for c in self.children.iter() {
+            let bc = c.data();
+            bc.do_aabb(
+                &(transform * bc.local_transform),
+                &scale.component_mul(&bc.local_scale),
+                res,
+            )
+        }
+    }
+
+    fn do_collect_trimeshes(
+        &self,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        res: &mut Vec<(String, TriMesh<f32>)>,
+    ) -> io::Result<()> {
+        if let Some(ref o) = self.object {
+            let mut mesh = o.mesh().borrow().to_trimesh().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::Other,
+                    "the mesh data is not available on the CPU",
+                )
+            })?;
+
+            for coord in &mut mesh.coords {
+                *coord = transform * Point3::from(coord.coords.component_mul(scale));
+            }
+
+            if let Some(ref mut normals) = mesh.normals {
+                for normal in normals {
+                    let n = transform.rotation * *normal;
+                    *normal = (transform.rotation * normal.component_div(scale))
+                        .try_normalize(1.0e-12)
+                        .unwrap_or(n);
+                }
+            }
+
+            let name = match self.name {
+                Some(ref name) => name.clone(),
+                None => format!("object{}", res.len()),
+            };
+
+            res.push((name, mesh));
+        }
+
+        for c in self.children.iter() {
+            let bc = c.data();
+            bc.do_collect_trimeshes(
+                &(transform * bc.local_transform),
+                &scale.component_mul(&bc.local_scale),
+                res,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies a closure to each visible object contained by this node and its children, along
+    /// with the world transformation and scale it is rendered with.
+    pub(crate) fn apply_to_visible_objects_with_transform(
+        &self,
+        f: &mut dyn FnMut(&Object, &Isometry3<f32>, &Vector3<f32>),
+    ) {
+        if self.visible {
+            let transform = self.world_transformation();
+            self.do_apply_to_visible_objects_with_transform(&transform, &self.world_scale, f)
+        }
+    }
+
+    fn do_apply_to_visible_objects_with_transform(
+        &self,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        f: &mut dyn FnMut(&Object, &Isometry3<f32>, &Vector3<f32>),
+    ) {
+        if let Some(ref o) = self.object {
+            f(o, transform, scale)
+        }
+
+        for c in self.children.iter() {
+            let bc = c.data();
+            if bc.visible {
+                bc.do_apply_to_visible_objects_with_transform(
+                    &(transform * bc.local_transform),
+                    &scale.component_mul(&bc.local_scale),
+                    f,
+                )
+            }
+        }
+    }
+
     /// Appends a transformation to this node local transformation.
     #[inline]
     pub fn append_transformation(&mut self, t: &Isometry3<f32>) {
@@ -572,8 +1133,8 @@ impl SceneNodeData {
                     let mut dp = (**p).borrow_mut();
 
                     dp.update();
-                    self.world_transform = self.local_transform * dp.world_transform;
-                    self.world_scale = self.local_scale.component_mul(&dp.local_scale);
+                    self.world_transform = dp.world_transform * self.local_transform;
+                    self.world_scale = dp.world_scale.component_mul(&self.local_scale);
                     self.up_to_date = true;
                     return;
                 },
@@ -604,6 +1165,8 @@ impl SceneNode {
             up_to_date: false,
             children: Vec::new(),
             object: object,
+            name: None,
+            spline: None,
             parent: None,
         };
 
@@ -624,6 +1187,165 @@ impl SceneNode {
         self.data_mut().parent = None
     }
 
+    /// Removes this node from its parent, and releases its object and the whole subtree rooted
+    /// by this node.
+    ///
+    /// The GPU buffers of the meshes are freed as soon as no other object uses them. Other
+    /// handles to this node or its descendants remain valid, but designate empty nodes.
+    pub fn remove(&mut self) {
+        self.unlink();
+
+        for mut child in self.take_children() {
+            child.remove()
+        }
+
+        self.data_mut().object = None
+    }
+
+    /// Removes all the children of this node.
+    ///
+    /// The children are not destroyed: they become root nodes which can be added to other
+    /// nodes, and their world transformations become their local transformations.
+    pub fn unlink_children(&mut self) {
+        let _ = self.take_children();
+    }
+
+    fn take_children(&mut self) -> Vec<SceneNode> {
+        let mut children = mem::replace(&mut self.data_mut().children, Vec::new());
+
+        for child in children.iter_mut() {
+            let mut bchild = child.data_mut();
+            bchild.parent = None;
+            bchild.invalidate();
+        }
+
+        children
+    }
+
+    /// Moves this node to the children of `new_parent`, without moving it in world space.
+    ///
+    /// The local transformation and scale of this node are adjusted so that its world
+    /// transformation and scale are left unchanged. This node may have no parent yet. Panics if
+    /// `new_parent` is this node or one of its descendants.
+    pub fn reparent(&mut self, new_parent: &mut SceneNode) {
+        let mut curr: Option<*const RefCell<SceneNodeData>> = Some(&*new_parent.data);
+
+        while let Some(node) = curr {
+            assert!(
+                node != &*self.data as *const RefCell<SceneNodeData>,
+                "A node cannot be reparented to itself or to one of its descendants."
+            );
+            curr = unsafe { (*node).borrow().parent };
+        }
+
+        let (world_transform, world_scale) = {
+            let data = self.data();
+            (data.world_transformation(), data.world_scale)
+        };
+        let (parent_transform, parent_scale) = {
+            let data = new_parent.data();
+            (data.world_transformation(), data.world_scale)
+        };
+
+        self.unlink();
+        {
+            let mut data = self.data_mut();
+            data.set_local_transformation(parent_transform.inverse() * world_transform);
+            data.local_scale = world_scale.component_div(&parent_scale);
+            data.invalidate();
+        }
+        new_parent.add_child(self.clone())
+    }
+
+    /// Creates a new node with the same geometry as this node.
+    ///
+    /// The new node shares the same meshes (and thus the same GPU buffers) as `self` and its
+    /// children, but has its own transformation, color, and visibility. It is added to the
+    /// parent of `self`, if any.
+    pub fn clone_geometry(&self) -> SceneNode {
+        let mut res = self.do_clone_geometry();
+        let parent = self.data().parent;
+
+        if let Some(p) = parent {
+            res.data_mut().set_parent(p);
+            unsafe { (*p).borrow_mut().children.push(res.clone()) };
+        }
+
+        res
+    }
+
+    fn do_clone_geometry(&self) -> SceneNode {
+        let data = self.data();
+        let mut res = SceneNode::new(
+            data.local_scale,
+            data.local_transform,
+            data.object.as_ref().map(|o| o.clone_geometry()),
+        );
+        res.data_mut().visible = data.visible;
+        res.data_mut().name = data.name.clone();
+
+        for child in data.children.iter() {
+            res.add_child(child.do_clone_geometry());
+        }
+
+        res
+    }
+
+    /// The name of this node, if any.
+    #[inline]
+    pub fn name(&self) -> Option<String> {
+        self.data().name().map(|name| name.to_string())
+    }
+
+    /// Sets the name of this node, used by `SceneNode::find_node`.
+    ///
+    /// Names do not need to be unique.
+    #[inline]
+    pub fn set_name(&mut self, name: &str) {
+        self.data_mut().set_name(name)
+    }
+
+    /// Finds a node named `name` among this node and its descendants.
+    ///
+    /// The nodes are searched depth-first, each node being tested before its children, and the
+    /// children in the order they were added. Removing a child may change the order of the
+    /// remaining ones. Returns the first match.
+    pub fn find_node(&self, name: &str) -> Option<SceneNode> {
+        let data = self.data();
+
+        if data.name() == Some(name) {
+            return Some(self.clone());
+        }
+
+        data.children.iter().find_map(|child| child.find_node(name))
+    }
+
+    /// Saves the meshes of this node and its children to the obj file at `path`.
+    ///
+    /// Each object is written as a separate `o` object named after its scene node, or
+    /// `objectN` if the node has no name. The vertices are expressed in the local frame of this
+    /// node, scaled by its local scale. Fails if a mesh has been unloaded from the RAM since
+    /// WebGL 1 cannot read the GPU buffers back.
+    pub fn save_as_obj(&self, path: &Path) -> io::Result<()> {
+        obj::write_file(path, &self.collect_trimeshes()?)
+    }
+
+    /// Saves the meshes of this node and its children to the binary stl file at `path`.
+    ///
+    /// See `SceneNode::save_as_obj` for details. All the triangles are written as a single solid.
+    pub fn save_as_stl(&self, path: &Path) -> io::Result<()> {
+        stl::write_file(path, &self.collect_trimeshes()?)
+    }
+
+    fn collect_trimeshes(&self) -> io::Result<Vec<(String, TriMesh<f32>)>> {
+        let data = self.data();
+        let mut res = Vec::new();
+
+        data.do_collect_trimeshes(&Isometry3::identity(), &data.local_scale, &mut res)?;
+
+        Ok(res)
+    }
+
     /// The data of this scene node.
     pub fn data(&self) -> Ref<SceneNodeData> {
         self.data.borrow()
@@ -648,6 +1370,18 @@ impl SceneNode {
         node
     }
 
+    /// Adds a coordinate frame to this node children.
+    ///
+    /// The frame is made of three arrows `scale` long, red for `x`, green for `y`, and blue for
+    /// `z`, placed by the local transformation `transform`.
+    pub fn add_coordinate_frame(
+        &mut self,
+        transform: Isometry3<f32>,
+        scale: f32,
+    ) -> CoordinateFrameNode {
+        CoordinateFrameNode::new(self, transform, scale)
+    }
+
     /// Adds a node as a child of `parent`.
     ///
     /// # Failures:
@@ -700,6 +1434,92 @@ impl SceneNode {
         res.expect("Unable to load the default sphere geometry.")
     }
 
+    /// Adds a sphere tessellated with `subdivs` subdivisions as a children of this node. The
+    /// sphere is initially centered at (0, 0, 0).
+    ///
+    /// The tessellated unit sphere is generated once, and shared by all the spheres with the
+    /// same number of subdivisions while they exist.
+    ///
+    /// # Arguments
+    /// * `r` - the sphere radius
+    /// * `subdivs` - the number of subdivisions along the sphere meridians and parallels
+    pub fn add_sphere_with_subdivisions(&mut self, r: f32, subdivs: u32) -> SceneNode {
+        self.add_geom_with_name(
+            &MeshManager::sphere_name(subdivs),
+            Vector3::new(r * 2.0, r * 2.0, r * 2.0),
+        )
+        .expect("Unable to generate the sphere geometry.")
+    }
+
+    /// Adds a sphere with several levels of detail to this node children.
+    ///
+    /// The sphere is initially centered at (0, 0, 0). Each level of detail `(distance, segments)`
+    /// is a sphere tessellated with `segments` subdivisions, rendered when the camera is at least
+    /// at `distance` from the sphere center. The closest level becomes the mesh of the object,
+    /// used until the distance of the next level. The tessellated spheres are generated once by
+    /// the `MeshManager`, and shared by every node using them.
+    ///
+    /// # Arguments
+    /// * `r` - the sphere radius
+    /// * `lods` - the levels of detail given as `(distance, segments)`. Must not be empty.
+    pub fn add_sphere_lod(&mut self, r: f32, lods: &[(f32, u32)]) -> SceneNode {
+        self.add_lod_geom(
+            lods,
+            Vector3::new(r * 2.0, r * 2.0, r * 2.0),
+            MeshManager::sphere_name,
+        )
+    }
+
+    /// Adds a cylinder with several levels of detail to this node children.
+    ///
+    /// See `add_sphere_lod` for details about the levels of detail.
+    ///
+    /// # Arguments
+    /// * `h` - the cylinder height
+    /// * `r` - the cylinder base radius
+    /// * `lods` - the levels of detail given as `(distance, segments)`. Must not be empty.
+    pub fn add_cylinder_lod(&mut self, r: f32, h: f32, lods: &[(f32, u32)]) -> SceneNode {
+        self.add_lod_geom(
+            lods,
+            Vector3::new(r * 2.0, h, r * 2.0),
+            MeshManager::cylinder_name,
+        )
+    }
+
+    fn add_lod_geom(
+        &mut self,
+        lods: &[(f32, u32)],
+        scale: Vector3<f32>,
+        geometry_name: fn(u32) -> String,
+    ) -> SceneNode {
+        assert!(
+            !lods.is_empty(),
+            "At least one level of detail is required."
+        );
+
+        let mut lods = lods.to_vec();
+        lods.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        let mut meshes: Vec<_> = lods
+            .iter()
+            .map(|&(dist, segments)| {
+                let name = geometry_name(segments);
+                let mesh = MeshManager::get_global_manager(|mm| mm.get_or_generate(&name))
+                    .expect("Unable to generate the level of detail geometry.");
+                (dist, mesh)
+            })
+            .collect();
+
+        // The closest level is the mesh of the object, the others replace it farther away.
+        let (_, closest) = meshes.remove(0);
+        let mut node = self.add_mesh(closest, scale);
+        if let Some(object) = node.data_mut().object_mut() {
+            object.set_lods(meshes);
+        }
+
+        node
+    }
+
     /// Adds a cone to the scene. The cone is initially centered at (0, 0, 0) and points toward the
     /// positive `y` axis.
     ///
@@ -724,6 +1544,41 @@ impl SceneNode {
         res.expect("Unable to load the default cylinder geometry.")
     }
 
+    /// Adds a cylinder without caps to this node children, e.g., for pipes. The cylinder is
+    /// initially centered at (0, 0, 0) and has its principal axis aligned with the `y` axis.
+    ///
+    /// The cylinder is double-sided so its inside is visible and lit through its open ends.
+    ///
+    /// # Arguments
+    /// * `h` - the cylinder height
+    /// * `r` - the cylinder base radius
+    pub fn add_open_cylinder(&mut self, r: f32, h: f32) -> SceneNode {
+        let mut node = self
+            .add_geom_with_name("open_cylinder", Vector3::new(r * 2.0, h, r * 2.0))
+            .expect("Unable to load the default open cylinder geometry.");
+        node.set_double_sided(true);
+
+        node
+    }
+
+    /// Adds a double-sided disk to this node children. The disk is initially centered at (0, 0,
+    /// 0) in the `xz` plane.
+    ///
+    /// # Arguments
+    /// * `r` - the disk radius
+    /// * `inner_r` - the radius of the hole at the center of the disk, `0.0` for a full disk
+    pub fn add_disk(&mut self, r: f32, inner_r: f32) -> SceneNode {
+        let mut node = self
+            .add_geom_with_name(
+                &MeshManager::disk_name(r, inner_r, 50),
+                Vector3::from_element(1.0),
+            )
+            .expect("Unable to generate the disk geometry.");
+        node.set_double_sided(true);
+
+        node
+    }
+
     /// Adds a capsule to this node children. The capsule is initially centered at (0, 0, 0) and
     /// has its principal axis aligned with the `y` axis.
     ///
@@ -731,10 +1586,11 @@ impl SceneNode {
     /// * `h` - the capsule height
     /// * `r` - the capsule caps radius
     pub fn add_capsule(&mut self, r: f32, h: f32) -> SceneNode {
-        self.add_trimesh(
-            procedural::capsule(&(r * 2.0), &h, 50, 50),
+        self.add_geom_with_name(
+            &MeshManager::capsule_name(r, h, 50),
             Vector3::from_element(1.0),
         )
+        .expect("Unable to generate the capsule geometry.")
     }
 
     /// Adds a double-sided quad to this node children. The quad is initially centered at (0, 0,
@@ -775,12 +1631,16 @@ impl SceneNode {
     }
 
     /// Creates and adds a new object using the geometry registered as `geometry_name`.
+    ///
+    /// The geometry is generated and registered first if it is one of the generated meshes, see
+    /// `MeshManager::get_or_generate`.
     pub fn add_geom_with_name(
         &mut self,
         geometry_name: &str,
         scale: Vector3<f32>,
     ) -> Option<SceneNode> {
-        MeshManager::get_global_manager(|mm| mm.get(geometry_name)).map(|g| self.add_mesh(g, scale))
+        MeshManager::get_global_manager(|mm| mm.get_or_generate(geometry_name))
+            .map(|g| self.add_mesh(g, scale))
     }
 
     /// Creates and adds a new object to this node children using a mesh.
@@ -800,10 +1660,157 @@ impl SceneNode {
         )
     }
 
+    /// Adds the convex hull of a set of points to this node children.
+    ///
+    /// The hull is flat-shaded, and its vertices are expressed in the local frame of the new
+    /// node. Returns an error if `points` does not contain at least 4 non-coplanar points.
+    pub fn add_convex_hull(&mut self, points: &[Point3<f32>]) -> Result<SceneNode, &'static str> {
+        let hull = crate::procedural::convex_hull(points)?;
+
+        Ok(self.add_trimesh(hull, Vector3::from_element(1.0)))
+    }
+
+    /// Adds a sequence of cubic Bézier curves, drawn as a polyline, to this node children.
+    ///
+    /// See `SplineKind::Bezier` for the layout of the control points. Each curve is tessellated
+    /// into `segments` segments. The control points are expressed in the local frame of the new
+    /// node, and can be modified with `SceneNode::update_control_points`.
+    pub fn add_bezier_curve(&mut self, control_points: &[Point3<f32>], segments: u32) -> SceneNode {
+        self.add_spline(Spline::bezier(segments), control_points)
+    }
+
+    /// Adds a Catmull-Rom spline passing through `points`, drawn as a polyline, to this node
+    /// children.
+    ///
+    /// The curve between two consecutive points is tessellated into `segments` segments. See
+    /// `SplineKind::CatmullRom` for the effect of the `tension`. The points are expressed in the
+    /// local frame of the new node, and can be modified with `SceneNode::update_control_points`.
+    pub fn add_catmull_rom(
+        &mut self,
+        points: &[Point3<f32>],
+        segments: u32,
+        tension: f32,
+    ) -> SceneNode {
+        self.add_spline(Spline::catmull_rom(segments, tension), points)
+    }
+
+    /// Adds a spline, drawn as a polyline, to this node children.
+    ///
+    /// The polyline is drawn with the lines of the new object, with a width of 1.0: use
+    /// `SceneNode::set_lines_width` and `SceneNode::set_lines_color` to change its appearance.
+    pub fn add_spline(&mut self, spline: Spline, control_points: &[Point3<f32>]) -> SceneNode {
+        let mesh = polyline_mesh(&spline.tessellate(control_points));
+        let mut node = self.add_mesh(mesh, Vector3::from_element(1.0));
+
+        node.set_surface_rendering_activation(false);
+        node.set_lines_width(1.0);
+        node.data_mut().spline = Some(SplineData {
+            spline,
+            control_points: control_points.to_vec(),
+            control_points_radius: None,
+            control_point_nodes: Vec::new(),
+        });
+
+        node
+    }
+
+    /// Replaces the control points of the spline drawn by this node.
+    ///
+    /// The polyline is re-tessellated, and uploaded in place to the existing GPU buffer if its
+    /// number of vertices did not change. Does nothing if this node was not created by
+    /// `SceneNode::add_bezier_curve`, `SceneNode::add_catmull_rom`, or `SceneNode::add_spline`.
+    pub fn update_control_points(&mut self, control_points: &[Point3<f32>]) {
+        let (vertices, radius, nodes_match) = {
+            let mut data = self.data_mut();
+            let spline = match data.spline.as_mut() {
+                Some(spline) => spline,
+                None => return,
+            };
+
+            let nodes_match = spline.control_point_nodes.len() == control_points.len();
+            spline.control_points = control_points.to_vec();
+
+            (
+                spline.spline.tessellate(control_points),
+                spline.control_points_radius,
+                nodes_match,
+            )
+        };
+
+        if let Some(object) = self.data_mut().object_mut() {
+            let mut num_vertices = 0;
+            object.read_vertices(&mut |coords| num_vertices = coords.len());
+
+            if num_vertices == vertices.len() {
+                object.modify_vertices(&mut |coords| coords.copy_from_slice(&vertices));
+            } else {
+                object.set_mesh(polyline_mesh(&vertices));
+            }
+        }
+
+        if nodes_match {
+            let nodes = self
+                .data()
+                .spline
+                .as_ref()
+                .unwrap()
+                .control_point_nodes
+                .clone();
+
+            for (node, pt) in nodes.into_iter().zip(control_points.iter()) {
+                let mut node = node;
+                node.set_local_translation(Translation3::from(pt.coords));
+            }
+        } else {
+            self.set_control_points_radius(radius);
+        }
+    }
+
+    /// Shows the control points of the spline drawn by this node as spheres of the given
+    /// radius, or hides them if `radius` is `None`.
+    ///
+    /// The spheres are children of this node. Does nothing if this node does not draw a spline,
+    /// see `SceneNode::update_control_points`.
+    pub fn set_control_points_radius(&mut self, radius: Option<f32>) {
+        let (control_points, old_nodes) = {
+            let mut data = self.data_mut();
+            let spline = match data.spline.as_mut() {
+                Some(spline) => spline,
+                None => return,
+            };
+
+            spline.control_points_radius = radius;
+            (
+                spline.control_points.clone(),
+                mem::replace(&mut spline.control_point_nodes, Vec::new()),
+            )
+        };
+
+        for mut node in old_nodes {
+            node.remove();
+        }
+
+        if let Some(radius) = radius {
+            let nodes = control_points
+                .iter()
+                .map(|pt| {
+                    let mut node = self.add_sphere(radius);
+                    node.set_local_translation(Translation3::from(pt.coords));
+                    node
+                })
+                .collect();
+
+            self.data_mut().spline.as_mut().unwrap().control_point_nodes = nodes;
+        }
+    }
+
     /// Creates and adds multiple nodes created from an obj file.
     ///
     /// This will create a new node serving as a root of the scene described by the obj file. This
     /// newly created node is added to this node's children.
+    ///
+    /// Each object records the path and group of its mesh, so that `SceneNode::save_scene` can
+    /// reference it.
     pub fn add_obj(&mut self, path: &Path, mtl_dir: &Path, scale: Vector3<f32>) -> SceneNode {
         let tex = TextureManager::get_global_manager(|tm| tm.get_default());
         let mat = MaterialManager::get_global_manager(|mm| mm.get_default());
@@ -824,8 +1831,9 @@ impl SceneNode {
                 child_scale = Vector3::from_element(1.0);
             }
 
-            for (_, mesh, mtl) in objs.into_iter() {
+            for (group, mesh, mtl) in objs.into_iter() {
                 let mut object = Object::new(mesh, 1.0, 1.0, 1.0, tex.clone(), mat.clone());
+                object.set_obj_source(path, &group);
 
                 match mtl {
                     None => {}
@@ -885,6 +1893,17 @@ impl SceneNode {
         }
     }
 
+    /// Handles to this node and all its descendants, in depth-first order.
+    ///
+    /// Unlike `apply_to_scene_nodes_mut`, the nodes can be modified with the handles after the
+    /// traversal, including adding or removing nodes. Use `SceneNodeData::has_object` to only
+    /// keep the nodes containing an object, e.g., to recolor every object of the scene.
+    pub fn descendants(&self) -> impl Iterator<Item = SceneNode> {
+        let mut nodes = Vec::new();
+        self.apply_to_scene_nodes(&mut |node| nodes.push(node.clone()));
+        nodes.into_iter()
+    }
+
     //
     //
     // fwd
@@ -920,6 +1939,155 @@ impl SceneNode {
         self.data_mut().set_lines_color(color)
     }
 
+    /// Sets whether both faces of the objects contained by this node and its children are
+    /// rendered and lit.
+    ///
+    /// See `Object::set_double_sided` for details.
+    #[inline]
+    pub fn set_double_sided(&mut self, double_sided: bool) {
+        self.data_mut().set_double_sided(double_sided)
+    }
+
+    /// Sets the depth offset applied when rendering the objects contained by this node and its
+    /// children.
+    ///
+    /// See `Object::set_depth_bias` for details.
+    #[inline]
+    pub fn set_depth_bias(&mut self, factor: f32, units: f32) {
+        self.data_mut().set_depth_bias(factor, units)
+    }
+
+    /// Sets the transformation applied to the texture coordinates of the objects contained by
+    /// this node and its children.
+    ///
+    /// See `Object::set_texture_uv_offset` for details.
+    #[inline]
+    pub fn set_texture_uv_offset(&mut self, offset: Point2<f32>, scale: Point2<f32>) {
+        self.data_mut().set_texture_uv_offset(offset, scale)
+    }
+
+    /// Sets the cube-map reflected by the objects contained by this node and its children.
+    ///
+    /// See `Object::set_environment_map` for details.
+    #[inline]
+    pub fn set_environment_map(&mut self, environment_map: Option<Rc<CubeMap>>) {
+        self.data_mut().set_environment_map(environment_map)
+    }
+
+    /// Sets how much of their environment map is reflected by the objects contained by this
+    /// node and its children.
+    ///
+    /// See `Object::set_reflectivity` for details.
+    #[inline]
+    pub fn set_reflectivity(&mut self, reflectivity: f32) {
+        self.data_mut().set_reflectivity(reflectivity)
+    }
+
+    /// Sets whether the objects contained by this node and its children reflect the other
+    /// visible objects.
+    ///
+    /// See `Object::set_ssr_reflector` for details.
+    #[inline]
+    pub fn set_ssr_reflector(&mut self, reflector: bool) {
+        self.data_mut().set_ssr_reflector(reflector)
+    }
+
+    /// Shades the objects contained by this node and its children with a physically-based
+    /// metallic-roughness material.
+    ///
+    /// See `Object::set_metallic_roughness` for details.
+    #[inline]
+    pub fn set_metallic_roughness(&mut self, metallic: f32, roughness: f32) {
+        self.data_mut().set_metallic_roughness(metallic, roughness)
+    }
+
+    /// Shades the objects contained by this node and its children with the Blinn-Phong model,
+    /// which is the default.
+    #[inline]
+    pub fn unset_metallic_roughness(&mut self) {
+        self.data_mut().unset_metallic_roughness()
+    }
+
+    /// Sets the render layer of the objects contained by this node and its children.
+    ///
+    /// See `Object::set_render_layer` for details.
+    #[inline]
+    pub fn set_render_layer(&mut self, layer: u32) {
+        self.data_mut().set_render_layer(layer)
+    }
+
+    /// Sets whether the objects contained by this node and its children are drawn on top of
+    /// every other object, regardless of depth, e.g., for gizmos or bounding boxes.
+    ///
+    /// This moves the objects to the `RENDER_LAYER_ALWAYS_ON_TOP` render layer, drawn after
+    /// the other objects without depth test, or back to the `RENDER_LAYER_OPAQUE` layer. See
+    /// `Object::set_render_layer` for details.
+    #[inline]
+    pub fn set_always_on_top(&mut self, always_on_top: bool) {
+        self.data_mut().set_always_on_top(always_on_top)
+    }
+
+    /// Sets the blend mode of the objects contained by this node and its children.
+    ///
+    /// See `Object::set_blend_mode` for details.
+    #[inline]
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.data_mut().set_blend_mode(mode)
+    }
+
+    /// Sets how the objects contained by this node and its children are rotated to face the
+    /// camera.
+    ///
+    /// See `Object::set_billboard` for details.
+    #[inline]
+    pub fn set_billboard(&mut self, mode: BillboardMode) {
+        self.data_mut().set_billboard(mode)
+    }
+
+    /// Sets the instances drawn for each object contained by this node and its children.
+    ///
+    /// This is the fast way to draw many copies of the same geometry, e.g., the cubes of a voxel
+    /// world. See `Object::set_instances` for details.
+    #[inline]
+    pub fn set_instances(&mut self, instances: &[InstanceData]) {
+        self.data_mut().set_instances(instances)
+    }
+
+    /// Draws the objects contained by this node and its children only once each.
+    #[inline]
+    pub fn clear_instances(&mut self) {
+        self.data_mut().clear_instances()
+    }
+
+    /// Draws the triangle edges of the objects contained by this node and its children on top
+    /// of their surfaces, with the given color.
+    ///
+    /// Set to `None` to stop drawing the edges.
+    #[inline]
+    pub fn set_wireframe_overlay(&mut self, color: Option<Point3<f32>>) {
+        self.data_mut().set_wireframe_overlay(color)
+    }
+
+    /// Activate or deactivate flat shading for the objects contained by this node and its
+    /// children.
+    ///
+    /// Each triangle is then rendered with the normal of its face, giving a faceted appearance.
+    #[inline]
+    pub fn set_flat_shading(&mut self, active: bool) {
+        self.data_mut().set_flat_shading(active)
+    }
+
+    /// Overrides the distances at which each level of detail of the objects contained by this
+    /// node and its children are used.
+    ///
+    /// The `i`-th distance applies to the `i`-th level of detail, sorted by increasing distance,
+    /// excluding the closest one which is always used below the distance of the next level. See
+    /// `Object::set_lod_distances`.
+    #[inline]
+    pub fn set_lod_distances(&mut self, distances: &[f32]) {
+        self.data_mut().set_lod_distances(distances)
+    }
+
     /// Sets the size of the points drawn for the objects contained by this node and its children.
     #[inline]
     pub fn set_points_size(&mut self, size: f32) {
@@ -940,6 +2108,15 @@ impl SceneNode {
         self.data_mut().enable_backface_culling(active)
     }
 
+    /// Sets the faces discarded before rasterization for the objects contained by this node and
+    /// its children.
+    ///
+    /// Defaults to `CullingMode::Back`.
+    #[inline]
+    pub fn set_backface_culling(&mut self, mode: CullingMode) {
+        self.data_mut().set_backface_culling(mode)
+    }
+
     /// Mutably accesses the vertices of the objects contained by this node and its children.
     ///
     /// The provided closure is called once per object.
@@ -956,6 +2133,25 @@ impl SceneNode {
         self.data().read_vertices(f)
     }
 
+    /// Adds a custom vertex attribute to the meshes of the objects contained by this node and
+    /// its children.
+    ///
+    /// See `Mesh::add_vertex_attribute` for details.
+    #[inline]
+    pub fn add_vertex_attribute(&mut self, name: &str, components: u32, data: &[f32]) {
+        self.data_mut().add_vertex_attribute(name, components, data)
+    }
+
+    /// Replaces the values of a custom vertex attribute of the meshes of the objects contained
+    /// by this node and its children.
+    ///
+    /// This is cheap enough to be called at each frame for streaming data. See
+    /// `Mesh::update_vertex_attribute` for details.
+    #[inline]
+    pub fn update_vertex_attribute(&mut self, name: &str, data: &[f32]) {
+        self.data_mut().update_vertex_attribute(name, data)
+    }
+
     /// Recomputes the normals of the meshes of the objects contained by this node and its
     /// children.
     #[inline]
@@ -1034,6 +2230,46 @@ impl SceneNode {
         self.data_mut().set_color(r, g, b)
     }
 
+    /// Smoothly changes the color of the objects contained by this node and its children from
+    /// `from` to `to` during `duration` seconds.
+    ///
+    /// The window advances the animation at each frame and calls `set_color` with the color
+    /// interpolated between `from` and `to` by `easing`, e.g., one of the functions of
+    /// `kiss3d::easing`. This replaces the color animation of this node, if any. The animation
+    /// is removed once it reaches `to`.
+    pub fn animate_color(
+        &mut self,
+        from: Point3<f32>,
+        to: Point3<f32>,
+        duration: f32,
+        easing: EasingFn,
+    ) {
+        let node = Rc::downgrade(&self.data);
+
+        KEY_COLOR_ANIMATIONS.with(|animations| {
+            let mut animations = animations.borrow_mut();
+            animations.retain(|animation| !animation.node.ptr_eq(&node));
+            animations.push(ColorAnimation {
+                node,
+                from,
+                to,
+                duration,
+                elapsed: 0.0,
+                easing,
+            })
+        });
+        self.set_color(from.x, from.y, from.z)
+    }
+
+    /// Sets the opacity of the objects contained by this node and its children.
+    ///
+    /// The alpha must be on the range `[0.0, 1.0]`. Transparent objects are rendered after all
+    /// the opaque objects, from the farthest to the closest to the camera.
+    #[inline]
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.data_mut().set_alpha(alpha)
+    }
+
     /// Sets the texture of the objects contained by this node and its children.
     ///
     /// The texture is loaded from a file and registered by the global `TextureManager`.
@@ -1142,4 +2378,13 @@ impl SceneNode {
     pub fn set_local_rotation(&mut self, r: UnitQuaternion<f32>) {
         self.data_mut().set_local_rotation(r)
     }
+
+    /// The world-space axis-aligned bounding box of the objects contained by this node and its
+    /// children.
+    ///
+    /// Returns `None` if neither this node nor its children contain any object.
+    #[inline]
+    pub fn aabb(&self) -> Option<AABB<f32>> {
+        self.data().aabb()
+    }
 }
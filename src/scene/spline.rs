@@ -0,0 +1,234 @@
+//! Tessellation of spline curves.
+
+use na::Point3;
+
+use crate::scene::SceneNode;
+
+/// The kind of curve interpolating or approximating the control points of a `Spline`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SplineKind {
+    /// A sequence of cubic Bézier curves.
+    ///
+    /// Each curve uses four control points, the last one being shared with the next curve: the
+    /// curves are defined by the points `0..=3`, `3..=6`, etc. The trailing control points not
+    /// forming a complete curve are ignored.
+    Bezier,
+    /// A cardinal spline passing through every control point.
+    ///
+    /// The tension, between `0.0` and `1.0`, shortens the tangents at the control points: `0.0`
+    /// gives a Catmull-Rom spline and `1.0` straight segments.
+    CatmullRom {
+        /// The tension of the spline.
+        tension: f32,
+    },
+}
+
+/// A curve tessellated into a polyline.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Spline {
+    /// The kind of this curve.
+    pub kind: SplineKind,
+    /// The number of segments of the polyline approximating each piece of the curve.
+    pub segments: u32,
+}
+
+impl Spline {
+    /// A sequence of cubic Bézier curves, each tessellated into `segments` segments.
+    pub fn bezier(segments: u32) -> Spline {
+        Spline {
+            kind: SplineKind::Bezier,
+            segments,
+        }
+    }
+
+    /// A cardinal spline, with `segments` segments between two consecutive control points.
+    pub fn catmull_rom(segments: u32, tension: f32) -> Spline {
+        Spline {
+            kind: SplineKind::CatmullRom { tension },
+            segments,
+        }
+    }
+
+    /// The number of vertices of the polyline approximating the curve defined by
+    /// `num_control_points` control points.
+    pub fn num_vertices(&self, num_control_points: usize) -> usize {
+        let segments = self.segments.max(1) as usize;
+
+        match self.kind {
+            SplineKind::Bezier if num_control_points >= 4 => {
+                (num_control_points - 1) / 3 * segments + 1
+            }
+            SplineKind::CatmullRom { .. } if num_control_points >= 2 => {
+                (num_control_points - 1) * segments + 1
+            }
+            _ => num_control_points,
+        }
+    }
+
+    /// The vertices of the polyline approximating the curve defined by `control_points`.
+    ///
+    /// The control points are returned unchanged if there are not enough of them to define a
+    /// curve, i.e., less than 4 for a Bézier spline, or less than 2 for a Catmull-Rom spline.
+    pub fn tessellate(&self, control_points: &[Point3<f32>]) -> Vec<Point3<f32>> {
+        let segments = self.segments.max(1);
+        let mut result = Vec::with_capacity(self.num_vertices(control_points.len()));
+
+        match self.kind {
+            SplineKind::Bezier if control_points.len() >= 4 => {
+                for curve in control_points.windows(4).step_by(3) {
+                    for i in 0..segments {
+                        let t = i as f32 / segments as f32;
+                        result.push(bezier_point(curve, t));
+                    }
+                }
+
+                let last = (control_points.len() - 1) / 3 * 3;
+                result.push(control_points[last]);
+            }
+            SplineKind::CatmullRom { tension } if control_points.len() >= 2 => {
+                let n = control_points.len();
+                let scale = (1.0 - tension.clamp(0.0, 1.0)) * 0.5;
+
+                for i in 0..n - 1 {
+                    let p0 = control_points[i.saturating_sub(1)];
+                    let p1 = control_points[i];
+                    let p2 = control_points[i + 1];
+                    let p3 = control_points[(i + 2).min(n - 1)];
+                    let m1 = (p2 - p0) * scale;
+                    let m2 = (p3 - p1) * scale;
+
+                    for j in 0..segments {
+                        let t = j as f32 / segments as f32;
+                        let t2 = t * t;
+                        let t3 = t2 * t;
+
+                        // The cubic Hermite basis.
+                        let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+                        let h10 = t3 - 2.0 * t2 + t;
+                        let h01 = -2.0 * t3 + 3.0 * t2;
+                        let h11 = t3 - t2;
+
+                        result.push(Point3::from(
+                            p1.coords * h00 + m1 * h10 + p2.coords * h01 + m2 * h11,
+                        ));
+                    }
+                }
+
+                result.push(control_points[n - 1]);
+            }
+            _ => result.extend_from_slice(control_points),
+        }
+
+        result
+    }
+}
+
+fn bezier_point(curve: &[Point3<f32>], t: f32) -> Point3<f32> {
+    let u = 1.0 - t;
+
+    Point3::from(
+        curve[0].coords * (u * u * u)
+            + curve[1].coords * (3.0 * u * u * t)
+            + curve[2].coords * (3.0 * u * t * t)
+            + curve[3].coords * (t * t * t),
+    )
+}
+
+/// The spline drawn by a scene node created by `SceneNode::add_bezier_curve` or
+/// `SceneNode::add_catmull_rom`.
+pub(crate) struct SplineData {
+    pub spline: Spline,
+    pub control_points: Vec<Point3<f32>>,
+    pub control_points_radius: Option<f32>,
+    pub control_point_nodes: Vec<SceneNode>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::Spline;
+    use na::Point3;
+
+    fn assert_relative_eq(a: &Point3<f32>, b: &Point3<f32>) {
+        assert!((a - b).norm() < 1.0e-5, "{} != {}", a, b);
+    }
+
+    fn control_points(n: usize) -> Vec<Point3<f32>> {
+        (0..n)
+            .map(|i| Point3::new(i as f32, (i * i) as f32, -(i as f32)))
+            .collect()
+    }
+
+    #[test]
+    fn bezier_interpolates_the_curve_ends() {
+        let spline = Spline::bezier(8);
+        let pts = control_points(8);
+        let vertices = spline.tessellate(&pts);
+
+        // The 8th control point does not complete a third curve.
+        assert_eq!(vertices.len(), 2 * 8 + 1);
+        assert_eq!(vertices.len(), spline.num_vertices(pts.len()));
+        assert_relative_eq(&vertices[0], &pts[0]);
+        assert_relative_eq(&vertices[8], &pts[3]);
+        assert_relative_eq(&vertices[16], &pts[6]);
+    }
+
+    #[test]
+    fn bezier_midpoint() {
+        let pts = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+        ];
+        let vertices = Spline::bezier(2).tessellate(&pts);
+
+        assert_eq!(vertices.len(), 3);
+        assert_relative_eq(&vertices[1], &Point3::new(0.5, 0.75, 0.0));
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_the_control_points() {
+        let pts = control_points(5);
+
+        for &tension in [0.0, 0.5, 1.0].iter() {
+            let spline = Spline::catmull_rom(4, tension);
+            let vertices = spline.tessellate(&pts);
+
+            assert_eq!(vertices.len(), 4 * 4 + 1);
+            assert_eq!(vertices.len(), spline.num_vertices(pts.len()));
+
+            for (i, pt) in pts.iter().enumerate() {
+                assert_relative_eq(&vertices[i * 4], pt);
+            }
+        }
+    }
+
+    #[test]
+    fn catmull_rom_with_full_tension_is_a_polyline() {
+        let pts = control_points(3);
+        let vertices = Spline::catmull_rom(2, 1.0).tessellate(&pts);
+
+        assert_relative_eq(&vertices[1], &na::center(&pts[0], &pts[1]));
+        assert_relative_eq(&vertices[3], &na::center(&pts[1], &pts[2]));
+    }
+
+    #[test]
+    fn too_few_control_points_are_returned_unchanged() {
+        let pts = control_points(3);
+        assert_eq!(Spline::bezier(8).tessellate(&pts), pts);
+        assert_eq!(Spline::bezier(8).num_vertices(3), 3);
+
+        let pts = control_points(1);
+        assert_eq!(Spline::catmull_rom(8, 0.0).tessellate(&pts), pts);
+        assert_eq!(Spline::catmull_rom(8, 0.0).num_vertices(1), 1);
+        assert!(Spline::catmull_rom(8, 0.0).tessellate(&[]).is_empty());
+    }
+
+    #[test]
+    fn zero_segments_are_treated_as_one() {
+        let pts = control_points(4);
+
+        assert_eq!(Spline::bezier(0).tessellate(&pts), vec![pts[0], pts[3]]);
+        assert_eq!(Spline::catmull_rom(0, 0.0).tessellate(&pts), pts);
+    }
+}
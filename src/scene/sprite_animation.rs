@@ -0,0 +1,179 @@
+//! Animation of the frames of a sprite sheet.
+
+use std::rc::Rc;
+
+use na::Point2;
+
+use crate::resource::Texture;
+use crate::scene::SceneNode;
+
+/// The way a `SpriteAnimation` continues after its last frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SpriteAnimationMode {
+    /// Restarts from the first frame.
+    Loop,
+    /// Stops on the last frame.
+    OneShot,
+    /// Plays the frames backward down to the first one, then forward again, etc.
+    PingPong,
+}
+
+/// An animation cycling through the frames of a sprite sheet.
+///
+/// The frames are laid out on a regular grid covering the atlas texture, and are numbered from
+/// left to right and from the top row to the bottom row of the image.
+pub struct SpriteAnimation {
+    atlas: Rc<Texture>,
+    frame_width: f32,
+    frame_height: f32,
+    fps: f32,
+    mode: SpriteAnimationMode,
+    num_frames: usize,
+    time: f32,
+}
+
+impl SpriteAnimation {
+    /// Creates a looping animation of the frames of `atlas`, played at `fps` frames per second.
+    ///
+    /// The size of each frame is given in texture coordinates, i.e., as a fraction of the atlas
+    /// size: an atlas with 4 columns and 2 rows of frames has `frame_width = 0.25` and
+    /// `frame_height = 0.5`. Every cell of the grid is a frame until `set_num_frames` is called.
+    pub fn new(atlas: Rc<Texture>, frame_width: f32, frame_height: f32, fps: f32) -> Self {
+        assert!(
+            frame_width > 0.0 && frame_height > 0.0,
+            "The frame size must be positive."
+        );
+
+        SpriteAnimation {
+            atlas,
+            frame_width,
+            frame_height,
+            fps,
+            mode: SpriteAnimationMode::Loop,
+            num_frames: grid_len(frame_width) * grid_len(frame_height),
+            time: 0.0,
+        }
+    }
+
+    /// The texture containing all the frames.
+    #[inline]
+    pub fn atlas(&self) -> &Rc<Texture> {
+        &self.atlas
+    }
+
+    /// Sets the way this animation continues after its last frame.
+    #[inline]
+    pub fn set_mode(&mut self, mode: SpriteAnimationMode) {
+        self.mode = mode
+    }
+
+    /// The way this animation continues after its last frame.
+    #[inline]
+    pub fn mode(&self) -> SpriteAnimationMode {
+        self.mode
+    }
+
+    /// Sets the number of frames played per second.
+    #[inline]
+    pub fn set_fps(&mut self, fps: f32) {
+        self.fps = fps
+    }
+
+    /// The number of frames played per second.
+    #[inline]
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+
+    /// Sets the number of frames of this animation, e.g., if the last row of the atlas is not
+    /// full.
+    #[inline]
+    pub fn set_num_frames(&mut self, num_frames: usize) {
+        assert!(num_frames != 0, "An animation needs at least one frame.");
+        self.num_frames = num_frames
+    }
+
+    /// The number of frames of this animation.
+    #[inline]
+    pub fn num_frames(&self) -> usize {
+        self.num_frames
+    }
+
+    /// Restarts this animation from its first frame.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.time = 0.0
+    }
+
+    /// Whether a one-shot animation finished displaying its last frame.
+    ///
+    /// Always `false` for the other modes.
+    pub fn is_finished(&self) -> bool {
+        self.mode == SpriteAnimationMode::OneShot && self.num_played_frames() >= self.num_frames
+    }
+
+    /// The index of the frame currently displayed.
+    pub fn frame(&self) -> usize {
+        let played = self.num_played_frames();
+        let n = self.num_frames;
+
+        match self.mode {
+            SpriteAnimationMode::Loop => played % n,
+            SpriteAnimationMode::OneShot => played.min(n - 1),
+            SpriteAnimationMode::PingPong if n > 1 => {
+                let period = 2 * n - 2;
+                let i = played % period;
+
+                if i < n {
+                    i
+                } else {
+                    period - i
+                }
+            }
+            SpriteAnimationMode::PingPong => 0,
+        }
+    }
+
+    /// The `(offset, scale)` texture coordinates transformation selecting the current frame.
+    ///
+    /// See `Object::set_texture_uv_offset` for details.
+    pub fn frame_uv(&self) -> (Point2<f32>, Point2<f32>) {
+        let columns = grid_len(self.frame_width);
+        let frame = self.frame();
+        let offset = Point2::new(
+            (frame % columns) as f32 * self.frame_width,
+            (frame / columns) as f32 * self.frame_height,
+        );
+
+        (offset, Point2::new(self.frame_width, self.frame_height))
+    }
+
+    /// Sets the atlas as the texture of `node`, and displays the current frame on it.
+    pub fn attach(&self, node: &mut SceneNode) {
+        node.set_texture(self.atlas.clone());
+        self.apply(node)
+    }
+
+    /// Advances this animation by `dt` seconds, and displays the new current frame on `node`.
+    ///
+    /// This is typically called once per frame for each animated node.
+    pub fn update(&mut self, dt: f32, node: &mut SceneNode) {
+        self.time += dt;
+        self.apply(node)
+    }
+
+    fn apply(&self, node: &mut SceneNode) {
+        let (offset, scale) = self.frame_uv();
+        node.set_texture_uv_offset(offset, scale)
+    }
+
+    fn num_played_frames(&self) -> usize {
+        (self.time * self.fps).max(0.0) as usize
+    }
+}
+
+// The number of frames of size `frame_size` fitting along one side of the atlas.
+fn grid_len(frame_size: f32) -> usize {
+    // The tolerance accounts for sizes like `1.0 / 3.0` not being exactly representable.
+    ((1.0 / frame_size + 1.0e-4) as usize).max(1)
+}
@@ -17,16 +17,21 @@ pub struct Font {
 
 impl Font {
     /// Loads a new ttf font from a file.
+    ///
+    /// Returns `None` if the file could not be read or is not a valid font.
     pub fn new(path: &Path) -> Option<Rc<Font>> {
         let mut memory = Vec::new();
-        let mut file = File::open(path).unwrap();
-        let _ = file.read_to_end(&mut memory).unwrap();
+        let mut file = File::open(path).ok()?;
+        let _ = file.read_to_end(&mut memory).ok()?;
         Font::from_bytes(&memory)
     }
 
     /// Loads a new ttf font from the memory.
+    ///
+    /// This is the way to load fonts on platforms without filesystem, e.g., using the
+    /// `include_bytes!` macro. Returns `None` if `memory` does not contain a valid font.
     pub fn from_bytes(memory: &[u8]) -> Option<Rc<Font>> {
-        let font = rusttype::Font::from_bytes(memory.to_vec()).unwrap();
+        let font = rusttype::Font::from_bytes(memory.to_vec()).ok()?;
         Some(Rc::new(Font { font }))
     }
 
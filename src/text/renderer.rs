@@ -261,7 +261,7 @@ impl TextRenderer {
         }
 
         self.pos.disable();
-        self.uvs.enable();
+        self.uvs.disable();
 
         verify!(ctxt.enable(Context::DEPTH_TEST));
         verify!(ctxt.disable(Context::BLEND));
@@ -1,6 +1,7 @@
 use std::sync::mpsc::Sender;
+use std::time::Duration;
 
-use crate::event::{Action, Key, MouseButton, WindowEvent};
+use crate::event::{Action, Key, Modifiers, MouseButton, WindowEvent};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::window::GLCanvas as CanvasImpl;
 #[cfg(target_arch = "wasm32")]
@@ -40,6 +41,68 @@ impl NumSamples {
     }
 }
 
+/// The appearance of the cursor when it is over the window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CursorStyle {
+    /// The platform-dependent default cursor, usually an arrow.
+    Default,
+    /// A crosshair, e.g., for aiming or picking.
+    Crosshair,
+    /// A pointing hand, usually indicating a link.
+    Hand,
+    /// Indicates something is to be moved.
+    Move,
+    /// Indicates text that may be selected.
+    Text,
+    /// Indicates the program is busy.
+    Wait,
+    /// Indicates help is available.
+    Help,
+    /// Indicates the requested action will not be carried out.
+    NotAllowed,
+    /// Indicates something can be grabbed, e.g., for dragging a camera.
+    Grab,
+    /// Indicates something is being grabbed.
+    Grabbing,
+    /// Indicates something can be resized horizontally.
+    EwResize,
+    /// Indicates something can be resized vertically.
+    NsResize,
+}
+
+/// The state of the buttons and axes of a gamepad.
+///
+/// The buttons and axes are indexed like `GamepadButton::from_index` and
+/// `GamepadAxis::from_index`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GamepadState {
+    /// The positions of the axes, between `-1.0` and `1.0`, after applying the deadzone.
+    pub axes: Vec<f32>,
+    /// Whether each button is pressed.
+    pub buttons: Vec<bool>,
+}
+
+/// A file dropped onto the canvas, see `WindowEvent::FileDropped`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DroppedFile {
+    /// The name of the file, without its path.
+    pub name: String,
+    /// The content of the file.
+    pub bytes: Vec<u8>,
+}
+
+/// The position of a gamepad axis, replaced by `0.0` if its magnitude is below `deadzone`.
+///
+/// The sticks at rest rarely report exactly zero.
+#[cfg(any(target_arch = "wasm32", test))]
+pub(crate) fn apply_deadzone(axis: f32, deadzone: f32) -> f32 {
+    if axis.abs() < deadzone {
+        0.0
+    } else {
+        axis
+    }
+}
+
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
 /// Canvas options.
 pub struct CanvasSetup {
@@ -49,6 +112,9 @@ pub struct CanvasSetup {
     pub samples: NumSamples,
 }
 
+/// A function deciding whether an event is kept, see `Window::set_event_filter`.
+pub(crate) type EventFilter = Box<dyn FnMut(&WindowEvent) -> bool>;
+
 /// An abstract structure representing a window for native applications, and a canvas for web applications.
 pub struct Canvas {
     canvas: CanvasImpl,
@@ -69,6 +135,22 @@ impl Canvas {
         }
     }
 
+    /// Initialize an OpenGL/WebGL context rendering to an off-screen buffer of `width * height`
+    /// pixels, without opening any window.
+    ///
+    /// On native platforms, this creates a headless context. On web platforms, this renders to a
+    /// canvas element which is not inserted into the page.
+    pub fn open_headless(
+        width: u32,
+        height: u32,
+        canvas_setup: Option<CanvasSetup>,
+        out_events: Sender<WindowEvent>,
+    ) -> Self {
+        Canvas {
+            canvas: CanvasImpl::open_headless(width, height, canvas_setup, out_events),
+        }
+    }
+
     /// Run the platform-specific render loop.
     pub fn render_loop(data: impl FnMut(f64) -> bool + 'static) {
         CanvasImpl::render_loop(data)
@@ -117,6 +199,11 @@ impl Canvas {
         self.canvas.set_cursor_grab(grab);
     }
 
+    /// Set the cursor appearance. See `Window::set_cursor` for details.
+    pub fn set_cursor(&self, style: CursorStyle) {
+        self.canvas.set_cursor(style);
+    }
+
     pub fn set_cursor_position(&self, x: f64, y: f64) {
         self.canvas.set_cursor_position(x, y);
     }
@@ -125,6 +212,32 @@ impl Canvas {
         self.canvas.hide_cursor(hide);
     }
 
+    /// Lock the cursor. See `Window::lock_cursor` for details.
+    pub fn lock_cursor(&mut self, locked: bool) {
+        self.canvas.lock_cursor(locked)
+    }
+
+    /// Set whether the window can be resized by the user.
+    pub fn set_resizable(&mut self, resizable: bool) {
+        self.canvas.set_resizable(resizable)
+    }
+
+    /// Set whether the window is displayed in fullscreen mode.
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.canvas.set_fullscreen(fullscreen)
+    }
+
+    /// Resizes the framebuffer of the window to `width * height` pixels.
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.canvas.set_size(width, height)
+    }
+
+    /// The underlying glutin window. See `Window::glutin_window` for details.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn glutin_window(&self) -> Option<&glutin::window::Window> {
+        self.canvas.glutin_window()
+    }
+
     /// Hide the window.
     pub fn hide(&mut self) {
         self.canvas.hide()
@@ -135,15 +248,116 @@ impl Canvas {
         self.canvas.show()
     }
 
+    /// Sets the maximum duration between two presses of a mouse button for them to emit a
+    /// `WindowEvent::DoubleClick`. Defaults to 500 milliseconds.
+    pub fn set_double_click_interval(&mut self, interval: Duration) {
+        self.canvas.set_double_click_interval(interval)
+    }
+
+    /// Sets the function called for each event before it is queued. The events for which it
+    /// returns `false` are dropped.
+    pub fn set_event_filter(&mut self, filter: impl FnMut(&WindowEvent) -> bool + 'static) {
+        self.canvas.set_event_filter(Some(Box::new(filter)))
+    }
+
+    /// Removes the function set by `set_event_filter`, so that every event is kept.
+    pub fn clear_event_filter(&mut self) {
+        self.canvas.set_event_filter(None)
+    }
+
+    /// Sets whether the default action of the browser is prevented for the DOM events whose
+    /// `WindowEvent` is dropped by the event filter. Does nothing on native platforms.
+    pub fn set_prevent_default_on_filtered(&mut self, prevent: bool) {
+        self.canvas.set_prevent_default_on_filtered(prevent)
+    }
+
+    /// Sets whether the browser context menu opens on right clicks on the canvas. Does nothing
+    /// on native platforms.
+    pub fn set_context_menu_enabled(&mut self, enabled: bool) {
+        self.canvas.set_context_menu_enabled(enabled)
+    }
+
+    /// Gives the keyboard focus to the canvas. Does nothing on native platforms.
+    pub fn request_focus(&mut self) {
+        self.canvas.request_focus()
+    }
+
+    /// The text content of the clipboard, if any.
+    pub fn clipboard_string(&self) -> Option<String> {
+        self.canvas.clipboard_string()
+    }
+
+    /// Copies `text` to the clipboard.
+    pub fn set_clipboard_string(&mut self, text: &str) {
+        self.canvas.set_clipboard_string(text)
+    }
+
     /// The state of a mouse button.
     pub fn get_mouse_button(&self, button: MouseButton) -> Action {
         self.canvas.get_mouse_button(button)
     }
 
+    /// Removes and returns the file dropped onto the canvas with the identifier `id`, if it has
+    /// not been taken yet. Always `None` on native platforms.
+    pub fn take_dropped_file(&mut self, id: u32) -> Option<DroppedFile> {
+        self.canvas.take_dropped_file(id)
+    }
+
+    /// The state of the gamepad with the given index during the last call to `poll_events`, if
+    /// it is connected. Always `None` on native platforms.
+    pub fn gamepad(&self, index: u32) -> Option<GamepadState> {
+        self.canvas.gamepad(index)
+    }
+
+    /// Sets the magnitude below which the gamepad axes positions are reported as `0.0`. Has no
+    /// effect on native platforms.
+    pub fn set_gamepad_deadzone(&mut self, deadzone: f32) {
+        self.canvas.set_gamepad_deadzone(deadzone)
+    }
+
     /// The state of a key.
     pub fn get_key(&self, key: Key) -> Action {
         self.canvas.get_key(key)
     }
+
+    /// The keys currently pressed.
+    pub fn pressed_keys(&self) -> impl Iterator<Item = Key> + '_ {
+        Key::ALL
+            .iter()
+            .cloned()
+            .filter(move |key| self.get_key(*key) == Action::Press)
+    }
+
+    /// The modifiers currently held, derived from the state of the modifier keys.
+    pub fn get_modifiers(&self) -> Modifiers {
+        let mut modifiers = Modifiers::empty();
+        let pressed = |left, right| {
+            self.get_key(left) == Action::Press || self.get_key(right) == Action::Press
+        };
+
+        if pressed(Key::LShift, Key::RShift) {
+            modifiers |= Modifiers::Shift;
+        }
+        if pressed(Key::LControl, Key::RControl) {
+            modifiers |= Modifiers::Control;
+        }
+        if pressed(Key::LAlt, Key::RAlt) {
+            modifiers |= Modifiers::Alt;
+        }
+        if pressed(Key::LWin, Key::RWin) {
+            modifiers |= Modifiers::Super;
+        }
+
+        modifiers
+    }
+
+    /// The mouse buttons currently pressed.
+    pub fn pressed_buttons(&self) -> impl Iterator<Item = MouseButton> + '_ {
+        MouseButton::ALL
+            .iter()
+            .cloned()
+            .filter(move |button| self.get_mouse_button(*button) == Action::Press)
+    }
 }
 
 pub(crate) trait AbstractCanvas {
@@ -155,6 +369,12 @@ pub(crate) trait AbstractCanvas {
         window_setup: Option<CanvasSetup>,
         out_events: Sender<WindowEvent>,
     ) -> Self;
+    fn open_headless(
+        width: u32,
+        height: u32,
+        window_setup: Option<CanvasSetup>,
+        out_events: Sender<WindowEvent>,
+    ) -> Self;
     fn render_loop(data: impl FnMut(f64) -> bool + 'static);
     fn poll_events(&mut self);
     fn swap_buffers(&mut self);
@@ -165,11 +385,44 @@ pub(crate) trait AbstractCanvas {
     fn set_title(&mut self, title: &str);
     fn set_icon(&mut self, icon: impl GenericImage<Pixel = impl Pixel<Subpixel = u8>>);
     fn set_cursor_grab(&self, grab: bool);
+    fn set_cursor(&self, style: CursorStyle);
     fn set_cursor_position(&self, x: f64, y: f64);
     fn hide_cursor(&self, hide: bool);
+    /// Locks (hides and captures) the cursor.
+    ///
+    /// While the cursor is locked, `WindowEvent::CursorPos` events report the cursor
+    /// displacement since the last event instead of its absolute position.
+    fn lock_cursor(&mut self, locked: bool);
+    fn set_resizable(&mut self, resizable: bool);
+    fn set_fullscreen(&mut self, fullscreen: bool);
+    fn set_size(&mut self, width: u32, height: u32);
     fn hide(&mut self);
     fn show(&mut self);
+    fn set_double_click_interval(&mut self, interval: Duration);
+    fn set_event_filter(&mut self, filter: Option<EventFilter>);
+    fn set_prevent_default_on_filtered(&mut self, prevent: bool);
+    fn set_context_menu_enabled(&mut self, enabled: bool);
+    fn request_focus(&mut self);
+    fn clipboard_string(&self) -> Option<String>;
+    fn set_clipboard_string(&mut self, text: &str);
+    fn take_dropped_file(&mut self, id: u32) -> Option<DroppedFile>;
 
     fn get_mouse_button(&self, button: MouseButton) -> Action;
+    fn gamepad(&self, index: u32) -> Option<GamepadState>;
+    fn set_gamepad_deadzone(&mut self, deadzone: f32);
     fn get_key(&self, key: Key) -> Action;
 }
+
+#[cfg(test)]
+mod test {
+    use super::apply_deadzone;
+
+    #[test]
+    fn deadzone_zeroes_the_small_axes() {
+        assert_eq!(apply_deadzone(0.05, 0.1), 0.0);
+        assert_eq!(apply_deadzone(-0.05, 0.1), 0.0);
+        assert_eq!(apply_deadzone(0.1, 0.1), 0.1);
+        assert_eq!(apply_deadzone(-0.5, 0.1), -0.5);
+        assert_eq!(apply_deadzone(0.05, 0.0), 0.05);
+    }
+}
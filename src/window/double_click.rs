@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use instant::Instant;
+
+use crate::event::MouseButton;
+
+/// The default maximum duration between the two presses of a double-click.
+pub(crate) const DEFAULT_DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+/// The maximum distance, in pixels, the cursor may move between the two presses of a
+/// double-click.
+const DOUBLE_CLICK_MAX_DISTANCE: f64 = 4.0;
+
+/// Detects the double-clicks from the successive mouse button presses of a canvas.
+pub(crate) struct DoubleClickDetector {
+    interval: Duration,
+    last_press: Option<(MouseButton, Instant, (f64, f64))>,
+}
+
+impl DoubleClickDetector {
+    pub fn new() -> Self {
+        DoubleClickDetector {
+            interval: DEFAULT_DOUBLE_CLICK_INTERVAL,
+            last_press: None,
+        }
+    }
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval
+    }
+
+    /// Registers a press of `button` at the cursor position `pos`, and returns whether it
+    /// completes a double-click.
+    pub fn press(&mut self, button: MouseButton, pos: (f64, f64)) -> bool {
+        self.press_at(button, pos, Instant::now())
+    }
+
+    fn press_at(&mut self, button: MouseButton, pos: (f64, f64), now: Instant) -> bool {
+        if let Some((last_button, last_time, last_pos)) = self.last_press {
+            let dx = pos.0 - last_pos.0;
+            let dy = pos.1 - last_pos.1;
+
+            if last_button == button
+                && now.duration_since(last_time) <= self.interval
+                && dx * dx + dy * dy <= DOUBLE_CLICK_MAX_DISTANCE * DOUBLE_CLICK_MAX_DISTANCE
+            {
+                // A third press starts a new double-click.
+                self.last_press = None;
+                return true;
+            }
+        }
+
+        self.last_press = Some((button, now, pos));
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DoubleClickDetector, DEFAULT_DOUBLE_CLICK_INTERVAL};
+    use crate::event::MouseButton;
+    use instant::Instant;
+    use std::time::Duration;
+
+    #[test]
+    fn two_close_presses_are_a_double_click() {
+        let mut detector = DoubleClickDetector::new();
+        let t0 = Instant::now();
+        let t1 = t0 + Duration::from_millis(100);
+
+        assert!(!detector.press_at(MouseButton::Button1, (10.0, 10.0), t0));
+        assert!(detector.press_at(MouseButton::Button1, (12.0, 11.0), t1));
+    }
+
+    #[test]
+    fn a_third_press_starts_a_new_double_click() {
+        let mut detector = DoubleClickDetector::new();
+        let t0 = Instant::now();
+        let dt = Duration::from_millis(100);
+
+        assert!(!detector.press_at(MouseButton::Button1, (0.0, 0.0), t0));
+        assert!(detector.press_at(MouseButton::Button1, (0.0, 0.0), t0 + dt));
+        assert!(!detector.press_at(MouseButton::Button1, (0.0, 0.0), t0 + dt * 2));
+        assert!(detector.press_at(MouseButton::Button1, (0.0, 0.0), t0 + dt * 3));
+    }
+
+    #[test]
+    fn slow_far_or_different_presses_are_not_double_clicks() {
+        let mut detector = DoubleClickDetector::new();
+        let t0 = Instant::now();
+        let late = t0 + DEFAULT_DOUBLE_CLICK_INTERVAL + Duration::from_millis(1);
+        assert!(!detector.press_at(MouseButton::Button1, (0.0, 0.0), t0));
+        assert!(!detector.press_at(MouseButton::Button1, (0.0, 0.0), late));
+
+        let mut detector = DoubleClickDetector::new();
+        assert!(!detector.press_at(MouseButton::Button1, (0.0, 0.0), t0));
+        assert!(!detector.press_at(MouseButton::Button1, (5.0, 0.0), t0));
+
+        let mut detector = DoubleClickDetector::new();
+        assert!(!detector.press_at(MouseButton::Button1, (0.0, 0.0), t0));
+        assert!(!detector.press_at(MouseButton::Button2, (0.0, 0.0), t0));
+    }
+
+    #[test]
+    fn the_interval_can_be_changed() {
+        let mut detector = DoubleClickDetector::new();
+        detector.set_interval(Duration::from_millis(50));
+        let t0 = Instant::now();
+
+        assert!(!detector.press_at(MouseButton::Button1, (0.0, 0.0), t0));
+        assert!(!detector.press_at(
+            MouseButton::Button1,
+            (0.0, 0.0),
+            t0 + Duration::from_millis(100)
+        ));
+    }
+}
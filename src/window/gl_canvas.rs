@@ -1,31 +1,162 @@
+use std::cell::RefCell;
 use std::sync::mpsc::Sender;
+use std::time::Duration;
 
 use crate::context::Context;
 use crate::event::{Action, Key, Modifiers, MouseButton, TouchAction, WindowEvent};
-use crate::window::canvas::{CanvasSetup, NumSamples};
+use crate::window::canvas::{
+    CanvasSetup, CursorStyle, DroppedFile, EventFilter, GamepadState, NumSamples,
+};
+use crate::window::double_click::DoubleClickDetector;
 use crate::window::AbstractCanvas;
+#[cfg(feature = "clipboard")]
+use copypasta::{ClipboardContext, ClipboardProvider};
 use glutin::{
     self,
-    dpi::LogicalSize,
+    dpi::{LogicalSize, PhysicalSize},
     event::TouchPhase,
     event_loop::{ControlFlow, EventLoop},
     platform::run_return::EventLoopExtRunReturn,
-    window::WindowBuilder,
-    ContextBuilder, GlRequest, PossiblyCurrent, WindowedContext,
+    window::{CursorIcon, Fullscreen, WindowBuilder},
+    ContextBuilder, GlRequest, NotCurrent, PossiblyCurrent, WindowedContext,
 };
 use image::{GenericImage, Pixel};
 
+// The OpenGL context of a canvas, and what it renders to.
+enum Surface {
+    Window(WindowedContext<PossiblyCurrent>),
+    // A context rendering to an off-screen buffer of the given size.
+    Headless(glutin::Context<PossiblyCurrent>, (u32, u32)),
+}
+
 /// A canvas based on glutin and OpenGL.
 pub struct GLCanvas {
-    window: WindowedContext<PossiblyCurrent>,
+    surface: Surface,
     events: EventLoop<()>,
     cursor_pos: Option<(f64, f64)>,
     key_states: [Action; Key::Unknown as usize + 1],
     button_states: [Action; MouseButton::Button8 as usize + 1],
     out_events: Sender<WindowEvent>,
+    cursor_locked: bool,
+    modifiers: Modifiers,
+    double_click: DoubleClickDetector,
+    event_filter: Option<EventFilter>,
+    clipboard: RefCell<Clipboard>,
     // listeners: Vec<EventListenerHandle>,
 }
 
+impl GLCanvas {
+    /// The underlying glutin window, or `None` if this canvas is headless.
+    pub fn glutin_window(&self) -> Option<&glutin::window::Window> {
+        match self.surface {
+            Surface::Window(ref window) => Some(window.window()),
+            Surface::Headless(..) => None,
+        }
+    }
+
+    fn new(surface: Surface, events: EventLoop<()>, out_events: Sender<WindowEvent>) -> Self {
+        let ctxt = Context::get();
+        let vao = ctxt.create_vertex_array();
+        ctxt.bind_vertex_array(vao.as_ref());
+
+        GLCanvas {
+            surface,
+            events,
+            cursor_pos: None,
+            key_states: [Action::Release; Key::Unknown as usize + 1],
+            button_states: [Action::Release; MouseButton::Button8 as usize + 1],
+            out_events,
+            cursor_locked: false,
+            modifiers: Modifiers::empty(),
+            double_click: DoubleClickDetector::new(),
+            event_filter: None,
+            clipboard: RefCell::new(Clipboard::default()),
+        }
+    }
+}
+
+// The clipboard of the system, only accessible with the `clipboard` feature.
+#[derive(Default)]
+struct Clipboard {
+    // Opened on first use. `Some(None)` if the clipboard cannot be accessed.
+    #[cfg(feature = "clipboard")]
+    context: Option<Option<ClipboardContext>>,
+}
+
+impl Clipboard {
+    #[cfg(feature = "clipboard")]
+    fn context(&mut self) -> Option<&mut ClipboardContext> {
+        self.context
+            .get_or_insert_with(|| ClipboardContext::new().ok())
+            .as_mut()
+    }
+
+    #[cfg(feature = "clipboard")]
+    fn contents(&mut self) -> Option<String> {
+        self.context()?.get_contents().ok()
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    fn contents(&mut self) -> Option<String> {
+        None
+    }
+
+    #[cfg(feature = "clipboard")]
+    fn set_contents(&mut self, text: &str) {
+        if let Some(context) = self.context() {
+            let _ = context.set_contents(text.to_string());
+        }
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    fn set_contents(&mut self, _: &str) {}
+}
+
+fn new_event_loop() -> EventLoop<()> {
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    let events = {
+        use glutin::platform::unix::EventLoopExtUnix;
+        EventLoop::new_any_thread()
+    };
+    #[cfg(windows)]
+    let events = {
+        use glutin::platform::windows::EventLoopExtWindows;
+        EventLoop::new_any_thread()
+    };
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        windows
+    )))]
+    let events = EventLoop::new();
+
+    events
+}
+
+fn context_builder<'a>(canvas_setup: Option<CanvasSetup>) -> ContextBuilder<'a, NotCurrent> {
+    let canvas_setup = canvas_setup.unwrap_or(CanvasSetup {
+        vsync: true,
+        samples: NumSamples::Zero,
+    });
+
+    ContextBuilder::new()
+        .with_vsync(canvas_setup.vsync)
+        .with_multisampling(canvas_setup.samples as u16)
+        .with_gl(GlRequest::GlThenGles {
+            opengl_version: (3, 2),
+            opengles_version: (2, 0),
+        })
+}
+
 impl AbstractCanvas for GLCanvas {
     fn open(
         title: &str,
@@ -35,47 +166,12 @@ impl AbstractCanvas for GLCanvas {
         canvas_setup: Option<CanvasSetup>,
         out_events: Sender<WindowEvent>,
     ) -> Self {
-        #[cfg(any(
-            target_os = "linux",
-            target_os = "dragonfly",
-            target_os = "freebsd",
-            target_os = "netbsd",
-            target_os = "openbsd"
-        ))]
-        let events = {
-            use glutin::platform::unix::EventLoopExtUnix;
-            EventLoop::new_any_thread()
-        };
-        #[cfg(windows)]
-        let events = {
-            use glutin::platform::windows::EventLoopExtWindows;
-            EventLoop::new_any_thread()
-        };
-        #[cfg(not(any(
-            target_os = "linux",
-            target_os = "dragonfly",
-            target_os = "freebsd",
-            target_os = "netbsd",
-            target_os = "openbsd",
-            windows
-        )))]
-        let events = EventLoop::new();
-
+        let events = new_event_loop();
         let window = WindowBuilder::new()
             .with_title(title)
             .with_inner_size(LogicalSize::new(width as f64, height as f64))
             .with_visible(!hide);
-        let canvas_setup = canvas_setup.unwrap_or(CanvasSetup {
-            vsync: true,
-            samples: NumSamples::Zero,
-        });
-        let window = ContextBuilder::new()
-            .with_vsync(canvas_setup.vsync)
-            .with_multisampling(canvas_setup.samples as u16)
-            .with_gl(GlRequest::GlThenGles {
-                opengl_version: (3, 2),
-                opengles_version: (2, 0),
-            })
+        let window = context_builder(canvas_setup)
             .build_windowed(window, &events)
             .unwrap();
         let window = unsafe { window.make_current().unwrap() };
@@ -83,18 +179,31 @@ impl AbstractCanvas for GLCanvas {
             glow::Context::from_loader_function(|name| window.get_proc_address(name) as *const _)
         });
 
-        let ctxt = Context::get();
-        let vao = ctxt.create_vertex_array();
-        ctxt.bind_vertex_array(vao.as_ref());
+        GLCanvas::new(Surface::Window(window), events, out_events)
+    }
 
-        GLCanvas {
-            window,
+    fn open_headless(
+        width: u32,
+        height: u32,
+        canvas_setup: Option<CanvasSetup>,
+        out_events: Sender<WindowEvent>,
+    ) -> Self {
+        // NOTE: glutin still needs an event loop, hence a connection to the display server on
+        // Linux, but no window is created.
+        let events = new_event_loop();
+        let context = context_builder(canvas_setup)
+            .build_headless(&events, PhysicalSize::new(width, height))
+            .unwrap();
+        let context = unsafe { context.make_current().unwrap() };
+        Context::init(|| unsafe {
+            glow::Context::from_loader_function(|name| context.get_proc_address(name) as *const _)
+        });
+
+        GLCanvas::new(
+            Surface::Headless(context, (width, height)),
             events,
-            cursor_pos: None,
-            key_states: [Action::Release; Key::Unknown as usize + 1],
-            button_states: [Action::Release; MouseButton::Button8 as usize + 1],
             out_events,
-        }
+        )
     }
 
     fn render_loop(mut callback: impl FnMut(f64) -> bool + 'static) {
@@ -107,10 +216,20 @@ impl AbstractCanvas for GLCanvas {
 
     fn poll_events(&mut self) {
         let out_events = &mut self.out_events;
-        let window = &mut self.window;
+        let event_filter = &mut self.event_filter;
+        let mut send = |event: WindowEvent| {
+            if event_filter.as_mut().map_or(true, |filter| filter(&event)) {
+                let _ = out_events.send(event);
+            }
+        };
+        let surface = &self.surface;
         let button_states = &mut self.button_states;
         let key_states = &mut self.key_states;
         let cursor_pos = &mut self.cursor_pos;
+        let cursor_locked = self.cursor_locked;
+        let current_modifiers = &mut self.modifiers;
+        let double_click = &mut self.double_click;
+        let clipboard = &self.clipboard;
 
         self.events.run_return(|event, _, control_flow| {
             use glutin::event::Event;
@@ -118,12 +237,14 @@ impl AbstractCanvas for GLCanvas {
             match event {
                 Event::WindowEvent { event, .. } => match event {
                     glutin::event::WindowEvent::CloseRequested => {
-                        let _ = out_events.send(WindowEvent::Close);
+                        send(WindowEvent::Close);
                     }
                     glutin::event::WindowEvent::Resized(physical_size) => {
-                        window.resize(physical_size);
+                        if let Surface::Window(ref window) = *surface {
+                            window.resize(physical_size);
+                        }
                         let fb_size: (u32, u32) = physical_size.into();
-                        let _ = out_events.send(WindowEvent::FramebufferSize(fb_size.0, fb_size.1));
+                        send(WindowEvent::FramebufferSize(fb_size.0, fb_size.1));
                     }
                     glutin::event::WindowEvent::CursorMoved {
                         position,
@@ -132,8 +253,14 @@ impl AbstractCanvas for GLCanvas {
                     } => {
                         let modifiers = translate_modifiers(modifiers);
                         *cursor_pos = Some(position.into());
-                        let _ = out_events
-                            .send(WindowEvent::CursorPos(position.x, position.y, modifiers));
+
+                        // Locked cursors report their motion with `DeviceEvent::MouseMotion`.
+                        if !cursor_locked {
+                            send(WindowEvent::CursorPos(position.x, position.y, modifiers));
+                        }
+                    }
+                    glutin::event::WindowEvent::ModifiersChanged(modifiers) => {
+                        *current_modifiers = translate_modifiers(modifiers);
                     }
                     glutin::event::WindowEvent::MouseInput {
                         state,
@@ -145,8 +272,15 @@ impl AbstractCanvas for GLCanvas {
                         let button = translate_mouse_button(button);
                         let modifiers = translate_modifiers(modifiers);
                         button_states[button as usize] = action;
-                        let _ =
-                            out_events.send(WindowEvent::MouseButton(button, action, modifiers));
+                        send(WindowEvent::MouseButton(button, action, modifiers));
+
+                        if action == Action::Press {
+                            let (x, y) = cursor_pos.unwrap_or((0.0, 0.0));
+
+                            if double_click.press(button, (x, y)) {
+                                send(WindowEvent::DoubleClick(button, x, y, modifiers));
+                            }
+                        }
                     }
                     glutin::event::WindowEvent::Touch(touch) => {
                         let action = match touch.phase {
@@ -156,7 +290,7 @@ impl AbstractCanvas for GLCanvas {
                             TouchPhase::Cancelled => TouchAction::Cancel,
                         };
 
-                        let _ = out_events.send(WindowEvent::Touch(
+                        send(WindowEvent::Touch(
                             touch.id,
                             touch.location.x,
                             touch.location.y,
@@ -174,20 +308,42 @@ impl AbstractCanvas for GLCanvas {
                             glutin::event::MouseScrollDelta::PixelDelta(delta) => delta.into(),
                         };
                         let modifiers = translate_modifiers(modifiers);
-                        let _ = out_events.send(WindowEvent::Scroll(x, y, modifiers));
+                        send(WindowEvent::Scroll(x, y, modifiers));
                     }
                     glutin::event::WindowEvent::KeyboardInput { input, .. } => {
                         let action = translate_action(input.state);
                         let key = translate_key(input.virtual_keycode);
                         let modifiers = translate_modifiers(input.modifiers);
                         key_states[key as usize] = action;
-                        let _ = out_events.send(WindowEvent::Key(key, action, modifiers));
+                        send(WindowEvent::Key(key, action, modifiers));
+
+                        // Mimic the `paste` events of the browsers.
+                        let paste_modifier = if cfg!(target_os = "macos") {
+                            Modifiers::Super
+                        } else {
+                            Modifiers::Control
+                        };
+                        if (key == Key::V
+                            && action == Action::Press
+                            && modifiers.contains(paste_modifier))
+                            || (key == Key::Paste && action == Action::Press)
+                        {
+                            if clipboard.borrow_mut().contents().is_some() {
+                                send(WindowEvent::Paste);
+                            }
+                        }
                     }
                     glutin::event::WindowEvent::ReceivedCharacter(c) => {
-                        let _ = out_events.send(WindowEvent::Char(c));
+                        send(WindowEvent::Char(c));
                     }
                     _ => {}
                 },
+                Event::DeviceEvent {
+                    event: glutin::event::DeviceEvent::MouseMotion { delta },
+                    ..
+                } if cursor_locked => {
+                    send(WindowEvent::CursorPos(delta.0, delta.1, *current_modifiers));
+                }
                 Event::RedrawEventsCleared => {
                     *control_flow = ControlFlow::Exit;
                 }
@@ -197,11 +353,16 @@ impl AbstractCanvas for GLCanvas {
     }
 
     fn swap_buffers(&mut self) {
-        let _ = self.window.swap_buffers();
+        if let Surface::Window(ref window) = self.surface {
+            let _ = window.swap_buffers();
+        }
     }
 
     fn size(&self) -> (u32, u32) {
-        self.window.window().inner_size().into()
+        match self.surface {
+            Surface::Window(ref window) => window.window().inner_size().into(),
+            Surface::Headless(_, size) => size,
+        }
     }
 
     fn cursor_pos(&self) -> Option<(f64, f64)> {
@@ -209,11 +370,14 @@ impl AbstractCanvas for GLCanvas {
     }
 
     fn scale_factor(&self) -> f64 {
-        self.window.window().scale_factor() as f64
+        self.glutin_window()
+            .map_or(1.0, |window| window.scale_factor() as f64)
     }
 
     fn set_title(&mut self, title: &str) {
-        self.window.window().set_title(title)
+        if let Some(window) = self.glutin_window() {
+            window.set_title(title)
+        }
     }
 
     fn set_icon(&mut self, icon: impl GenericImage<Pixel = impl Pixel<Subpixel = u8>>) {
@@ -223,32 +387,137 @@ impl AbstractCanvas for GLCanvas {
             rgba.extend_from_slice(&pixel.to_rgba().0);
         }
         let icon = glutin::window::Icon::from_rgba(rgba, width, height).unwrap();
-        self.window.window().set_window_icon(Some(icon))
+
+        if let Some(window) = self.glutin_window() {
+            window.set_window_icon(Some(icon))
+        }
     }
 
     fn set_cursor_grab(&self, grab: bool) {
-        let _ = self.window.window().set_cursor_grab(grab);
+        if let Some(window) = self.glutin_window() {
+            let _ = window.set_cursor_grab(grab);
+        }
+    }
+
+    fn set_cursor(&self, style: CursorStyle) {
+        let icon = match style {
+            CursorStyle::Default => CursorIcon::Default,
+            CursorStyle::Crosshair => CursorIcon::Crosshair,
+            CursorStyle::Hand => CursorIcon::Hand,
+            CursorStyle::Move => CursorIcon::Move,
+            CursorStyle::Text => CursorIcon::Text,
+            CursorStyle::Wait => CursorIcon::Wait,
+            CursorStyle::Help => CursorIcon::Help,
+            CursorStyle::NotAllowed => CursorIcon::NotAllowed,
+            CursorStyle::Grab => CursorIcon::Grab,
+            CursorStyle::Grabbing => CursorIcon::Grabbing,
+            CursorStyle::EwResize => CursorIcon::EwResize,
+            CursorStyle::NsResize => CursorIcon::NsResize,
+        };
+        if let Some(window) = self.glutin_window() {
+            window.set_cursor_icon(icon)
+        }
     }
 
     fn set_cursor_position(&self, x: f64, y: f64) {
-        self.window
-            .window()
-            .set_cursor_position(glutin::dpi::PhysicalPosition::new(x, y))
-            .unwrap();
+        if let Some(window) = self.glutin_window() {
+            window
+                .set_cursor_position(glutin::dpi::PhysicalPosition::new(x, y))
+                .unwrap();
+        }
     }
 
     fn hide_cursor(&self, hide: bool) {
-        self.window.window().set_cursor_visible(!hide)
+        if let Some(window) = self.glutin_window() {
+            window.set_cursor_visible(!hide)
+        }
+    }
+
+    fn lock_cursor(&mut self, locked: bool) {
+        if let Some(window) = self.glutin_window() {
+            let _ = window.set_cursor_grab(locked);
+            window.set_cursor_visible(!locked);
+        }
+        self.cursor_locked = locked;
+    }
+
+    fn set_resizable(&mut self, resizable: bool) {
+        if let Some(window) = self.glutin_window() {
+            window.set_resizable(resizable)
+        }
+    }
+
+    fn set_fullscreen(&mut self, fullscreen: bool) {
+        if let Some(window) = self.glutin_window() {
+            let mode = if fullscreen {
+                Some(Fullscreen::Borderless(window.current_monitor()))
+            } else {
+                None
+            };
+            window.set_fullscreen(mode)
+        }
+    }
+
+    fn set_size(&mut self, width: u32, height: u32) {
+        // The `Resized` event emitted by glutin updates the framebuffer size.
+        // NOTE: the off-screen buffer of a headless canvas cannot be resized.
+        if let Some(window) = self.glutin_window() {
+            window.set_inner_size(PhysicalSize::new(width, height))
+        }
     }
 
     fn hide(&mut self) {
-        self.window.window().set_visible(false)
+        if let Some(window) = self.glutin_window() {
+            window.set_visible(false)
+        }
     }
 
     fn show(&mut self) {
-        self.window.window().set_visible(true)
+        if let Some(window) = self.glutin_window() {
+            window.set_visible(true)
+        }
+    }
+
+    fn set_double_click_interval(&mut self, interval: Duration) {
+        self.double_click.set_interval(interval)
     }
 
+    fn set_event_filter(&mut self, filter: Option<EventFilter>) {
+        self.event_filter = filter
+    }
+
+    fn set_prevent_default_on_filtered(&mut self, _: bool) {
+        // There is no default action to prevent.
+    }
+
+    fn set_context_menu_enabled(&mut self, _: bool) {
+        // There is no context menu.
+    }
+
+    fn request_focus(&mut self) {
+        // The window manager decides which window has the focus.
+    }
+
+    fn clipboard_string(&self) -> Option<String> {
+        self.clipboard.borrow_mut().contents()
+    }
+
+    fn set_clipboard_string(&mut self, text: &str) {
+        self.clipboard.borrow_mut().set_contents(text)
+    }
+
+    fn take_dropped_file(&mut self, _: u32) -> Option<DroppedFile> {
+        // The files dropped onto the window are not supported yet.
+        None
+    }
+
+    fn gamepad(&self, _: u32) -> Option<GamepadState> {
+        // Glutin has no gamepad API: gamepads are not supported on native platforms.
+        None
+    }
+
+    fn set_gamepad_deadzone(&mut self, _: f32) {}
+
     fn get_mouse_button(&self, button: MouseButton) -> Action {
         self.button_states[button as usize]
     }
@@ -1,18 +1,33 @@
 //! The window, and things to handle the rendering loop and events.
 
 pub(crate) use self::canvas::AbstractCanvas;
-pub use self::canvas::{Canvas, CanvasSetup, NumSamples};
+pub use self::canvas::{Canvas, CanvasSetup, CursorStyle, DroppedFile, GamepadState, NumSamples};
 #[cfg(not(target_arch = "wasm32"))]
 pub use self::gl_canvas::GLCanvas;
 pub use self::state::State;
+#[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+pub use self::video_recording::RecordingError;
+pub use self::viewport::{ViewportConfig, MAX_VIEWPORTS};
 #[cfg(target_arch = "wasm32")]
 pub use self::webgl_canvas::WebGLCanvas;
 pub use self::window::Window;
+pub use self::window_builder::WindowBuilder;
 
 mod canvas;
+mod double_click;
 #[cfg(not(target_arch = "wasm32"))]
 mod gl_canvas;
+#[cfg(not(target_arch = "wasm32"))]
+mod recording;
+#[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+mod shader_watcher;
+mod shadow_map;
 mod state;
+mod svg;
+#[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+mod video_recording;
+mod viewport;
 #[cfg(target_arch = "wasm32")]
 mod webgl_canvas;
 mod window;
+mod window_builder;
@@ -0,0 +1,88 @@
+//! Recording of the rendered frames to a sequence of images.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+
+use image::{ImageBuffer, Rgb};
+
+/// A recording of rendered frames to a sequence of PNG files.
+///
+/// The images are encoded and written by a separate thread to avoid slowing down the rendering.
+pub(crate) struct Recording {
+    dir: PathBuf,
+    every_nth_frame: u32,
+    num_rendered_frames: u32,
+    num_recorded_frames: u32,
+    sender: Option<Sender<(PathBuf, ImageBuffer<Rgb<u8>, Vec<u8>>)>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl Recording {
+    /// Starts a recording writing one every `every_nth_frame` frames to the directory `dir`.
+    ///
+    /// The directory is created if it does not exist yet. Fails with
+    /// `io::ErrorKind::InvalidInput` if `every_nth_frame` is zero.
+    pub fn new(dir: &Path, every_nth_frame: u32) -> io::Result<Recording> {
+        if every_nth_frame == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot record every 0-th frame",
+            ));
+        }
+
+        fs::create_dir_all(dir)?;
+
+        let (sender, receiver) = mpsc::channel::<(PathBuf, ImageBuffer<Rgb<u8>, Vec<u8>>)>();
+        let writer = thread::spawn(move || {
+            for (path, image) in receiver.iter() {
+                if let Err(e) = image.save(&path) {
+                    eprintln!("Failed to save the frame `{}`: {}", path.display(), e);
+                }
+            }
+        });
+
+        Ok(Recording {
+            dir: dir.to_path_buf(),
+            every_nth_frame,
+            num_rendered_frames: 0,
+            num_recorded_frames: 0,
+            sender: Some(sender),
+            writer: Some(writer),
+        })
+    }
+
+    /// Whether the next rendered frame has to be recorded.
+    pub fn needs_frame(&self) -> bool {
+        self.num_rendered_frames % self.every_nth_frame == 0
+    }
+
+    /// Notifies the recording that a frame has been rendered, giving its image if it was needed.
+    pub fn push_frame(&mut self, image: Option<ImageBuffer<Rgb<u8>, Vec<u8>>>) {
+        if let Some(image) = image {
+            let path = self
+                .dir
+                .join(format!("frame_{:06}.png", self.num_recorded_frames));
+            self.num_recorded_frames += 1;
+
+            if let Some(ref sender) = self.sender {
+                let _ = sender.send((path, image));
+            }
+        }
+
+        self.num_rendered_frames += 1;
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        // Close the channel, and wait for all the pending frames to be written.
+        self.sender = None;
+
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Hot-reloading of the shaders of the default material.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::builtin::{ObjectMaterial, OBJECT_FRAGMENT_SRC, OBJECT_VERTEX_SRC};
+use crate::resource::MaterialManager;
+
+/// Watches a directory for changes of the `default.vert` and `default.frag` shaders.
+pub(crate) struct ShaderWatcher {
+    dir: PathBuf,
+    events: Receiver<DebouncedEvent>,
+    _watcher: RecommendedWatcher,
+}
+
+impl ShaderWatcher {
+    /// Starts watching the directory `dir`.
+    pub fn new(dir: &Path) -> notify::Result<ShaderWatcher> {
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::watcher(sender, Duration::from_millis(100))?;
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+        Ok(ShaderWatcher {
+            dir: dir.to_path_buf(),
+            events,
+            _watcher: watcher,
+        })
+    }
+
+    /// Whether a shader file changed since the last call to this method.
+    pub fn shaders_changed(&mut self) -> bool {
+        let mut changed = false;
+
+        for event in self.events.try_iter() {
+            let path = match event {
+                DebouncedEvent::Create(path)
+                | DebouncedEvent::Write(path)
+                | DebouncedEvent::Rename(_, path) => path,
+                DebouncedEvent::Error(e, _) => {
+                    eprintln!("Failed to watch the shaders: {}", e);
+                    continue;
+                }
+                _ => continue,
+            };
+
+            let is_shader = path
+                .extension()
+                .map_or(false, |ext| ext == "vert" || ext == "frag");
+            changed = changed || is_shader;
+        }
+
+        changed
+    }
+
+    /// Recompiles the default material from the shaders of the watched directory.
+    ///
+    /// A missing shader file is replaced by its built-in counterpart. On failure, the errors
+    /// are logged to stderr and the previous material remains in use.
+    pub fn reload(&self) {
+        let read = |name: &str, builtin: &str| {
+            fs::read_to_string(self.dir.join(name)).unwrap_or_else(|_| builtin.to_string())
+        };
+        let vshader = read("default.vert", OBJECT_VERTEX_SRC);
+        let fshader = read("default.frag", OBJECT_FRAGMENT_SRC);
+
+        match ObjectMaterial::try_new_from_str(&vshader, &fshader) {
+            Ok(material) => {
+                let default = MaterialManager::get_global_manager(|mm| mm.get_default());
+                *default.borrow_mut() = Box::new(material);
+            }
+            Err(e) => eprintln!("Failed to reload the shaders: {}", e),
+        }
+    }
+}
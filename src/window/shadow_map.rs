@@ -0,0 +1,225 @@
+//! Shadow map rendered from the point of view of the light.
+
+use na::{Isometry3, Matrix3, Matrix4, Orthographic3, Point3, Vector3};
+
+use crate::builtin::{ObjectMaterial, ShadowMapParams};
+use crate::context::Context;
+use crate::resource::{Effect, FramebufferManager, RenderTarget, ShaderAttribute, ShaderUniform};
+use crate::scene::SceneNode;
+
+#[path = "../error.rs"]
+mod error;
+
+/// The depth of the scene seen from the light.
+///
+/// The light is treated as a directional light pointing from its position toward the center of
+/// the scene. The depth is packed into the RGBA channels of an ordinary color texture so no
+/// depth-texture extension is needed on WebGL.
+pub(crate) struct ShadowMap {
+    resolution: u32,
+    target: RenderTarget,
+    effect: Effect,
+    pos: ShaderAttribute<Point3<f32>>,
+    transform: ShaderUniform<Matrix4<f32>>,
+    scale: ShaderUniform<Matrix3<f32>>,
+    light_view_proj: ShaderUniform<Matrix4<f32>>,
+}
+
+impl ShadowMap {
+    /// Creates a square shadow map with `resolution` pixels on each side.
+    pub fn new(resolution: u32) -> ShadowMap {
+        let ctxt = Context::get();
+        let target =
+            FramebufferManager::new_render_target(resolution as usize, resolution as usize, false);
+
+        // Interpolating packed depths would make no sense.
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, target.texture_id()));
+        verify!(ctxt.tex_parameteri(
+            Context::TEXTURE_2D,
+            Context::TEXTURE_MAG_FILTER,
+            Context::NEAREST as i32
+        ));
+        verify!(ctxt.tex_parameteri(
+            Context::TEXTURE_2D,
+            Context::TEXTURE_MIN_FILTER,
+            Context::NEAREST as i32
+        ));
+        verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
+
+        let mut effect = Effect::new_from_str(VERTEX_SHADER, FRAGMENT_SHADER);
+        effect.use_program();
+
+        ShadowMap {
+            resolution,
+            target,
+            pos: effect.get_attrib("position").unwrap(),
+            transform: effect.get_uniform("transform").unwrap(),
+            scale: effect.get_uniform("scale").unwrap(),
+            light_view_proj: effect.get_uniform("light_view_proj").unwrap(),
+            effect,
+        }
+    }
+
+    /// The number of pixels on each side of this shadow map.
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
+    /// Renders the depth of `scene` seen from a light at `light_pos`.
+    ///
+    /// Returns `None` if the scene has nothing casting shadows. The viewport is left to the size
+    /// of the shadow map, and the screen is selected as the render target afterward.
+    pub fn render(
+        &mut self,
+        framebuffer_manager: &mut FramebufferManager,
+        scene: &SceneNode,
+        light_pos: &Point3<f32>,
+    ) -> Option<ShadowMapParams> {
+        let light_view_proj = light_view_proj(scene, light_pos)?;
+        let ctxt = Context::get();
+        let res = self.resolution as i32;
+
+        framebuffer_manager.select(&self.target);
+        verify!(ctxt.viewport(0, 0, res, res));
+        verify!(ctxt.scissor(0, 0, res, res));
+        verify!(ctxt.clear_color(1.0, 1.0, 1.0, 1.0));
+        verify!(ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT));
+        verify!(ctxt.disable(Context::CULL_FACE));
+
+        self.effect.use_program();
+        self.pos.enable();
+        self.light_view_proj.upload(&light_view_proj);
+
+        let (pos, transform_uniform, scale_uniform) =
+            (&mut self.pos, &mut self.transform, &mut self.scale);
+
+        scene
+            .data()
+            .apply_to_visible_objects_with_transform(&mut |object, transform, scale| {
+                if !object.data().surface_rendering_active() {
+                    return;
+                }
+
+                scale_uniform.upload(&Matrix3::from_diagonal(scale));
+
+                let mut mesh = object.mesh().borrow_mut();
+                mesh.bind_coords(pos);
+                mesh.bind_faces();
+
+                let mut draw = |transform: &Isometry3<f32>| {
+                    transform_uniform.upload(&transform.to_homogeneous());
+                    verify!(ctxt.draw_elements(
+                        Context::TRIANGLES,
+                        mesh.num_pts() as i32,
+                        mesh.index_type().to_gl(),
+                        0
+                    ));
+                };
+
+                // The shadow shader has no per-instance attributes: instances are drawn one at a time.
+                match object.data().instances() {
+                    Some(instances) => {
+                        for instance in &instances.borrow().data {
+                            draw(&(transform * instance.transform));
+                        }
+                    }
+                    None => draw(transform),
+                }
+
+                mesh.unbind();
+            });
+
+        self.pos.disable();
+        framebuffer_manager.select(&FramebufferManager::screen());
+
+        Some(ShadowMapParams {
+            texture: self.target.texture()?,
+            light_view_proj,
+            resolution: self.resolution,
+        })
+    }
+}
+
+impl Drop for ShadowMap {
+    fn drop(&mut self) {
+        // Don't let the materials sample a shadow map that no longer exists.
+        ObjectMaterial::set_shadow_map(None);
+    }
+}
+
+// Orthographic projection of the bounding sphere of the scene along the light direction.
+fn light_view_proj(scene: &SceneNode, light_pos: &Point3<f32>) -> Option<Matrix4<f32>> {
+    let mut bounds: Option<(Point3<f32>, Point3<f32>)> = None;
+
+    scene
+        .data()
+        .apply_to_visible_objects_with_transform(&mut |object, transform, scale| {
+            if !object.data().surface_rendering_active() {
+                return;
+            }
+
+            // The cached bounding boxes avoid reading every vertex at each frame.
+            let mut add = |transform: &Isometry3<f32>| {
+                if let Some(aabb) = object.world_aabb(transform, scale) {
+                    bounds = Some(match bounds {
+                        Some((mins, maxs)) => (mins.inf(&aabb.mins), maxs.sup(&aabb.maxs)),
+                        None => (aabb.mins, aabb.maxs),
+                    });
+                }
+            };
+
+            match object.data().instances() {
+                Some(instances) => {
+                    for instance in &instances.borrow().data {
+                        add(&(transform * instance.transform));
+                    }
+                }
+                None => add(transform),
+            }
+        });
+
+    let (mins, maxs) = bounds?;
+    let center = na::center(&mins, &maxs);
+    let radius = ((maxs - mins).norm() * 0.5).max(1.0e-3);
+
+    let dir = (light_pos - center)
+        .try_normalize(1.0e-6)
+        .unwrap_or(Vector3::y());
+    let up = if dir.y.abs() > 0.99 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+
+    let eye = center + dir * (radius * 2.0);
+    let view = Isometry3::look_at_rh(&eye, &center, &up);
+    let proj = Orthographic3::new(-radius, radius, -radius, radius, radius * 0.5, radius * 3.5);
+
+    Some(proj.to_homogeneous() * view.to_homogeneous())
+}
+
+static VERTEX_SHADER: &'static str = "#version 100
+    attribute vec3 position;
+    uniform mat4   light_view_proj, transform;
+    uniform mat3   scale;
+
+    void main() {
+      gl_Position = light_view_proj * transform * vec4(scale * position, 1.0);
+    }";
+
+static FRAGMENT_SHADER: &'static str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    // Spreads the depth over the four 8-bits channels.
+    vec4 pack_depth(float depth) {
+      vec4 res = fract(vec4(1.0, 255.0, 65025.0, 16581375.0) * depth);
+      return res - res.yzww * vec4(1.0 / 255.0, 1.0 / 255.0, 1.0 / 255.0, 0.0);
+    }
+
+    void main() {
+      gl_FragColor = pack_depth(gl_FragCoord.z);
+    }";
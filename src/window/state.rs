@@ -13,6 +13,21 @@ pub trait State: 'static {
     /// Method called at each render loop before a rendering.
     fn step(&mut self, window: &mut Window);
 
+    /// The duration, in seconds, of the fixed time steps passed to `fixed_update`.
+    ///
+    /// Returns `None` by default, in which case `fixed_update` is never called.
+    fn fixed_timestep(&self) -> Option<f32> {
+        None
+    }
+
+    /// Method called with a constant `dt`, given by `fixed_timestep`, as many times as needed to
+    /// catch up with the time elapsed since the last rendering.
+    ///
+    /// This is useful for deterministic simulations, e.g., physics. It is called right before
+    /// `step` which can use `Window::fixed_update_alpha` to interpolate between the last two
+    /// simulation states.
+    fn fixed_update(&mut self, _window: &mut Window, _dt: f32) {}
+
     /// Unless `cameras_and_effect_and_renderer` is implemented, this method called at each render loop to retrieve
     /// the cameras and post-processing effects to be used for the next render.
     #[deprecated(
@@ -0,0 +1,190 @@
+//! Export of a 3D scene to a vector image.
+
+use std::cmp::Ordering;
+use std::fmt::Write;
+
+use na::{Point2, Point3, Vector3};
+
+use crate::camera::Camera;
+use crate::light::Light;
+use crate::scene::{CullingMode, SceneNode};
+
+struct SvgPolygon {
+    depth: f32,
+    points: Vec<Point2<f32>>,
+    color: Point3<f32>,
+    alpha: f32,
+}
+
+/// Projects every visible triangle of `scene` with `camera` and writes them as SVG 1.1 polygons.
+///
+/// Triangles are clipped against the near and far planes, faces are culled according to the
+/// culling mode of each object, and hidden surfaces are handled with the painter's algorithm.
+pub(crate) fn render_to_svg(
+    scene: &SceneNode,
+    camera: &dyn Camera,
+    light: &Light,
+    background: &Vector3<f32>,
+    width: u32,
+    height: u32,
+) -> String {
+    let view = camera.view_transform();
+    let proj = camera.transformation() * view.inverse().to_homogeneous();
+    let (znear, zfar) = camera.clip_planes();
+    let light_pos = match *light {
+        Light::Absolute(ref p) => *p,
+        Light::StickToCamera => camera.eye(),
+    };
+
+    let mut polygons = Vec::new();
+
+    scene
+        .data()
+        .apply_to_visible_objects_with_transform(&mut |object, transform, scale| {
+            let data = object.data();
+
+            if !data.surface_rendering_active() {
+                return;
+            }
+
+            object.read_vertices(&mut |coords| {
+                object.read_faces(&mut |faces| {
+                    for face in faces {
+                        let to_world = |i: u16| {
+                            transform * Point3::from(coords[i as usize].coords.component_mul(scale))
+                        };
+                        let world = [to_world(face.x), to_world(face.y), to_world(face.z)];
+                        let local = [view * world[0], view * world[1], view * world[2]];
+
+                        // Back-face culling: the camera looks toward the negative `z` axis.
+                        let mut normal = (local[1] - local[0]).cross(&(local[2] - local[0]));
+                        if normal.norm_squared() == 0.0 {
+                            continue; // Degenerate triangle.
+                        }
+
+                        let back_facing = normal.dot(&local[0].coords) >= 0.0;
+
+                        match data.culling_mode() {
+                            _ if data.double_sided() => {}
+                            CullingMode::Back if back_facing => continue,
+                            CullingMode::Front if !back_facing => continue,
+                            _ => {}
+                        }
+
+                        if back_facing {
+                            normal = -normal;
+                        }
+
+                        let clipped = clip_polygon(&local, znear, zfar);
+                        if clipped.len() < 3 {
+                            continue;
+                        }
+
+                        let points: Vec<_> = clipped
+                            .iter()
+                            .map(|p| {
+                                let ndc = proj.transform_point(p);
+                                Point2::new(
+                                    (ndc.x + 1.0) * 0.5 * width as f32,
+                                    (1.0 - ndc.y) * 0.5 * height as f32,
+                                )
+                            })
+                            .collect();
+
+                        // Same lighting as the default material, without the specular term.
+                        let center = na::center(&na::center(&world[0], &world[1]), &world[2]);
+                        let light_dir = (view * (light_pos - center)).normalize();
+                        let lambertian = normal.normalize().dot(&light_dir).max(0.0);
+                        let color = data.color() * ((1.0 + lambertian) / 3.0);
+                        let depth = -(local[0].z + local[1].z + local[2].z) / 3.0;
+
+                        polygons.push(SvgPolygon {
+                            depth,
+                            points,
+                            color,
+                            alpha: data.alpha(),
+                        });
+                    }
+                })
+            })
+        });
+
+    // Painter's algorithm: draw the farthest polygons first.
+    polygons.sort_by(|a, b| b.depth.partial_cmp(&a.depth).unwrap_or(Ordering::Equal));
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>"
+    );
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+        width, height, width, height
+    );
+    let _ = writeln!(
+        svg,
+        "<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+        width,
+        height,
+        svg_color(&Point3::from(*background))
+    );
+
+    for polygon in polygons {
+        let _ = write!(svg, "<polygon points=\"");
+        for (i, pt) in polygon.points.iter().enumerate() {
+            let sep = if i == 0 { "" } else { " " };
+            let _ = write!(svg, "{}{:.3},{:.3}", sep, pt.x, pt.y);
+        }
+        let _ = write!(svg, "\" fill=\"{}\"", svg_color(&polygon.color));
+        if polygon.alpha < 1.0 {
+            let _ = write!(svg, " fill-opacity=\"{:.3}\"", polygon.alpha);
+        }
+        let _ = writeln!(svg, "/>");
+    }
+
+    let _ = writeln!(svg, "</svg>");
+
+    svg
+}
+
+fn svg_color(color: &Point3<f32>) -> String {
+    let to_u8 = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!(
+        "rgb({},{},{})",
+        to_u8(color.x),
+        to_u8(color.y),
+        to_u8(color.z)
+    )
+}
+
+// Clips a camera-space polygon against the near and far planes (Sutherland-Hodgman).
+fn clip_polygon(polygon: &[Point3<f32>], znear: f32, zfar: f32) -> Vec<Point3<f32>> {
+    let near = clip_polygon_against(polygon.to_vec(), |p| -p.z - znear);
+    clip_polygon_against(near, |p| zfar + p.z)
+}
+
+// Keeps the part of `polygon` where `dist` is positive.
+fn clip_polygon_against(
+    polygon: Vec<Point3<f32>>,
+    dist: impl Fn(&Point3<f32>) -> f32,
+) -> Vec<Point3<f32>> {
+    let mut res = Vec::with_capacity(polygon.len() + 1);
+
+    for i in 0..polygon.len() {
+        let a = &polygon[i];
+        let b = &polygon[(i + 1) % polygon.len()];
+        let (da, db) = (dist(a), dist(b));
+
+        if da >= 0.0 {
+            res.push(*a);
+        }
+
+        if (da >= 0.0) != (db >= 0.0) {
+            let t = da / (da - db);
+            res.push(a + (b - a) * t);
+        }
+    }
+
+    res
+}
@@ -0,0 +1,347 @@
+//! Recording of the rendered frames to an H.264 encoded MP4 video.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use bytes::Bytes;
+use image::{ImageBuffer, Rgb};
+use instant::Instant;
+use mp4::{AvcConfig, MediaConfig, Mp4Config, Mp4Sample, Mp4Writer, TrackConfig, TrackType};
+use openh264::encoder::{Encoder, EncoderConfig};
+use openh264::formats::RBGYUVConverter;
+
+/// The number of time units per second of the recorded video track.
+const TIMESCALE: u32 = 1000;
+
+/// An error raised while starting or running a video recording.
+#[derive(Debug)]
+pub enum RecordingError {
+    /// The video file could not be created or written.
+    Io(io::Error),
+    /// The frames could not be encoded.
+    Encoder(String),
+    /// The encoded frames could not be written to the MP4 container.
+    Container(String),
+}
+
+impl fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            RecordingError::Io(ref e) => write!(f, "video file error: {}", e),
+            RecordingError::Encoder(ref e) => write!(f, "video encoding error: {}", e),
+            RecordingError::Container(ref e) => write!(f, "MP4 error: {}", e),
+        }
+    }
+}
+
+impl Error for RecordingError {}
+
+impl From<io::Error> for RecordingError {
+    fn from(e: io::Error) -> RecordingError {
+        RecordingError::Io(e)
+    }
+}
+
+impl From<openh264::Error> for RecordingError {
+    fn from(e: openh264::Error) -> RecordingError {
+        RecordingError::Encoder(e.to_string())
+    }
+}
+
+impl From<mp4::Error> for RecordingError {
+    fn from(e: mp4::Error) -> RecordingError {
+        RecordingError::Container(e.to_string())
+    }
+}
+
+/// A recording of rendered frames to an MP4 video, at most `fps` frames per second.
+///
+/// The frames are encoded and written by a separate thread to avoid slowing down the rendering.
+pub(crate) struct VideoRecording {
+    width: u32,
+    height: u32,
+    frame_duration: Duration,
+    start: Instant,
+    next_frame: Instant,
+    sender: Option<Sender<(Duration, ImageBuffer<Rgb<u8>, Vec<u8>>)>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl VideoRecording {
+    /// Starts a recording of frames of size `width * height` to the file at `path`.
+    ///
+    /// H.264 requires even dimensions, so `width` and `height` are rounded down to even numbers.
+    /// Fails with `io::ErrorKind::InvalidInput` if `fps` is zero.
+    pub fn new(
+        path: &Path,
+        fps: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<VideoRecording, RecordingError> {
+        if fps == 0 {
+            return Err(RecordingError::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot record a video at 0 frames per second",
+            )));
+        }
+
+        let (width, height) = (width & !1, height & !1);
+        let file = BufWriter::new(File::create(path)?);
+
+        // The encoder is created and used by the writer thread only, so it does not need to be
+        // `Send`. Its creation result is sent back before the first frame.
+        let (init_sender, init_receiver) = mpsc::channel();
+        let (sender, receiver) = mpsc::channel::<(Duration, ImageBuffer<Rgb<u8>, Vec<u8>>)>();
+        let writer = thread::spawn(move || {
+            let mut encoder = match VideoEncoder::new(file, width, height, fps) {
+                Ok(encoder) => {
+                    let _ = init_sender.send(Ok(()));
+                    encoder
+                }
+                Err(e) => {
+                    let _ = init_sender.send(Err(e));
+                    return;
+                }
+            };
+
+            for (time, image) in receiver.iter() {
+                if let Err(e) = encoder.push_frame(time, &image) {
+                    eprintln!("Failed to record a video frame: {}", e);
+                    return;
+                }
+            }
+
+            if let Err(e) = encoder.finish() {
+                eprintln!("Failed to finalize the video: {}", e);
+            }
+        });
+
+        init_receiver.recv().unwrap_or_else(|_| {
+            Err(RecordingError::Encoder(
+                "the encoder thread panicked".to_string(),
+            ))
+        })?;
+
+        let start = Instant::now();
+
+        Ok(VideoRecording {
+            width,
+            height,
+            frame_duration: Duration::from_secs(1) / fps,
+            start,
+            next_frame: start,
+            sender: Some(sender),
+            writer: Some(writer),
+        })
+    }
+
+    /// The size of the recorded frames.
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Whether the next rendered frame has to be recorded, i.e., if it would not exceed the
+    /// frame rate of the video.
+    pub fn needs_frame(&self) -> bool {
+        Instant::now() >= self.next_frame
+    }
+
+    /// Records a rendered frame, of the size of the video.
+    pub fn push_frame(&mut self, image: ImageBuffer<Rgb<u8>, Vec<u8>>) {
+        let now = Instant::now();
+
+        // A slow frame delays the next one instead of making the following frames catch up.
+        self.next_frame = (self.next_frame + self.frame_duration).max(now);
+
+        if let Some(ref sender) = self.sender {
+            let _ = sender.send((now.duration_since(self.start), image));
+        }
+    }
+}
+
+impl Drop for VideoRecording {
+    fn drop(&mut self) {
+        // Close the channel, and wait for all the pending frames to be written.
+        self.sender = None;
+
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// Encodes frames with H.264 and writes them to an MP4 file.
+struct VideoEncoder {
+    encoder: Encoder,
+    // The YUV frame converted from the RGB image, reused for all the frames.
+    yuv: RBGYUVConverter,
+    mp4: Mp4Writer<BufWriter<File>>,
+    width: u32,
+    height: u32,
+    // The duration of a frame at the target frame rate, in units of `TIMESCALE`.
+    frame_duration: u64,
+    has_track: bool,
+    // The last encoded frame, written once the start time of the next one gives its duration.
+    pending: Option<(u64, bool, Vec<u8>)>,
+}
+
+impl VideoEncoder {
+    fn new(
+        file: BufWriter<File>,
+        width: u32,
+        height: u32,
+        fps: u32,
+    ) -> Result<Self, RecordingError> {
+        let encoder = Encoder::with_config(EncoderConfig::new(width, height))?;
+        let config = Mp4Config {
+            major_brand: "isom".parse()?,
+            minor_version: 512,
+            compatible_brands: vec!["isom".parse()?, "avc1".parse()?, "mp41".parse()?],
+            timescale: TIMESCALE,
+        };
+        let mp4 = Mp4Writer::write_start(file, &config)?;
+
+        Ok(VideoEncoder {
+            encoder,
+            yuv: RBGYUVConverter::new(width as usize, height as usize),
+            mp4,
+            width,
+            height,
+            frame_duration: (TIMESCALE / fps).max(1) as u64,
+            has_track: false,
+            pending: None,
+        })
+    }
+
+    fn push_frame(
+        &mut self,
+        time: Duration,
+        image: &ImageBuffer<Rgb<u8>, Vec<u8>>,
+    ) -> Result<(), RecordingError> {
+        self.yuv.convert(image.as_raw());
+        let bitstream = self.encoder.encode(&self.yuv)?.to_vec();
+
+        // The encoder outputs Annex B NAL units, whereas MP4 stores the parameter sets in the
+        // track configuration, and the other units prefixed by their length.
+        let mut sample = Vec::with_capacity(bitstream.len());
+        let mut sps = None;
+        let mut pps = None;
+        let mut is_sync = false;
+
+        for nal in nal_units(&bitstream) {
+            match nal[0] & 0x1f {
+                7 => sps = Some(nal.to_vec()),
+                8 => pps = Some(nal.to_vec()),
+                unit_type => {
+                    is_sync |= unit_type == 5;
+                    sample.extend_from_slice(&(nal.len() as u32).to_be_bytes());
+                    sample.extend_from_slice(nal);
+                }
+            }
+        }
+
+        if !self.has_track {
+            match (sps, pps) {
+                (Some(seq_param_set), Some(pic_param_set)) => {
+                    self.mp4.add_track(&TrackConfig {
+                        track_type: TrackType::Video,
+                        timescale: TIMESCALE,
+                        language: "und".to_string(),
+                        media_conf: MediaConfig::AvcConfig(AvcConfig {
+                            width: self.width as u16,
+                            height: self.height as u16,
+                            seq_param_set,
+                            pic_param_set,
+                        }),
+                    })?;
+                    self.has_track = true;
+                }
+                _ => {
+                    return Err(RecordingError::Encoder(
+                        "the first frame has no parameter sets".to_string(),
+                    ))
+                }
+            }
+        }
+
+        let start_time = time.as_millis() as u64 * TIMESCALE as u64 / 1000;
+        self.write_pending(start_time)?;
+
+        if !sample.is_empty() {
+            self.pending = Some((start_time, is_sync, sample));
+        }
+
+        Ok(())
+    }
+
+    // Writes the pending frame, lasting until `end_time`.
+    fn write_pending(&mut self, end_time: u64) -> Result<(), RecordingError> {
+        if let Some((start_time, is_sync, bytes)) = self.pending.take() {
+            self.mp4.write_sample(
+                1,
+                &Mp4Sample {
+                    start_time,
+                    duration: end_time.saturating_sub(start_time).max(1) as u32,
+                    rendering_offset: 0,
+                    is_sync,
+                    bytes: Bytes::from(bytes),
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), RecordingError> {
+        // The last frame lasts one period of the target frame rate.
+        let end_time = self
+            .pending
+            .as_ref()
+            .map(|pending| pending.0 + self.frame_duration)
+            .unwrap_or(0);
+        self.write_pending(end_time)?;
+        self.mp4.write_end()?;
+        Ok(())
+    }
+}
+
+/// The NAL units of an Annex B byte stream, without their start codes.
+fn nal_units(stream: &[u8]) -> Vec<&[u8]> {
+    let mut units = Vec::new();
+    let mut start = None;
+    let mut i = 0;
+
+    while i + 3 <= stream.len() {
+        if stream[i] == 0 && stream[i + 1] == 0 && stream[i + 2] == 1 {
+            if let Some(start) = start {
+                // A 4 bytes start code begins with an extra zero.
+                let mut end = i;
+                while end > start && stream[end - 1] == 0 {
+                    end -= 1;
+                }
+                if end > start {
+                    units.push(&stream[start..end]);
+                }
+            }
+
+            i += 3;
+            start = Some(i);
+        } else {
+            i += 1;
+        }
+    }
+
+    if let Some(start) = start {
+        if start < stream.len() {
+            units.push(&stream[start..]);
+        }
+    }
+
+    units
+}
@@ -0,0 +1,46 @@
+use crate::camera::Camera;
+
+/// The maximum number of viewports supported by `Window::set_split_screen`.
+pub const MAX_VIEWPORTS: usize = 4;
+
+/// A region of the window rendering the scene from its own camera.
+///
+/// See `Window::set_split_screen`.
+pub struct ViewportConfig {
+    /// The region of the window covered by this viewport, as `(x, y, width, height)` fractions
+    /// of the window size.
+    ///
+    /// The origin is the top-left corner of the window, e.g., `(0.5, 0.0, 0.5, 0.5)` is the
+    /// top-right quarter of the window.
+    pub rect: (f32, f32, f32, f32),
+    /// The camera this viewport renders the scene from.
+    pub camera: Box<dyn Camera>,
+}
+
+impl ViewportConfig {
+    /// Creates a viewport covering `rect` and rendering the scene from `camera`.
+    pub fn new(rect: (f32, f32, f32, f32), camera: Box<dyn Camera>) -> ViewportConfig {
+        ViewportConfig { rect, camera }
+    }
+
+    /// Whether the point at the given fractions of the window size, from its top-left corner,
+    /// lies inside of this viewport.
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        let (rx, ry, rw, rh) = self.rect;
+        x >= rx && x < rx + rw && y >= ry && y < ry + rh
+    }
+
+    /// The region covered by this viewport in a framebuffer of size `w * h`, as
+    /// `(x, y, width, height)` pixels from the bottom-left corner of the framebuffer.
+    pub(crate) fn pixel_rect(&self, w: u32, h: u32) -> (i32, i32, i32, i32) {
+        let (rx, ry, rw, rh) = self.rect;
+        let (w, h) = (w as f32, h as f32);
+
+        (
+            (rx * w) as i32,
+            ((1.0 - ry - rh) * h) as i32,
+            (rw * w) as i32,
+            (rh * h) as i32,
+        )
+    }
+}
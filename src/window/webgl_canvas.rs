@@ -1,20 +1,34 @@
 #![allow(unused_results)]
 
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ops::DerefMut;
 use std::rc::Rc;
 use std::sync::mpsc::Sender;
+use std::time::Duration;
 
 use crate::context::Context;
-use crate::event::{Action, Key, Modifiers, MouseButton, TouchAction, WindowEvent};
-use crate::window::{AbstractCanvas, CanvasSetup};
+use crate::event::{
+    Action, GamepadAxis, GamepadButton, Key, Modifiers, MouseButton, TouchAction, WindowEvent,
+};
+use crate::window::canvas::{self, EventFilter};
+use crate::window::double_click::DoubleClickDetector;
+use crate::window::{
+    AbstractCanvas, CanvasSetup, CursorStyle, DroppedFile, GamepadState, NumSamples,
+};
 use image::{GenericImage, Pixel};
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::{JsCast, JsValue};
 use web_sys::{
-    EventTarget, HtmlCanvasElement, KeyboardEvent, MouseEvent, TouchEvent, UiEvent, WheelEvent,
+    ClipboardEvent, DragEvent, EventTarget, FileReader, HtmlCanvasElement, KeyboardEvent,
+    MouseEvent, ProgressEvent, TouchEvent, UiEvent, WebGlContextAttributes, WheelEvent,
 };
 
+// The approximate height, in pixels, of a line scrolled by a `wheel` event in
+// `DOM_DELTA_LINE` mode. Browsers typically scroll about 100 pixels, or 3 lines, per
+// notch of a mouse wheel.
+const WHEEL_LINE_HEIGHT: f64 = 100.0 / 3.0;
+
 struct WebGLCanvasData {
     canvas: HtmlCanvasElement,
     cursor_pos: Option<(f64, f64)>,
@@ -24,6 +38,39 @@ struct WebGLCanvasData {
     out_events: Sender<WindowEvent>,
     scale_factor: f64,
     mouse_capture_state: MouseCaptureState,
+    cursor_style: CursorStyle,
+    cursor_hidden: bool,
+    gamepads: Vec<Option<GamepadState>>,
+    gamepad_deadzone: f32,
+    // The resolution set by `set_size`, overriding the size of the canvas on the page.
+    fixed_size: Option<(u32, u32)>,
+    double_click: DoubleClickDetector,
+    event_filter: Option<EventFilter>,
+    // Whether the default action of the browser is prevented for the filtered DOM events.
+    prevent_default_filtered: bool,
+    suppress_context_menu: bool,
+    context_menu_enabled: bool,
+    // The text last pasted into the canvas or copied by `set_clipboard_string`.
+    clipboard: Option<String>,
+    // The files dropped onto the canvas and not taken yet, with the identifier of the next one.
+    dropped_files: HashMap<u32, DroppedFile>,
+    next_dropped_file: u32,
+}
+
+impl WebGLCanvasData {
+    // Queues `event` unless the event filter rejects it. Returns whether the event was kept.
+    fn push_event(&mut self, event: WindowEvent) -> bool {
+        let keep = self
+            .event_filter
+            .as_mut()
+            .map_or(true, |filter| filter(&event));
+
+        if keep {
+            self.pending_events.push(event);
+        }
+
+        keep
+    }
 }
 
 #[derive(PartialEq, Eq)]
@@ -39,6 +86,8 @@ enum EventListener {
     Touch(EventListenerHandle<dyn FnMut(TouchEvent)>),
     Wheel(EventListenerHandle<dyn FnMut(WheelEvent)>),
     Keyboard(EventListenerHandle<dyn FnMut(KeyboardEvent)>),
+    Drag(EventListenerHandle<dyn FnMut(DragEvent)>),
+    Clipboard(EventListenerHandle<dyn FnMut(ClipboardEvent)>),
 }
 
 struct EventListenerHandle<T: ?Sized> {
@@ -100,13 +149,11 @@ impl Drop for WebGLCanvas {
     }
 }
 
-impl AbstractCanvas for WebGLCanvas {
-    fn open(
-        _: &str,
-        _: bool,
-        _: u32,
-        _: u32,
-        _setup: Option<CanvasSetup>,
+impl WebGLCanvas {
+    fn open_canvas(
+        canvas: HtmlCanvasElement,
+        fixed_size: Option<(u32, u32)>,
+        setup: Option<CanvasSetup>,
         out_events: Sender<WindowEvent>,
     ) -> Self {
         fn get_scale_factor() -> f64 {
@@ -114,26 +161,32 @@ impl AbstractCanvas for WebGLCanvas {
         }
 
         let window = web_sys::window().unwrap();
-        let document = window.document().unwrap();
         let initial_scale_factor = get_scale_factor();
-        let canvas: HtmlCanvasElement = document
-            .get_element_by_id("canvas")
-            .expect("No canvas found.")
-            .dyn_into::<HtmlCanvasElement>()
-            .expect("Canvas element is not an actual canvas.");
+
+        // WebGL only supports enabling or disabling antialiasing, the number of samples is
+        // chosen by the browser.
+        let antialias = setup.map_or(false, |setup| setup.samples != NumSamples::Zero);
+        let mut context_attributes = WebGlContextAttributes::new();
+        context_attributes.antialias(antialias);
 
         Context::init(|| {
             let webgl_context = canvas
-                .get_context("webgl")
+                .get_context_with_context_options("webgl", &context_attributes)
                 .unwrap()
                 .unwrap()
                 .dyn_into::<web_sys::WebGlRenderingContext>()
                 .unwrap();
+            // Needed to draw the meshes with 32-bits indices.
+            let _ = webgl_context.get_extension("OES_element_index_uint");
             glow::Context::from_webgl1_context(webgl_context)
         });
 
-        let w = (canvas.offset_width() as f64 * initial_scale_factor) as u32;
-        let h = (canvas.offset_height() as f64 * initial_scale_factor) as u32;
+        let (w, h) = fixed_size.unwrap_or_else(|| {
+            (
+                (canvas.offset_width() as f64 * initial_scale_factor) as u32,
+                (canvas.offset_height() as f64 * initial_scale_factor) as u32,
+            )
+        });
         canvas.set_width(w);
         canvas.set_height(h);
         // We set tabIndex to make the canvas focusable to allow keyboard
@@ -144,6 +197,8 @@ impl AbstractCanvas for WebGLCanvas {
         if canvas.tab_index() <= 0 {
             canvas.set_tab_index(0)
         }
+        // Receive the keyboard events right away, without having to click the canvas first.
+        let _ = canvas.focus();
 
         let data = Rc::new(RefCell::new(WebGLCanvasData {
             canvas,
@@ -154,6 +209,19 @@ impl AbstractCanvas for WebGLCanvas {
             out_events,
             scale_factor: initial_scale_factor,
             mouse_capture_state: MouseCaptureState::NotCaptured,
+            cursor_style: CursorStyle::Default,
+            cursor_hidden: false,
+            gamepads: Vec::new(),
+            gamepad_deadzone: 0.0,
+            fixed_size,
+            double_click: DoubleClickDetector::new(),
+            event_filter: None,
+            prevent_default_filtered: false,
+            suppress_context_menu: false,
+            context_menu_enabled: false,
+            clipboard: None,
+            dropped_files: HashMap::new(),
+            next_dropped_file: 0,
         }));
 
         let mut event_listeners = Vec::new();
@@ -167,16 +235,19 @@ impl AbstractCanvas for WebGLCanvas {
             // factor.
             let scale_factor = get_scale_factor();
             edata.scale_factor = scale_factor;
+
+            if edata.fixed_size.is_some() {
+                return;
+            }
+
             let (w, h) = (
                 (edata.canvas.offset_width() as f64 * scale_factor) as u32,
                 (edata.canvas.offset_height() as f64 * scale_factor) as u32,
             );
             edata.canvas.set_width(w);
             edata.canvas.set_height(h);
-            let _ = edata
-                .pending_events
-                .push(WindowEvent::FramebufferSize(w, h));
-            let _ = edata.pending_events.push(WindowEvent::Size(w, h));
+            let _ = edata.push_event(WindowEvent::FramebufferSize(w, h));
+            let _ = edata.push_event(WindowEvent::Size(w, h));
         }) as Box<dyn FnMut(_)>);
         let listener = EventListenerHandle::new(&window, "resize", callback);
         event_listeners.push(EventListener::Ui(listener));
@@ -204,12 +275,27 @@ impl AbstractCanvas for WebGLCanvas {
             }
             edata.mouse_capture_state = MouseCaptureState::Captured;
             let button = translate_mouse_button(&e);
-            let _ = edata.pending_events.push(WindowEvent::MouseButton(
-                button,
-                Action::Press,
-                translate_mouse_modifiers(&e),
-            ));
+            let modifiers = translate_mouse_modifiers(&e);
+            let kept = edata.push_event(WindowEvent::MouseButton(button, Action::Press, modifiers));
+            let prevent_default = !kept && edata.prevent_default_filtered;
             edata.button_states[button as usize] = Action::Press;
+
+            if prevent_default {
+                e.prevent_default();
+            }
+
+            if button == MouseButton::Button2 {
+                // The context menu is opened by a separate event following the press.
+                edata.suppress_context_menu = prevent_default;
+            }
+
+            let bounding_client_rect = edata.canvas.get_bounding_client_rect();
+            let x = (e.client_x() as f64 - bounding_client_rect.x()) * edata.scale_factor;
+            let y = (e.client_y() as f64 - bounding_client_rect.y()) * edata.scale_factor;
+
+            if edata.double_click.press(button, (x, y)) {
+                let _ = edata.push_event(WindowEvent::DoubleClick(button, x, y, modifiers));
+            }
         }) as Box<dyn FnMut(_)>);
         let listener = EventListenerHandle::new(&window, "mousedown", callback);
         event_listeners.push(EventListener::Mouse(listener));
@@ -234,11 +320,15 @@ impl AbstractCanvas for WebGLCanvas {
                 }
             }
             let button = translate_mouse_button(&e);
-            let _ = edata.pending_events.push(WindowEvent::MouseButton(
+            let kept = edata.push_event(WindowEvent::MouseButton(
                 button,
                 Action::Release,
                 translate_mouse_modifiers(&e),
             ));
+
+            if !kept && edata.prevent_default_filtered {
+                e.prevent_default();
+            }
             edata.button_states[button as usize] = Action::Release;
             if edata
                 .button_states
@@ -251,6 +341,20 @@ impl AbstractCanvas for WebGLCanvas {
         let listener = EventListenerHandle::new(&window, "mouseup", callback);
         event_listeners.push(EventListener::Mouse(listener));
 
+        let edata = data.clone();
+        let callback = Closure::wrap(Box::new(move |e: MouseEvent| {
+            let mut edata = edata.borrow_mut();
+
+            // The context menu would pop up at the end of every right-drag of the camera.
+            if !edata.context_menu_enabled || edata.suppress_context_menu {
+                e.prevent_default();
+            }
+
+            edata.suppress_context_menu = false;
+        }) as Box<dyn FnMut(_)>);
+        let listener = EventListenerHandle::new(&data.borrow().canvas, "contextmenu", callback);
+        event_listeners.push(EventListener::Mouse(listener));
+
         let edata = data.clone();
         let callback = Closure::wrap(Box::new(move |e: MouseEvent| {
             let mut edata = edata.borrow_mut();
@@ -271,15 +375,31 @@ impl AbstractCanvas for WebGLCanvas {
                 }
             }
             let scale_factor = edata.scale_factor;
+
+            // With a locked pointer, report the displacement instead of the position.
+            let locked = web_sys::window()
+                .and_then(|w| w.document())
+                .and_then(|d| d.pointer_lock_element())
+                .map_or(false, |elt| {
+                    let elt: &JsValue = elt.as_ref();
+                    let canvas: &JsValue = edata.canvas.as_ref();
+                    elt == canvas
+                });
+
+            if locked {
+                let _ = edata.push_event(WindowEvent::CursorPos(
+                    e.movement_x() as f64 * scale_factor,
+                    e.movement_y() as f64 * scale_factor,
+                    translate_mouse_modifiers(&e),
+                ));
+                return;
+            }
+
             let bounding_client_rect = edata.canvas.get_bounding_client_rect();
             let x = (e.client_x() as f64 - bounding_client_rect.x()) * scale_factor;
             let y = (e.client_y() as f64 - bounding_client_rect.y()) * scale_factor;
             edata.cursor_pos = Some((x, y));
-            let _ = edata.pending_events.push(WindowEvent::CursorPos(
-                x,
-                y,
-                translate_mouse_modifiers(&e),
-            ));
+            let _ = edata.push_event(WindowEvent::CursorPos(x, y, translate_mouse_modifiers(&e)));
         }) as Box<dyn FnMut(_)>);
         let listener = EventListenerHandle::new(&window, "mousemove", callback);
         event_listeners.push(EventListener::Mouse(listener));
@@ -291,7 +411,7 @@ impl AbstractCanvas for WebGLCanvas {
             let changed_touches = e.changed_touches();
             for i in 0..changed_touches.length() {
                 let t = changed_touches.get(i).unwrap();
-                let _ = edata.pending_events.push(WindowEvent::Touch(
+                let _ = edata.push_event(WindowEvent::Touch(
                     t.identifier() as u64,
                     t.client_x() as f64 * scale_factor,
                     t.client_y() as f64 * scale_factor,
@@ -310,7 +430,7 @@ impl AbstractCanvas for WebGLCanvas {
             let changed_touches = e.changed_touches();
             for i in 0..changed_touches.length() {
                 let t = changed_touches.get(i).unwrap();
-                let _ = edata.pending_events.push(WindowEvent::Touch(
+                let _ = edata.push_event(WindowEvent::Touch(
                     t.identifier() as u64,
                     t.client_x() as f64 * scale_factor,
                     t.client_y() as f64 * scale_factor,
@@ -329,7 +449,7 @@ impl AbstractCanvas for WebGLCanvas {
             let changed_touches = e.changed_touches();
             for i in 0..changed_touches.length() {
                 let t = changed_touches.get(i).unwrap();
-                let _ = edata.pending_events.push(WindowEvent::Touch(
+                let _ = edata.push_event(WindowEvent::Touch(
                     t.identifier() as u64,
                     t.client_x() as f64 * scale_factor,
                     t.client_y() as f64 * scale_factor,
@@ -352,7 +472,7 @@ impl AbstractCanvas for WebGLCanvas {
                     t.client_x() as f64 * scale_factor,
                     t.client_y() as f64 * scale_factor,
                 ));
-                let _ = edata.pending_events.push(WindowEvent::Touch(
+                let _ = edata.push_event(WindowEvent::Touch(
                     t.identifier() as u64,
                     t.client_x() as f64 * scale_factor,
                     t.client_y() as f64 * scale_factor,
@@ -366,26 +486,25 @@ impl AbstractCanvas for WebGLCanvas {
 
         let edata = data.clone();
         let callback = Closure::wrap(Box::new(move |e: WheelEvent| {
-            let delta_x: f64 = e.delta_x();
-            let delta_y: f64 = e.delta_y();
-            // The values of deltaMode:
-            // 0x00 => DOM_DELTA_PIXEL
-            // 0x01 => DOM_DELTA_LINE
-            // 0x02 => DOM_DELTA_PAGE
-            let delta_mode = e.delta_mode();
-            let (delta_x, delta_y) = match delta_mode {
-                // It doesn't really make much sense to scroll a "page" in
-                // case of scrolling the cameras so we treat DOM_DELTA_PAGE
-                // the same way as DOM_DELTA_LINE.
-                0x01 | 0x02 => (delta_x * 10.0, delta_y * 10.0),
-                _ => (delta_x, delta_y),
+            // Normalize every delta to pixels so that line-based mouse wheels and pixel-based
+            // trackpads scroll by similar amounts.
+            let scale = match e.delta_mode() {
+                WheelEvent::DOM_DELTA_LINE => WHEEL_LINE_HEIGHT,
+                WheelEvent::DOM_DELTA_PAGE => edata.borrow().canvas.client_height() as f64,
+                _ => 1.0,
             };
+            let delta_x = e.delta_x() * scale;
+            let delta_y = e.delta_y() * scale;
             let mut edata = edata.borrow_mut();
-            let _ = edata.pending_events.push(WindowEvent::Scroll(
+            let kept = edata.push_event(WindowEvent::Scroll(
                 delta_x / 10.0,
                 -delta_y / 10.0,
                 translate_mouse_modifiers(&e),
             ));
+
+            if !kept && edata.prevent_default_filtered {
+                e.prevent_default();
+            }
         }) as Box<dyn FnMut(_)>);
         let listener = EventListenerHandle::new(&data.borrow().canvas, "wheel", callback);
         event_listeners.push(EventListener::Wheel(listener));
@@ -394,12 +513,16 @@ impl AbstractCanvas for WebGLCanvas {
         let callback = Closure::wrap(Box::new(move |e: KeyboardEvent| {
             let mut edata = edata.borrow_mut();
             let key = translate_key(&e);
-            let _ = edata.pending_events.push(WindowEvent::Key(
+            let kept = edata.push_event(WindowEvent::Key(
                 key,
                 Action::Press,
                 translate_key_modifiers(&e),
             ));
             edata.key_states[key as usize] = Action::Press;
+
+            if !kept && edata.prevent_default_filtered {
+                e.prevent_default();
+            }
         }) as Box<dyn FnMut(_)>);
         let listener = EventListenerHandle::new(&data.borrow().canvas, "keydown", callback);
         event_listeners.push(EventListener::Keyboard(listener));
@@ -408,29 +531,169 @@ impl AbstractCanvas for WebGLCanvas {
         let callback = Closure::wrap(Box::new(move |e: KeyboardEvent| {
             let mut edata = edata.borrow_mut();
             let key = translate_key(&e);
-            let _ = edata.pending_events.push(WindowEvent::Key(
+            let kept = edata.push_event(WindowEvent::Key(
                 key,
                 Action::Release,
                 translate_key_modifiers(&e),
             ));
             edata.key_states[key as usize] = Action::Release;
+
+            if !kept && edata.prevent_default_filtered {
+                e.prevent_default();
+            }
         }) as Box<dyn FnMut(_)>);
         let listener = EventListenerHandle::new(&data.borrow().canvas, "keyup", callback);
         event_listeners.push(EventListener::Keyboard(listener));
 
+        // The default behavior of the browser must be prevented for the canvas to accept drops.
+        let callback = Closure::wrap(Box::new(move |e: DragEvent| {
+            e.prevent_default();
+        }) as Box<dyn FnMut(_)>);
+        let listener = EventListenerHandle::new(&data.borrow().canvas, "dragover", callback);
+        event_listeners.push(EventListener::Drag(listener));
+
+        let edata = data.clone();
+        let callback = Closure::wrap(Box::new(move |e: DragEvent| {
+            e.prevent_default();
+
+            let files = match e.data_transfer().and_then(|t| t.files()) {
+                Some(files) => files,
+                None => return,
+            };
+
+            for i in 0..files.length() {
+                let file = match files.item(i) {
+                    Some(file) => file,
+                    None => continue,
+                };
+                let reader = match FileReader::new() {
+                    Ok(reader) => reader,
+                    Err(_) => continue,
+                };
+
+                // The file is read asynchronously: its event is emitted by the next
+                // `poll_events` following the end of the reading.
+                let name = file.name();
+                let fdata = edata.clone();
+                let ereader = reader.clone();
+                let onload = Closure::once_into_js(move |_: ProgressEvent| {
+                    if let Ok(result) = ereader.result() {
+                        let bytes = js_sys::Uint8Array::new(&result).to_vec();
+                        let mut fdata = fdata.borrow_mut();
+                        let id = fdata.next_dropped_file;
+
+                        if fdata.push_event(WindowEvent::FileDropped(id)) {
+                            fdata.next_dropped_file = id.wrapping_add(1);
+                            let _ = fdata.dropped_files.insert(id, DroppedFile { name, bytes });
+                        }
+                    }
+                });
+
+                reader.set_onload(Some(onload.unchecked_ref()));
+                let _ = reader.read_as_array_buffer(&file);
+            }
+        }) as Box<dyn FnMut(_)>);
+        let listener = EventListenerHandle::new(&data.borrow().canvas, "drop", callback);
+        event_listeners.push(EventListener::Drag(listener));
+
+        // Browsers only fire this event, and grant access to the clipboard, when the user
+        // actually pastes into the focused canvas.
+        let edata = data.clone();
+        let callback = Closure::wrap(Box::new(move |e: ClipboardEvent| {
+            if let Some(text) = e.clipboard_data().and_then(|d| d.get_data("text").ok()) {
+                e.prevent_default();
+                let mut edata = edata.borrow_mut();
+                edata.clipboard = Some(text);
+                let _ = edata.push_event(WindowEvent::Paste);
+            }
+        }) as Box<dyn FnMut(_)>);
+        let listener = EventListenerHandle::new(&data.borrow().canvas, "paste", callback);
+        event_listeners.push(EventListener::Clipboard(listener));
+
         WebGLCanvas {
             data,
             event_listeners,
         }
     }
 
+    fn update_cursor(&self) {
+        let data = self.data.borrow();
+        let cursor = if data.cursor_hidden {
+            "none"
+        } else {
+            match data.cursor_style {
+                CursorStyle::Default => "",
+                CursorStyle::Crosshair => "crosshair",
+                CursorStyle::Hand => "pointer",
+                CursorStyle::Move => "move",
+                CursorStyle::Text => "text",
+                CursorStyle::Wait => "wait",
+                CursorStyle::Help => "help",
+                CursorStyle::NotAllowed => "not-allowed",
+                CursorStyle::Grab => "grab",
+                CursorStyle::Grabbing => "grabbing",
+                CursorStyle::EwResize => "ew-resize",
+                CursorStyle::NsResize => "ns-resize",
+            }
+        };
+        let _ = data.canvas.style().set_property("cursor", cursor);
+    }
+
+    // Uses the Pointer Lock API. Note that browsers only grant the lock in response to a user
+    // action (e.g. a click), and release it when the user presses escape.
+    fn set_pointer_lock(&self, locked: bool) {
+        if locked {
+            self.data.borrow().canvas.request_pointer_lock();
+        } else if let Some(document) = web_sys::window().and_then(|w| w.document()) {
+            document.exit_pointer_lock();
+        }
+    }
+}
+
+impl AbstractCanvas for WebGLCanvas {
+    fn open(
+        _: &str,
+        _: bool,
+        _: u32,
+        _: u32,
+        setup: Option<CanvasSetup>,
+        out_events: Sender<WindowEvent>,
+    ) -> Self {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let canvas: HtmlCanvasElement = document
+            .get_element_by_id("canvas")
+            .expect("No canvas found.")
+            .dyn_into::<HtmlCanvasElement>()
+            .expect("Canvas element is not an actual canvas.");
+
+        WebGLCanvas::open_canvas(canvas, None, setup, out_events)
+    }
+
+    fn open_headless(
+        width: u32,
+        height: u32,
+        setup: Option<CanvasSetup>,
+        out_events: Sender<WindowEvent>,
+    ) -> Self {
+        // A canvas which is never inserted into the page.
+        let document = web_sys::window().unwrap().document().unwrap();
+        let canvas: HtmlCanvasElement = document
+            .create_element("canvas")
+            .unwrap()
+            .dyn_into::<HtmlCanvasElement>()
+            .unwrap();
+
+        WebGLCanvas::open_canvas(canvas, Some((width, height)), setup, out_events)
+    }
+
     fn render_loop(mut callback: impl FnMut(f64) -> bool + 'static) {
         // See https://rustwasm.github.io/docs/wasm-bindgen/examples/request-animation-frame.html
         if let Some(window) = web_sys::window() {
             let f = Rc::new(RefCell::new(None));
             let g: Rc<RefCell<Option<Closure<_>>>> = f.clone();
-            *g.borrow_mut() = Some(Closure::wrap(Box::new(move || {
-                if callback(0.0) {
+            // The closure receives the frame timestamp, in milliseconds.
+            *g.borrow_mut() = Some(Closure::wrap(Box::new(move |timestamp: f64| {
+                if callback(timestamp) {
                     let _ = window.request_animation_frame(
                         f.borrow().as_ref().unwrap().as_ref().unchecked_ref(),
                     );
@@ -438,7 +701,7 @@ impl AbstractCanvas for WebGLCanvas {
                     // Drop the closure.
                     f.borrow_mut().take();
                 }
-            }) as Box<dyn FnMut()>));
+            }) as Box<dyn FnMut(f64)>));
 
             let _ = web_sys::window()
                 .unwrap()
@@ -454,6 +717,8 @@ impl AbstractCanvas for WebGLCanvas {
         let mut data_borrow = self.data.borrow_mut();
         let data = data_borrow.deref_mut();
 
+        poll_gamepads(data);
+
         for e in data.pending_events.drain(..) {
             let _ = data.out_events.send(e);
         }
@@ -464,6 +729,10 @@ impl AbstractCanvas for WebGLCanvas {
     }
 
     fn size(&self) -> (u32, u32) {
+        if let Some(size) = self.data.borrow().fixed_size {
+            return size;
+        }
+
         let scale_factor = self.scale_factor();
         (
             (self.data.borrow().canvas.offset_width() as f64 * scale_factor) as u32,
@@ -483,18 +752,53 @@ impl AbstractCanvas for WebGLCanvas {
         // Not supported.
     }
 
-    fn set_cursor_grab(&self, _: bool) {
-        // Not supported.
+    fn set_cursor_grab(&self, grab: bool) {
+        // Browsers cannot confine a visible cursor to an element, so this locks the pointer
+        // like `lock_cursor` does.
+        self.set_pointer_lock(grab)
     }
 
     fn set_cursor_position(&self, _: f64, _: f64) {
         // Not supported.
     }
 
-    fn hide_cursor(&self, _: bool) {
+    fn set_cursor(&self, style: CursorStyle) {
+        self.data.borrow_mut().cursor_style = style;
+        self.update_cursor()
+    }
+
+    fn hide_cursor(&self, hide: bool) {
+        self.data.borrow_mut().cursor_hidden = hide;
+        self.update_cursor()
+    }
+
+    fn lock_cursor(&mut self, locked: bool) {
+        self.set_pointer_lock(locked)
+    }
+
+    fn set_resizable(&mut self, _: bool) {
+        // Not supported.
+    }
+
+    fn set_fullscreen(&mut self, _: bool) {
         // Not supported.
     }
 
+    fn set_size(&mut self, width: u32, height: u32) {
+        let mut data = self.data.borrow_mut();
+        let style = data.canvas.style();
+        let _ = style.set_property("width", &format!("{}px", width as f64 / data.scale_factor));
+        let _ = style.set_property(
+            "height",
+            &format!("{}px", height as f64 / data.scale_factor),
+        );
+        data.canvas.set_width(width);
+        data.canvas.set_height(height);
+        data.fixed_size = Some((width, height));
+        let _ = data.push_event(WindowEvent::FramebufferSize(width, height));
+        let _ = data.push_event(WindowEvent::Size(width, height));
+    }
+
     fn hide(&mut self) {
         // Not supported.
     }
@@ -503,6 +807,65 @@ impl AbstractCanvas for WebGLCanvas {
         // Not supported.
     }
 
+    fn set_double_click_interval(&mut self, interval: Duration) {
+        self.data.borrow_mut().double_click.set_interval(interval)
+    }
+
+    fn set_event_filter(&mut self, filter: Option<EventFilter>) {
+        self.data.borrow_mut().event_filter = filter
+    }
+
+    fn set_prevent_default_on_filtered(&mut self, prevent: bool) {
+        self.data.borrow_mut().prevent_default_filtered = prevent
+    }
+
+    fn set_context_menu_enabled(&mut self, enabled: bool) {
+        self.data.borrow_mut().context_menu_enabled = enabled
+    }
+
+    fn request_focus(&mut self) {
+        let _ = self.data.borrow().canvas.focus();
+    }
+
+    fn clipboard_string(&self) -> Option<String> {
+        self.data.borrow().clipboard.clone()
+    }
+
+    fn set_clipboard_string(&mut self, text: &str) {
+        self.data.borrow_mut().clipboard = Some(text.to_string());
+
+        // `navigator.clipboard` is still an unstable API of `web_sys`. Its promise is ignored,
+        // the browser may deny the access, e.g., if the page does not have the focus.
+        if let Some(navigator) = web_sys::window().map(|w| w.navigator()) {
+            let clipboard = js_sys::Reflect::get(&navigator, &JsValue::from_str("clipboard"));
+            if let Ok(clipboard) = clipboard {
+                let write_text = js_sys::Reflect::get(&clipboard, &JsValue::from_str("writeText"))
+                    .ok()
+                    .and_then(|f| f.dyn_into::<js_sys::Function>().ok());
+                if let Some(write_text) = write_text {
+                    let _ = write_text.call1(&clipboard, &JsValue::from_str(text));
+                }
+            }
+        }
+    }
+
+    fn take_dropped_file(&mut self, id: u32) -> Option<DroppedFile> {
+        self.data.borrow_mut().dropped_files.remove(&id)
+    }
+
+    fn gamepad(&self, index: u32) -> Option<GamepadState> {
+        self.data
+            .borrow()
+            .gamepads
+            .get(index as usize)
+            .cloned()
+            .flatten()
+    }
+
+    fn set_gamepad_deadzone(&mut self, deadzone: f32) {
+        self.data.borrow_mut().gamepad_deadzone = deadzone.max(0.0)
+    }
+
     fn get_mouse_button(&self, button: MouseButton) -> Action {
         self.data.borrow().button_states[button as usize]
     }
@@ -565,6 +928,97 @@ fn translate_key_modifiers(event: &KeyboardEvent) -> Modifiers {
     res
 }
 
+// The Gamepad API has no events for the buttons and axes, so their states are compared to the
+// ones of the previous call.
+fn poll_gamepads(data: &mut WebGLCanvasData) {
+    let gamepads = match web_sys::window().and_then(|w| w.navigator().get_gamepads().ok()) {
+        Some(gamepads) => gamepads,
+        None => return,
+    };
+
+    for i in 0..gamepads.length().max(data.gamepads.len() as u32) {
+        let gamepad = gamepads
+            .get(i)
+            .dyn_into::<web_sys::Gamepad>()
+            .ok()
+            .filter(|gamepad| gamepad.connected());
+
+        if data.gamepads.len() <= i as usize {
+            data.gamepads.resize_with(i as usize + 1, || None);
+        }
+
+        let gamepad = match gamepad {
+            Some(gamepad) => gamepad,
+            None => {
+                if data.gamepads[i as usize].take().is_some() {
+                    let _ = data.push_event(WindowEvent::GamepadDisconnected(i));
+                }
+                continue;
+            }
+        };
+
+        let buttons: Vec<bool> = gamepad
+            .buttons()
+            .iter()
+            .map(|button| {
+                button
+                    .dyn_into::<web_sys::GamepadButton>()
+                    .map_or(false, |button| button.pressed())
+            })
+            .collect();
+        let deadzone = data.gamepad_deadzone;
+        let axes: Vec<f32> = gamepad
+            .axes()
+            .iter()
+            .map(|axis| canvas::apply_deadzone(axis.as_f64().unwrap_or(0.0) as f32, deadzone))
+            .collect();
+
+        if data.gamepads[i as usize].is_none() {
+            let _ = data.push_event(WindowEvent::GamepadConnected(i));
+            data.gamepads[i as usize] = Some(GamepadState {
+                buttons: Vec::new(),
+                axes: Vec::new(),
+            });
+        }
+
+        let state = data.gamepads[i as usize].as_mut().unwrap();
+        // Queued once `state` is not borrowed anymore.
+        let mut events = Vec::new();
+
+        for (j, pressed) in buttons.iter().enumerate() {
+            if state.buttons.get(j).cloned().unwrap_or(false) != *pressed {
+                let action = if *pressed {
+                    Action::Press
+                } else {
+                    Action::Release
+                };
+                events.push(WindowEvent::GamepadButton(
+                    i,
+                    GamepadButton::from_index(j as u32),
+                    action,
+                ));
+            }
+        }
+
+        for (j, axis) in axes.iter().enumerate() {
+            if state.axes.get(j).cloned().unwrap_or(0.0) != *axis {
+                events.push(WindowEvent::GamepadAxis(
+                    i,
+                    GamepadAxis::from_index(j as u32),
+                    *axis,
+                ));
+            }
+        }
+
+        state.buttons = buttons;
+        state.axes = axes;
+
+        for event in events {
+            let _ = data.push_event(event);
+        }
+    }
+}
+
 fn translate_mouse_button(event: &MouseEvent) -> MouseButton {
     match event.button() {
         0 => MouseButton::Button1,
@@ -3,33 +3,57 @@
  * FIXME: this file is too big. Some heavy refactoring need to be done here.
  */
 use std::cell::RefCell;
+use std::f32;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io;
 use std::iter::repeat;
+use std::mem;
 use std::path::Path;
 use std::rc::Rc;
-use std::sync::mpsc::{self, Receiver};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
 use std::thread;
 use std::time::Duration;
 
 use instant::Instant;
-use na::{Point2, Point3, Vector2, Vector3};
+use na::{Isometry3, Point2, Point3, Unit, UnitQuaternion, Vector2, Vector3, Vector4};
 
+use crate::builtin::ObjectMaterial;
 use crate::camera::{ArcBall, Camera};
 use crate::context::Context;
-use crate::event::{Action, EventManager, Key, WindowEvent};
+use crate::event::{Action, EventBus, EventManager, Key, Modifiers, MouseButton, WindowEvent};
+use crate::fog::FogMode;
 use crate::light::Light;
 use crate::planar_camera::{FixedView, PlanarCamera};
 use crate::planar_line_renderer::PlanarLineRenderer;
 use crate::post_processing::PostProcessingEffect;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::post_processing::{Outline, OutlineConfig, ScreenSpaceReflection, SsrConfig};
 #[cfg(feature = "conrod")]
 use crate::renderer::ConrodRenderer;
-use crate::renderer::{LineRenderer, PointRenderer, Renderer};
+use crate::renderer::{
+    GradientRenderer, LineRenderer, ParticleRenderer, PointRenderer, Renderer, SkyboxRenderer,
+};
 use crate::resource::{
-    FramebufferManager, Mesh, PlanarMesh, RenderTarget, Texture, TextureManager,
+    CubeMap, FramebufferManager, Mesh, PlanarMesh, RenderTarget, Texture, TextureManager,
+};
+use crate::scene::{
+    self, CoordinateFrameNode, ParticleConfig, ParticleSystem, PlanarSceneNode, SceneError,
+    SceneNode,
 };
-use crate::scene::{PlanarSceneNode, SceneNode};
 use crate::text::{Font, TextRenderer};
 use crate::window::canvas::CanvasSetup;
-use crate::window::{Canvas, State};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::window::recording::Recording;
+#[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+use crate::window::shader_watcher::ShaderWatcher;
+use crate::window::shadow_map::ShadowMap;
+use crate::window::svg;
+#[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+use crate::window::video_recording::{RecordingError, VideoRecording};
+use crate::window::WindowBuilder;
+use crate::window::{
+    Canvas, CursorStyle, DroppedFile, GamepadState, State, ViewportConfig, MAX_VIEWPORTS,
+};
 use image::imageops;
 use image::{GenericImage, Pixel};
 use image::{ImageBuffer, Rgb};
@@ -37,9 +61,15 @@ use ncollide3d::procedural::TriMesh;
 
 #[cfg(feature = "conrod")]
 use std::collections::HashMap;
+use std::collections::VecDeque;
 
-static DEFAULT_WIDTH: u32 = 800u32;
-static DEFAULT_HEIGHT: u32 = 600u32;
+pub(crate) static DEFAULT_WIDTH: u32 = 800u32;
+pub(crate) static DEFAULT_HEIGHT: u32 = 600u32;
+static DEFAULT_FRAME_STATS_WINDOW: usize = 60;
+static MAX_FIXED_UPDATES_PER_FRAME: usize = 8;
+// The number of points above which convex hulls are computed on a background thread.
+#[cfg(not(target_arch = "wasm32"))]
+static CONVEX_HULL_THREAD_THRESHOLD: usize = 10_000;
 
 #[cfg(feature = "conrod")]
 struct ConrodContext {
@@ -73,11 +103,34 @@ pub struct Window {
     line_renderer: LineRenderer,
     planar_line_renderer: PlanarLineRenderer,
     point_renderer: PointRenderer,
+    particle_renderer: ParticleRenderer,
+    skybox_renderer: SkyboxRenderer,
+    gradient_renderer: GradientRenderer,
     text_renderer: TextRenderer,
     framebuffer_manager: FramebufferManager,
     post_process_render_target: RenderTarget,
+    post_processing_effect: Option<Box<dyn PostProcessingEffect>>,
+    shadow_map: Option<ShadowMap>,
+    viewports: Vec<ViewportConfig>,
+    /// The viewport receiving the mouse events while a button is pressed.
+    captured_viewport: Option<usize>,
+    pending_convex_hulls: Vec<(SceneNode, Receiver<TriMesh<f32>>)>,
+    #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+    shader_watcher: Option<ShaderWatcher>,
+    frame_times: VecDeque<f32>,
+    frame_stats_window: usize,
+    last_frame: Option<Instant>,
+    show_fps: bool,
+    fixed_accumulator: f32,
+    fixed_alpha: f32,
     #[cfg(not(target_arch = "wasm32"))]
+    last_clip_planes: (f32, f32),
     curr_time: Instant,
+    #[cfg(not(target_arch = "wasm32"))]
+    recording: Option<Recording>,
+    world_axes: Option<f32>,
+    #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+    video_recording: Option<VideoRecording>,
     planar_camera: Rc<RefCell<FixedView>>,
     camera: Rc<RefCell<ArcBall>>,
     should_close: bool,
@@ -113,11 +166,15 @@ impl Window {
     }
 
     /// Sets the maximum number of frames per second. Cannot be 0. `None` means there is no limit.
+    ///
+    /// On native platforms, the remainder of the frame budget is spent sleeping after each
+    /// frame. On WebGL, the frames are already paced by `requestAnimationFrame`, so the frames
+    /// coming too early are skipped without doing any rendering work, nor calling `State::step`.
     #[inline]
     pub fn set_framerate_limit(&mut self, fps: Option<u64>) {
         self.max_dur_per_frame = fps.map(|f| {
             assert!(f != 0);
-            Duration::from_millis(1000 / f)
+            Duration::from_micros(1_000_000 / f)
         })
     }
 
@@ -143,14 +200,39 @@ impl Window {
         self.canvas.set_icon(icon)
     }
 
+    /// The underlying glutin window, for features of the native windowing system not wrapped
+    /// by kiss3d, e.g., setting the window position or querying the current monitor.
+    ///
+    /// Returns `None` for a window created by `Window::new_headless`, which has no native
+    /// window. The `glutin` crate is re-exported as `kiss3d::glutin`. This method is not
+    /// available on the WebGL backend which is not based on glutin. Changing the window state
+    /// through this handle (e.g., its size) will be seen by kiss3d through the usual window
+    /// events.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn glutin_window(&self) -> Option<&glutin::window::Window> {
+        self.canvas.glutin_window()
+    }
+
     /// Set the cursor grabbing behaviour.
     ///
     /// If cursor grabbing is on, the cursor is prevented from leaving the window.
-    /// Does nothing on web platforms.
+    ///
+    /// On web platforms, where a visible cursor cannot be confined to the canvas, this locks
+    /// the cursor like `Window::lock_cursor` does: it gets hidden, and `WindowEvent::CursorPos`
+    /// events report its displacement until the grab is released.
     pub fn set_cursor_grab(&self, grab: bool) {
         self.canvas.set_cursor_grab(grab);
     }
 
+    /// Sets the appearance of the cursor when it is over the window.
+    ///
+    /// On web platforms, this sets the `cursor` CSS property of the canvas. The style is kept
+    /// while the cursor is hidden, and is restored when it is shown again.
+    #[inline]
+    pub fn set_cursor(&self, style: CursorStyle) {
+        self.canvas.set_cursor(style);
+    }
+
     #[inline]
     pub fn set_cursor_position(&self, x: f64, y: f64) {
         self.canvas.set_cursor_position(x, y);
@@ -161,6 +243,50 @@ impl Window {
         self.canvas.hide_cursor(hide);
     }
 
+    /// Shows or hides the cursor when it is over the window.
+    #[inline]
+    pub fn set_cursor_visible(&self, visible: bool) {
+        self.canvas.hide_cursor(!visible);
+    }
+
+    /// Locks the cursor, e.g., for first-person cameras.
+    ///
+    /// A locked cursor is hidden and cannot leave the window. While the cursor is locked,
+    /// `WindowEvent::CursorPos` events report the cursor displacement since the last event
+    /// instead of its absolute position. On web platforms, this uses the Pointer Lock API:
+    /// browsers grant the lock only in response to a user action, and release it when the user
+    /// presses escape.
+    #[inline]
+    pub fn lock_cursor(&mut self, locked: bool) {
+        self.canvas.lock_cursor(locked);
+    }
+
+    /// Sets whether the window can be resized by the user.
+    ///
+    /// Does nothing on web platforms.
+    #[inline]
+    pub fn set_resizable(&mut self, resizable: bool) {
+        self.canvas.set_resizable(resizable);
+    }
+
+    /// Switches the window to or from fullscreen mode, on its current monitor.
+    ///
+    /// Does nothing on web platforms.
+    #[inline]
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.canvas.set_fullscreen(fullscreen);
+    }
+
+    /// Resizes the framebuffer of the window to `width * height` pixels, e.g., to render
+    /// screenshots with a fixed resolution.
+    ///
+    /// On web platforms, this sets the resolution of the canvas and its CSS size. The canvas then
+    /// keeps this resolution regardless of the page layout.
+    #[inline]
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.canvas.set_size(width, height);
+    }
+
     /// Closes the window.
     #[inline]
     pub fn close(&mut self) {
@@ -179,12 +305,288 @@ impl Window {
         self.canvas.show()
     }
 
+    /// Sets the maximum duration between two presses of a mouse button for them to emit a
+    /// `WindowEvent::DoubleClick`. Defaults to 500 milliseconds.
+    #[inline]
+    pub fn set_double_click_interval(&mut self, interval: Duration) {
+        self.canvas.set_double_click_interval(interval)
+    }
+
+    /// Sets a function called for each event emitted by the window, before it is queued.
+    ///
+    /// The events for which `filter` returns `false` are dropped: they are neither handled by
+    /// the window and its camera, nor returned by `events`. This replaces the previous filter,
+    /// if any.
+    #[inline]
+    pub fn set_event_filter(&mut self, filter: impl FnMut(&WindowEvent) -> bool + 'static) {
+        self.canvas.set_event_filter(filter)
+    }
+
+    /// Removes the function set by `set_event_filter`, so that every event is kept.
+    #[inline]
+    pub fn clear_event_filter(&mut self) {
+        self.canvas.clear_event_filter()
+    }
+
+    /// Sets whether the default action of the browser is prevented for the DOM events dropped
+    /// by the event filter, e.g., to keep the context menu from opening on right clicks.
+    ///
+    /// Defaults to `false`. Does nothing on native platforms.
+    #[inline]
+    pub fn set_prevent_default_on_filtered(&mut self, prevent: bool) {
+        self.canvas.set_prevent_default_on_filtered(prevent)
+    }
+
+    /// Sets whether the browser context menu opens on right clicks on the canvas.
+    ///
+    /// Defaults to `false`, so that the menu does not pop up when panning the camera with the
+    /// right mouse button. Does nothing on native platforms.
+    #[inline]
+    pub fn set_context_menu_enabled(&mut self, enabled: bool) {
+        self.canvas.set_context_menu_enabled(enabled)
+    }
+
+    /// Gives the keyboard focus to the canvas, so that it receives the key events.
+    ///
+    /// The canvas is focused when it is opened, but loses the focus when the user interacts
+    /// with another element of the page. Does nothing on native platforms, where the window
+    /// manager decides which window has the focus.
+    #[inline]
+    pub fn request_focus(&mut self) {
+        self.canvas.request_focus()
+    }
+
+    /// The text content of the clipboard, if any.
+    ///
+    /// On native platforms, this requires the `clipboard` feature and is `None` otherwise. On
+    /// the web, the browsers only give access to the clipboard when the user pastes into the
+    /// page, so this is the text last pasted into the canvas, see `WindowEvent::Paste`, or
+    /// copied by `set_clipboard_string`.
+    #[inline]
+    pub fn clipboard_string(&self) -> Option<String> {
+        self.canvas.clipboard_string()
+    }
+
+    /// Removes and returns the file dropped onto the canvas with the identifier `id`.
+    ///
+    /// The identifier is given by the `WindowEvent::FileDropped` event emitted once the file
+    /// has been read. The file is kept by the window until it is taken, so this returns `None`
+    /// if it has already been taken. Files can only be dropped on the web, so this is always
+    /// `None` on native platforms.
+    #[inline]
+    pub fn take_dropped_file(&mut self, id: u32) -> Option<DroppedFile> {
+        self.canvas.take_dropped_file(id)
+    }
+
+    /// Copies `text` to the clipboard.
+    ///
+    /// On native platforms, this requires the `clipboard` feature and does nothing otherwise.
+    /// On the web, the browser may refuse the access to the clipboard, e.g., if the page does
+    /// not have the focus.
+    #[inline]
+    pub fn set_clipboard_string(&mut self, text: &str) {
+        self.canvas.set_clipboard_string(text)
+    }
+
     /// Sets the background color.
+    ///
+    /// This removes the background gradient, if any. Defaults to black.
     #[inline]
     pub fn set_background_color(&mut self, r: f32, g: f32, b: f32) {
         self.background.x = r;
         self.background.y = g;
         self.background.z = b;
+        self.gradient_renderer.set_colors(None);
+    }
+
+    /// Sets a vertical gradient from the `top` to the `bottom` of the window as the background.
+    ///
+    /// The gradient is replaced by a solid color by `Window::set_background_color`, and covered
+    /// by the skybox, if any.
+    #[inline]
+    pub fn set_background_gradient(&mut self, top: Point3<f32>, bottom: Point3<f32>) {
+        self.gradient_renderer.set_colors(Some((top, bottom)));
+    }
+
+    /// Sets the cube-map drawn behind the scene instead of the background color.
+    ///
+    /// The skybox is seen from the camera eye, so only the camera rotation affects it. Use
+    /// `None` to only draw the background color. The same cube-map can be given to
+    /// `SceneNode::set_environment_map` so the objects reflect it.
+    #[inline]
+    pub fn set_skybox(&mut self, skybox: Option<Rc<CubeMap>>) {
+        self.skybox_renderer.set_cube_map(skybox)
+    }
+
+    /// The cube-map drawn behind the scene, if any.
+    #[inline]
+    pub fn skybox(&self) -> Option<&Rc<CubeMap>> {
+        self.skybox_renderer.cube_map()
+    }
+
+    /// Rotates the skybox by `angle_deg` degrees around `axis`, relative to the world axes.
+    ///
+    /// Updating the rotation at each frame gives, e.g., a day/night cycle. The environment and
+    /// irradiance maps of the objects are rotated the same way, so the reflections and the
+    /// image-based lighting keep matching the background. A null `axis` resets the rotation.
+    #[inline]
+    pub fn set_skybox_rotation(&mut self, angle_deg: f32, axis: Vector3<f32>) {
+        let rotation = Unit::try_new(axis, f32::EPSILON)
+            .map(|axis| UnitQuaternion::from_axis_angle(&axis, angle_deg.to_radians()))
+            .unwrap_or_else(UnitQuaternion::identity);
+        self.skybox_renderer.set_rotation(rotation);
+        ObjectMaterial::set_environment_rotation(rotation)
+    }
+
+    /// Sets the diffuse irradiance cube-map lighting the objects with a physically-based
+    /// material, see `SceneNode::set_metallic_roughness`.
+    ///
+    /// Such a map is computed from an environment map by `CubeMap::compute_irradiance`. It
+    /// replaces the uniform ambient light of these objects, and is still scaled by the ambient
+    /// light intensity, see `Window::set_ambient_light`. Use `None` to go back to the uniform
+    /// ambient light.
+    #[inline]
+    pub fn set_irradiance_map(&mut self, irradiance_map: Option<Rc<CubeMap>>) {
+        ObjectMaterial::set_irradiance_map(irradiance_map)
+    }
+
+    /// The diffuse irradiance cube-map lighting the objects with a physically-based material.
+    #[inline]
+    pub fn irradiance_map(&self) -> Option<Rc<CubeMap>> {
+        ObjectMaterial::irradiance_map()
+    }
+
+    /// Sets the number of samples of the multisample anti-aliasing (MSAA), `0` meaning off.
+    ///
+    /// Enabling MSAA requires a multisampled context: the number of samples of the framebuffer
+    /// is fixed when the window is created, so use `Window::new_with_setup` with
+    /// `CanvasSetup::samples` to request one. `samples` is clamped to the number of samples of
+    /// the context, so this has no effect if the framebuffer is not multisampled. Note that MSAA
+    /// does not apply to the scene when a post-processing effect is active since it renders to
+    /// an offscreen texture; use the `Fxaa` effect instead in that case.
+    ///
+    /// On WebGL, antialiasing can only be chosen at the context creation (as the `antialias`
+    /// context attribute) so this method has no effect.
+    pub fn set_msaa_samples(&mut self, samples: u32) {
+        if cfg!(not(target_arch = "wasm32")) {
+            let ctxt = Context::get();
+            let context_samples = verify!(ctxt.get_parameter_i32(Context::SAMPLES)).max(0) as u32;
+
+            if samples.min(context_samples) == 0 {
+                verify!(ctxt.disable(Context::MULTISAMPLE));
+            } else {
+                verify!(ctxt.enable(Context::MULTISAMPLE));
+            }
+        }
+    }
+
+    /// Enables or disables the shadows cast by the light on the objects rendered with the default
+    /// material.
+    ///
+    /// The depth of the scene is first rendered from the light into a square shadow map with
+    /// `resolution` pixels on each side. The light is treated as a directional light pointing
+    /// from its position toward the center of the scene. The shadows are smoothed with a 3x3
+    /// percentage-closer filtering.
+    ///
+    /// Panics if `enabled` is `true` and `resolution` is zero.
+    pub fn set_shadows(&mut self, enabled: bool, resolution: u32) {
+        if enabled {
+            assert!(
+                resolution != 0,
+                "The shadow map resolution must not be zero."
+            );
+
+            if self.shadow_map.as_ref().map(|s| s.resolution()) != Some(resolution) {
+                self.shadow_map = Some(ShadowMap::new(resolution));
+            }
+        } else {
+            self.shadow_map = None;
+        }
+    }
+
+    /// Whether shadows are enabled.
+    #[inline]
+    pub fn shadows(&self) -> bool {
+        self.shadow_map.is_some()
+    }
+
+    /// Watches the directory `path` to recompile the shaders of the default material whenever
+    /// they are modified.
+    ///
+    /// The shaders are read from the files `default.vert` and `default.frag`, which can be
+    /// copied from the built-in `OBJECT_VERTEX_SRC` and `OBJECT_FRAGMENT_SRC`. A missing file is
+    /// replaced by its built-in counterpart. They are compiled once right away, then before the
+    /// next frame each time one of them changes. Compilation errors are logged to stderr, and
+    /// the previous shaders remain in use until the errors are fixed.
+    ///
+    /// This requires the `hot_reload` feature, and is not available on WebGL.
+    #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+    pub fn watch_shader_dir(&mut self, path: &Path) -> notify::Result<()> {
+        let watcher = ShaderWatcher::new(path)?;
+        watcher.reload();
+        self.shader_watcher = Some(watcher);
+        Ok(())
+    }
+
+    /// Sets whether all the objects rendered with the default material are drawn as wireframes.
+    #[inline]
+    pub fn set_wireframe(&mut self, wireframe: bool) {
+        ObjectMaterial::set_wireframe(wireframe)
+    }
+
+    /// Sets the fog applied to all the objects rendered with the default material.
+    ///
+    /// Use `FogMode::None` to disable the fog.
+    #[inline]
+    pub fn set_fog(&mut self, fog: FogMode) {
+        ObjectMaterial::set_fog(fog)
+    }
+
+    /// The fog applied to all the objects rendered with the default material.
+    #[inline]
+    pub fn fog(&self) -> FogMode {
+        ObjectMaterial::fog()
+    }
+
+    /// Sets the intensity of the ambient light of all the objects rendered with the default
+    /// material.
+    ///
+    /// The intensity is clamped to `[0.0, 1.0]`. Low values suit night scenes, and `1.0` disables
+    /// all directional shading. Defaults to `1.0 / 3.0`.
+    #[inline]
+    pub fn set_ambient_light(&mut self, intensity: f32) {
+        ObjectMaterial::set_ambient_light(intensity)
+    }
+
+    /// The intensity of the ambient light of all the objects rendered with the default material.
+    #[inline]
+    pub fn ambient_light(&self) -> f32 {
+        ObjectMaterial::ambient_light()
+    }
+
+    /// Sets whether the objects entirely outside of the camera frustum are skipped when rendering
+    /// the scene.
+    ///
+    /// An object is culled when the bounding sphere of its mesh, see `Object::bounding_sphere`,
+    /// does not intersect the frustum. This must be disabled if a custom material moves the
+    /// vertices away from their mesh positions. Instanced objects are never culled. Disabled by
+    /// default.
+    #[inline]
+    pub fn set_frustum_culling(&mut self, enabled: bool) {
+        scene::set_frustum_culling(enabled)
+    }
+
+    /// Whether the objects entirely outside of the camera frustum are skipped when rendering the
+    /// scene.
+    #[inline]
+    pub fn frustum_culling(&self) -> bool {
+        scene::frustum_culling()
+    }
+
+    /// The number of objects skipped by frustum culling during the last render pass.
+    #[inline]
+    pub fn num_culled_objects(&self) -> usize {
+        scene::num_culled_objects()
     }
 
     /// Set the size of all subsequent points to be drawn until the next time this function is envoked.
@@ -208,6 +610,182 @@ impl Window {
         self.line_renderer.draw_line(*a, *b, *color);
     }
 
+    /// Adds a wireframe sphere to be drawn during the next render.
+    ///
+    /// The sphere is represented by three great circles, one on each axis-aligned plane.
+    /// Just like `draw_line`, this must be called at each frame the sphere should remain visible.
+    pub fn draw_wireframe_sphere(
+        &mut self,
+        center: &Point3<f32>,
+        radius: f32,
+        color: &Point3<f32>,
+    ) {
+        const NSUBDIVS: usize = 32;
+
+        let axes = [Vector3::x(), Vector3::y(), Vector3::z()];
+
+        for i in 0..3 {
+            let u = axes[i] * radius;
+            let v = axes[(i + 1) % 3] * radius;
+            let mut last = center + u;
+
+            for k in 1..=NSUBDIVS {
+                let angle = k as f32 * f32::consts::PI * 2.0 / NSUBDIVS as f32;
+                let curr = center + u * angle.cos() + v * angle.sin();
+                self.line_renderer.draw_line(last, curr, *color);
+                last = curr;
+            }
+        }
+    }
+
+    /// Adds a wireframe box to be drawn during the next render.
+    ///
+    /// The box is centered at `center`, has the half-widths `half_extents` along its local axes,
+    /// and is oriented by `rotation`. Use the identity rotation to draw an AABB.
+    /// Just like `draw_line`, this must be called at each frame the box should remain visible.
+    pub fn draw_wireframe_box(
+        &mut self,
+        center: &Point3<f32>,
+        half_extents: &Vector3<f32>,
+        rotation: &UnitQuaternion<f32>,
+        color: &Point3<f32>,
+    ) {
+        let corner = |i: usize| {
+            let sign = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+            let local = Vector3::new(
+                sign(1) * half_extents.x,
+                sign(2) * half_extents.y,
+                sign(4) * half_extents.z,
+            );
+            center + rotation * local
+        };
+
+        // Two corners share an edge iff their indices differ by exactly one bit.
+        for i in 0..8 {
+            for bit in &[1, 2, 4] {
+                if i & bit == 0 {
+                    self.line_renderer
+                        .draw_line(corner(i), corner(i | bit), *color);
+                }
+            }
+        }
+    }
+
+    /// Adds the frustum of `camera` to be drawn during the next render, e.g., to see it from
+    /// another camera.
+    ///
+    /// The frustum is the unprojection of the normalized device coordinates cube by
+    /// `camera.inverse_transformation()`. Its far plane is drawn darker than its near plane to
+    /// show its orientation. Just like `draw_line`, this must be called at each frame the
+    /// frustum should remain visible.
+    pub fn draw_camera_frustum(&mut self, camera: &dyn Camera, color: &Point3<f32>) {
+        let inv_proj_view = camera.inverse_transformation();
+        let corner = |i: usize| {
+            let sign = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+            let ndc = Point3::new(sign(1), sign(2), sign(4));
+            inv_proj_view.transform_point(&ndc)
+        };
+        let far_color = color * 0.5;
+
+        // The corners with the bit 4 set are on the far plane.
+        for i in 0..8 {
+            for bit in &[1, 2, 4] {
+                if i & bit == 0 {
+                    let edge_color = if i & 4 != 0 { far_color } else { *color };
+                    self.line_renderer
+                        .draw_line(corner(i), corner(i | bit), edge_color);
+                }
+            }
+        }
+    }
+
+    /// Adds a grid on the XZ plane to be drawn during the next render.
+    ///
+    /// The grid is centered at the origin, has a width of `size` along both the `x` and `z`
+    /// axes, and is split into `subdivisions` cells along each of those axes. If `axes_color`
+    /// is set, the two lines passing through the origin are drawn with this color instead.
+    /// Just like `draw_line`, this must be called at each frame the grid should remain visible.
+    pub fn draw_grid(
+        &mut self,
+        size: f32,
+        subdivisions: usize,
+        color: &Point3<f32>,
+        axes_color: Option<&Point3<f32>>,
+    ) {
+        assert!(
+            subdivisions != 0,
+            "A grid must have at least one subdivision."
+        );
+
+        let half = size / 2.0;
+        let spacing = size / subdivisions as f32;
+
+        for i in 0..=subdivisions {
+            let t = -half + i as f32 * spacing;
+            self.line_renderer.draw_line(
+                Point3::new(t, 0.0, -half),
+                Point3::new(t, 0.0, half),
+                *color,
+            );
+            self.line_renderer.draw_line(
+                Point3::new(-half, 0.0, t),
+                Point3::new(half, 0.0, t),
+                *color,
+            );
+        }
+
+        if let Some(axes_color) = axes_color {
+            self.line_renderer.draw_line(
+                Point3::new(0.0, 0.0, -half),
+                Point3::new(0.0, 0.0, half),
+                *axes_color,
+            );
+            self.line_renderer.draw_line(
+                Point3::new(-half, 0.0, 0.0),
+                Point3::new(half, 0.0, 0.0),
+                *axes_color,
+            );
+        }
+    }
+
+    /// Adds the three coordinate axes to be drawn during the next render.
+    ///
+    /// The axes start at the origin and have a length of `scale`. The `x` axis is red, the `y`
+    /// axis is green, and the `z` axis is blue.
+    /// Just like `draw_line`, this must be called at each frame the axes should remain visible.
+    pub fn draw_axes(&mut self, scale: f32) {
+        let o = Point3::origin();
+        self.line_renderer
+            .draw_line(o, Point3::new(scale, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0));
+        self.line_renderer
+            .draw_line(o, Point3::new(0.0, scale, 0.0), Point3::new(0.0, 1.0, 0.0));
+        self.line_renderer
+            .draw_line(o, Point3::new(0.0, 0.0, scale), Point3::new(0.0, 0.0, 1.0));
+    }
+
+    /// Moves the default camera such that all the objects of the scene are in view.
+    ///
+    /// The camera keeps its orientation, and looks at the center of the world-space bounding box
+    /// of the scene, see `SceneNode::aabb` and `ArcBall::fit_to` for details. The `padding`
+    /// multiplies the radius of the box bounding sphere, e.g., `1.1` leaves a small margin
+    /// around the objects. Does nothing if the scene contains no object.
+    pub fn frame_scene(&mut self, padding: f32) {
+        if let Some(aabb) = self.scene.aabb() {
+            self.camera.borrow_mut().fit_to(&aabb, padding);
+        }
+    }
+
+    /// Draws the three coordinate axes at each frame, like `draw_axes`, until
+    /// `hide_world_axes` is called.
+    pub fn draw_world_axes(&mut self, scale: f32) {
+        self.world_axes = Some(scale);
+    }
+
+    /// Stops drawing the coordinate axes enabled by `draw_world_axes`.
+    pub fn hide_world_axes(&mut self) {
+        self.world_axes = None;
+    }
+
     /// Draws a 2D line to be drawn during the next render.
     ///
     /// The line is being drawn only during the next frame after this call.
@@ -223,7 +801,66 @@ impl Window {
         self.point_renderer.draw_point(*pt, *color);
     }
 
+    /// The duration, in seconds, of the last frame.
+    ///
+    /// This is the time elapsed between the ends of the last two renderings. Before the second
+    /// frame is rendered, the duration of a frame at 60 FPS is returned instead.
+    #[inline]
+    pub fn delta_time(&self) -> f64 {
+        self.frame_times.back().map_or(1.0 / 60.0, |dt| *dt as f64)
+    }
+
+    /// The average duration, in seconds, of the last frames.
+    ///
+    /// The number of frames taken into account is set by `set_frame_stats_window`. Returns `0.0`
+    /// until two frames have been rendered.
+    pub fn average_frame_time(&self) -> f32 {
+        if self.frame_times.is_empty() {
+            0.0
+        } else {
+            self.frame_times.iter().sum::<f32>() / self.frame_times.len() as f32
+        }
+    }
+
+    /// The number of frames per second, averaged over the last frames.
+    ///
+    /// Returns `0.0` until two frames have been rendered.
+    pub fn fps(&self) -> f32 {
+        let frame_time = self.average_frame_time();
+
+        if frame_time > 0.0 {
+            1.0 / frame_time
+        } else {
+            0.0
+        }
+    }
+
+    /// Sets the number of frames averaged by `average_frame_time` and `fps`.
+    ///
+    /// Larger values give steadier numbers that react slower to changes. Defaults to 60.
+    pub fn set_frame_stats_window(&mut self, num_frames: usize) {
+        assert!(num_frames != 0, "At least one frame must be averaged.");
+        self.frame_stats_window = num_frames;
+
+        while self.frame_times.len() > num_frames {
+            let _ = self.frame_times.pop_front();
+        }
+    }
+
+    /// Sets whether the number of frames per second is drawn on the top-left corner of the window.
+    #[inline]
+    pub fn set_show_fps(&mut self, show: bool) {
+        self.show_fps = show
+    }
+
     /// Adds a string to be drawn during the next frame.
+    ///
+    /// The text is drawn on top of the 3D and 2D scenes, without depth test. `pos` is the
+    /// position of the top-left corner of the text, starting from the top-left corner of the
+    /// window, and `scale` is the height of a line of text. Use `Font::default()` for the
+    /// built-in font, or `Font::new` to load a TrueType font.
+    ///
+    /// Just like `draw_line`, this must be called at each frame the text should remain visible.
     #[inline]
     pub fn draw_text(
         &mut self,
@@ -259,6 +896,18 @@ impl Window {
         self.scene.add_group()
     }
 
+    /// Adds a coordinate frame to the scene.
+    ///
+    /// The frame is made of three arrows `scale` long, red for `x`, green for `y`, and blue for
+    /// `z`, placed by `transform`.
+    pub fn add_coordinate_frame(
+        &mut self,
+        transform: Isometry3<f32>,
+        scale: f32,
+    ) -> CoordinateFrameNode {
+        self.scene.add_coordinate_frame(transform, scale)
+    }
+
     /// Adds a 2D group to the scene.
     ///
     /// A group is a node not containing any object.
@@ -295,6 +944,72 @@ impl Window {
         self.scene.add_trimesh(descr, scale)
     }
 
+    /// Finds a node of the scene named `name`.
+    ///
+    /// See `SceneNode::find_node` for details about the search order.
+    pub fn find_node(&self, name: &str) -> Option<SceneNode> {
+        self.scene.find_node(name)
+    }
+
+    /// Saves the scene to the RON file at `path`.
+    ///
+    /// See `SceneNode::save_scene` for details.
+    pub fn save_scene(&self, path: &Path) -> Result<(), SceneError> {
+        self.scene.save_scene(path)
+    }
+
+    /// Replaces the scene by the one saved to the RON file at `path`.
+    ///
+    /// See `SceneNode::load_scene` for details.
+    pub fn load_scene(&mut self, path: &Path) -> Result<(), SceneError> {
+        self.scene.load_scene(path)
+    }
+
+    /// Adds the convex hull of a set of points to the scene.
+    ///
+    /// Returns an error if `points` does not contain at least 4 non-coplanar points. On native
+    /// platforms, the hull of large point sets is computed on a background thread: the returned
+    /// node is then an empty group until the computation completes, and the hull is added to
+    /// its children at the beginning of the next frame rendered after that. Properties like the
+    /// color set on the group before that do not apply to the hull.
+    pub fn add_convex_hull(&mut self, points: &[Point3<f32>]) -> Result<SceneNode, &'static str> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if points.len() >= CONVEX_HULL_THREAD_THRESHOLD {
+                if !crate::procedural::has_volume(points) {
+                    return Err("A convex hull needs at least 4 non-coplanar points.");
+                }
+
+                let (hull_send, hull_receive) = mpsc::channel();
+                let points = points.to_vec();
+                let _ = thread::spawn(move || {
+                    if let Ok(hull) = crate::procedural::convex_hull(&points) {
+                        let _ = hull_send.send(hull);
+                    }
+                });
+
+                let node = self.scene.add_group();
+                self.pending_convex_hulls.push((node.clone(), hull_receive));
+                return Ok(node);
+            }
+        }
+
+        self.scene.add_convex_hull(points)
+    }
+
+    // Adds the convex hulls computed on background threads to their nodes.
+    fn add_pending_convex_hulls(&mut self) {
+        self.pending_convex_hulls
+            .retain(|(node, hull)| match hull.try_recv() {
+                Ok(hull) => {
+                    let _ = node.clone().add_trimesh(hull, Vector3::from_element(1.0));
+                    false
+                }
+                Err(TryRecvError::Empty) => true,
+                Err(TryRecvError::Disconnected) => false,
+            })
+    }
+
     /// Creates and adds a new object using the geometry registered as `geometry_name`.
     pub fn add_geom_with_name(
         &mut self,
@@ -322,6 +1037,40 @@ impl Window {
         self.scene.add_sphere(r)
     }
 
+    /// Adds a sphere tessellated with `subdivs` subdivisions to the scene.
+    ///
+    /// See `SceneNode::add_sphere_with_subdivisions` for details.
+    ///
+    /// # Arguments
+    /// * `r` - the sphere radius
+    /// * `subdivs` - the number of subdivisions along the sphere meridians and parallels
+    pub fn add_sphere_with_subdivisions(&mut self, r: f32, subdivs: u32) -> SceneNode {
+        self.scene.add_sphere_with_subdivisions(r, subdivs)
+    }
+
+    /// Adds a sphere with several levels of detail to the scene.
+    ///
+    /// See `SceneNode::add_sphere_lod` for details.
+    ///
+    /// # Arguments
+    /// * `r` - the sphere radius
+    /// * `lods` - the levels of detail given as `(distance, segments)`
+    pub fn add_sphere_lod(&mut self, r: f32, lods: &[(f32, u32)]) -> SceneNode {
+        self.scene.add_sphere_lod(r, lods)
+    }
+
+    /// Adds a cylinder with several levels of detail to the scene.
+    ///
+    /// See `SceneNode::add_sphere_lod` for details about the levels of detail.
+    ///
+    /// # Arguments
+    /// * `h` - the cylinder height
+    /// * `r` - the cylinder base radius
+    /// * `lods` - the levels of detail given as `(distance, segments)`
+    pub fn add_cylinder_lod(&mut self, r: f32, h: f32, lods: &[(f32, u32)]) -> SceneNode {
+        self.scene.add_cylinder_lod(r, h, lods)
+    }
+
     /// Adds a cone to the scene. The cone is initially centered at (0, 0, 0) and points toward the
     /// positive `y` axis.
     ///
@@ -336,10 +1085,30 @@ impl Window {
     /// principal axis aligned with the `y` axis.
     ///
     /// # Arguments
-    /// * `h` - the cylinder height
-    /// * `r` - the cylinder base radius
-    pub fn add_cylinder(&mut self, r: f32, h: f32) -> SceneNode {
-        self.scene.add_cylinder(r, h)
+    /// * `h` - the cylinder height
+    /// * `r` - the cylinder base radius
+    pub fn add_cylinder(&mut self, r: f32, h: f32) -> SceneNode {
+        self.scene.add_cylinder(r, h)
+    }
+
+    /// Adds a cylinder without caps to the scene, e.g., for pipes. The cylinder is initially
+    /// centered at (0, 0, 0) and has its principal axis aligned with the `y` axis.
+    ///
+    /// # Arguments
+    /// * `h` - the cylinder height
+    /// * `r` - the cylinder base radius
+    pub fn add_open_cylinder(&mut self, r: f32, h: f32) -> SceneNode {
+        self.scene.add_open_cylinder(r, h)
+    }
+
+    /// Adds a double-sided disk to the scene. The disk is initially centered at (0, 0, 0) in the
+    /// `xz` plane.
+    ///
+    /// # Arguments
+    /// * `r` - the disk radius
+    /// * `inner_r` - the radius of the hole at the center of the disk, `0.0` for a full disk
+    pub fn add_disk(&mut self, r: f32, inner_r: f32) -> SceneNode {
+        self.scene.add_disk(r, inner_r)
     }
 
     /// Adds a capsule to the scene. The capsule is initially centered at (0, 0, 0) and has its
@@ -362,6 +1131,25 @@ impl Window {
         self.scene2.add_capsule(r, h)
     }
 
+    /// Adds a sequence of cubic Bézier curves, drawn as a polyline, to the scene.
+    ///
+    /// See `SceneNode::add_bezier_curve` for details.
+    pub fn add_bezier_curve(&mut self, control_points: &[Point3<f32>], segments: u32) -> SceneNode {
+        self.scene.add_bezier_curve(control_points, segments)
+    }
+
+    /// Adds a Catmull-Rom spline passing through `points`, drawn as a polyline, to the scene.
+    ///
+    /// See `SceneNode::add_catmull_rom` for details.
+    pub fn add_catmull_rom(
+        &mut self,
+        points: &[Point3<f32>],
+        segments: u32,
+        tension: f32,
+    ) -> SceneNode {
+        self.scene.add_catmull_rom(points, segments, tension)
+    }
+
     /// Adds a double-sided quad to the scene. The quad is initially centered at (0, 0, 0). The
     /// quad itself is composed of a user-defined number of triangles regularly spaced on a grid.
     /// This is the main way to draw height maps.
@@ -423,6 +1211,56 @@ impl Window {
         self.scene2.add_convex_polygon(polygon, scale)
     }
 
+    /// Adds a particle system updated and rendered at each frame.
+    ///
+    /// The maximum number of particles alive at the same time is the number of particles spawned
+    /// during one lifetime, i.e., `config.emission_rate * config.lifetime`. Use
+    /// `ParticleSystem::new` and `Window::add_existing_particle_system` to choose another bound.
+    pub fn add_particle_system(&mut self, config: ParticleConfig) -> ParticleSystem {
+        let max_particles = (config.emission_rate * config.lifetime).max(0.0).ceil() as usize;
+        let system = ParticleSystem::new(max_particles, config);
+        self.add_existing_particle_system(system.clone());
+        system
+    }
+
+    /// Adds a particle system created with `ParticleSystem::new` to the ones updated and
+    /// rendered at each frame.
+    pub fn add_existing_particle_system(&mut self, system: ParticleSystem) {
+        self.particle_renderer.add(system)
+    }
+
+    /// Stops updating and rendering a particle system.
+    pub fn remove_particle_system(&mut self, system: &ParticleSystem) {
+        self.particle_renderer.remove(system)
+    }
+
+    /// Splits the window into several viewports, each rendering the scene from its own camera.
+    ///
+    /// The viewports are rendered in order, at most `MAX_VIEWPORTS` of them: the extra ones are
+    /// ignored. The mouse and keyboard events are sent to the camera of the viewport under the
+    /// cursor, or to the viewport where the current drag started. The window's camera still
+    /// drives the light stuck to the camera, the shadows, and the post-processing effects. The
+    /// lines and points drawn with `draw_line` and `draw_point` only appear in the first
+    /// viewport.
+    ///
+    /// Use an empty vector to go back to rendering the whole window from a single camera.
+    pub fn set_split_screen(&mut self, mut viewports: Vec<ViewportConfig>) {
+        viewports.truncate(MAX_VIEWPORTS);
+        self.viewports = viewports;
+        self.captured_viewport = None;
+    }
+
+    /// The viewports set by `set_split_screen`.
+    pub fn viewports(&self) -> &[ViewportConfig] {
+        &self.viewports[..]
+    }
+
+    /// A mutable reference to the viewports set by `set_split_screen`, e.g., to modify their
+    /// cameras.
+    pub fn viewports_mut(&mut self) -> &mut [ViewportConfig] {
+        &mut self.viewports[..]
+    }
+
     /// Returns whether this window is closed or not.
     pub fn is_closed(&self) -> bool {
         false // FIXME
@@ -438,6 +1276,49 @@ impl Window {
         self.light_mode = pos;
     }
 
+    /// Sets the post-processing effect applied at each frame, e.g., `Fxaa` for anti-aliasing.
+    ///
+    /// Use an `EffectChain` to apply several effects, e.g., `Sepia` followed by `Fxaa`.
+    ///
+    /// This effect is used whenever no other post-processing effect is explicitly provided to
+    /// the rendering methods. Set to `None` to disable post-processing.
+    pub fn set_post_effect(&mut self, effect: Option<Box<dyn PostProcessingEffect>>) {
+        self.post_processing_effect = effect;
+    }
+
+    /// Outlines the objects of the scene, e.g., for cel-shaded rendering.
+    ///
+    /// This replaces the post-processing effect applied at each frame by an `Outline` effect,
+    /// see `Window::set_post_effect`. Use `Window::set_post_effect(None)` to remove the outlines,
+    /// or an `EffectChain` to combine them with other effects.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn enable_outline(&mut self, config: OutlineConfig) {
+        self.set_post_effect(Some(Box::new(Outline::new(config))))
+    }
+
+    /// Adds screen-space reflections onto the objects marked with `SceneNode::set_ssr_reflector`.
+    ///
+    /// This replaces the post-processing effect applied at each frame by a
+    /// `ScreenSpaceReflection` effect, see `Window::set_post_effect`. The rays leaving the screen
+    /// reflect the current skybox, if any, or `config.fallback_color`. Call this again after
+    /// changing the skybox.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn enable_ssr(&mut self, config: SsrConfig) {
+        let mut ssr = ScreenSpaceReflection::new(config);
+        ssr.set_skybox(self.skybox_renderer.cube_map().cloned());
+        self.set_post_effect(Some(Box::new(ssr)))
+    }
+
+    /// Sets the `index`-th clip plane, or disables it if `plane` is `None`.
+    ///
+    /// The plane is given by its world-space equation `(a, b, c, d)`: only the parts of the
+    /// objects where `a * x + b * y + c * z + d >= 0` are rendered. At most `MAX_CLIP_PLANES`
+    /// clip planes are supported. Clip planes only affect objects rendered with the default
+    /// `ObjectMaterial`.
+    pub fn set_clip_plane(&mut self, index: usize, plane: Option<Vector4<f32>>) {
+        ObjectMaterial::set_clip_plane(index, plane)
+    }
+
     /// Retrieve a mutable reference to the UI based on Conrod.
     #[cfg(feature = "conrod")]
     pub fn conrod_ui_mut(&mut self) -> &mut conrod::Ui {
@@ -494,10 +1375,12 @@ impl Window {
 
     /// Opens a window then calls a user-defined procedure.
     ///
+    /// This is a shortcut for `WindowBuilder::new(title).build()`.
+    ///
     /// # Arguments
     /// * `title` - the window title
     pub fn new(title: &str) -> Window {
-        Window::do_new(title, false, DEFAULT_WIDTH, DEFAULT_HEIGHT, None)
+        WindowBuilder::new(title).build()
     }
 
     /// Opens a window with a custom size then calls a user-defined procedure.
@@ -511,12 +1394,14 @@ impl Window {
     }
 
     /// Opens a window with custom options for vsync and AA.
+    ///
+    /// A non-zero `setup.samples` requests a multisampled framebuffer (`antialias: true` on
+    /// WebGL). See `Window::set_msaa_samples` to toggle it afterwards.
     pub fn new_with_setup(title: &str, width: u32, height: u32, setup: CanvasSetup) -> Window {
         Window::do_new(title, false, width, height, Some(setup))
     }
 
-    // FIXME: make this pub?
-    fn do_new(
+    pub(crate) fn do_new(
         title: &str,
         hide: bool,
         width: u32,
@@ -525,7 +1410,36 @@ impl Window {
     ) -> Window {
         let (event_send, event_receive) = mpsc::channel();
         let canvas = Canvas::open(title, hide, width, height, setup, event_send);
+        let mut window = Window::from_canvas(canvas, event_receive, width, height);
+
+        if hide {
+            window.canvas.hide()
+        }
+
+        window
+    }
+
+    /// Creates a window rendering to an off-screen buffer of `width * height` pixels, without
+    /// displaying anything.
+    ///
+    /// This is useful for automated image tests: the frames are rendered as usual and can be
+    /// retrieved with `snap`. On native platforms, a headless OpenGL context is used. On Linux,
+    /// this still requires a connection to a display server, e.g., Xvfb, but no window is
+    /// created. On web platforms, the frames are rendered to a canvas element which is not
+    /// inserted into the page. The size of the off-screen buffer cannot be changed by
+    /// `set_size` on native platforms.
+    pub fn new_headless(width: u32, height: u32) -> Window {
+        let (event_send, event_receive) = mpsc::channel();
+        let canvas = Canvas::open_headless(width, height, None, event_send);
+        Window::from_canvas(canvas, event_receive, width, height)
+    }
 
+    fn from_canvas(
+        canvas: Canvas,
+        event_receive: Receiver<WindowEvent>,
+        width: u32,
+        height: u32,
+    ) -> Window {
         init_gl();
 
         let mut usr_window = Window {
@@ -541,6 +1455,9 @@ impl Window {
             line_renderer: LineRenderer::new(),
             planar_line_renderer: PlanarLineRenderer::new(),
             point_renderer: PointRenderer::new(),
+            particle_renderer: ParticleRenderer::new(),
+            skybox_renderer: SkyboxRenderer::new(),
+            gradient_renderer: GradientRenderer::new(),
             text_renderer: TextRenderer::new(),
             #[cfg(feature = "conrod")]
             conrod_context: ConrodContext::new(width as f64, height as f64),
@@ -549,9 +1466,28 @@ impl Window {
                 height as usize,
                 true,
             ),
-            framebuffer_manager: FramebufferManager::new(),
+            post_processing_effect: None,
+            shadow_map: None,
+            viewports: Vec::new(),
+            captured_viewport: None,
+            pending_convex_hulls: Vec::new(),
+            #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+            shader_watcher: None,
+            frame_times: VecDeque::new(),
+            frame_stats_window: DEFAULT_FRAME_STATS_WINDOW,
+            last_frame: None,
+            show_fps: false,
+            fixed_accumulator: 0.0,
+            fixed_alpha: 0.0,
             #[cfg(not(target_arch = "wasm32"))]
+            last_clip_planes: (0.1, 1024.0),
+            framebuffer_manager: FramebufferManager::new(),
             curr_time: Instant::now(),
+            #[cfg(not(target_arch = "wasm32"))]
+            recording: None,
+            world_axes: None,
+            #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+            video_recording: None,
             planar_camera: Rc::new(RefCell::new(FixedView::new())),
             camera: Rc::new(RefCell::new(ArcBall::new(
                 Point3::new(0.0f32, 0.0, -1.0),
@@ -559,10 +1495,6 @@ impl Window {
             ))),
         };
 
-        if hide {
-            usr_window.canvas.hide()
-        }
-
         // usr_window.framebuffer_size_callback(DEFAULT_WIDTH, DEFAULT_HEIGHT);
         let light = usr_window.light_mode.clone();
         usr_window.set_light(light);
@@ -582,6 +1514,16 @@ impl Window {
         &mut self.scene
     }
 
+    /// Handles to all the scene nodes of this window containing an object, in depth-first order.
+    ///
+    /// See `SceneNode::descendants` to also get the nodes without objects, or the nodes of a
+    /// sub-graph only.
+    pub fn objects(&self) -> impl Iterator<Item = SceneNode> {
+        self.scene
+            .descendants()
+            .filter(|node| node.data().has_object())
+    }
+
     // FIXME: give more options for the snap size and offset.
     /// Read the pixels currently displayed to the screen.
     ///
@@ -630,16 +1572,211 @@ impl Window {
         imageops::flip_vertical(&img)
     }
 
+    /// Read the pixels currently displayed to the screen as RGBA.
+    ///
+    /// Returns the pixels, and the width and height of the framebuffer. The rows are ordered
+    /// from the top of the screen to its bottom.
+    pub fn snap_rgba(&self) -> (Vec<u8>, u32, u32) {
+        let (width, height) = self.canvas.size();
+        let row_len = width as usize * 4;
+        let mut pixels = vec![0; row_len * height as usize];
+
+        let ctxt = Context::get();
+        ctxt.pixel_storei(Context::PACK_ALIGNMENT, 1);
+        ctxt.read_pixels(
+            0,
+            0,
+            width as i32,
+            height as i32,
+            Context::RGBA,
+            Some(&mut pixels),
+        );
+
+        // OpenGL returns the rows from the bottom of the screen to its top.
+        let mut flipped = Vec::with_capacity(pixels.len());
+        for row in pixels.chunks(row_len).rev() {
+            flipped.extend_from_slice(row);
+        }
+
+        (flipped, width, height)
+    }
+
+    /// Read the depth buffer of the screen, converted to distances along the camera view
+    /// direction.
+    ///
+    /// The depths are linearized with the near and far clipping planes of the camera used for
+    /// the last rendered frame, assuming a perspective projection. Pixels where nothing has been
+    /// drawn are set to `f32::INFINITY`. Returns the depths, and the width and height of the
+    /// framebuffer. The rows are ordered from the top of the screen to its bottom.
+    ///
+    /// The depth buffer does not contain the scene if it was rendered with a post-processing
+    /// effect. Reading the depth buffer is not supported by WebGL.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn snap_depth(&self) -> (Vec<f32>, u32, u32) {
+        let (width, height) = self.canvas.size();
+        let mut depths = vec![0.0; width as usize * height as usize];
+
+        let ctxt = Context::get();
+        ctxt.pixel_storei(Context::PACK_ALIGNMENT, 4);
+        ctxt.read_depth_pixels(0, 0, width as i32, height as i32, &mut depths);
+
+        let (znear, zfar) = self.last_clip_planes;
+        let mut linear = Vec::with_capacity(depths.len());
+
+        // OpenGL returns the rows from the bottom of the screen to its top.
+        for row in depths.chunks(width.max(1) as usize).rev() {
+            linear.extend(row.iter().map(|d| {
+                if *d >= 1.0 {
+                    f32::INFINITY
+                } else {
+                    let z_ndc = d * 2.0 - 1.0;
+                    2.0 * znear * zfar / (zfar + znear - z_ndc * (zfar - znear))
+                }
+            }));
+        }
+
+        (linear, width, height)
+    }
+
+    /// Saves the current screen to an image file.
+    ///
+    /// The image format is deduced from the extension of `path`, e.g., `.png`.
+    pub fn snap_image_to_file(&self, path: &Path) -> image::ImageResult<()> {
+        self.snap_image().save(path)
+    }
+
+    /// Starts recording the rendered frames to the directory `dir`.
+    ///
+    /// Until `stop_recording` is called, one every `every_nth_frame` rendered frames is saved
+    /// as a sequentially numbered PNG file, e.g., `frame_000042.png`. The images are written by
+    /// a separate thread. The directory is created if it does not exist yet. Any recording
+    /// already in progress is stopped first. Fails with `io::ErrorKind::InvalidInput` if
+    /// `every_nth_frame` is zero.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn start_recording(&mut self, dir: &Path, every_nth_frame: u32) -> io::Result<()> {
+        self.stop_recording();
+        self.recording = Some(Recording::new(dir, every_nth_frame)?);
+        Ok(())
+    }
+
+    /// Starts recording the rendered frames to the MP4 video file at `path`.
+    ///
+    /// Until `stop_recording` is called, the rendered frames are encoded with H.264, at most
+    /// `fps` frames per second: the frames rendered faster than that are dropped, and the
+    /// frames rendered slower are shown longer, so the video plays in real time. Its resolution
+    /// is the current framebuffer size, rounded down to even numbers, and does not follow the
+    /// later resizes of the window. The frames are encoded by a separate thread.
+    /// Any recording already in progress is stopped first. Fails with
+    /// `io::ErrorKind::InvalidInput` if `fps` is zero.
+    #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+    pub fn start_video_recording(&mut self, path: &Path, fps: u32) -> Result<(), RecordingError> {
+        self.stop_recording();
+        let (width, height) = self.canvas.size();
+        self.video_recording = Some(VideoRecording::new(path, fps, width, height)?);
+        Ok(())
+    }
+
+    /// Stops the current recording, if any.
+    ///
+    /// This blocks until all the recorded frames are written to the disk, and the video file,
+    /// if any, is finalized.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn stop_recording(&mut self) {
+        self.recording = None;
+        #[cfg(feature = "video")]
+        {
+            self.video_recording = None;
+        }
+    }
+
+    /// Whether the rendered frames are currently being recorded.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn is_recording(&self) -> bool {
+        #[cfg(feature = "video")]
+        {
+            if self.video_recording.is_some() {
+                return true;
+            }
+        }
+
+        self.recording.is_some()
+    }
+
+    /// Renders the 3D scene seen by `camera` as an SVG 1.1 image.
+    ///
+    /// Each visible triangle is projected to the screen and written as a flat-colored polygon.
+    /// Hidden surfaces are removed with the painter's algorithm and back-face culling, so
+    /// intersecting triangles may not be rendered correctly. Lines, points, texts, textures,
+    /// and the 2D scene are ignored.
+    pub fn render_to_svg(&self, camera: &dyn Camera) -> String {
+        let (width, height) = self.canvas.size();
+        svg::render_to_svg(
+            &self.scene,
+            camera,
+            &self.light_mode,
+            &self.background,
+            width,
+            height,
+        )
+    }
+
     /// Gets the events manager that gives access to an event iterator.
     pub fn events(&self) -> EventManager {
         EventManager::new(self.events.clone(), self.unhandled_events.clone())
     }
 
+    /// Sends all the events collected since the last frame to the subscribers of `bus`.
+    ///
+    /// The events are still handled by the window and its camera afterwards.
+    pub fn dispatch_events(&self, bus: &mut EventBus) {
+        for event in self.events().iter() {
+            bus.dispatch(&event.value)
+        }
+    }
+
+    /// The state of the gamepad with the given index, if it is connected.
+    ///
+    /// The state is polled once per frame, when the `WindowEvent::GamepadButton` and
+    /// `WindowEvent::GamepadAxis` events are emitted for its changes. Gamepads are only
+    /// supported on the web, through the Gamepad API. Glutin has no gamepad API, so gamepads are
+    /// not supported on native platforms, where this is always `None`.
+    pub fn gamepad(&self, index: u32) -> Option<GamepadState> {
+        self.canvas.gamepad(index)
+    }
+
+    /// Sets the magnitude below which the positions of the gamepad axes are reported as `0.0`.
+    ///
+    /// This keeps the sticks at rest from emitting `WindowEvent::GamepadAxis` events because of
+    /// their noise. Defaults to `0.0`. Has no effect on native platforms.
+    pub fn set_gamepad_deadzone(&mut self, deadzone: f32) {
+        self.canvas.set_gamepad_deadzone(deadzone)
+    }
+
     /// Gets the status of a key.
     pub fn get_key(&self, key: Key) -> Action {
         self.canvas.get_key(key)
     }
 
+    /// The keys currently pressed.
+    ///
+    /// This is more convenient than calling `get_key` for each key of interest.
+    pub fn pressed_keys(&self) -> impl Iterator<Item = Key> + '_ {
+        self.canvas.pressed_keys()
+    }
+
+    /// The modifiers currently held, e.g., to snap a gizmo while Control is held.
+    ///
+    /// This is derived from the state of the left and right modifier keys, independently from
+    /// the modifiers attached to the events.
+    pub fn get_modifiers(&self) -> Modifiers {
+        self.canvas.get_modifiers()
+    }
+
+    /// The mouse buttons currently pressed.
+    pub fn pressed_buttons(&self) -> impl Iterator<Item = MouseButton> + '_ {
+        self.canvas.pressed_buttons()
+    }
+
     /// Gets the last known position of the mouse.
     ///
     /// The position of the mouse is automatically updated when the mouse moves over the canvas.
@@ -903,12 +2040,49 @@ impl Window {
             None => self.camera.borrow_mut().handle_event(&self.canvas, event),
         }
 
+        if !self.viewports.is_empty() {
+            self.handle_viewport_event(event);
+            return;
+        }
+
         match *camera {
             Some(ref mut cam) => cam.handle_event(&self.canvas, event),
             None => self.camera.borrow_mut().handle_event(&self.canvas, event),
         }
     }
 
+    fn handle_viewport_event(&mut self, event: &WindowEvent) {
+        let (w, h) = (self.width() as f64, self.height() as f64);
+        let cursor = match *event {
+            WindowEvent::CursorPos(x, y, _) => Some((x, y)),
+            _ => self.canvas.cursor_pos(),
+        };
+        let hovered = cursor.and_then(|(x, y)| {
+            self.viewports
+                .iter()
+                .position(|v| v.contains((x / w) as f32, (y / h) as f32))
+        });
+        let target = self.captured_viewport.or(hovered);
+
+        match *event {
+            // Each viewport camera is given the size of its viewport when it is rendered.
+            WindowEvent::FramebufferSize(..) => return,
+            WindowEvent::MouseButton(_, Action::Press, _) => self.captured_viewport = target,
+            WindowEvent::MouseButton(_, Action::Release, _) => {
+                if self.canvas.pressed_buttons().next().is_none() {
+                    self.captured_viewport = None
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(i) = target {
+            if let Some(viewport) = self.viewports.get_mut(i) {
+                viewport.camera.handle_event(&self.canvas, event)
+            }
+        }
+    }
+
     /// Runs the render and event loop until the window is closed.
     pub fn render_loop<S: State>(mut self, mut state: S) {
         Canvas::render_loop(move |_| self.do_render_with_state(&mut state))
@@ -921,18 +2095,63 @@ impl Window {
     }
 
     fn do_render_with_state<S: State>(&mut self, state: &mut S) -> bool {
+        #[cfg(target_arch = "wasm32")]
+        {
+            if let Some(dur) = self.max_dur_per_frame {
+                // Allow some jitter so a 30 FPS limit doesn't drop to 20 FPS on a 60 Hz display.
+                if self.curr_time.elapsed() + Duration::from_millis(2) < dur {
+                    return !self.should_close;
+                }
+            }
+        }
+
         {
             let (camera, planar_camera, renderer, effect) = state.cameras_and_effect_and_renderer();
             self.should_close = !self.do_render_with(camera, planar_camera, renderer, effect);
         }
 
         if !self.should_close {
+            self.run_fixed_updates(state);
             state.step(self)
         }
 
         !self.should_close
     }
 
+    fn run_fixed_updates<S: State>(&mut self, state: &mut S) {
+        let dt = match state.fixed_timestep() {
+            Some(dt) if dt > 0.0 => dt,
+            _ => return,
+        };
+
+        self.fixed_accumulator += self.frame_times.back().cloned().unwrap_or(0.0);
+
+        let mut num_updates = 0;
+        while self.fixed_accumulator >= dt {
+            if num_updates == MAX_FIXED_UPDATES_PER_FRAME {
+                // Drop the time we can't catch up with instead of slowing down even more.
+                self.fixed_accumulator %= dt;
+                break;
+            }
+
+            state.fixed_update(self, dt);
+            self.fixed_accumulator -= dt;
+            num_updates += 1;
+        }
+
+        self.fixed_alpha = self.fixed_accumulator / dt;
+    }
+
+    /// The fraction of a fixed time step elapsed since the last call to `State::fixed_update`.
+    ///
+    /// This is between 0.0 and 1.0, and can be used to interpolate between the last two states
+    /// of a simulation updated at a fixed rate. Always zero if the state given to the render
+    /// loop does not use fixed time steps.
+    #[inline]
+    pub fn fixed_update_alpha(&self) -> f32 {
+        self.fixed_alpha
+    }
+
     /// Renders the scene using the default camera.
     ///
     /// Returns `false` if the window should be closed.
@@ -941,6 +2160,20 @@ impl Window {
         self.render_with(None, None, None)
     }
 
+    /// Polls the pending events, renders one frame with the default camera, and swaps the
+    /// buffers.
+    ///
+    /// This lets the caller drive the render loop, e.g., `while window.render_one_frame() { .. }`,
+    /// and is equivalent to `Window::render`. Returns `false` if the window should be closed.
+    ///
+    /// This is not available on WASM, where frames can only be rendered from the
+    /// `requestAnimationFrame` callbacks of `Window::render_loop`.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[inline]
+    pub fn render_one_frame(&mut self) -> bool {
+        self.render()
+    }
+
     /// Render using a specific post processing effect.
     ///
     /// Returns `false` if the window should be closed.
@@ -1008,6 +2241,65 @@ impl Window {
         self.do_render_with(camera, planar_camera, None, post_processing)
     }
 
+    /// Renders the scene seen from `camera` into the off-screen `target` instead of the screen.
+    ///
+    /// The target can be created with `FramebufferManager::new_render_target` and its color
+    /// texture, given by `target.texture()`, can then be applied to any object with
+    /// `Object::set_texture`, e.g., to implement mirrors or in-game monitors. Objects textured
+    /// with `target` itself must be hidden while rendering to it. Nothing is done if `target` is
+    /// the screen.
+    pub fn render_to(&mut self, target: &RenderTarget, camera: &mut dyn Camera) {
+        let mut scene = self.scene.clone();
+        self.render_scene_to(&mut scene, target, camera)
+    }
+
+    /// Renders the given sub-scene seen from `camera` into the off-screen `target`.
+    ///
+    /// Only the objects of `scene` are drawn, with the window's background color and light. See
+    /// `Window::render_to` for details.
+    pub fn render_scene_to(
+        &mut self,
+        scene: &mut SceneNode,
+        target: &RenderTarget,
+        camera: &mut dyn Camera,
+    ) {
+        let (tw, th) = match target.size() {
+            Some(size) => size,
+            None => return,
+        };
+        let (w, h) = (self.width(), self.height());
+        let ctxt = Context::get();
+
+        camera.handle_event(&self.canvas, &WindowEvent::FramebufferSize(tw, th));
+        self.framebuffer_manager.select(target);
+        verify!(ctxt.viewport(0, 0, tw as i32, th as i32));
+        verify!(ctxt.scissor(0, 0, tw as i32, th as i32));
+
+        let light = self.light_mode.clone();
+
+        for pass in 0usize..camera.num_passes() {
+            camera.start_pass(pass, &self.canvas);
+
+            verify!(ctxt.active_texture(Context::TEXTURE0));
+            verify!(ctxt.clear_color(self.background.x, self.background.y, self.background.z, 1.0));
+            verify!(ctxt.clear(Context::COLOR_BUFFER_BIT));
+            verify!(ctxt.clear(Context::DEPTH_BUFFER_BIT));
+
+            self.gradient_renderer.render(pass, camera);
+            self.skybox_renderer.render(pass, camera);
+            scene.data_mut().render(pass, camera, &light);
+        }
+
+        camera.render_complete(&self.canvas);
+
+        // Restore the screen as the render target.
+        self.framebuffer_manager
+            .select(&FramebufferManager::screen());
+        verify!(ctxt.viewport(0, 0, w as i32, h as i32));
+        verify!(ctxt.scissor(0, 0, w as i32, h as i32));
+        camera.handle_event(&self.canvas, &WindowEvent::FramebufferSize(w, h));
+    }
+
     fn do_render_with(
         &mut self,
         camera: Option<&mut dyn Camera>,
@@ -1019,29 +2311,46 @@ impl Window {
         let mut planar_camera = planar_camera;
         self.handle_events(&mut camera, &mut planar_camera);
 
-        let self_cam2 = self.planar_camera.clone(); // FIXME: this is ugly.
-        let mut bself_cam2 = self_cam2.borrow_mut();
+        // Fallback to the window's post-processing effect, if any.
+        let mut self_post_processing = self.post_processing_effect.take();
 
-        let self_cam = self.camera.clone(); // FIXME: this is ugly.
-        let mut bself_cam = self_cam.borrow_mut();
+        let res = {
+            // The borrow of the window's effect must end before it is restored.
+            let post_processing = match post_processing {
+                Some(p) => Some(&mut *p as &mut dyn PostProcessingEffect),
+                None => self_post_processing
+                    .as_mut()
+                    .map(|p| &mut **p as &mut dyn PostProcessingEffect),
+            };
 
-        match (camera, planar_camera) {
-            (Some(cam), Some(cam2)) => {
-                self.render_single_frame(cam, cam2, renderer, post_processing)
-            }
-            (None, Some(cam2)) => {
-                self.render_single_frame(&mut *bself_cam, cam2, renderer, post_processing)
-            }
-            (Some(cam), None) => {
-                self.render_single_frame(cam, &mut *bself_cam2, renderer, post_processing)
+            let self_cam2 = self.planar_camera.clone(); // FIXME: this is ugly.
+            let mut bself_cam2 = self_cam2.borrow_mut();
+
+            let self_cam = self.camera.clone(); // FIXME: this is ugly.
+            let mut bself_cam = self_cam.borrow_mut();
+
+            match (camera, planar_camera) {
+                (Some(cam), Some(cam2)) => {
+                    self.render_single_frame(cam, cam2, renderer, post_processing)
+                }
+                (None, Some(cam2)) => {
+                    self.render_single_frame(&mut *bself_cam, cam2, renderer, post_processing)
+                }
+                (Some(cam), None) => {
+                    self.render_single_frame(cam, &mut *bself_cam2, renderer, post_processing)
+                }
+                (None, None) => self.render_single_frame(
+                    &mut *bself_cam,
+                    &mut *bself_cam2,
+                    renderer,
+                    post_processing,
+                ),
             }
-            (None, None) => self.render_single_frame(
-                &mut *bself_cam,
-                &mut *bself_cam2,
-                renderer,
-                post_processing,
-            ),
-        }
+        };
+
+        self.post_processing_effect = self_post_processing;
+
+        res
     }
 
     fn render_single_frame(
@@ -1051,6 +2360,23 @@ impl Window {
         mut renderer: Option<&mut dyn Renderer>,
         mut post_processing: Option<&mut dyn PostProcessingEffect>,
     ) -> bool {
+        #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+        {
+            if let Some(ref mut watcher) = self.shader_watcher {
+                if watcher.shaders_changed() {
+                    watcher.reload();
+                }
+            }
+        }
+
+        self.add_pending_convex_hulls();
+        self.particle_renderer.update(self.delta_time() as f32);
+        scene::update_color_animations(self.delta_time() as f32);
+
+        if let Some(scale) = self.world_axes {
+            self.draw_axes(scale);
+        }
+
         // XXX: too bad we have to do this at each frame…
         let w = self.width();
         let h = self.height();
@@ -1058,13 +2384,27 @@ impl Window {
         planar_camera.handle_event(&self.canvas, &WindowEvent::FramebufferSize(w, h));
         camera.handle_event(&self.canvas, &WindowEvent::FramebufferSize(w, h));
         planar_camera.update(&self.canvas);
-        camera.update(&self.canvas);
+        camera.update_with_delta_time(&self.canvas, self.delta_time() as f32);
 
         match self.light_mode {
             Light::StickToCamera => self.set_light(Light::StickToCamera),
             _ => {}
         }
 
+        if let Some(ref mut shadow_map) = self.shadow_map {
+            let light_pos = match self.light_mode {
+                Light::Absolute(ref p) => *p,
+                Light::StickToCamera => camera.eye(),
+            };
+            let params = shadow_map.render(&mut self.framebuffer_manager, &self.scene, &light_pos);
+            ObjectMaterial::set_shadow_map(params);
+
+            // Restore the viewport changed by the shadow pass.
+            let ctxt = Context::get();
+            verify!(ctxt.viewport(0, 0, w as i32, h as i32));
+            verify!(ctxt.scissor(0, 0, w as i32, h as i32));
+        }
+
         if post_processing.is_some() {
             // if we need post-processing, render to our own frame buffer
             self.framebuffer_manager
@@ -1074,20 +2414,67 @@ impl Window {
                 .select(&FramebufferManager::screen());
         }
 
-        for pass in 0usize..camera.num_passes() {
-            camera.start_pass(pass, &self.canvas);
-            self.render_scene(camera, pass);
+        // The screen-space reflectors are only marked in the input of the effects reading them.
+        ObjectMaterial::set_ssr_output(
+            post_processing
+                .as_ref()
+                .map_or(false, |p| p.wants_ssr_output()),
+        );
+
+        if self.viewports.is_empty() {
+            for pass in 0usize..camera.num_passes() {
+                camera.start_pass(pass, &self.canvas);
+                self.render_scene(camera, pass);
+
+                if let Some(ref mut renderer) = renderer {
+                    renderer.render(pass, camera)
+                }
+            }
 
-            if let Some(ref mut renderer) = renderer {
-                renderer.render(pass, camera)
+            camera.render_complete(&self.canvas);
+        } else {
+            let mut viewports = mem::replace(&mut self.viewports, Vec::new());
+            let ctxt = Context::get();
+            let dt = self.delta_time() as f32;
+
+            for viewport in &mut viewports {
+                let (x, y, vw, vh) = viewport.pixel_rect(w, h);
+                let camera = &mut *viewport.camera;
+
+                verify!(ctxt.viewport(x, y, vw, vh));
+                verify!(ctxt.scissor(x, y, vw, vh));
+                camera.handle_event(
+                    &self.canvas,
+                    &WindowEvent::FramebufferSize(vw.max(1) as u32, vh.max(1) as u32),
+                );
+                camera.update_with_delta_time(&self.canvas, dt);
+
+                for pass in 0usize..camera.num_passes() {
+                    camera.start_pass(pass, &self.canvas);
+                    self.render_scene(camera, pass);
+
+                    if let Some(ref mut renderer) = renderer {
+                        renderer.render(pass, camera)
+                    }
+                }
+
+                camera.render_complete(&self.canvas);
             }
+
+            self.viewports = viewports;
+            verify!(ctxt.viewport(0, 0, w as i32, h as i32));
+            verify!(ctxt.scissor(0, 0, w as i32, h as i32));
         }
 
-        camera.render_complete(&self.canvas);
+        ObjectMaterial::set_ssr_output(false);
 
         self.render_planar_scene(planar_camera);
 
         let (znear, zfar) = camera.clip_planes();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.last_clip_planes = (znear, zfar);
+        }
 
         // FIXME: remove this completely?
         // swatch off the wireframe mode for post processing and text rendering.
@@ -1101,10 +2488,22 @@ impl Window {
                 .select(&FramebufferManager::screen());
             // … and execute the post-process
             // FIXME: use the real time value instead of 0.016!
+            p.set_camera(&*camera);
             p.update(0.016, w as f32, h as f32, znear, zfar);
             p.draw(&self.post_process_render_target);
         }
 
+        if self.show_fps {
+            let fps = format!("{:.1} FPS", self.fps());
+            self.text_renderer.draw_text(
+                &fps,
+                &Point2::new(0.0, 0.0),
+                40.0,
+                &Font::default(),
+                &Point3::new(1.0, 1.0, 1.0),
+            );
+        }
+
         self.text_renderer.render(w as f32, h as f32);
         #[cfg(feature = "conrod")]
         self.conrod_context.renderer.render(
@@ -1114,6 +2513,39 @@ impl Window {
             &self.conrod_context.textures,
         );
 
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let needs_frame = self.recording.as_ref().map(|r| r.needs_frame());
+            if let Some(needs_frame) = needs_frame {
+                let image = if needs_frame {
+                    Some(self.snap_image())
+                } else {
+                    None
+                };
+                self.recording.as_mut().unwrap().push_frame(image);
+            }
+        }
+
+        // The frame is read before the swap, as the back buffer is undefined afterwards.
+        #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+        {
+            let video_size = self
+                .video_recording
+                .as_ref()
+                .filter(|r| r.needs_frame())
+                .map(|r| r.size());
+            if let Some((vw, vh)) = video_size {
+                let mut pixels = Vec::new();
+                self.snap_rect(&mut pixels, 0, 0, vw as usize, vh as usize);
+                let image: ImageBuffer<Rgb<u8>, _> = ImageBuffer::from_vec(vw, vh, pixels)
+                    .expect("Buffer created from window was not big enough for image.");
+                self.video_recording
+                    .as_mut()
+                    .unwrap()
+                    .push_frame(imageops::flip_vertical(&image));
+            }
+        }
+
         // We are done: swap buffers
         self.canvas.swap_buffers();
 
@@ -1126,9 +2558,20 @@ impl Window {
                     thread::sleep(dur - elapsed);
                 }
             }
+        }
+
+        self.curr_time = Instant::now();
+
+        let now = Instant::now();
+        if let Some(last_frame) = self.last_frame {
+            if self.frame_times.len() == self.frame_stats_window {
+                let _ = self.frame_times.pop_front();
+            }
 
-            self.curr_time = Instant::now();
+            self.frame_times
+                .push_back(now.duration_since(last_frame).as_secs_f32());
         }
+        self.last_frame = Some(now);
 
         // self.transparent_objects.clear();
         // self.opaque_objects.clear();
@@ -1145,9 +2588,12 @@ impl Window {
         verify!(ctxt.clear(Context::COLOR_BUFFER_BIT));
         verify!(ctxt.clear(Context::DEPTH_BUFFER_BIT));
 
+        self.gradient_renderer.render(pass, camera);
+        self.skybox_renderer.render(pass, camera);
         self.line_renderer.render(pass, camera);
         self.point_renderer.render(pass, camera);
         self.scene.data_mut().render(pass, camera, &self.light_mode);
+        self.particle_renderer.render(pass, camera);
     }
 
     fn render_planar_scene(&mut self, camera: &mut dyn PlanarCamera) {
@@ -0,0 +1,152 @@
+//! A builder for windows with custom options.
+
+use crate::window::window::{DEFAULT_HEIGHT, DEFAULT_WIDTH};
+use crate::window::{CanvasSetup, NumSamples, Window};
+
+/// A builder to open a window with custom options.
+///
+/// ```no_run
+/// # use kiss3d::window::WindowBuilder;
+/// let window = WindowBuilder::default()
+///     .title("My window")
+///     .width(1280)
+///     .height(720)
+///     .msaa_samples(4)
+///     .build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct WindowBuilder {
+    title: String,
+    width: u32,
+    height: u32,
+    resizable: bool,
+    fullscreen: bool,
+    msaa_samples: NumSamples,
+    hide_cursor: bool,
+    vsync: bool,
+    background_color: (f32, f32, f32),
+}
+
+impl WindowBuilder {
+    /// Creates a new window builder with the given title, and default values for all the other
+    /// options.
+    pub fn new(title: &str) -> WindowBuilder {
+        WindowBuilder {
+            title: title.to_string(),
+            ..WindowBuilder::default()
+        }
+    }
+
+    /// Sets the title of the window.
+    pub fn title(mut self, title: &str) -> WindowBuilder {
+        self.title = title.to_string();
+        self
+    }
+
+    /// Sets the width of the window.
+    pub fn width(mut self, width: u32) -> WindowBuilder {
+        self.width = width;
+        self
+    }
+
+    /// Sets the height of the window.
+    pub fn height(mut self, height: u32) -> WindowBuilder {
+        self.height = height;
+        self
+    }
+
+    /// Sets whether the window can be resized by the user. Defaults to `true`.
+    ///
+    /// Ignored on web platforms.
+    pub fn resizable(mut self, resizable: bool) -> WindowBuilder {
+        self.resizable = resizable;
+        self
+    }
+
+    /// Sets whether the window is opened in fullscreen mode. Defaults to `false`.
+    ///
+    /// Ignored on web platforms.
+    pub fn fullscreen(mut self, fullscreen: bool) -> WindowBuilder {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    /// Sets the number of samples used for multisample anti-aliasing, `0` meaning off.
+    ///
+    /// # Panics
+    /// If `samples` is not one of 0, 1, 2, 4, 8, or 16.
+    pub fn msaa_samples(mut self, samples: u32) -> WindowBuilder {
+        self.msaa_samples = NumSamples::from_u32(samples)
+            .unwrap_or_else(|| panic!("Unsupported number of MSAA samples: {}", samples));
+        self
+    }
+
+    /// Sets whether the cursor is hidden when it is over the window. Defaults to `false`.
+    ///
+    /// Ignored on web platforms.
+    pub fn hide_cursor(mut self, hide: bool) -> WindowBuilder {
+        self.hide_cursor = hide;
+        self
+    }
+
+    /// Sets whether the buffer swaps are synchronized with the display refresh. Defaults to
+    /// `true`.
+    ///
+    /// This can only be chosen when the window is created. Disable it, possibly together with
+    /// `Window::set_framerate_limit`, to render faster than the display refresh rate. Ignored on
+    /// web platforms where `requestAnimationFrame` is always synchronized with the display.
+    pub fn vsync(mut self, vsync: bool) -> WindowBuilder {
+        self.vsync = vsync;
+        self
+    }
+
+    /// Sets the background color of the window. Defaults to black.
+    ///
+    /// Colors components must be on the range `[0.0, 1.0]`.
+    pub fn background_color(mut self, r: f32, g: f32, b: f32) -> WindowBuilder {
+        self.background_color = (r, g, b);
+        self
+    }
+
+    /// Opens the window.
+    pub fn build(self) -> Window {
+        let setup = CanvasSetup {
+            vsync: self.vsync,
+            samples: self.msaa_samples,
+        };
+        let mut window = Window::do_new(&self.title, false, self.width, self.height, Some(setup));
+
+        if !self.resizable {
+            window.set_resizable(false);
+        }
+
+        if self.fullscreen {
+            window.set_fullscreen(true);
+        }
+
+        if self.hide_cursor {
+            window.hide_cursor(true);
+        }
+
+        let (r, g, b) = self.background_color;
+        window.set_background_color(r, g, b);
+
+        window
+    }
+}
+
+impl Default for WindowBuilder {
+    fn default() -> WindowBuilder {
+        WindowBuilder {
+            title: "kiss3d".to_string(),
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            resizable: true,
+            fullscreen: false,
+            msaa_samples: NumSamples::Zero,
+            hide_cursor: false,
+            vsync: true,
+            background_color: (0.0, 0.0, 0.0),
+        }
+    }
+}